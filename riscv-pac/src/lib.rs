@@ -1,6 +1,6 @@
 #![no_std]
 
-pub mod result;
+pub use riscv_types::result;
 
 use result::Result;
 
@@ -0,0 +1,87 @@
+//! Pends machine software interrupts via the CLINT `MSWI` peripheral of the QEMU `virt` machine.
+//!
+//! The main thread and the `MachineSoft` handler print the sequence in which they run
+//! through semihosting, so the host can check that each interrupt is dispatched right after
+//! being pended. Link with `riscv-rt/examples/qemu.x` and run with
+//! `qemu-system-riscv{32,64} -machine virt -bios none -display none -serial null -monitor none -semihosting-config enable=on,target=native -kernel <elf>`.
+//! The same output is expected with and without the `riscv-rt/v-trap` feature.
+//!
+//! This example only runs on RISC-V targets. On other targets, it does nothing.
+#![cfg_attr(any(target_arch = "riscv32", target_arch = "riscv64"), no_std, no_main)]
+
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+mod qemu {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use riscv::{asm::wfi, interrupt::Interrupt};
+    use riscv_rt::{core_interrupt, entry};
+    use riscv_semihosting::{debug, hprintln};
+
+    riscv_peripheral::clint_codegen!(base 0x0200_0000,);
+
+    /// Number of software interrupts to pend from the main thread.
+    const N_PENDS: usize = 2;
+
+    /// Current step of the execution sequence.
+    static STEP: AtomicUsize = AtomicUsize::new(0);
+
+    /// Number of times the `MachineSoft` handler has been executed.
+    static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Prints the current step together with `who` and advances the sequence.
+    fn step(who: &str) {
+        // Load and store are enough (and available without the A extension): no preemption here
+        let step = STEP.load(Ordering::Relaxed);
+        hprintln!("{}: {}", step, who);
+        STEP.store(step + 1, Ordering::Relaxed);
+    }
+
+    #[entry]
+    fn main() -> ! {
+        step("main");
+        unsafe {
+            CLINT::mswi_enable();
+            riscv::interrupt::enable();
+        }
+        for _ in 0..N_PENDS {
+            let handled = HANDLED.load(Ordering::Relaxed);
+            // Pend the interrupt and wait for the handler to run
+            CLINT::mswi_pend();
+            while HANDLED.load(Ordering::Relaxed) == handled {
+                // With interrupts disabled, the handler cannot run between the check and `wfi`.
+                // `wfi` still returns when the interrupt is pending, and it is taken on exit.
+                riscv::interrupt::free(|| {
+                    if HANDLED.load(Ordering::Relaxed) == handled {
+                        wfi();
+                    }
+                });
+            }
+            step("main");
+        }
+        riscv::interrupt::disable();
+
+        match HANDLED.load(Ordering::Relaxed) {
+            N_PENDS => debug::exit(debug::EXIT_SUCCESS),
+            _ => debug::exit(debug::EXIT_FAILURE),
+        }
+        loop {}
+    }
+
+    #[core_interrupt(Interrupt::MachineSoft)]
+    fn machine_soft() {
+        // Clear the pending interrupt before returning to avoid re-entering the handler
+        CLINT::mswi_unpend();
+        step("MachineSoft");
+        HANDLED.store(HANDLED.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+    }
+
+    #[panic_handler]
+    fn panic(info: &core::panic::PanicInfo) -> ! {
+        hprintln!("{}", info);
+        debug::exit(debug::EXIT_FAILURE);
+        loop {}
+    }
+}
+
+#[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+fn main() {}
@@ -2,13 +2,30 @@
 //!
 //! CLINT pecification: <https://github.com/pulp-platform/clint>
 //! ACLINT Specification: <https://chromitem-soc.readthedocs.io/en/latest/clint.html>
+//!
+//! # Memory ordering
+//!
+//! The RISC-V memory model does not order device accesses with respect to regular memory accesses.
+//! The [`Reg::read_ordered`](crate::common::Reg::read_ordered) and
+//! [`Reg::write_ordered`](crate::common::Reg::write_ordered) methods pair register accesses
+//! with the required fences. In the CLINT:
+//!
+//! - [`mswi::MSIP::pend`] and [`sswi::SETSSIP::pend`] use ordered writes, so the target HART
+//!   observes the data written before the inter-processor interrupt.
+//! - Timer registers (`MTIME` and `MTIMECMP`) do not need ordering, as they do not signal memory.
 
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod mswi;
 pub mod mtimer;
 pub mod sswi;
 
+use riscv_pac::result::Result;
 pub use riscv_pac::HartIdNumber; // re-export useful riscv-pac traits
 
+/// Maximum number of HARTs supported by the CLINT specification.
+const N_HARTS: usize = 4095;
+
 /// Trait for a CLINT peripheral.
 ///
 /// # Safety
@@ -30,34 +47,141 @@ pub unsafe trait Clint: Copy {
 /// Each HART has an assigned index starting from 0 to up to 4_094.
 /// In this way, each HART's timer and software interrupts can be independently configured.
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq)]
 pub struct CLINT<C: Clint> {
     _marker: core::marker::PhantomData<C>,
 }
 
 impl<C: Clint> CLINT<C> {
+    /// Creates a new `CLINT` ownership token.
+    ///
+    /// Drivers can take the token by value to ensure that only one owner configures the peripheral.
+    ///
+    /// # Safety
+    ///
+    /// The token must be unique. Use [`crate::take`] to get the tokens of the platform peripherals.
+    #[inline]
+    pub const unsafe fn steal() -> Self {
+        Self {
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the CLINT with the base address of the [`Clint`] trait.
+    #[inline]
+    const fn as_dyn() -> DynCLINT {
+        DynCLINT { base: C::BASE }
+    }
+
+    /// Returns the `MSWI` peripheral.
+    #[inline]
+    pub const fn mswi() -> mswi::MSWI {
+        Self::as_dyn().mswi()
+    }
+
+    /// Returns the `MTIMER` peripheral.
+    #[inline]
+    pub const fn mtimer() -> mtimer::MTIMER {
+        Self::as_dyn().mtimer()
+    }
+
+    /// Saves the `MTIMECMP` registers of all the HARTs into `state` before power-gating the CLINT.
+    /// It returns the number of values written.
+    ///
+    /// See [`mtimer::MTIMER::suspend_state`] for more details.
+    #[inline]
+    pub fn suspend_state<H: HartIdNumber>(state: &mut [u64]) -> Result<usize> {
+        Self::mtimer().suspend_state::<H>(state)
+    }
+
+    /// Restores the `MTIMECMP` registers saved by [`CLINT::suspend_state`] once the CLINT is powered again.
+    ///
+    /// See [`mtimer::MTIMER::resume_state`] for more details.
+    #[inline]
+    pub fn resume_state<H: HartIdNumber>(state: &[u64]) -> Result<()> {
+        Self::mtimer().resume_state::<H>(state)
+    }
+}
+
+/// CLINT peripheral with a base address provided at runtime.
+///
+/// [`CLINT`] gets the base address from the [`Clint`] trait at compile time, which is the zero-cost
+/// choice for PACs. Kernels that discover the address of the CLINT at boot (e.g., from a device tree)
+/// can use `DynCLINT` instead. It provides the same functionality as [`CLINT`], with methods that take `&self`.
+///
+/// # Example
+///
+/// ```
+/// use riscv_peripheral::aclint::DynCLINT;
+///
+/// let base = 0x0200_0000; // e.g., read from the device tree
+/// // SAFETY: `base` is the base address of the CLINT
+/// let clint = unsafe { DynCLINT::new(base) };
+/// assert_eq!(clint.mtimer().mtime.get_ptr() as usize, base + 0xBFF8);
+/// ```
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Eq, PartialEq)]
+pub struct DynCLINT {
+    base: usize,
+}
+
+impl DynCLINT {
     const MTIMECMP_OFFSET: usize = 0x4000;
 
     const MTIME_OFFSET: usize = 0xBFF8;
 
+    /// Creates a new `DynCLINT` with base address `base`.
+    ///
+    /// # Safety
+    ///
+    /// * `base` must be the base address of a CLINT peripheral of the target device.
+    /// * The instance must be unique, as for the [`CLINT`] ownership token.
+    #[inline]
+    pub const unsafe fn new(base: usize) -> Self {
+        Self { base }
+    }
+
+    /// Returns the base address of the CLINT.
+    #[inline]
+    pub const fn base(&self) -> usize {
+        self.base
+    }
+
     /// Returns the `MSWI` peripheral.
     #[inline]
-    pub const fn mswi() -> mswi::MSWI {
+    pub const fn mswi(&self) -> mswi::MSWI {
         // SAFETY: valid base address
-        unsafe { mswi::MSWI::new(C::BASE) }
+        unsafe { mswi::MSWI::new(self.base) }
     }
 
     /// Returns the `MTIMER` peripheral.
     #[inline]
-    pub const fn mtimer() -> mtimer::MTIMER {
+    pub const fn mtimer(&self) -> mtimer::MTIMER {
         // SAFETY: valid base address
         unsafe {
             mtimer::MTIMER::new(
-                C::BASE + Self::MTIMECMP_OFFSET,
-                C::BASE + Self::MTIME_OFFSET,
+                self.base + Self::MTIMECMP_OFFSET,
+                self.base + Self::MTIME_OFFSET,
             )
         }
     }
+
+    /// Saves the `MTIMECMP` registers of all the HARTs into `state` before power-gating the CLINT.
+    /// It returns the number of values written.
+    ///
+    /// See [`mtimer::MTIMER::suspend_state`] for more details.
+    #[inline]
+    pub fn suspend_state<H: HartIdNumber>(&self, state: &mut [u64]) -> Result<usize> {
+        self.mtimer().suspend_state::<H>(state)
+    }
+
+    /// Restores the `MTIMECMP` registers saved by [`DynCLINT::suspend_state`] once the CLINT is powered again.
+    ///
+    /// See [`mtimer::MTIMER::resume_state`] for more details.
+    #[inline]
+    pub fn resume_state<H: HartIdNumber>(&self, state: &[u64]) -> Result<()> {
+        self.mtimer().resume_state::<H>(state)
+    }
 }
 
 #[cfg(test)]
@@ -139,4 +263,22 @@ pub(crate) mod test {
         assert_eq!(CLINT::msip1(), mswi.msip(HartId::H1));
         assert_eq!(CLINT::msip2(), mswi.msip(HartId::H2));
     }
+
+    #[test]
+    fn check_dyn_clint() {
+        let clint = unsafe { super::DynCLINT::new(0x0200_0000) };
+        assert_eq!(clint.base(), 0x0200_0000);
+
+        let mswi = clint.mswi();
+        let mtimer = clint.mtimer();
+
+        assert_eq!(mswi.msip0.get_ptr() as usize, 0x0200_0000);
+        assert_eq!(mtimer.mtimecmp0.get_ptr() as usize, 0x0200_4000);
+        assert_eq!(mtimer.mtime.get_ptr() as usize, 0x0200_bff8);
+        assert_eq!(
+            mtimer.mtimecmp(HartId::H2).get_ptr() as usize,
+            0x0200_4000 + 2 * 8
+        );
+        assert_eq!(mswi.msip(HartId::H1).get_ptr() as usize, 0x0200_0000 + 4);
+    }
 }
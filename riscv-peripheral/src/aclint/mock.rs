@@ -0,0 +1,177 @@
+//! Memory-backed CLINT registers for host-side testing.
+//!
+//! [`MockClint`] emulates the `MSIP`, `MTIMECMP`, and `MTIME` registers of a CLINT in regular memory.
+//! It also provides assertion helpers (e.g., [`MockClint::assert_mtimecmp`]), so HAL and application
+//! crates can check the timer and software interrupt configuration applied by their code in unit tests.
+//! This module is only available with the `mock` feature.
+//!
+//! # Note
+//!
+//! The mock does not emulate the CLINT logic (e.g., `MTIME` does not increase over time).
+//! Tests must update the registers to reproduce the expected behavior of the CLINT.
+//!
+//! As [`super::Clint`] requires a constant base address, the mock does not implement it.
+//! Instead, code under test should receive the register views of the CLINT (e.g., [`MTIMER`]).
+
+use super::{mswi::MSWI, mtimer::MTIMER, HartIdNumber, N_HARTS};
+use core::sync::atomic::AtomicU32;
+
+/// Cleared 32-bit register, to initialize register arrays in constant contexts.
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU32 = AtomicU32::new(0);
+
+/// 64-bit register backed by two 32-bit atomic integers.
+///
+/// `AtomicU64` is not available in all the targets, so 64-bit registers are emulated
+/// with naturally aligned pairs of `AtomicU32`.
+#[repr(C, align(8))]
+struct Reg64([AtomicU32; 2]);
+
+impl Reg64 {
+    /// Cleared 64-bit register, to initialize register arrays in constant contexts.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Self = Self([ZERO, ZERO]);
+}
+
+/// Memory-backed emulation of the registers of a CLINT.
+///
+/// Register views borrow the mock for `'static`, so it is usually declared as a `static`.
+/// Registers are backed by atomic integers, so the mock can be shared among test threads.
+///
+/// # Example
+///
+/// ```
+/// use riscv_peripheral::aclint::mock::MockClint;
+///
+/// static CLINT: MockClint = MockClint::new();
+///
+/// let mtimer = CLINT.mtimer();
+/// mtimer.mtime.write(1_000);
+/// mtimer.mtimecmp0.write(mtimer.mtime.read() + 500); // hypothetical driver code
+///
+/// assert_eq!(CLINT.mtime(), 1_000);
+/// assert_eq!(mtimer.mtimecmp0.read(), 1_500);
+/// ```
+pub struct MockClint {
+    msips: [AtomicU32; N_HARTS],
+    mtimecmps: [Reg64; N_HARTS],
+    mtime: Reg64,
+}
+
+impl MockClint {
+    /// Creates a new mock CLINT with all its registers cleared.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            msips: [ZERO; N_HARTS],
+            mtimecmps: [Reg64::ZERO; N_HARTS],
+            mtime: Reg64::ZERO,
+        }
+    }
+
+    /// Returns the `MSWI` peripheral of the mock CLINT.
+    #[inline]
+    pub fn mswi(&'static self) -> MSWI {
+        // SAFETY: valid memory address during the whole program execution
+        unsafe { MSWI::new(self.msips.as_ptr() as _) }
+    }
+
+    /// Returns the `MTIMER` peripheral of the mock CLINT.
+    #[inline]
+    pub fn mtimer(&'static self) -> MTIMER {
+        // SAFETY: valid memory addresses during the whole program execution
+        unsafe {
+            MTIMER::new(
+                self.mtimecmps.as_ptr() as _,
+                &self.mtime as *const Reg64 as _,
+            )
+        }
+    }
+
+    /// Returns the current value of the `MTIME` register of the mock CLINT.
+    #[inline]
+    pub fn mtime(&'static self) -> u64 {
+        self.mtimer().mtime.read()
+    }
+
+    /// Sets the `MTIME` register of the mock CLINT to emulate the passage of time.
+    #[inline]
+    pub fn set_mtime(&'static self, value: u64) {
+        self.mtimer().mtime.write(value);
+    }
+
+    /// Asserts that the machine software interrupt of the HART `hart_id` is pending.
+    #[track_caller]
+    pub fn assert_msip_pending<H: HartIdNumber>(&'static self, hart_id: H) {
+        assert!(
+            self.mswi().msip(hart_id).is_pending(),
+            "software interrupt of HART {} is not pending",
+            hart_id.number()
+        );
+    }
+
+    /// Asserts that the machine software interrupt of the HART `hart_id` is not pending.
+    #[track_caller]
+    pub fn assert_msip_not_pending<H: HartIdNumber>(&'static self, hart_id: H) {
+        assert!(
+            !self.mswi().msip(hart_id).is_pending(),
+            "software interrupt of HART {} is pending",
+            hart_id.number()
+        );
+    }
+
+    /// Asserts that the `MTIMECMP` register of the HART `hart_id` is `value`.
+    #[track_caller]
+    pub fn assert_mtimecmp<H: HartIdNumber>(&'static self, hart_id: H, value: u64) {
+        assert_eq!(
+            self.mtimer().mtimecmp(hart_id).read(),
+            value,
+            "unexpected MTIMECMP value of HART {}",
+            hart_id.number()
+        );
+    }
+}
+
+impl Default for MockClint {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::HartId;
+    use super::*;
+
+    #[test]
+    fn test_mock_clint() {
+        static CLINT: MockClint = MockClint::new();
+
+        CLINT.assert_msip_not_pending(HartId::H1);
+        CLINT.assert_mtimecmp(HartId::H1, 0);
+        assert_eq!(CLINT.mtime(), 0);
+
+        CLINT.mswi().msip(HartId::H1).pend();
+        CLINT.set_mtime(100);
+        let mtimer = CLINT.mtimer();
+        mtimer.mtimecmp(HartId::H1).write(mtimer.mtime.read() + 50);
+
+        CLINT.assert_msip_pending(HartId::H1);
+        CLINT.assert_msip_not_pending(HartId::H0);
+        CLINT.assert_mtimecmp(HartId::H1, 150);
+        CLINT.assert_mtimecmp(HartId::H2, 0);
+        assert_eq!(CLINT.mtime(), 100);
+
+        CLINT.mswi().msip(HartId::H1).unpend();
+        CLINT.assert_msip_not_pending(HartId::H1);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected MTIMECMP value of HART 2")]
+    fn test_mock_clint_assert_mtimecmp() {
+        static CLINT: MockClint = MockClint::new();
+
+        CLINT.assert_mtimecmp(HartId::H2, 1);
+    }
+}
@@ -1,7 +1,8 @@
 //! Machine-level Software Interrupt Device.
 
 pub use super::HartIdNumber;
-use crate::common::unsafe_peripheral;
+use super::N_HARTS;
+use crate::common::{unsafe_peripheral, RegArray, RW};
 
 /// MSWI peripheral.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -25,6 +26,13 @@ impl MSWI {
         }
     }
 
+    /// Returns the array of `MSIP` registers of all the HARTs supported by the CLINT specification.
+    #[inline]
+    fn msips(&self) -> RegArray<u32, RW, N_HARTS, 4> {
+        // SAFETY: `msip0` is the first register of the array
+        unsafe { RegArray::new(self.msip0.get_ptr()) }
+    }
+
     /// Returns the `MSIP` register for the HART which ID is `hart_id`.
     ///
     /// # Note
@@ -33,7 +41,7 @@ impl MSWI {
     #[inline]
     pub fn msip<H: HartIdNumber>(&self, hart_id: H) -> MSIP {
         // SAFETY: `hart_id` is valid for the target
-        unsafe { MSIP::new(self.msip0.get_ptr().add(hart_id.number()) as _) }
+        unsafe { MSIP::new(self.msips().get_unchecked(hart_id.number()).get_ptr() as _) }
     }
 
     /// Returns the `MSIP` register for the current HART.
@@ -46,7 +54,7 @@ impl MSWI {
     pub fn msip_mhartid(&self) -> MSIP {
         let hart_id = riscv::register::mhartid::read();
         // SAFETY: `hart_id` is valid for the target and is the current hart
-        unsafe { MSIP::new(self.msip0.get_ptr().add(hart_id) as _) }
+        unsafe { MSIP::new(self.msips().get_unchecked(hart_id).get_ptr() as _) }
     }
 }
 
@@ -60,9 +68,15 @@ impl MSIP {
     }
 
     /// Writes to the register to trigger a machine software interrupt.
+    ///
+    /// # Note
+    ///
+    /// All the previous memory accesses are ordered before the interrupt is triggered
+    /// (see [`Reg::write_ordered`](crate::common::Reg::write_ordered)). Thus, the target HART
+    /// observes the data written before sending the inter-processor interrupt.
     #[inline]
     pub fn pend(self) {
-        self.register.write(1);
+        self.register.write_ordered(1);
     }
 
     /// Clears the register to unpend a machine software interrupt.
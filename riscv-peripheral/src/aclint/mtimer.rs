@@ -1,7 +1,9 @@
 //! Machine-level Timer Device.
 
 pub use super::HartIdNumber;
-use crate::common::safe_peripheral;
+use super::N_HARTS;
+use crate::common::{safe_peripheral, RegArray, RW};
+use riscv_pac::result::{Error, Result};
 
 /// MTIMER peripheral.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -27,6 +29,18 @@ impl MTIMER {
         }
     }
 
+    /// Returns the array of `MTIMECMP` registers of all the HARTs supported by the CLINT specification.
+    ///
+    /// # Note
+    ///
+    /// Only the `MTIMECMP` registers of the HARTs present in the target are valid.
+    /// Use [`MTIMER::mtimecmp`] to access the `MTIMECMP` register of a given HART.
+    #[inline]
+    pub fn mtimecmps(&self) -> RegArray<u64, RW, N_HARTS, 8> {
+        // SAFETY: `mtimecmp0` is the first register of the array
+        unsafe { RegArray::new(self.mtimecmp0.get_ptr()) }
+    }
+
     /// Returns the `MTIMECMP` register for the HART which ID is `hart_id`.
     ///
     /// # Note
@@ -35,7 +49,7 @@ impl MTIMER {
     #[inline]
     pub fn mtimecmp<H: HartIdNumber>(&self, hart_id: H) -> MTIMECMP {
         // SAFETY: `hart_id` is valid for the target
-        unsafe { MTIMECMP::new(self.mtimecmp0.get_ptr().add(hart_id.number()) as _) }
+        unsafe { MTIMECMP::new(self.mtimecmps().get_unchecked(hart_id.number()).get_ptr() as _) }
     }
 
     /// Returns the `MTIMECMP` register for the current HART.
@@ -48,7 +62,63 @@ impl MTIMER {
     pub fn mtimecmp_mhartid(&self) -> MTIMECMP {
         let hart_id = riscv::register::mhartid::read();
         // SAFETY: `hart_id` is valid for the target and is the current hart
-        unsafe { MTIMECMP::new(self.mtimecmp0.get_ptr().add(hart_id) as _) }
+        unsafe { MTIMECMP::new(self.mtimecmps().get_unchecked(hart_id).get_ptr() as _) }
+    }
+
+    /// Returns the number of `MTIMECMP` values saved by [`MTIMER::suspend_state`].
+    #[inline]
+    pub const fn state_len<H: HartIdNumber>() -> usize {
+        H::MAX_HART_ID_NUMBER + 1
+    }
+
+    /// Saves the `MTIMECMP` registers of all the HARTs up to `H::MAX_HART_ID_NUMBER` into `state`
+    /// before power-gating the timer (e.g., in deep sleep). It returns the number of values written.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::IndexOutOfBounds`] with the length of `state` as index
+    /// if `state` is shorter than [`MTIMER::state_len`].
+    ///
+    /// # Note
+    ///
+    /// The `MTIME` register is not saved, as it must keep counting in an always-on domain
+    /// for timer interrupts to be triggered at the right time after resuming.
+    #[inline]
+    pub fn suspend_state<H: HartIdNumber>(&self, state: &mut [u64]) -> Result<usize> {
+        let len = check_state_len::<H>(state.len())?;
+        for (mtimecmp, value) in self.mtimecmps().iter().zip(&mut state[..len]) {
+            *value = mtimecmp.read();
+        }
+        Ok(len)
+    }
+
+    /// Restores the `MTIMECMP` registers saved by [`MTIMER::suspend_state`] once the timer is powered again.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::IndexOutOfBounds`] with the length of `state` as index
+    /// if `state` is shorter than [`MTIMER::state_len`].
+    #[inline]
+    pub fn resume_state<H: HartIdNumber>(&self, state: &[u64]) -> Result<()> {
+        let len = check_state_len::<H>(state.len())?;
+        for (mtimecmp, &value) in self.mtimecmps().iter().zip(&state[..len]) {
+            mtimecmp.write(value);
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a state buffer of `len` values can hold the `MTIMECMP` registers of all the HARTs.
+#[inline]
+fn check_state_len<H: HartIdNumber>(len: usize) -> Result<usize> {
+    let min = MTIMER::state_len::<H>();
+    match len >= min {
+        true => Ok(min),
+        false => Err(Error::IndexOutOfBounds {
+            index: len,
+            min,
+            max: usize::MAX,
+        }),
     }
 }
 
@@ -84,9 +154,68 @@ mod test {
             raw_mtimecmp.as_ptr().offset(2)
         }
             as usize);
+        for (i, mtimecmp) in mtimer
+            .mtimecmps()
+            .iter()
+            .take(HartId::MAX_HART_ID_NUMBER + 1)
+            .enumerate()
+        {
+            assert_eq!(
+                mtimecmp.get_ptr() as usize,
+                unsafe { raw_mtimecmp.as_ptr().add(i) } as usize
+            );
+        }
+        assert!(mtimer.mtimecmps().get(N_HARTS).is_err());
         assert_eq!(
             mtimer.mtime.get_ptr() as usize,
             &raw_mtime as *const u64 as _
         );
     }
+
+    #[test]
+    fn check_suspend_resume_state() {
+        // slice to emulate the mtimecmp registers
+        let mut raw_mtimecmp = [0u64; HartId::MAX_HART_ID_NUMBER as usize + 1];
+        let raw_mtime = 0u64;
+        // SAFETY: valid memory addresses
+        let mtimer = unsafe {
+            MTIMER::new(
+                raw_mtimecmp.as_mut_ptr() as _,
+                &raw_mtime as *const u64 as _,
+            )
+        };
+
+        assert_eq!(MTIMER::state_len::<HartId>(), 3);
+        let mut state = [0u64; 4];
+        assert_eq!(
+            mtimer.suspend_state::<HartId>(&mut state[..2]),
+            Err(Error::IndexOutOfBounds {
+                index: 2,
+                min: 3,
+                max: usize::MAX
+            })
+        );
+
+        mtimer.mtimecmp(HartId::H0).write(0x1000);
+        mtimer.mtimecmp(HartId::H1).write(u64::MAX);
+        mtimer.mtimecmp(HartId::H2).write(0x1234_5678_9abc);
+        assert_eq!(mtimer.suspend_state::<HartId>(&mut state), Ok(3));
+        assert_eq!(state, [0x1000, u64::MAX, 0x1234_5678_9abc, 0]);
+
+        for mtimecmp in mtimer.mtimecmps().iter().take(3) {
+            mtimecmp.write(0);
+        }
+        assert_eq!(
+            mtimer.resume_state::<HartId>(&state[..2]),
+            Err(Error::IndexOutOfBounds {
+                index: 2,
+                min: 3,
+                max: usize::MAX
+            })
+        );
+        assert_eq!(mtimer.resume_state::<HartId>(&state), Ok(()));
+        assert_eq!(mtimer.mtimecmp(HartId::H0).read(), 0x1000);
+        assert_eq!(mtimer.mtimecmp(HartId::H1).read(), u64::MAX);
+        assert_eq!(mtimer.mtimecmp(HartId::H2).read(), 0x1234_5678_9abc);
+    }
 }
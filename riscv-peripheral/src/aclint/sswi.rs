@@ -1,7 +1,8 @@
 //! Supervisor-level Software Interrupt Device.
 
 pub use super::HartIdNumber;
-use crate::common::unsafe_peripheral;
+use super::N_HARTS;
+use crate::common::{unsafe_peripheral, RegArray, RW};
 
 /// SSWI peripheral.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -25,6 +26,13 @@ impl SSWI {
         }
     }
 
+    /// Returns the array of `SETSSIP` registers of all the HARTs supported by the CLINT specification.
+    #[inline]
+    fn setssips(&self) -> RegArray<u32, RW, N_HARTS, 4> {
+        // SAFETY: `setssip0` is the first register of the array
+        unsafe { RegArray::new(self.setssip0.get_ptr()) }
+    }
+
     /// Returns `true` if a supervisor software interrupt is pending.
     #[inline]
     pub fn is_interrupting() -> bool {
@@ -64,7 +72,7 @@ impl SSWI {
     #[inline]
     pub fn setssip<H: HartIdNumber>(&self, hart_id: H) -> SETSSIP {
         // SAFETY: `hart_id` is valid for the target
-        unsafe { SETSSIP::new(self.setssip0.get_ptr().add(hart_id.number()) as _) }
+        unsafe { SETSSIP::new(self.setssips().get_unchecked(hart_id.number()).get_ptr() as _) }
     }
 }
 
@@ -78,9 +86,15 @@ impl SETSSIP {
     }
 
     /// Writes to the register to trigger a supervisor software interrupt.
+    ///
+    /// # Note
+    ///
+    /// All the previous memory accesses are ordered before the interrupt is triggered
+    /// (see [`Reg::write_ordered`](crate::common::Reg::write_ordered)). Thus, the target HART
+    /// observes the data written before sending the inter-processor interrupt.
     #[inline]
     pub fn pend(self) {
-        self.register.write(1);
+        self.register.write_ordered(1);
     }
 
     /// Clears the register to unpend a supervisor software interrupt.
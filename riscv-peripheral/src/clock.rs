@@ -0,0 +1,130 @@
+//! Time sources.
+//!
+//! The [`Clock`] trait abstracts a free-running 64-bit time counter, so higher-level utilities
+//! (e.g., delays, timeouts, or monotonic timers) can be generic over the time source.
+//! This crate provides the following time sources:
+//!
+//! - [`MtimeClock`]: the memory-mapped `MTIME` register of the (A)CLINT peripheral. It is usually only accessible in M-mode.
+//! - [`TimeCsrClock`]: the `time` CSR. It is accessible in S-mode and U-mode if allowed by `mcounteren`/`scounteren`.
+
+use crate::aclint::mtimer::MTIME;
+
+/// Free-running 64-bit time counter.
+pub trait Clock {
+    /// Returns the current value of the time counter.
+    fn now_ticks(&self) -> u64;
+
+    /// Returns the frequency (in Hz) of the time counter.
+    fn freq(&self) -> usize;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    #[inline]
+    fn now_ticks(&self) -> u64 {
+        (**self).now_ticks()
+    }
+
+    #[inline]
+    fn freq(&self) -> usize {
+        (**self).freq()
+    }
+}
+
+/// Time source backed by the `MTIME` register of the (A)CLINT peripheral.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MtimeClock {
+    mtime: MTIME,
+    freq: usize,
+}
+
+impl MtimeClock {
+    /// Creates a new `MtimeClock` from the `MTIME` register and its frequency.
+    #[inline]
+    pub const fn new(mtime: MTIME, freq: usize) -> Self {
+        Self { mtime, freq }
+    }
+
+    /// Returns the `MTIME` register.
+    #[inline]
+    pub const fn mtime(&self) -> MTIME {
+        self.mtime
+    }
+}
+
+impl Clock for MtimeClock {
+    #[inline]
+    fn now_ticks(&self) -> u64 {
+        self.mtime.read()
+    }
+
+    #[inline]
+    fn freq(&self) -> usize {
+        self.freq
+    }
+}
+
+/// Time source backed by the `time` CSR.
+///
+/// # Note
+///
+/// The `time` CSR is a read-only shadow of the `MTIME` register. In M-mode, it may not be implemented,
+/// and accessing it raises an illegal instruction exception. Use [`MtimeClock`] in these targets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimeCsrClock {
+    freq: usize,
+}
+
+impl TimeCsrClock {
+    /// Creates a new `TimeCsrClock` with the frequency of the `time` CSR.
+    #[inline]
+    pub const fn new(freq: usize) -> Self {
+        Self { freq }
+    }
+}
+
+impl Clock for TimeCsrClock {
+    #[inline]
+    fn now_ticks(&self) -> u64 {
+        riscv::register::time::read64()
+    }
+
+    #[inline]
+    fn freq(&self) -> usize {
+        self.freq
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn elapsed<C: Clock>(clock: C, t0: u64) -> u64 {
+        clock.now_ticks().wrapping_sub(t0)
+    }
+
+    #[test]
+    fn check_mtime_clock() {
+        let mut raw_mtime = 0u64;
+        // SAFETY: valid memory address
+        let mtime = unsafe { MTIME::new(&mut raw_mtime as *mut u64 as _) };
+        let clock = MtimeClock::new(mtime, 32_768);
+
+        assert_eq!(clock.mtime(), mtime);
+        assert_eq!(clock.freq(), 32_768);
+        assert_eq!(clock.now_ticks(), 0);
+
+        mtime.write(0x1234_5678_9abc_def0);
+        assert_eq!(clock.now_ticks(), 0x1234_5678_9abc_def0);
+        assert_eq!(elapsed(clock, 0x1234_5678_0000_0000), 0x9abc_def0);
+
+        let dyn_clock: &dyn Clock = &clock;
+        assert_eq!(elapsed(dyn_clock, 0x1234_5678_0000_0000), 0x9abc_def0);
+        assert_eq!(dyn_clock.freq(), 32_768);
+    }
+
+    #[test]
+    fn check_time_csr_clock() {
+        let clock = TimeCsrClock::new(10_000_000);
+        assert_eq!(clock.freq(), 10_000_000);
+    }
+}
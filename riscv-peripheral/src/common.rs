@@ -1,5 +1,13 @@
 //! Common definitions for all the peripheral registers.
 
+use riscv_pac::result::{Error, Result};
+
+#[cfg(all(
+    feature = "trace",
+    not(any(target_arch = "riscv32", target_arch = "riscv64"))
+))]
+pub mod trace;
+
 /// Read-only type state for `A` in [`Reg`].
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct RO;
@@ -68,6 +76,123 @@ impl<T: Copy, A: Access> Reg<T, A> {
     }
 }
 
+/// Generic array of `N` registers. `T` and `A` are the data type and access level of each register,
+/// and `STRIDE` is the distance (in bytes) between the addresses of two consecutive registers.
+///
+/// # Note
+///
+/// This structure assumes that it points to `N` valid peripheral registers.
+/// [`RegArray::get`] checks the bounds of the index, so it always returns a valid register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct RegArray<T: Copy, A: Access, const N: usize, const STRIDE: usize> {
+    ptr: *mut T,
+    phantom: core::marker::PhantomData<A>,
+}
+
+unsafe impl<T: Copy + Send, A: Access, const N: usize, const STRIDE: usize> Send
+    for RegArray<T, A, N, STRIDE>
+{
+}
+unsafe impl<T: Copy + Sync, A: Access, const N: usize, const STRIDE: usize> Sync
+    for RegArray<T, A, N, STRIDE>
+{
+}
+
+impl<T: Copy, A: Access, const N: usize, const STRIDE: usize> RegArray<T, A, N, STRIDE> {
+    /// Creates a new register array from a pointer to its first register.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be valid and must be correctly aligned.
+    /// The `N` registers, separated `STRIDE` bytes, must also be valid and correctly aligned.
+    #[inline]
+    pub const unsafe fn new(ptr: *mut T) -> Self {
+        Self {
+            ptr,
+            phantom: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns a pointer to the first register of the array.
+    #[inline]
+    pub const fn get_ptr(self) -> *mut T {
+        self.ptr
+    }
+
+    /// Returns the number of registers of the array.
+    #[inline]
+    pub const fn len(self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the array has no registers.
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        N == 0
+    }
+
+    /// Returns the `i`th register of the array.
+    ///
+    /// # Errors
+    ///
+    /// If `i` is out of bounds, it returns [`Error::IndexOutOfBounds`].
+    #[inline]
+    pub fn get(self, i: usize) -> Result<Reg<T, A>> {
+        if i < N {
+            // SAFETY: index within bounds
+            Ok(unsafe { self.get_unchecked(i) })
+        } else {
+            Err(Error::IndexOutOfBounds {
+                index: i,
+                min: 0,
+                max: N.saturating_sub(1),
+            })
+        }
+    }
+
+    /// Returns the `i`th register of the array without checking the bounds of the index.
+    ///
+    /// # Safety
+    ///
+    /// `i` must be lower than `N`.
+    #[inline]
+    pub const unsafe fn get_unchecked(self, i: usize) -> Reg<T, A> {
+        Reg::new(self.ptr.cast::<u8>().add(i * STRIDE).cast())
+    }
+
+    /// Returns an iterator over the registers of the array.
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = Reg<T, A>> {
+        // SAFETY: indices within bounds
+        (0..N).map(move |i| unsafe { self.get_unchecked(i) })
+    }
+}
+
+/// Orders all the previous device input and output before all the subsequent memory reads and writes.
+#[inline(always)]
+fn fence_io_rw() {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        // SAFETY: fences have no side effects
+        () => unsafe { core::arch::asm!("fence io, rw", options(nostack, preserves_flags)) },
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst),
+    }
+}
+
+/// Orders all the previous memory reads and writes before all the subsequent device input and output.
+#[inline(always)]
+fn fence_rw_io() {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        // SAFETY: fences have no side effects
+        () => unsafe { core::arch::asm!("fence rw, io", options(nostack, preserves_flags)) },
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst),
+    }
+}
+
 impl<T: Copy, A: Read> Reg<T, A> {
     /// Performs a volatile read of the peripheral register with no side effects.
     ///
@@ -77,7 +202,30 @@ impl<T: Copy, A: Read> Reg<T, A> {
     #[inline]
     pub fn read(self) -> T {
         // SAFETY: valid address and register is readable
-        unsafe { self.ptr.read_volatile() }
+        let val = unsafe { self.ptr.read_volatile() };
+        #[cfg(all(
+            feature = "trace",
+            not(any(target_arch = "riscv32", target_arch = "riscv64"))
+        ))]
+        trace::record(trace::Kind::Read, self.ptr, val);
+        val
+    }
+
+    /// Performs a volatile read of the peripheral register followed by a `fence io, rw` instruction.
+    ///
+    /// The read is ordered before all the subsequent memory accesses. Use it when the value of the
+    /// register tells that some memory is ready (e.g., a DMA transfer status or an interrupt claim).
+    /// Otherwise, the hart could read stale memory contents.
+    ///
+    /// # Note
+    ///
+    /// In non-`riscv` targets, the fence instruction is replaced by a sequentially consistent
+    /// [`core::sync::atomic::fence`].
+    #[inline]
+    pub fn read_ordered(self) -> T {
+        let val = self.read();
+        fence_io_rw();
+        val
     }
 }
 
@@ -89,9 +237,30 @@ impl<T: Copy, A: Write> Reg<T, A> {
     /// If you want to perform a read-modify-write operation, use [`Reg::modify`] instead.
     #[inline]
     pub fn write(self, val: T) {
+        #[cfg(all(
+            feature = "trace",
+            not(any(target_arch = "riscv32", target_arch = "riscv64"))
+        ))]
+        trace::record(trace::Kind::Write, self.ptr, val);
         // SAFETY: valid address and register is writable
         unsafe { self.ptr.write_volatile(val) }
     }
+
+    /// Performs a `fence rw, io` instruction followed by a volatile write of the peripheral register.
+    ///
+    /// All the previous memory accesses are ordered before the write. Use it when the write
+    /// makes a device or another hart use some memory (e.g., starting a DMA transfer or sending
+    /// an inter-processor interrupt). Otherwise, they could observe stale memory contents.
+    ///
+    /// # Note
+    ///
+    /// In non-`riscv` targets, the fence instruction is replaced by a sequentially consistent
+    /// [`core::sync::atomic::fence`].
+    #[inline]
+    pub fn write_ordered(self, val: T) {
+        fence_rw_io();
+        self.write(val)
+    }
 }
 
 impl<T: Copy, A: Read + Write> Reg<T, A> {
@@ -177,6 +346,13 @@ bitwise_reg!(i128);
 bitwise_reg!(isize);
 
 /// Macro to provide atomic bit-wise operations to integer number registers.
+#[cfg(any(
+    target_has_atomic = "8",
+    target_has_atomic = "16",
+    target_has_atomic = "32",
+    target_has_atomic = "64",
+    target_has_atomic = "ptr"
+))]
 macro_rules! bitwise_atomic_reg {
     ($TYPE: ty, $ATOMIC: ty) => {
         impl<A: Read + Write> Reg<$TYPE, A> {
@@ -214,10 +390,176 @@ macro_rules! bitwise_atomic_reg {
                 // SAFETY: guaranteed by the caller
                 unsafe { self.as_atomic() }.fetch_or(1 << n, order);
             }
+
+            /// Writes a range of bits of the register specified by the `start` and `end` indexes
+            /// (both included) atomically, and returns the previous value of the register.
+            ///
+            /// The rest of the bits are preserved, even if other harts modify them concurrently.
+            ///
+            /// # Safety
+            ///
+            /// * Register must be properly aligned **for atomic operations**.
+            /// * The register must not be accessed through non-atomic operations until this function returns.
+            #[inline]
+            pub unsafe fn atomic_write_bits(
+                &self,
+                start: usize,
+                end: usize,
+                val: $TYPE,
+                order: core::sync::atomic::Ordering,
+            ) -> $TYPE {
+                let n_bits = end - start + 1;
+                let mask = ((1 << n_bits) - 1) << start;
+                let bits = (val << start) & mask;
+                // SAFETY: guaranteed by the caller
+                unsafe { self.atomic_modify(|v| (v & !mask) | bits, order) }
+            }
+
+            /// Modifies the value of the register atomically according to a given function `f`,
+            /// and returns the previous value of the register.
+            ///
+            /// The operation is a compare-and-swap loop (i.e., `lr`/`sc` sequence). Thus,
+            /// `f` may be called several times if other harts modify the register concurrently.
+            ///
+            /// # Safety
+            ///
+            /// * Register must be properly aligned **for atomic operations**.
+            /// * The register must not be accessed through non-atomic operations until this function returns.
+            #[inline]
+            pub unsafe fn atomic_modify(
+                &self,
+                mut f: impl FnMut($TYPE) -> $TYPE,
+                order: core::sync::atomic::Ordering,
+            ) -> $TYPE {
+                // SAFETY: guaranteed by the caller
+                let atomic = unsafe { self.as_atomic() };
+                match atomic.fetch_update(order, load_ordering(order), |v| Some(f(v))) {
+                    Ok(prev) | Err(prev) => prev,
+                }
+            }
+
+            /// Writes a value to the register atomically (i.e., `amoswap` instruction),
+            /// and returns the previous value of the register.
+            ///
+            /// # Safety
+            ///
+            /// * Register must be properly aligned **for atomic operations**.
+            /// * The register must not be accessed through non-atomic operations until this function returns.
+            #[inline]
+            pub unsafe fn atomic_swap(
+                &self,
+                val: $TYPE,
+                order: core::sync::atomic::Ordering,
+            ) -> $TYPE {
+                // SAFETY: guaranteed by the caller
+                unsafe { self.as_atomic() }.swap(val, order)
+            }
         }
     };
 }
 
+/// Macro to provide the atomic read-modify-write operations of [`bitwise_atomic_reg`] to
+/// integer number registers in targets without atomic instructions (i.e., without the `A` extension).
+///
+/// The operations are performed with interrupts disabled. Thus, they are only atomic with respect
+/// to the current hart, and the ordering parameter is ignored.
+#[cfg(not(all(
+    target_has_atomic = "8",
+    target_has_atomic = "16",
+    target_has_atomic = "32",
+    target_has_atomic = "64",
+    target_has_atomic = "ptr"
+)))]
+macro_rules! bitwise_atomic_reg_fallback {
+    ($TYPE: ty) => {
+        impl<A: Read + Write> Reg<$TYPE, A> {
+            /// Writes a range of bits of the register specified by the `start` and `end` indexes
+            /// (both included) with interrupts disabled, and returns the previous value of the register.
+            ///
+            /// # Note
+            ///
+            /// This target does not support atomic instructions. Thus, the operation is only
+            /// atomic with respect to the current hart, and `order` is ignored.
+            ///
+            /// # Safety
+            ///
+            /// Other harts and devices must not modify the register until this function returns.
+            #[inline]
+            pub unsafe fn atomic_write_bits(
+                &self,
+                start: usize,
+                end: usize,
+                val: $TYPE,
+                order: core::sync::atomic::Ordering,
+            ) -> $TYPE {
+                let n_bits = end - start + 1;
+                let mask = ((1 << n_bits) - 1) << start;
+                let bits = (val << start) & mask;
+                // SAFETY: guaranteed by the caller
+                unsafe { self.atomic_modify(|v| (v & !mask) | bits, order) }
+            }
+
+            /// Modifies the value of the register with interrupts disabled according to a given function `f`,
+            /// and returns the previous value of the register.
+            ///
+            /// # Note
+            ///
+            /// This target does not support atomic instructions. Thus, the operation is only
+            /// atomic with respect to the current hart, and `order` is ignored.
+            ///
+            /// # Safety
+            ///
+            /// Other harts and devices must not modify the register until this function returns.
+            #[inline]
+            pub unsafe fn atomic_modify(
+                &self,
+                mut f: impl FnMut($TYPE) -> $TYPE,
+                _order: core::sync::atomic::Ordering,
+            ) -> $TYPE {
+                riscv::interrupt::free(|| {
+                    self.modify(|v| {
+                        let prev = *v;
+                        *v = f(prev);
+                        prev
+                    })
+                })
+            }
+
+            /// Writes a value to the register with interrupts disabled, and returns the previous value of the register.
+            ///
+            /// # Note
+            ///
+            /// This target does not support atomic instructions. Thus, the operation is only
+            /// atomic with respect to the current hart, and `order` is ignored.
+            ///
+            /// # Safety
+            ///
+            /// Other harts and devices must not modify the register until this function returns.
+            #[inline]
+            pub unsafe fn atomic_swap(
+                &self,
+                val: $TYPE,
+                order: core::sync::atomic::Ordering,
+            ) -> $TYPE {
+                // SAFETY: guaranteed by the caller
+                unsafe { self.atomic_modify(|_| val, order) }
+            }
+        }
+    };
+}
+
+/// Returns the ordering of the load operation of a compare-and-swap loop with the given `order`.
+#[cfg_attr(not(target_has_atomic = "8"), allow(dead_code))]
+#[inline]
+const fn load_ordering(order: core::sync::atomic::Ordering) -> core::sync::atomic::Ordering {
+    use core::sync::atomic::Ordering;
+    match order {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        order => order,
+    }
+}
+
 #[cfg(target_has_atomic = "8")]
 bitwise_atomic_reg!(u8, core::sync::atomic::AtomicU8);
 #[cfg(target_has_atomic = "16")]
@@ -238,6 +580,26 @@ bitwise_atomic_reg!(i32, core::sync::atomic::AtomicI32);
 bitwise_atomic_reg!(i64, core::sync::atomic::AtomicI64);
 #[cfg(target_has_atomic = "ptr")]
 bitwise_atomic_reg!(isize, core::sync::atomic::AtomicIsize);
+#[cfg(not(target_has_atomic = "8"))]
+bitwise_atomic_reg_fallback!(u8);
+#[cfg(not(target_has_atomic = "16"))]
+bitwise_atomic_reg_fallback!(u16);
+#[cfg(not(target_has_atomic = "32"))]
+bitwise_atomic_reg_fallback!(u32);
+#[cfg(not(target_has_atomic = "64"))]
+bitwise_atomic_reg_fallback!(u64);
+#[cfg(not(target_has_atomic = "ptr"))]
+bitwise_atomic_reg_fallback!(usize);
+#[cfg(not(target_has_atomic = "8"))]
+bitwise_atomic_reg_fallback!(i8);
+#[cfg(not(target_has_atomic = "16"))]
+bitwise_atomic_reg_fallback!(i16);
+#[cfg(not(target_has_atomic = "32"))]
+bitwise_atomic_reg_fallback!(i32);
+#[cfg(not(target_has_atomic = "64"))]
+bitwise_atomic_reg_fallback!(i64);
+#[cfg(not(target_has_atomic = "ptr"))]
+bitwise_atomic_reg_fallback!(isize);
 
 /// Macro to define the archetypal behavior of registers.
 macro_rules! peripheral {
@@ -405,3 +767,61 @@ mod sealed {
     impl Access for WO {}
     impl Access for RW {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reg_array() {
+        // array of 64-bit registers with 64-bit gaps in between
+        let mut raw_reg = [0u64; 8];
+        // SAFETY: valid memory address
+        let regs: RegArray<u64, RW, 4, 16> = unsafe { RegArray::new(raw_reg.as_mut_ptr() as _) };
+        assert_eq!(regs.len(), 4);
+        assert!(!regs.is_empty());
+
+        for (i, reg) in regs.iter().enumerate() {
+            assert_eq!(reg.get_ptr() as usize, raw_reg.as_ptr() as usize + i * 16);
+            assert_eq!(regs.get(i).unwrap(), reg);
+            reg.write(i as u64);
+            assert_eq!(raw_reg[2 * i], i as u64);
+        }
+        assert_eq!(
+            regs.get(4),
+            Err(Error::IndexOutOfBounds {
+                index: 4,
+                min: 0,
+                max: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_atomic_reg() {
+        use core::sync::atomic::Ordering;
+
+        let mut raw_reg = 0u32;
+        // SAFETY: valid memory address
+        let reg: Reg<u32, RW> = unsafe { Reg::new(&mut raw_reg) };
+
+        // SAFETY: the register is aligned and only accessed atomically
+        unsafe {
+            assert_eq!(reg.atomic_swap(0xffff_0000, Ordering::SeqCst), 0);
+            assert_eq!(
+                reg.atomic_write_bits(4, 19, 0x1234, Ordering::AcqRel),
+                0xffff_0000
+            );
+            assert_eq!(
+                reg.atomic_modify(|v| v ^ 0xf, Ordering::Release),
+                0xfff1_2340
+            );
+            assert_eq!(reg.atomic_swap(0, Ordering::Relaxed), 0xfff1_234f);
+        }
+        assert_eq!(raw_reg, 0);
+
+        assert_eq!(load_ordering(Ordering::Release), Ordering::Relaxed);
+        assert_eq!(load_ordering(Ordering::AcqRel), Ordering::Acquire);
+        assert_eq!(load_ordering(Ordering::SeqCst), Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,169 @@
+//! Tracing of peripheral register accesses for host-side testing.
+//!
+//! When the `trace` feature is enabled in non-`riscv` targets, [`Reg::read`](super::Reg::read) and
+//! [`Reg::write`](super::Reg::write) record every access in a thread-local log. Unit tests can then
+//! check the exact sequence of register accesses performed by a driver (e.g., a claim/complete
+//! sequence of a PLIC context) without hardware. As the log is thread-local, tests running in
+//! parallel do not interfere with each other.
+//!
+//! # Example
+//!
+//! ```
+//! use riscv_peripheral::common::{trace, Reg, RW};
+//!
+//! let mut raw_reg = 0u32;
+//! let reg: Reg<u32, RW> = unsafe { Reg::new(&mut raw_reg) };
+//!
+//! trace::clear();
+//! reg.write(0x10);
+//! reg.modify(|val| *val |= 1);
+//!
+//! let addr = reg.get_ptr() as usize;
+//! assert_eq!(
+//!     trace::take(),
+//!     [
+//!         trace::Access::write(addr, 0x10),
+//!         trace::Access::read(addr, 0x10),
+//!         trace::Access::write(addr, 0x11),
+//!     ]
+//! );
+//! ```
+//!
+//! # Note
+//!
+//! Atomic accesses (e.g., [`Reg::atomic_set_bit`](super::Reg::atomic_set_bit)) are not recorded.
+//! Values are recorded as the raw bits of the register, so only registers of up to 16 bytes are fully recorded.
+
+use std::{cell::RefCell, thread_local, vec::Vec};
+
+/// Kind of register access.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Read of the register.
+    Read,
+    /// Write of the register.
+    Write,
+}
+
+/// Register access recorded in the log.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Access {
+    /// Kind of access.
+    pub kind: Kind,
+    /// Address of the register.
+    pub addr: usize,
+    /// Value read from or written to the register.
+    pub value: u128,
+}
+
+impl Access {
+    /// Creates a new read access.
+    #[inline]
+    pub const fn read(addr: usize, value: u128) -> Self {
+        Self {
+            kind: Kind::Read,
+            addr,
+            value,
+        }
+    }
+
+    /// Creates a new write access.
+    #[inline]
+    pub const fn write(addr: usize, value: u128) -> Self {
+        Self {
+            kind: Kind::Write,
+            addr,
+            value,
+        }
+    }
+}
+
+thread_local! {
+    static LOG: RefCell<Vec<Access>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns the raw bits of `val`, zero-extended to 128 bits.
+#[inline]
+fn to_bits<T: Copy>(val: T) -> u128 {
+    let mut bits = 0u128;
+    let len = core::mem::size_of::<T>().min(core::mem::size_of::<u128>());
+    // SAFETY: both pointers are valid for `len` bytes and do not overlap
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            &val as *const T as *const u8,
+            &mut bits as *mut u128 as *mut u8,
+            len,
+        )
+    };
+    u128::from_le(bits)
+}
+
+/// Records an access to the register at `addr`.
+#[inline]
+pub(super) fn record<T: Copy>(kind: Kind, addr: *mut T, val: T) {
+    let access = Access {
+        kind,
+        addr: addr as usize,
+        value: to_bits(val),
+    };
+    LOG.with(|log| log.borrow_mut().push(access));
+}
+
+/// Clears the log of the current thread.
+#[inline]
+pub fn clear() {
+    LOG.with(|log| log.borrow_mut().clear());
+}
+
+/// Returns the log of the current thread, leaving it empty.
+#[inline]
+pub fn take() -> Vec<Access> {
+    LOG.with(|log| log.take())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::plic::{mock::MockPlic, test::Interrupt};
+
+    #[test]
+    fn test_trace() {
+        let mut raw_reg = [0u64; 2];
+        // SAFETY: valid memory address
+        let reg =
+            unsafe { crate::common::Reg::<u64, crate::common::RW>::new(raw_reg.as_mut_ptr()) };
+        let addr = raw_reg.as_ptr() as usize;
+
+        clear();
+        reg.write(u64::MAX);
+        assert_eq!(reg.read(), u64::MAX);
+        reg.clear_bit(63);
+        assert_eq!(
+            take(),
+            [
+                Access::write(addr, u64::MAX as u128),
+                Access::read(addr, u64::MAX as u128),
+                Access::read(addr, u64::MAX as u128),
+                Access::write(addr, (u64::MAX >> 1) as u128),
+            ]
+        );
+        assert_eq!(take(), []);
+    }
+
+    #[test]
+    fn test_trace_plic() {
+        static PLIC: MockPlic = MockPlic::new();
+
+        let claim = PLIC.claim();
+        claim.complete(Interrupt::I3);
+
+        clear();
+        let interrupt = claim.claim::<Interrupt>().unwrap();
+        claim.complete(interrupt);
+        // the interrupt must be completed with the claimed source
+        let log = take();
+        assert_eq!(log.len(), 2);
+        assert_eq!((log[0].kind, log[0].value), (Kind::Read, 3));
+        assert_eq!(log[1], Access::write(log[0].addr, 3));
+    }
+}
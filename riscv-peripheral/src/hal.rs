@@ -3,3 +3,6 @@
 pub use embedded_hal::*; // re-export embedded-hal to allow macros to use it
 
 pub mod aclint; // ACLINT and CLINT peripherals
+pub mod clock; // generic time sources
+#[cfg(feature = "embedded-io")]
+pub mod uart; // 16550-compatible UART
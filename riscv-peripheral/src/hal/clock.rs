@@ -0,0 +1,126 @@
+//! Delay trait implementation for generic time sources
+
+use crate::clock::{Clock, TimeCsrClock};
+pub use crate::hal::delay::DelayNs;
+
+/// Delay implementation for any [`Clock`] time source.
+pub struct Delay<C> {
+    clock: C,
+}
+
+impl<C: Clock> Delay<C> {
+    /// Creates a new `Delay` instance.
+    #[inline]
+    pub const fn new(clock: C) -> Self {
+        Self { clock }
+    }
+
+    /// Returns the time source.
+    #[inline]
+    pub const fn clock(&self) -> &C {
+        &self.clock
+    }
+}
+
+impl<C: Clock> DelayNs for Delay<C> {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        wait_ticks(&self.clock, ns_to_ticks(ns, self.clock.freq()));
+    }
+}
+
+/// Delay implementation based on the `time` CSR.
+///
+/// It is intended for S-mode targets where neither `mcycle` nor the (A)CLINT are accessible
+/// (e.g., guests of a hypervisor). The `time` CSR must be readable from S-mode (see `mcounteren`).
+pub type SupervisorDelay = Delay<TimeCsrClock>;
+
+/// Converts a duration in nanoseconds to a number of ticks of a clock running at `freq` Hz.
+///
+/// The result is rounded up, so the delay is never shorter than requested.
+/// The product cannot overflow in RV32 targets, as both operands are 32-bit wide.
+#[inline]
+fn ns_to_ticks(ns: u32, freq: usize) -> u64 {
+    u64::from(ns)
+        .saturating_mul(freq as u64)
+        .div_ceil(1_000_000_000)
+}
+
+/// Busy-waits until `n_ticks` ticks of `clock` have elapsed.
+///
+/// The elapsed time is computed with wrapping arithmetic, so it is correct even if the
+/// counter overflows during the delay (e.g., the carry from `time` to `timeh` in RV32 targets).
+#[inline]
+fn wait_ticks<C: Clock>(clock: C, n_ticks: u64) {
+    let t0 = clock.now_ticks();
+    while clock.now_ticks().wrapping_sub(t0) < n_ticks {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    /// Time source that advances one tick per read.
+    struct MockClock {
+        ticks: Cell<u64>,
+        freq: usize,
+    }
+
+    impl Clock for MockClock {
+        fn now_ticks(&self) -> u64 {
+            let ticks = self.ticks.get();
+            self.ticks.set(ticks.wrapping_add(1));
+            ticks
+        }
+
+        fn freq(&self) -> usize {
+            self.freq
+        }
+    }
+
+    #[test]
+    fn check_delay() {
+        let clock = MockClock {
+            ticks: Cell::new(u64::MAX - 10),
+            freq: 1_000_000,
+        };
+        let mut delay = Delay::new(&clock);
+
+        delay.delay_us(100);
+        // 1 read for t0, and 100 reads until 100 ticks elapse
+        assert_eq!(clock.ticks.get(), (u64::MAX - 10).wrapping_add(101));
+
+        delay.delay_ns(500);
+        assert_eq!(delay.clock().ticks.get(), (u64::MAX - 10).wrapping_add(103));
+    }
+
+    #[test]
+    fn check_ns_to_ticks() {
+        assert_eq!(ns_to_ticks(0, 10_000_000), 0);
+        assert_eq!(ns_to_ticks(100, 10_000_000), 1);
+        assert_eq!(ns_to_ticks(101, 10_000_000), 2); // rounded up
+        assert_eq!(ns_to_ticks(1_000_000_000, 32_768), 32_768);
+        // largest values in RV32 targets
+        let max = u32::MAX as u64;
+        assert_eq!(
+            ns_to_ticks(u32::MAX, u32::MAX as usize),
+            (max * max).div_ceil(1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn check_wait_ticks() {
+        // the counter overflows during the delay
+        let clock = MockClock {
+            ticks: Cell::new(u64::MAX - 2),
+            freq: 1_000_000,
+        };
+        wait_ticks(&clock, 5);
+        // 1 read for t0, and 5 reads until 5 ticks elapse
+        assert_eq!(clock.ticks.get(), (u64::MAX - 2).wrapping_add(6));
+
+        let delay = SupervisorDelay::new(TimeCsrClock::new(32_768));
+        assert_eq!(delay.clock().freq(), 32_768);
+    }
+}
@@ -0,0 +1,87 @@
+//! `embedded-io` trait implementations for the 16550-compatible UART
+
+pub use embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
+
+use crate::uart::UART;
+use core::convert::Infallible;
+
+impl ErrorType for UART {
+    type Error = Infallible;
+}
+
+impl Read for UART {
+    /// Waits until at least one byte is received, and then reads all the available bytes that fit in `buf`.
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let Some((first, rest)) = buf.split_first_mut() else {
+            return Ok(0);
+        };
+        *first = self.read_byte();
+        let n = rest
+            .iter_mut()
+            .map_while(|byte| self.try_read_byte().map(|b| *byte = b))
+            .count();
+        Ok(1 + n)
+    }
+}
+
+impl ReadReady for UART {
+    #[inline]
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_rx_ready())
+    }
+}
+
+impl Write for UART {
+    /// Waits until at least one byte is written, and then writes bytes of `buf` while the transmitter is ready.
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let Some((&first, rest)) = buf.split_first() else {
+            return Ok(0);
+        };
+        self.write_byte(first);
+        let n = rest
+            .iter()
+            .take_while(|&&byte| self.try_write_byte(byte))
+            .count();
+        Ok(1 + n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        UART::flush(*self);
+        Ok(())
+    }
+}
+
+impl WriteReady for UART {
+    #[inline]
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_tx_ready())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_uart_io() {
+        // LSR (offset 5): data ready, transmitter holding register empty, and transmitter empty
+        let mut raw_regs = [0, 0, 0, 0, 0, 0b110_0001, 0, 0u8];
+        // SAFETY: valid memory addresses
+        let mut uart = unsafe { UART::new(raw_regs.as_mut_ptr() as _, 0) };
+
+        assert_eq!(uart.write(&[]), Ok(0));
+        assert_eq!(uart.write(b"abc"), Ok(3));
+        assert_eq!(Write::flush(&mut uart), Ok(()));
+        assert_eq!(uart.write_ready(), Ok(true));
+
+        // the mock always has data ready: the last written byte is read back
+        let mut buf = [0; 4];
+        assert_eq!(uart.read(&mut []), Ok(0));
+        assert_eq!(uart.read(&mut buf), Ok(4));
+        assert_eq!(&buf, b"cccc");
+        assert_eq!(uart.read_ready(), Ok(true));
+    }
+}
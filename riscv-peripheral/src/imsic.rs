@@ -0,0 +1,238 @@
+//! Incoming Message-Signaled Interrupt Controller (IMSIC) peripheral.
+//!
+//! Specification: <https://github.com/riscv/riscv-aia>
+//!
+//! The IMSIC of the Advanced Interrupt Architecture (AIA) receives external interrupts as
+//! message-signaled interrupts (MSIs). Each HART has a machine-level interrupt file, which has
+//! two interfaces:
+//!
+//! - A memory-mapped page where devices and other HARTs write the identity of the interrupt
+//!   to be pended (see [`IMSIC::interrupt_file`]).
+//! - The `miselect`/`mireg` and `mtopei` CSRs of the HART, which configure the interrupt file and
+//!   claim interrupts (see [`LOCAL`]). Thus, the current HART can only configure its own interrupt file.
+//!
+//! # Note
+//!
+//! Supervisor-level and guest interrupt files are not supported yet.
+
+pub use riscv_pac::{ExternalInterruptNumber, HartIdNumber};
+
+use crate::common::unsafe_peripheral;
+use riscv::register::{mireg, mtopei};
+
+/// Trait for an IMSIC peripheral.
+///
+/// # Safety
+///
+/// * This trait must only be implemented on a PAC of a target with an IMSIC peripheral.
+/// * The base address `BASE` and the `STRIDE` between interrupt files must be valid for the target device.
+pub unsafe trait Imsic: Copy {
+    /// Base address of the machine-level interrupt file of HART 0.
+    const BASE: usize;
+    /// Distance (in bytes) between the machine-level interrupt files of two consecutive HARTs.
+    const STRIDE: usize = 0x1000;
+}
+
+/// Incoming Message-Signaled Interrupt Controller (IMSIC) peripheral.
+///
+/// The RISC-V standard does not specify a fixed location for the IMSIC.
+/// Thus, each platform must specify the base address of its interrupt files in the [`Imsic`] trait.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct IMSIC<I: Imsic> {
+    _marker: core::marker::PhantomData<I>,
+}
+
+impl<I: Imsic> IMSIC<I> {
+    /// Returns the memory-mapped machine-level interrupt file of the HART which ID is `hart_id`.
+    #[inline]
+    pub fn interrupt_file<H: HartIdNumber>(hart_id: H) -> SETEIPNUM {
+        // SAFETY: valid address
+        unsafe { SETEIPNUM::new(I::BASE + hart_id.number() * I::STRIDE) }
+    }
+
+    /// Returns the machine-level interrupt file of the current HART, accessed through its CSRs.
+    #[inline]
+    pub const fn local() -> LOCAL {
+        LOCAL
+    }
+}
+
+unsafe_peripheral!(SETEIPNUM, u32, WO);
+
+impl SETEIPNUM {
+    /// Sends a message-signaled interrupt to the interrupt file (i.e., pends the interrupt `source`).
+    ///
+    /// # Note
+    ///
+    /// All the previous memory accesses are ordered before the interrupt is sent
+    /// (see [`Reg::write_ordered`](crate::common::Reg::write_ordered)). Thus, the target HART
+    /// observes the data written before sending the interrupt.
+    #[inline]
+    pub fn send<E: ExternalInterruptNumber>(self, source: E) {
+        self.register.write_ordered(source.number() as _)
+    }
+}
+
+/// Machine-level interrupt file of the current HART, accessed through the AIA CSRs.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LOCAL;
+
+impl LOCAL {
+    const EIDELIVERY: usize = 0x70;
+    const EITHRESHOLD: usize = 0x72;
+    const EIP0: usize = 0x80;
+    const EIE0: usize = 0xc0;
+
+    /// Enables the delivery of interrupts from the interrupt file to the HART.
+    ///
+    /// # Safety
+    ///
+    /// Enabling interrupt delivery can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn enable_delivery(self) {
+        mireg::write_indirect(Self::EIDELIVERY, 1);
+    }
+
+    /// Disables the delivery of interrupts from the interrupt file to the HART.
+    #[inline]
+    pub fn disable_delivery(self) {
+        mireg::write_indirect(Self::EIDELIVERY, 0);
+    }
+
+    /// Returns the interrupt threshold. Only interrupts with an identity lower than
+    /// the threshold are delivered. A threshold of 0 delivers all the enabled interrupts.
+    #[inline]
+    pub fn threshold(self) -> usize {
+        mireg::read_indirect(Self::EITHRESHOLD)
+    }
+
+    /// Sets the interrupt threshold.
+    ///
+    /// # Safety
+    ///
+    /// Changing the threshold can break priority-based critical sections.
+    #[inline]
+    pub unsafe fn set_threshold(self, threshold: usize) {
+        mireg::write_indirect(Self::EITHRESHOLD, threshold);
+    }
+
+    /// Checks if an interrupt source is enabled in the interrupt file.
+    #[inline]
+    pub fn is_enabled<E: ExternalInterruptNumber>(self, source: E) -> bool {
+        let (select, mask) = indirect(Self::EIE0, source.number());
+        mireg::read_indirect(select) & mask != 0
+    }
+
+    /// Enables an interrupt source in the interrupt file.
+    ///
+    /// # Safety
+    ///
+    /// Enabling an interrupt source can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn enable<E: ExternalInterruptNumber>(self, source: E) {
+        let (select, mask) = indirect(Self::EIE0, source.number());
+        modify(select, |bits| bits | mask);
+    }
+
+    /// Disables an interrupt source in the interrupt file.
+    #[inline]
+    pub fn disable<E: ExternalInterruptNumber>(self, source: E) {
+        let (select, mask) = indirect(Self::EIE0, source.number());
+        modify(select, |bits| bits & !mask);
+    }
+
+    /// Checks if an interrupt source is pending in the interrupt file.
+    #[inline]
+    pub fn is_pending<E: ExternalInterruptNumber>(self, source: E) -> bool {
+        let (select, mask) = indirect(Self::EIP0, source.number());
+        mireg::read_indirect(select) & mask != 0
+    }
+
+    /// Pends an interrupt source in the interrupt file from the current HART.
+    ///
+    /// # Note
+    ///
+    /// Other HARTs and devices must use [`SETEIPNUM::send`] instead.
+    #[inline]
+    pub fn pend<E: ExternalInterruptNumber>(self, source: E) {
+        let (select, mask) = indirect(Self::EIP0, source.number());
+        modify(select, |bits| bits | mask);
+    }
+
+    /// Claims the pending and enabled interrupt with the lowest identity (i.e., the highest priority).
+    /// If no interrupt is pending and enabled, it returns [`None`].
+    #[inline]
+    pub fn claim<E: ExternalInterruptNumber>(self) -> Option<E> {
+        match mtopei::claim().iid() {
+            0 => None,
+            i => Some(E::from_number(i).unwrap()),
+        }
+    }
+}
+
+/// Returns the indirect register (relative to `base`) and the bit mask of the interrupt `number`.
+///
+/// Each indirect register holds the bits of 32 interrupts. In RV64, odd-numbered
+/// registers do not exist, and even-numbered registers hold the bits of 64 interrupts.
+#[inline]
+const fn indirect(base: usize, number: usize) -> (usize, usize) {
+    let xlen = usize::BITS as usize;
+    (base + (number / xlen) * (xlen / 32), 1 << (number % xlen))
+}
+
+/// Performs a read-modify-write operation on an indirect register with interrupts disabled.
+#[inline]
+fn modify(select: usize, f: impl FnOnce(usize) -> usize) {
+    riscv::interrupt::machine::free(|| {
+        let bits = mireg::read_indirect(select);
+        mireg::write_indirect(select, f(bits));
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::plic::test::{Context, Interrupt};
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    struct Target;
+
+    // SAFETY: only for testing purposes
+    unsafe impl Imsic for Target {
+        const BASE: usize = 0x2400_0000;
+    }
+
+    #[test]
+    fn test_indirect() {
+        let xlen = usize::BITS as usize;
+        assert_eq!(indirect(LOCAL::EIP0, 0), (0x80, 1));
+        assert_eq!(indirect(LOCAL::EIE0, 5), (0xc0, 1 << 5));
+        assert_eq!(indirect(LOCAL::EIP0, xlen + 1), (0x80 + xlen / 32, 1 << 1));
+        assert_eq!(
+            indirect(LOCAL::EIE0, 2047),
+            (0xc0 + 2047 / xlen * (xlen / 32), 1 << (2047 % xlen))
+        );
+        // the last indirect register is within the EIE range
+        assert!(indirect(LOCAL::EIE0, 2047).0 <= 0xff);
+    }
+
+    #[test]
+    fn test_interrupt_file() {
+        assert_eq!(
+            IMSIC::<Target>::interrupt_file(Context::C0).get_ptr() as usize,
+            0x2400_0000
+        );
+        assert_eq!(
+            IMSIC::<Target>::interrupt_file(Context::C2).get_ptr() as usize,
+            0x2400_2000
+        );
+
+        let mut raw_reg = 0u32;
+        // SAFETY: valid memory address
+        let seteipnum = unsafe { SETEIPNUM::new(&mut raw_reg as *mut _ as _) };
+        seteipnum.send(Interrupt::I3);
+        assert_eq!(raw_reg, 3);
+    }
+}
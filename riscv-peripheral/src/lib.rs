@@ -4,13 +4,28 @@
 //!
 //! - `aclint-hal-async`: enables the [`hal_async::delay::DelayNs`] implementation for the ACLINT peripheral.
 //!   This feature relies on external functions that must be provided by the user. See [`hal_async::aclint`] for more information.
+//! - `trace`: in non-`riscv` targets, records every register read and write in a thread-local log that
+//!   unit tests can inspect. See `common::trace` for more information. This feature requires `std`.
+//! - `tick`: provides the default tick service [`tick::SYSTICK`] and a `MachineTimer` interrupt handler
+//!   that drives it. See [`tick`] for more information.
+//! - `embedded-io`: enables the `embedded-io` trait implementations for the 16550-compatible [`uart::UART`].
+//! - `mock`: provides memory-backed emulations of the PLIC (`plic::mock::MockPlic`) and CLINT
+//!   (`aclint::mock::MockClint`) registers with assertion helpers, so drivers can be unit tested on the host.
 
 #![deny(missing_docs)]
 #![no_std]
 
+#[cfg(all(
+    feature = "trace",
+    not(any(target_arch = "riscv32", target_arch = "riscv64"))
+))]
+extern crate std;
+
 pub use riscv; // re-export riscv crate to allow macros to use it
 pub use riscv_pac::result; // re-export the result module
 
+pub use peripherals::{take, Peripherals};
+
 pub mod common; // common definitions for all peripherals
 pub mod hal; // trait implementations for embedded-hal
 #[cfg(feature = "embedded-hal-async")]
@@ -18,4 +33,11 @@ pub mod hal_async; // async trait implementations for embedded-hal
 pub mod macros; // macros for easing the definition of peripherals in PACs
 
 pub mod aclint; // ACLINT and CLINT peripherals
+pub mod clock; // time sources
+pub mod imsic; // IMSIC peripheral (AIA)
+pub mod peripherals; // ownership tokens of the standard peripherals
 pub mod plic; // PLIC peripheral
+pub mod stats; // interrupt statistics
+pub mod systimer; // system timer facade
+pub mod tick; // millisecond tick service
+pub mod uart; // 16550-compatible UART
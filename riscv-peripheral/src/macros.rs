@@ -24,6 +24,7 @@
 /// let mswi = CLINT::mswi();     // MSWI peripheral
 /// let mtimer = CLINT::mtimer(); // MTIMER peripheral
 /// let delay = CLINT::delay();   // For the `embedded_hal::delay::DelayNs` trait
+/// let clock = CLINT::clock();   // For the `riscv_peripheral::clock::Clock` trait
 /// ```
 ///
 /// ## Base address and per-HART mtimecmp registers
@@ -147,6 +148,28 @@ macro_rules! clint_codegen {
                 $crate::aclint::CLINT::<CLINT>::mswi()
             }
 
+            /// Pends a machine software interrupt in the current HART by writing to its `MSIP` register.
+            ///
+            /// # Note
+            ///
+            /// This function determines the current HART ID by reading the `mhartid` CSR.
+            /// Thus, it can only be used in M-mode.
+            #[inline]
+            pub fn mswi_pend() {
+                Self::mswi().msip_mhartid().pend();
+            }
+
+            /// Clears the pending machine software interrupt of the current HART.
+            ///
+            /// # Note
+            ///
+            /// This function determines the current HART ID by reading the `mhartid` CSR.
+            /// Thus, it can only be used in M-mode.
+            #[inline]
+            pub fn mswi_unpend() {
+                Self::mswi().msip_mhartid().unpend();
+            }
+
             /// Returns `true` if a machine timer interrupt is pending.
             #[inline]
             pub fn mtimer_is_interrupting() -> bool {
@@ -189,6 +212,22 @@ macro_rules! clint_codegen {
             pub const fn mtime() -> $crate::aclint::mtimer::MTIME {
                 Self::mtimer().mtime
             }
+
+            /// Saves the `MTIMECMP` registers of all the HARTs into `state` before power-gating the CLINT.
+            #[inline]
+            pub fn suspend_state<H: $crate::aclint::HartIdNumber>(
+                state: &mut [u64],
+            ) -> $crate::result::Result<usize> {
+                $crate::aclint::CLINT::<CLINT>::suspend_state::<H>(state)
+            }
+
+            /// Restores the `MTIMECMP` registers saved by [`CLINT::suspend_state`].
+            #[inline]
+            pub fn resume_state<H: $crate::aclint::HartIdNumber>(
+                state: &[u64],
+            ) -> $crate::result::Result<()> {
+                $crate::aclint::CLINT::<CLINT>::resume_state::<H>(state)
+            }
         }
         $crate::clint_codegen!($($tail)*);
     };
@@ -209,6 +248,12 @@ macro_rules! clint_codegen {
             pub const fn delay() -> $crate::hal::aclint::Delay {
                 $crate::hal::aclint::Delay::new(Self::mtime(), Self::freq())
             }
+
+            /// Returns the `MTIME` register of the CLINT as a `Clock` time source.
+            #[inline]
+            pub const fn clock() -> $crate::clock::MtimeClock {
+                $crate::clock::MtimeClock::new(Self::mtime(), Self::freq())
+            }
         }
         $crate::clint_codegen!($($tail)*);
     };
@@ -357,6 +402,41 @@ macro_rules! plic_codegen {
             pub fn ctx_mhartid() -> $crate::plic::CTX<Self> {
                 $crate::plic::PLIC::<PLIC>::ctx_mhartid()
             }
+
+            /// Returns the number of words required by [`PLIC::suspend_state`].
+            #[inline]
+            pub const fn state_len<
+                I: $crate::plic::ExternalInterruptNumber,
+                H: $crate::plic::HartIdNumber,
+            >() -> usize {
+                $crate::plic::PLIC::<PLIC>::state_len::<I, H>()
+            }
+
+            /// Saves the priorities, enables, and thresholds of the PLIC into `state` before power-gating it.
+            #[inline]
+            pub fn suspend_state<
+                I: $crate::plic::ExternalInterruptNumber,
+                H: $crate::plic::HartIdNumber,
+            >(
+                state: &mut [u32],
+            ) -> $crate::result::Result<usize> {
+                $crate::plic::PLIC::<PLIC>::suspend_state::<I, H>(state)
+            }
+
+            /// Restores the PLIC configuration saved by [`PLIC::suspend_state`].
+            ///
+            /// # Safety
+            ///
+            /// Restoring the PLIC configuration can break priority-based and mask-based critical sections.
+            #[inline]
+            pub unsafe fn resume_state<
+                I: $crate::plic::ExternalInterruptNumber,
+                H: $crate::plic::HartIdNumber,
+            >(
+                state: &[u32],
+            ) -> $crate::result::Result<()> {
+                $crate::plic::PLIC::<PLIC>::resume_state::<I, H>(state)
+            }
         }
         $crate::plic_codegen!($($tail)*);
     };
@@ -0,0 +1,125 @@
+//! Ownership tokens of the standard peripherals.
+//!
+//! [`take`] returns the [`CLINT`] and [`PLIC`] tokens of the platform only once per program.
+//! HALs can take these tokens by value (e.g., in the constructor of a timer driver), so two drivers
+//! cannot configure the same peripheral by accident. The tokens are not `Clone` nor `Copy`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // PAC's CLINT and PLIC, defined with `clint_codegen!` and `plic_codegen!`
+//! let p = riscv_peripheral::take::<CLINT, PLIC>().unwrap();
+//! assert!(riscv_peripheral::take::<CLINT, PLIC>().is_none()); // already taken
+//!
+//! let timer = MyHalTimer::new(p.clint); // the HAL timer owns the CLINT from now on
+//! ```
+//!
+//! # Note
+//!
+//! The associated functions of [`CLINT`] and [`PLIC`] do not require the tokens. Thus, ownership
+//! is only enforced for the APIs that take the tokens.
+
+use crate::{
+    aclint::{Clint, CLINT},
+    plic::{Plic, PLIC},
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once the tokens have been taken.
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Ownership tokens of the standard peripherals of a platform.
+#[derive(Debug)]
+pub struct Peripherals<C: Clint, P: Plic> {
+    /// CLINT peripheral
+    pub clint: CLINT<C>,
+    /// PLIC peripheral
+    pub plic: PLIC<P>,
+}
+
+impl<C: Clint, P: Plic> Peripherals<C, P> {
+    /// Returns the tokens the first time it is called, and `None` afterwards.
+    ///
+    /// # Note
+    ///
+    /// In targets without atomic read-modify-write instructions (i.e., without the `A` extension),
+    /// the flag is checked and set with interrupts disabled. In these targets, this function must not be
+    /// called concurrently from multiple HARTs.
+    #[inline]
+    pub fn take() -> Option<Self> {
+        #[cfg(target_has_atomic = "8")]
+        let taken = TAKEN.swap(true, Ordering::AcqRel);
+        #[cfg(not(target_has_atomic = "8"))]
+        let taken = riscv::interrupt::free(|| {
+            let taken = TAKEN.load(Ordering::Acquire);
+            TAKEN.store(true, Ordering::Release);
+            taken
+        });
+        match taken {
+            // SAFETY: this is the first time the tokens are taken
+            false => Some(unsafe { Self::steal() }),
+            true => None,
+        }
+    }
+
+    /// Returns the tokens without checking if they have already been taken.
+    ///
+    /// Subsequent calls to [`Peripherals::take`] return `None`.
+    ///
+    /// # Safety
+    ///
+    /// The tokens may alias with tokens that have already been taken.
+    #[inline]
+    pub unsafe fn steal() -> Self {
+        TAKEN.store(true, Ordering::Release);
+        Self {
+            clint: CLINT::steal(),
+            plic: PLIC::steal(),
+        }
+    }
+}
+
+/// Returns the ownership tokens of the standard peripherals the first time it is called, and `None` afterwards.
+///
+/// See [`Peripherals::take`] for more details.
+#[inline]
+pub fn take<C: Clint, P: Plic>() -> Option<Peripherals<C, P>> {
+    Peripherals::take()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    struct TestClint;
+
+    unsafe impl Clint for TestClint {
+        const BASE: usize = 0x0200_0000;
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    struct TestPlic;
+
+    unsafe impl Plic for TestPlic {
+        const BASE: usize = 0x0C00_0000;
+    }
+
+    /// Driver that takes the CLINT token by value.
+    struct Driver(CLINT<TestClint>);
+
+    #[test]
+    fn check_take() {
+        let p = take::<TestClint, TestPlic>().unwrap();
+        assert!(take::<TestClint, TestPlic>().is_none());
+        assert!(Peripherals::<TestClint, TestPlic>::take().is_none());
+
+        let driver = Driver(p.clint);
+        assert_eq!(driver.0, unsafe { CLINT::steal() });
+        assert_eq!(p.plic, unsafe { PLIC::steal() });
+
+        // stealing does not release the tokens
+        let _ = unsafe { Peripherals::<TestClint, TestPlic>::steal() };
+        assert!(take::<TestClint, TestPlic>().is_none());
+    }
+}
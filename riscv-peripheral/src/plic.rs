@@ -1,15 +1,65 @@
 //! Platform-Level Interrupt Controller (PLIC) peripheral.
 //!
 //! Specification: <https://github.com/riscv/riscv-plic-spec/blob/master/riscv-plic.adoc>
+//!
+//! # Memory ordering
+//!
+//! The RISC-V memory model does not order device accesses with respect to regular memory accesses.
+//! The [`Reg::read_ordered`](crate::common::Reg::read_ordered) and
+//! [`Reg::write_ordered`](crate::common::Reg::write_ordered) methods pair register accesses
+//! with the required fences. In the PLIC:
+//!
+//! - Claiming an interrupt ([`claim::CLAIM::claim`]) uses an ordered read, so interrupt handlers
+//!   observe the memory written by the device before raising the interrupt (e.g., via DMA).
+//! - Priorities, enables, thresholds, and completions do not need ordering, as they do not signal memory.
+//!   However, drivers must use ordered accesses to their own device registers when they share memory
+//!   with the device (e.g., DMA descriptors).
 
 pub mod claim;
 pub mod enables;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod pendings;
 pub mod priorities;
 pub mod threshold;
 
 // re-export useful riscv-pac traits
-pub use riscv_pac::{HartIdNumber, InterruptNumber, PriorityNumber};
+pub use riscv_pac::{ExternalInterruptNumber, HartIdNumber, InterruptNumber, PriorityNumber};
+
+use crate::common::{Read, RegArray};
+use riscv_pac::result::{Error, Result};
+
+/// Maximum number of interrupt sources supported by the PLIC specification.
+const N_SOURCES: usize = 1024;
+
+/// Number of 32-bit words required to store one bit per interrupt source.
+const N_WORDS: usize = N_SOURCES / u32::BITS as usize;
+
+/// Returns an iterator over the interrupt sources with their bit set in a bit array of `N_WORDS` registers.
+///
+/// Only the words with bits of valid interrupt sources are read, once per word,
+/// and words with all the bits cleared are skipped at once.
+fn iter_sources<I: ExternalInterruptNumber, A: Read>(
+    regs: RegArray<u32, A, N_WORDS, 4>,
+) -> impl Iterator<Item = I> {
+    let n_words = I::MAX_INTERRUPT_NUMBER / u32::BITS as usize + 1;
+    regs.iter()
+        .take(n_words)
+        .enumerate()
+        .flat_map(|(index, reg)| {
+            let mut word = reg.read();
+            core::iter::from_fn(move || {
+                while word != 0 {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1; // clear the lowest bit set
+                    if let Ok(source) = I::from_number(index * u32::BITS as usize + bit) {
+                        return Some(source);
+                    }
+                }
+                None
+            })
+        })
+}
 
 /// Trait for a PLIC peripheral.
 ///
@@ -22,6 +72,73 @@ pub unsafe trait Plic: Copy {
     const BASE: usize;
 }
 
+/// Privilege mode of a PLIC context.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Machine mode.
+    Machine = 0,
+    /// Supervisor mode.
+    Supervisor = 1,
+}
+
+/// Common schemes to map HART and privilege mode pairs to PLIC contexts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContextScheme {
+    /// Each HART has a single machine-mode context, whose number is the HART ID.
+    MachineOnly,
+    /// Each HART has a machine-mode and a supervisor-mode context, whose numbers are
+    /// `2 * hart_id` and `2 * hart_id + 1` (e.g., SiFive parts and the QEMU `virt` machine).
+    MachineSupervisor,
+}
+
+impl ContextScheme {
+    /// Returns the context number of HART `hart_id` in privilege mode `mode`.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::InvalidVariant`] with the mode number if the scheme has no contexts for `mode`.
+    #[inline]
+    pub const fn context(self, hart_id: usize, mode: Mode) -> Result<usize> {
+        match (self, mode) {
+            (Self::MachineOnly, Mode::Machine) => Ok(hart_id),
+            (Self::MachineOnly, Mode::Supervisor) => Err(Error::InvalidVariant(mode as usize)),
+            (Self::MachineSupervisor, _) => Ok(2 * hart_id + mode as usize),
+        }
+    }
+}
+
+/// Trait to map HART and privilege mode pairs to PLIC contexts.
+///
+/// Generic code can use [`PLIC::ctx_mode`] to get the context of a HART in a given privilege mode
+/// instead of hardcoding the context numbers of a target. Most targets can implement this trait
+/// with a [`ContextScheme`]:
+///
+/// ```
+/// use riscv_peripheral::plic::{ContextMapping, ContextScheme, HartIdNumber, Mode};
+/// use riscv_peripheral::result::Result;
+///
+/// riscv_peripheral::plic_codegen!(base 0x0C00_0000,);
+///
+/// unsafe impl ContextMapping for PLIC {
+///     fn context<H: HartIdNumber>(hart_id: H, mode: Mode) -> Result<usize> {
+///         ContextScheme::MachineSupervisor.context(hart_id.number(), mode)
+///     }
+/// }
+/// ```
+///
+/// # Safety
+///
+/// * This trait must only be implemented on a PAC of a target with a PLIC peripheral.
+/// * All the context numbers returned by [`ContextMapping::context`] must be valid for the target device.
+pub unsafe trait ContextMapping: Plic {
+    /// Returns the context number of HART `hart_id` in privilege mode `mode`.
+    ///
+    /// # Errors
+    ///
+    /// It must return an error if the HART has no context for `mode`.
+    fn context<H: HartIdNumber>(hart_id: H, mode: Mode) -> Result<usize>;
+}
+
 /// Platform-Level Interrupt Controler (PLIC) peripheral.
 ///
 /// The RISC-V standard does not specify a fixed location for the PLIC.
@@ -33,38 +150,69 @@ pub unsafe trait Plic: Copy {
 /// Usually, each HART uses a dedicated context. In this way, they do not interfere
 /// with each other when attending to external interruptions.
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq)]
 pub struct PLIC<P: Plic> {
     _marker: core::marker::PhantomData<P>,
 }
 
 impl<P: Plic> PLIC<P> {
-    const PRIORITIES_OFFSET: usize = 0;
+    /// Creates a new `PLIC` ownership token.
+    ///
+    /// Drivers can take the token by value to ensure that only one owner configures the peripheral.
+    ///
+    /// # Safety
+    ///
+    /// The token must be unique. Use [`crate::take`] to get the tokens of the platform peripherals.
+    #[inline]
+    pub const unsafe fn steal() -> Self {
+        Self {
+            _marker: core::marker::PhantomData,
+        }
+    }
 
-    const PENDINGS_OFFSET: usize = 0x1000;
+    /// Returns the PLIC with the base address of the [`Plic`] trait.
+    #[inline]
+    const fn as_dyn() -> DynPLIC {
+        DynPLIC { base: P::BASE }
+    }
 
     /// Returns the priorities register of the PLIC.
     /// This register allows to set the priority level of each interrupt source.
     /// The priority level of each interrupt source is shared among all the contexts.
     #[inline]
     pub fn priorities() -> priorities::PRIORITIES {
-        // SAFETY: valid address
-        unsafe { priorities::PRIORITIES::new(P::BASE + Self::PRIORITIES_OFFSET) }
+        Self::as_dyn().priorities()
     }
 
     /// Returns the pendings register of the PLIC.
     /// This register allows to check if a particular interrupt source is pending.
     #[inline]
     pub fn pendings() -> pendings::PENDINGS {
-        // SAFETY: valid address
-        unsafe { pendings::PENDINGS::new(P::BASE + Self::PENDINGS_OFFSET) }
+        Self::as_dyn().pendings()
     }
 
     /// Returns a proxy to access to all the PLIC registers of a given HART context.
     #[inline]
     pub fn ctx<H: HartIdNumber>(hart_id: H) -> CTX<P> {
         // SAFETY: valid context number
-        unsafe { CTX::new(hart_id.number() as _) }
+        unsafe { CTX::new(hart_id.number()) }
+    }
+
+    /// Returns a proxy to access to all the PLIC registers of the context of a HART in a given privilege mode.
+    ///
+    /// The context number is computed with the [`ContextMapping`] of the PLIC.
+    ///
+    /// # Errors
+    ///
+    /// It returns the error of [`ContextMapping::context`] if the HART has no context for `mode`.
+    #[inline]
+    pub fn ctx_mode<H: HartIdNumber>(hart_id: H, mode: Mode) -> Result<CTX<P>>
+    where
+        P: ContextMapping,
+    {
+        let context = P::context(hart_id, mode)?;
+        // SAFETY: valid context number
+        Ok(unsafe { CTX::new(context) })
     }
 
     /// Returns the PLIC HART context for the current HART.
@@ -77,8 +225,271 @@ impl<P: Plic> PLIC<P> {
     pub fn ctx_mhartid() -> CTX<P> {
         let hart_id = riscv::register::mhartid::read();
         // SAFETY: `hart_id` is valid for the target and is the current hart
-        unsafe { CTX::new(hart_id as _) }
+        unsafe { CTX::new(hart_id) }
+    }
+
+    /// Returns the number of words required by [`PLIC::suspend_state`] to save the PLIC configuration.
+    #[inline]
+    pub const fn state_len<I: ExternalInterruptNumber, H: HartIdNumber>() -> usize {
+        state_len::<I>(H::MAX_HART_ID_NUMBER + 1)
+    }
+
+    /// Saves the PLIC configuration into `state` before power-gating the PLIC (e.g., in deep sleep).
+    /// It returns the number of words written, i.e., [`PLIC::state_len`].
+    ///
+    /// The state contains the priority levels of all the interrupt sources up to `I::MAX_INTERRUPT_NUMBER`,
+    /// followed by the enable bits and the priority threshold of all the contexts up to `H::MAX_HART_ID_NUMBER`.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::IndexOutOfBounds`] with the length of `state` as index
+    /// if `state` is shorter than [`PLIC::state_len`].
+    #[inline]
+    pub fn suspend_state<I: ExternalInterruptNumber, H: HartIdNumber>(
+        state: &mut [u32],
+    ) -> Result<usize> {
+        save_state::<I>(Self::priorities(), Self::contexts::<H>(), state)
+    }
+
+    /// Restores a PLIC configuration saved by [`PLIC::suspend_state`] once the PLIC is powered again.
+    ///
+    /// The priority levels are restored first. Then, for each context, the priority threshold
+    /// is restored before the enable bits.
+    ///
+    /// # Errors
+    ///
+    /// It returns [`Error::IndexOutOfBounds`] with the length of `state` as index
+    /// if `state` is shorter than [`PLIC::state_len`].
+    ///
+    /// # Safety
+    ///
+    /// Restoring the PLIC configuration can break priority-based and mask-based critical sections.
+    /// `I` and `H` must be the same types used to save `state`.
+    #[inline]
+    pub unsafe fn resume_state<I: ExternalInterruptNumber, H: HartIdNumber>(
+        state: &[u32],
+    ) -> Result<()> {
+        restore_state::<I>(Self::priorities(), Self::contexts::<H>(), state)
+    }
+
+    /// Returns the enables and threshold registers of all the contexts up to `H::MAX_HART_ID_NUMBER`.
+    #[inline]
+    fn contexts<H: HartIdNumber>(
+    ) -> impl ExactSizeIterator<Item = (enables::ENABLES, threshold::THRESHOLD)> {
+        (0..H::MAX_HART_ID_NUMBER + 1).map(|context| {
+            // SAFETY: valid context number
+            let ctx = unsafe { CTX::<P>::new(context) };
+            (ctx.enables(), ctx.threshold())
+        })
+    }
+}
+
+/// Platform-Level Interrupt Controler (PLIC) peripheral with a base address provided at runtime.
+///
+/// [`PLIC`] gets the base address from the [`Plic`] trait at compile time, which is the zero-cost
+/// choice for PACs. Kernels that discover the address of the PLIC at boot (e.g., from a device tree)
+/// can use `DynPLIC` instead. It provides the same functionality as [`PLIC`], with methods that take `&self`.
+///
+/// # Example
+///
+/// ```
+/// use riscv_peripheral::plic::DynPLIC;
+///
+/// let base = 0x0C00_0000; // e.g., read from the device tree
+/// // SAFETY: `base` is the base address of the PLIC
+/// let plic = unsafe { DynPLIC::new(base) };
+/// assert_eq!(plic.base(), base);
+/// ```
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Eq, PartialEq)]
+pub struct DynPLIC {
+    base: usize,
+}
+
+impl DynPLIC {
+    const PRIORITIES_OFFSET: usize = 0;
+
+    const PENDINGS_OFFSET: usize = 0x1000;
+
+    /// Creates a new `DynPLIC` with base address `base`.
+    ///
+    /// # Safety
+    ///
+    /// * `base` must be the base address of a PLIC peripheral of the target device.
+    /// * The instance must be unique, as for the [`PLIC`] ownership token.
+    #[inline]
+    pub const unsafe fn new(base: usize) -> Self {
+        Self { base }
+    }
+
+    /// Returns the base address of the PLIC.
+    #[inline]
+    pub const fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Returns the priorities register of the PLIC.
+    ///
+    /// See [`PLIC::priorities`] for more details.
+    #[inline]
+    pub fn priorities(&self) -> priorities::PRIORITIES {
+        // SAFETY: valid address
+        unsafe { priorities::PRIORITIES::new(self.base + Self::PRIORITIES_OFFSET) }
+    }
+
+    /// Returns the pendings register of the PLIC.
+    ///
+    /// See [`PLIC::pendings`] for more details.
+    #[inline]
+    pub fn pendings(&self) -> pendings::PENDINGS {
+        // SAFETY: valid address
+        unsafe { pendings::PENDINGS::new(self.base + Self::PENDINGS_OFFSET) }
+    }
+
+    /// Returns a proxy to access to all the PLIC registers of a given HART context.
+    #[inline]
+    pub fn ctx<H: HartIdNumber>(&self, hart_id: H) -> DynCTX {
+        // SAFETY: valid context number
+        unsafe { DynCTX::new(self.base, hart_id.number()) }
+    }
+
+    /// Returns a proxy to access to all the PLIC registers of the context of a HART in a given privilege mode.
+    ///
+    /// The context number is computed with the context `scheme` of the target.
+    ///
+    /// # Errors
+    ///
+    /// It returns the error of [`ContextScheme::context`] if the HART has no context for `mode`.
+    #[inline]
+    pub fn ctx_mode<H: HartIdNumber>(
+        &self,
+        hart_id: H,
+        mode: Mode,
+        scheme: ContextScheme,
+    ) -> Result<DynCTX> {
+        let context = scheme.context(hart_id.number(), mode)?;
+        // SAFETY: valid context number
+        Ok(unsafe { DynCTX::new(self.base, context) })
+    }
+
+    /// Returns the PLIC HART context for the current HART.
+    ///
+    /// See [`PLIC::ctx_mhartid`] for more details.
+    #[inline]
+    pub fn ctx_mhartid(&self) -> DynCTX {
+        let hart_id = riscv::register::mhartid::read();
+        // SAFETY: `hart_id` is valid for the target and is the current hart
+        unsafe { DynCTX::new(self.base, hart_id) }
+    }
+
+    /// Returns the number of words required by [`DynPLIC::suspend_state`] to save the PLIC configuration.
+    #[inline]
+    pub const fn state_len<I: ExternalInterruptNumber, H: HartIdNumber>() -> usize {
+        state_len::<I>(H::MAX_HART_ID_NUMBER + 1)
+    }
+
+    /// Saves the PLIC configuration into `state` before power-gating the PLIC (e.g., in deep sleep).
+    /// It returns the number of words written, i.e., [`DynPLIC::state_len`].
+    ///
+    /// See [`PLIC::suspend_state`] for more details.
+    #[inline]
+    pub fn suspend_state<I: ExternalInterruptNumber, H: HartIdNumber>(
+        &self,
+        state: &mut [u32],
+    ) -> Result<usize> {
+        save_state::<I>(self.priorities(), self.contexts::<H>(), state)
+    }
+
+    /// Restores a PLIC configuration saved by [`DynPLIC::suspend_state`] once the PLIC is powered again.
+    ///
+    /// See [`PLIC::resume_state`] for more details.
+    ///
+    /// # Safety
+    ///
+    /// Restoring the PLIC configuration can break priority-based and mask-based critical sections.
+    /// `I` and `H` must be the same types used to save `state`.
+    #[inline]
+    pub unsafe fn resume_state<I: ExternalInterruptNumber, H: HartIdNumber>(
+        &self,
+        state: &[u32],
+    ) -> Result<()> {
+        restore_state::<I>(self.priorities(), self.contexts::<H>(), state)
+    }
+
+    /// Returns the enables and threshold registers of all the contexts up to `H::MAX_HART_ID_NUMBER`.
+    #[inline]
+    fn contexts<H: HartIdNumber>(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (enables::ENABLES, threshold::THRESHOLD)> {
+        let base = self.base;
+        (0..H::MAX_HART_ID_NUMBER + 1).map(move |context| {
+            // SAFETY: valid context number
+            let ctx = unsafe { DynCTX::new(base, context) };
+            (ctx.enables(), ctx.threshold())
+        })
+    }
+}
+
+/// Returns the number of words required to save the PLIC configuration of `n_contexts` contexts.
+const fn state_len<I: ExternalInterruptNumber>(n_contexts: usize) -> usize {
+    priorities::PRIORITIES::state_len::<I>() + n_contexts * (enables::ENABLES::state_len::<I>() + 1)
+}
+
+/// Checks that `state` can hold the PLIC configuration of `n_contexts` contexts.
+fn check_state_len<I: ExternalInterruptNumber>(state: &[u32], n_contexts: usize) -> Result<usize> {
+    let len = state_len::<I>(n_contexts);
+    match state.len() >= len {
+        true => Ok(len),
+        false => Err(Error::IndexOutOfBounds {
+            index: state.len(),
+            min: len,
+            max: usize::MAX,
+        }),
+    }
+}
+
+/// Saves the priorities, and the enables and threshold of every context into `state`.
+fn save_state<I: ExternalInterruptNumber>(
+    priorities: priorities::PRIORITIES,
+    contexts: impl ExactSizeIterator<Item = (enables::ENABLES, threshold::THRESHOLD)>,
+    state: &mut [u32],
+) -> Result<usize> {
+    let len = check_state_len::<I>(state, contexts.len())?;
+    let (state_priorities, state_contexts) =
+        state.split_at_mut(priorities::PRIORITIES::state_len::<I>());
+    priorities.save(state_priorities);
+    let n_enables = enables::ENABLES::state_len::<I>();
+    for ((enables, threshold), state_ctx) in
+        contexts.zip(state_contexts.chunks_exact_mut(n_enables + 1))
+    {
+        enables.save(&mut state_ctx[..n_enables]);
+        state_ctx[n_enables] = threshold.save();
     }
+    Ok(len)
+}
+
+/// Restores the priorities, and the threshold and enables of every context from `state`.
+///
+/// # Safety
+///
+/// Restoring the PLIC configuration can break priority-based and mask-based critical sections.
+unsafe fn restore_state<I: ExternalInterruptNumber>(
+    priorities: priorities::PRIORITIES,
+    contexts: impl ExactSizeIterator<Item = (enables::ENABLES, threshold::THRESHOLD)>,
+    state: &[u32],
+) -> Result<()> {
+    check_state_len::<I>(state, contexts.len())?;
+    let (state_priorities, state_contexts) =
+        state.split_at(priorities::PRIORITIES::state_len::<I>());
+    priorities.restore(state_priorities);
+    let n_enables = enables::ENABLES::state_len::<I>();
+    for ((enables, threshold), state_ctx) in
+        contexts.zip(state_contexts.chunks_exact(n_enables + 1))
+    {
+        threshold.restore(state_ctx[n_enables]);
+        enables.restore(&state_ctx[..n_enables]);
+    }
+    Ok(())
 }
 
 /// PLIC context proxy. It provides access to the PLIC registers of a given context.
@@ -90,6 +501,78 @@ pub struct CTX<P: Plic> {
 }
 
 impl<P: Plic> CTX<P> {
+    /// Creates a new PLIC context proxy
+    ///
+    /// # Safety
+    ///
+    /// The context number must be valid for the target device.
+    #[inline]
+    pub(crate) unsafe fn new(context: usize) -> Self {
+        Self {
+            context,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the context number of this proxy.
+    #[inline]
+    pub const fn context(self) -> usize {
+        self.context
+    }
+
+    /// Returns the context proxy with the base address of the [`Plic`] trait.
+    #[inline]
+    const fn as_dyn(self) -> DynCTX {
+        DynCTX {
+            base: P::BASE,
+            context: self.context,
+        }
+    }
+
+    /// Returns the highest priority level of `T`.
+    ///
+    /// Setting the threshold to this level masks all the interrupt sources of the context.
+    #[inline]
+    pub fn max_priority<T: PriorityNumber>(self) -> T {
+        T::from_number(T::MAX_PRIORITY_NUMBER).unwrap()
+    }
+
+    /// Returns the lowest priority level of `T` (i.e., 0).
+    ///
+    /// Setting the threshold to this level accepts interrupts from any enabled source.
+    #[inline]
+    pub fn min_priority<T: PriorityNumber>(self) -> T {
+        T::from_number(0).unwrap()
+    }
+
+    /// Returns the interrupts enable register of the context.
+    #[inline]
+    pub const fn enables(self) -> enables::ENABLES {
+        self.as_dyn().enables()
+    }
+
+    /// Returns the interrupt threshold register of the context.
+    #[inline]
+    pub const fn threshold(self) -> threshold::THRESHOLD {
+        self.as_dyn().threshold()
+    }
+
+    /// Returns the interrupt claim/complete register of the context.
+    #[inline]
+    pub const fn claim(self) -> claim::CLAIM {
+        self.as_dyn().claim()
+    }
+}
+
+/// PLIC context proxy of a [`DynPLIC`]. It provides access to the PLIC registers of a given context.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DynCTX {
+    base: usize,
+    context: usize,
+}
+
+impl DynCTX {
     const ENABLES_OFFSET: usize = 0x2000;
     const ENABLES_SEPARATION: usize = 0x80;
 
@@ -103,25 +586,38 @@ impl<P: Plic> CTX<P> {
     ///
     /// # Safety
     ///
-    /// The context number must be valid for the target device.
+    /// The base address and the context number must be valid for the target device.
     #[inline]
-    pub(crate) unsafe fn new(context: u16) -> Self {
-        Self {
-            context: context as _,
-            _marker: core::marker::PhantomData,
-        }
+    pub(crate) const unsafe fn new(base: usize, context: usize) -> Self {
+        Self { base, context }
     }
 
     /// Returns the context number of this proxy.
     #[inline]
-    pub const fn context(self) -> u16 {
-        self.context as _
+    pub const fn context(self) -> usize {
+        self.context
+    }
+
+    /// Returns the highest priority level of `T`.
+    ///
+    /// Setting the threshold to this level masks all the interrupt sources of the context.
+    #[inline]
+    pub fn max_priority<T: PriorityNumber>(self) -> T {
+        T::from_number(T::MAX_PRIORITY_NUMBER).unwrap()
+    }
+
+    /// Returns the lowest priority level of `T` (i.e., 0).
+    ///
+    /// Setting the threshold to this level accepts interrupts from any enabled source.
+    #[inline]
+    pub fn min_priority<T: PriorityNumber>(self) -> T {
+        T::from_number(0).unwrap()
     }
 
     /// Returns the interrupts enable register of the context.
     #[inline]
     pub const fn enables(self) -> enables::ENABLES {
-        let addr = P::BASE + Self::ENABLES_OFFSET + self.context * Self::ENABLES_SEPARATION;
+        let addr = self.base + Self::ENABLES_OFFSET + self.context * Self::ENABLES_SEPARATION;
         // SAFETY: valid address
         unsafe { enables::ENABLES::new(addr) }
     }
@@ -129,7 +625,7 @@ impl<P: Plic> CTX<P> {
     /// Returns the interrupt threshold register of the context.
     #[inline]
     pub const fn threshold(self) -> threshold::THRESHOLD {
-        let addr = P::BASE + Self::THRESHOLDS_OFFSET + self.context * Self::THRESHOLDS_SEPARATION;
+        let addr = self.base + Self::THRESHOLDS_OFFSET + self.context * Self::THRESHOLDS_SEPARATION;
         // SAFETY: valid address
         unsafe { threshold::THRESHOLD::new(addr) }
     }
@@ -137,7 +633,7 @@ impl<P: Plic> CTX<P> {
     /// Returns the interrupt claim/complete register of the context.
     #[inline]
     pub const fn claim(self) -> claim::CLAIM {
-        let addr = P::BASE + Self::CLAIMS_OFFSET + self.context * Self::CLAIMS_SEPARATION;
+        let addr = self.base + Self::CLAIMS_OFFSET + self.context * Self::CLAIMS_SEPARATION;
         // SAFETY: valid address
         unsafe { claim::CLAIM::new(addr) }
     }
@@ -145,6 +641,7 @@ impl<P: Plic> CTX<P> {
 
 #[cfg(test)]
 pub(crate) mod test {
+    use super::{ContextMapping, ContextScheme, Mode};
     use riscv_pac::result::{Error, Result};
     use riscv_pac::{ExternalInterruptNumber, HartIdNumber, InterruptNumber, PriorityNumber};
 
@@ -193,6 +690,29 @@ pub(crate) mod test {
 
     unsafe impl ExternalInterruptNumber for Interrupt {}
 
+    /// Interrupt source of a mock target with 128 interrupt sources (i.e., 4 words of bits).
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub(crate) struct LargeInterrupt(pub(crate) usize);
+
+    unsafe impl InterruptNumber for LargeInterrupt {
+        const MAX_INTERRUPT_NUMBER: usize = 127;
+
+        #[inline]
+        fn number(self) -> usize {
+            self.0
+        }
+
+        #[inline]
+        fn from_number(number: usize) -> Result<Self> {
+            match number {
+                1..=Self::MAX_INTERRUPT_NUMBER => Ok(Self(number)),
+                _ => Err(Error::InvalidVariant(number)),
+            }
+        }
+    }
+
+    unsafe impl ExternalInterruptNumber for LargeInterrupt {}
+
     unsafe impl PriorityNumber for Priority {
         const MAX_PRIORITY_NUMBER: usize = Self::P3 as usize;
 
@@ -310,5 +830,166 @@ pub(crate) mod test {
         assert_eq!(PLIC::ctx0(), PLIC::ctx(Context::C0));
         assert_eq!(PLIC::ctx1(), PLIC::ctx(Context::C1));
         assert_eq!(PLIC::ctx2(), PLIC::ctx(Context::C2));
+
+        assert_eq!(PLIC::ctx0().max_priority::<Priority>(), Priority::P3);
+        assert_eq!(PLIC::ctx0().min_priority::<Priority>(), Priority::P0);
+
+        unsafe impl ContextMapping for PLIC {
+            fn context<H: HartIdNumber>(hart_id: H, mode: Mode) -> Result<usize> {
+                ContextScheme::MachineSupervisor.context(hart_id.number(), mode)
+            }
+        }
+
+        type P = super::PLIC<PLIC>;
+        let ctx = P::ctx_mode(Context::C1, Mode::Supervisor).unwrap();
+        assert_eq!(ctx.context(), 3);
+        assert_eq!(ctx.enables().address(), 0x0C00_0000 + 0x2000 + 3 * 0x80);
+        assert_eq!(P::ctx_mode(Context::C0, Mode::Machine), Ok(PLIC::ctx0()));
+    }
+
+    #[allow(dead_code)]
+    #[test]
+    fn check_dyn_plic() {
+        use super::DynPLIC;
+
+        let plic = unsafe { DynPLIC::new(0x0C00_0000) };
+        assert_eq!(plic.base(), 0x0C00_0000);
+        assert_eq!(plic.priorities().address(), 0x0C00_0000);
+        assert_eq!(plic.pendings().address(), 0x0C00_1000);
+
+        for i in 0..=Context::MAX_HART_ID_NUMBER {
+            let ctx = plic.ctx(Context::from_number(i).unwrap());
+
+            assert_eq!(ctx.context(), i);
+            assert_eq!(ctx.enables().address(), 0x0C00_0000 + 0x2000 + i * 0x80);
+            assert_eq!(
+                ctx.threshold().get_ptr() as usize,
+                0x0C00_0000 + 0x20_0000 + i * 0x1000
+            );
+            assert_eq!(
+                ctx.claim().get_ptr() as usize,
+                0x0C00_0000 + 0x20_0004 + i * 0x1000
+            );
+        }
+
+        let ctx = plic
+            .ctx_mode(
+                Context::C1,
+                Mode::Supervisor,
+                ContextScheme::MachineSupervisor,
+            )
+            .unwrap();
+        assert_eq!(ctx.context(), 3);
+        assert_eq!(
+            plic.ctx_mode(Context::C1, Mode::Supervisor, ContextScheme::MachineOnly),
+            Err(Error::InvalidVariant(1))
+        );
+        assert_eq!(ctx.max_priority::<Priority>(), Priority::P3);
+        assert_eq!(ctx.min_priority::<Priority>(), Priority::P0);
+
+        // the static PLIC uses the same register layout
+        crate::plic_codegen!(base 0x0C00_0000,);
+        assert_eq!(
+            PLIC::ctx(Context::C2).enables(),
+            plic.ctx(Context::C2).enables()
+        );
+        assert_eq!(
+            PLIC::ctx(Context::C2).claim(),
+            plic.ctx(Context::C2).claim()
+        );
+        assert_eq!(
+            DynPLIC::state_len::<Interrupt, Context>(),
+            PLIC::state_len::<Interrupt, Context>()
+        );
+    }
+
+    #[test]
+    fn check_context_scheme() {
+        assert_eq!(ContextScheme::MachineOnly.context(2, Mode::Machine), Ok(2));
+        assert_eq!(
+            ContextScheme::MachineOnly.context(2, Mode::Supervisor),
+            Err(Error::InvalidVariant(1))
+        );
+        assert_eq!(
+            ContextScheme::MachineSupervisor.context(0, Mode::Machine),
+            Ok(0)
+        );
+        assert_eq!(
+            ContextScheme::MachineSupervisor.context(0, Mode::Supervisor),
+            Ok(1)
+        );
+        assert_eq!(
+            ContextScheme::MachineSupervisor.context(2, Mode::Machine),
+            Ok(4)
+        );
+        assert_eq!(
+            ContextScheme::MachineSupervisor.context(2, Mode::Supervisor),
+            Ok(5)
+        );
+    }
+
+    #[test]
+    fn check_suspend_resume_state() {
+        static MOCK: super::mock::MockPlic = super::mock::MockPlic::new();
+        let contexts = || core::iter::once((MOCK.enables(), MOCK.threshold()));
+
+        // 5 priorities + 1 enables word + 1 threshold
+        assert_eq!(super::state_len::<Interrupt>(1), 7);
+        assert_eq!(super::state_len::<Interrupt>(3), 11);
+
+        let mut state = [0u32; 7];
+        assert_eq!(
+            super::save_state::<Interrupt>(MOCK.priorities(), contexts(), &mut state[..6]),
+            Err(Error::IndexOutOfBounds {
+                index: 6,
+                min: 7,
+                max: usize::MAX
+            })
+        );
+
+        unsafe {
+            MOCK.priorities().set_priority(Interrupt::I1, Priority::P1);
+            MOCK.priorities().set_priority(Interrupt::I4, Priority::P3);
+            MOCK.enables().enable(Interrupt::I2);
+            MOCK.enables().enable(Interrupt::I4);
+            MOCK.threshold().set_threshold(Priority::P2);
+        }
+        assert_eq!(
+            super::save_state::<Interrupt>(MOCK.priorities(), contexts(), &mut state),
+            Ok(7)
+        );
+        assert_eq!(state, [0, 1, 0, 0, 3, 0b10100, 2]);
+
+        // power-gating the PLIC clears its configuration
+        MOCK.priorities().reset::<Interrupt>();
+        MOCK.enables().disable_all::<Interrupt>();
+        MOCK.threshold().reset();
+
+        unsafe {
+            assert_eq!(
+                super::restore_state::<Interrupt>(MOCK.priorities(), contexts(), &state[..6]),
+                Err(Error::IndexOutOfBounds {
+                    index: 6,
+                    min: 7,
+                    max: usize::MAX
+                })
+            );
+            assert_eq!(
+                super::restore_state::<Interrupt>(MOCK.priorities(), contexts(), &state),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            MOCK.priorities().get_priority::<_, Priority>(Interrupt::I1),
+            Priority::P1
+        );
+        assert_eq!(
+            MOCK.priorities().get_priority::<_, Priority>(Interrupt::I4),
+            Priority::P3
+        );
+        assert!(!MOCK.enables().is_enabled(Interrupt::I1));
+        assert!(MOCK.enables().is_enabled(Interrupt::I2));
+        assert!(MOCK.enables().is_enabled(Interrupt::I4));
+        assert_eq!(MOCK.threshold().get_threshold::<Priority>(), Priority::P2);
     }
 }
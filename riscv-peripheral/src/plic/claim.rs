@@ -1,6 +1,6 @@
 //! Interrupt claim/complete register
 
-use crate::common::unsafe_peripheral;
+use crate::{common::unsafe_peripheral, stats::InterruptStats};
 use riscv_pac::ExternalInterruptNumber;
 
 unsafe_peripheral!(CLAIM, u32, RW);
@@ -8,9 +8,15 @@ unsafe_peripheral!(CLAIM, u32, RW);
 impl CLAIM {
     /// Claims the number of a pending interrupt for for the PLIC context.
     /// If no interrupt is pending for this context, it returns [`None`].
+    ///
+    /// # Note
+    ///
+    /// The claim is ordered before all the subsequent memory accesses
+    /// (see [`Reg::read_ordered`](crate::common::Reg::read_ordered)). Thus, interrupt handlers
+    /// observe the memory written by the device before raising the interrupt (e.g., via DMA).
     #[inline]
     pub fn claim<I: ExternalInterruptNumber>(self) -> Option<I> {
-        match self.register.read() {
+        match self.register.read_ordered() {
             0 => None,
             i => Some(I::from_number(i as _).unwrap()),
         }
@@ -26,6 +32,74 @@ impl CLAIM {
     pub fn complete<I: ExternalInterruptNumber>(self, source: I) {
         self.register.write(source.number() as _)
     }
+
+    /// Claims a pending interrupt for the PLIC context, returning a guard that completes it.
+    /// If no interrupt is pending for this context, it returns [`None`].
+    ///
+    /// Unlike [`CLAIM::claim`], the interrupt cannot be left uncompleted, nor completed
+    /// with a different source: the guard completes it when it is dropped.
+    #[inline]
+    pub fn claim_guarded<I: ExternalInterruptNumber>(self) -> Option<ClaimedInterrupt<I>> {
+        self.claim().map(|source| ClaimedInterrupt {
+            claim: self,
+            source,
+        })
+    }
+
+    /// Claims a pending interrupt for the PLIC context, calls `handler` with it, and completes it.
+    /// It returns the handled interrupt, or [`None`] if no interrupt is pending for this context.
+    #[inline]
+    pub fn dispatch<I: ExternalInterruptNumber, F: FnOnce(I)>(self, handler: F) -> Option<I> {
+        let claimed = self.claim_guarded()?;
+        let source = claimed.source();
+        handler(source);
+        claimed.complete();
+        Some(source)
+    }
+
+    /// Same as [`CLAIM::dispatch`], but it also records the handled interrupt in `stats`.
+    #[inline]
+    pub fn dispatch_counted<I: ExternalInterruptNumber, F: FnOnce(I), const N: usize>(
+        self,
+        stats: &InterruptStats<N>,
+        handler: F,
+    ) -> Option<I> {
+        self.dispatch(|source| {
+            stats.record(source);
+            handler(source)
+        })
+    }
+}
+
+/// Interrupt claimed with [`CLAIM::claim_guarded`].
+///
+/// The interrupt is marked as complete when this guard is dropped or [`completed`](Self::complete).
+#[must_use = "the interrupt is completed as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ClaimedInterrupt<I: ExternalInterruptNumber> {
+    claim: CLAIM,
+    source: I,
+}
+
+impl<I: ExternalInterruptNumber> ClaimedInterrupt<I> {
+    /// Returns the claimed interrupt source.
+    #[inline]
+    pub fn source(&self) -> I {
+        self.source
+    }
+
+    /// Marks the claimed interrupt as complete.
+    #[inline]
+    pub fn complete(self) {
+        // completed in `drop`
+    }
+}
+
+impl<I: ExternalInterruptNumber> Drop for ClaimedInterrupt<I> {
+    #[inline]
+    fn drop(&mut self) {
+        self.claim.complete(self.source);
+    }
 }
 
 #[cfg(test)]
@@ -48,4 +122,50 @@ mod test {
             assert_eq!(claim.claim(), Some(interrupt));
         }
     }
+
+    #[test]
+    fn test_claim_guarded() {
+        let mut raw_reg = 0u32;
+        // SAFETY: valid memory address
+        let claim = unsafe { CLAIM::new(&mut raw_reg as *mut _ as _) };
+
+        assert!(claim.claim_guarded::<Interrupt>().is_none());
+        assert_eq!(claim.dispatch(|_: Interrupt| unreachable!()), None);
+
+        let interrupt = Interrupt::from_number(2).unwrap();
+        claim.complete(interrupt);
+        let claimed = claim.claim_guarded::<Interrupt>().unwrap();
+        assert_eq!(claimed.source(), interrupt);
+        // the completion is written when the guard is dropped
+        claim.complete(Interrupt::from_number(1).unwrap());
+        drop(claimed);
+        assert_eq!(claim.claim(), Some(interrupt));
+
+        let interrupt = Interrupt::from_number(3).unwrap();
+        claim.complete(interrupt);
+        let mut handled = None;
+        assert_eq!(claim.dispatch(|i| handled = Some(i)), Some(interrupt));
+        assert_eq!(handled, Some(interrupt));
+        assert_eq!(claim.claim(), Some(interrupt));
+    }
+
+    #[test]
+    fn test_dispatch_counted() {
+        let mut raw_reg = 0u32;
+        // SAFETY: valid memory address
+        let claim = unsafe { CLAIM::new(&mut raw_reg as *mut _ as _) };
+        let stats = InterruptStats::<{ Interrupt::MAX_INTERRUPT_NUMBER + 1 }>::new();
+
+        assert_eq!(
+            claim.dispatch_counted(&stats, |_: Interrupt| unreachable!()),
+            None
+        );
+        assert_eq!(stats.total(), 0);
+
+        let interrupt = Interrupt::from_number(3).unwrap();
+        claim.complete(interrupt);
+        assert_eq!(claim.dispatch_counted(&stats, |_| {}), Some(interrupt));
+        assert_eq!(stats.count(interrupt), 1);
+        assert_eq!(stats.total(), 1);
+    }
 }
@@ -1,13 +1,14 @@
 //! Interrupt enables register of a PLIC context.
 
-use crate::common::{Reg, RW};
-use riscv_pac::ExternalInterruptNumber;
+use super::{iter_sources, N_WORDS};
+use crate::common::{Reg, RegArray, RW};
+use riscv_pac::{result::Result, ExternalInterruptNumber};
 
 /// Enables register of a PLIC context.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct ENABLES {
-    ptr: *mut u32,
+    regs: RegArray<u32, RW, N_WORDS, 4>,
 }
 
 impl ENABLES {
@@ -18,25 +19,69 @@ impl ENABLES {
     /// The base address must point to a valid Interrupts enables register.
     #[inline]
     pub(crate) const unsafe fn new(address: usize) -> Self {
-        Self { ptr: address as _ }
+        Self {
+            regs: RegArray::new(address as _),
+        }
     }
 
     #[cfg(test)]
     #[inline]
     pub(crate) fn address(self) -> usize {
-        self.ptr as _
+        self.regs.get_ptr() as _
+    }
+
+    /// Returns the register with the bit of a given interrupt source.
+    #[inline]
+    fn reg<I: ExternalInterruptNumber>(self, source: I) -> Reg<u32, RW> {
+        let offset = source.number() / u32::BITS as usize;
+        // SAFETY: valid interrupt number
+        unsafe { self.regs.get_unchecked(offset) }
     }
 
     /// Checks if an interrupt source is enabled for the PLIC context.
     #[inline]
     pub fn is_enabled<I: ExternalInterruptNumber>(self, source: I) -> bool {
+        let reg = self.reg(source);
         let source = source.number();
-        let offset = (source / u32::BITS as usize) as _;
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
         reg.read_bit(source % u32::BITS as usize)
     }
 
+    /// Returns the `index`th word of enable bits. Bit `i` of word `index` corresponds to the source `32 * index + i`.
+    ///
+    /// # Errors
+    ///
+    /// If `index` is not lower than 32, it returns [`Error::IndexOutOfBounds`](riscv_pac::result::Error::IndexOutOfBounds).
+    #[inline]
+    pub fn read_word(self, index: usize) -> Result<u32> {
+        Ok(self.regs.get(index)?.read())
+    }
+
+    /// Writes the `index`th word of enable bits. Bit `i` of word `index` corresponds to the source `32 * index + i`.
+    ///
+    /// # Errors
+    ///
+    /// If `index` is not lower than 32, it returns [`Error::IndexOutOfBounds`](riscv_pac::result::Error::IndexOutOfBounds).
+    ///
+    /// # Safety
+    ///
+    /// * Enabling interrupt sources can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn write_word(self, index: usize, value: u32) -> Result<()> {
+        self.regs.get(index)?.write(value);
+        Ok(())
+    }
+
+    /// Returns an iterator over the interrupt sources enabled for the PLIC context, sorted by interrupt number.
+    ///
+    /// It only reads the words with bits of sources up to [`InterruptNumber::MAX_INTERRUPT_NUMBER`],
+    /// and skips the words with no enabled sources. Bits that do not correspond to a valid source are ignored.
+    ///
+    /// [`InterruptNumber::MAX_INTERRUPT_NUMBER`]: riscv_pac::InterruptNumber::MAX_INTERRUPT_NUMBER
+    #[inline]
+    pub fn iter_enabled<I: ExternalInterruptNumber>(self) -> impl Iterator<Item = I> {
+        iter_sources(self.regs)
+    }
+
     /// Enables an interrupt source for the PLIC context.
     ///
     /// # Note
@@ -48,10 +93,8 @@ impl ENABLES {
     /// * Enabling an interrupt source can break mask-based critical sections.
     #[inline]
     pub unsafe fn enable<I: ExternalInterruptNumber>(self, source: I) {
+        let reg = self.reg(source);
         let source = source.number();
-        let offset = (source / u32::BITS as usize) as _;
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
         reg.set_bit(source % u32::BITS as usize);
     }
 
@@ -73,10 +116,8 @@ impl ENABLES {
         source: I,
         order: core::sync::atomic::Ordering,
     ) {
+        let reg = self.reg(source);
         let source = source.number();
-        let offset = (source / u32::BITS as usize) as _;
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
         reg.atomic_set_bit(source % u32::BITS as usize, order);
     }
 
@@ -87,10 +128,8 @@ impl ENABLES {
     /// It performs non-atomic read-modify-write operations, which may lead to **wrong** behavior.
     #[inline]
     pub fn disable<I: ExternalInterruptNumber>(self, source: I) {
+        let reg = self.reg(source);
         let source = source.number();
-        let offset = (source / u32::BITS as usize) as _;
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
         reg.clear_bit(source % u32::BITS as usize);
     }
 
@@ -111,13 +150,37 @@ impl ENABLES {
         source: I,
         order: core::sync::atomic::Ordering,
     ) {
+        let reg = self.reg(source);
         let source = source.number();
-        let offset = (source / u32::BITS as usize) as _;
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
         reg.atomic_clear_bit(source % u32::BITS as usize, order);
     }
 
+    /// Returns the number of words required to save the enable bits of all the interrupt sources.
+    #[inline]
+    pub(crate) const fn state_len<I: ExternalInterruptNumber>() -> usize {
+        I::MAX_INTERRUPT_NUMBER / u32::BITS as usize + 1
+    }
+
+    /// Saves the first `state.len()` words of enable bits into `state`.
+    #[inline]
+    pub(crate) fn save(self, state: &mut [u32]) {
+        for (reg, word) in self.regs.iter().zip(state) {
+            *word = reg.read();
+        }
+    }
+
+    /// Restores the first `state.len()` words of enable bits from `state`.
+    ///
+    /// # Safety
+    ///
+    /// Enabling interrupt sources can break mask-based critical sections.
+    #[inline]
+    pub(crate) unsafe fn restore(self, state: &[u32]) {
+        for (reg, &word) in self.regs.iter().zip(state) {
+            reg.write(word);
+        }
+    }
+
     /// Enables all the external interrupt sources for the PLIC context.
     ///
     /// # Safety
@@ -125,9 +188,8 @@ impl ENABLES {
     ///* Enabling all interrupt sources can break mask-based critical sections.
     #[inline]
     pub unsafe fn enable_all<I: ExternalInterruptNumber>(self) {
-        for offset in 0..=(I::MAX_INTERRUPT_NUMBER as u32 / u32::BITS) as isize {
-            // SAFETY: valid offset
-            let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
+        let n_words = I::MAX_INTERRUPT_NUMBER / u32::BITS as usize + 1;
+        for reg in self.regs.iter().take(n_words) {
             reg.write(0xFFFF_FFFF);
         }
     }
@@ -135,9 +197,8 @@ impl ENABLES {
     /// Disables all the external interrupt sources for the PLIC context.
     #[inline]
     pub fn disable_all<I: ExternalInterruptNumber>(self) {
-        for offset in 0..=(I::MAX_INTERRUPT_NUMBER as u32 / u32::BITS) as _ {
-            // SAFETY: valid offset
-            let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(offset)) };
+        let n_words = I::MAX_INTERRUPT_NUMBER / u32::BITS as usize + 1;
+        for reg in self.regs.iter().take(n_words) {
             reg.write(0);
         }
     }
@@ -145,8 +206,9 @@ impl ENABLES {
 
 #[cfg(test)]
 mod test {
-    use super::super::test::Interrupt;
+    use super::super::test::{Interrupt, LargeInterrupt};
     use super::*;
+    use riscv_pac::result::Error;
 
     #[test]
     fn test_enables() {
@@ -233,4 +295,38 @@ mod test {
             assert_eq!(enables.is_enabled(Interrupt::I4), i & 0x10 != 0);
         }
     }
+
+    #[test]
+    fn test_enables_words() {
+        // slice to emulate the interrupt enables register of a target with 128 sources
+        let mut raw_reg = [0u32; 32];
+        // SAFETY: valid memory address
+        let enables = unsafe { ENABLES::new(raw_reg.as_mut_ptr() as _) };
+
+        assert_eq!(enables.iter_enabled::<LargeInterrupt>().next(), None);
+
+        unsafe {
+            assert_eq!(enables.write_word(1, 0x0001_0001), Ok(()));
+            enables.enable(LargeInterrupt(127));
+            assert_eq!(
+                enables.write_word(32, 1),
+                Err(Error::IndexOutOfBounds {
+                    index: 32,
+                    min: 0,
+                    max: 31
+                })
+            );
+        }
+        assert_eq!(enables.read_word(1), Ok(0x0001_0001));
+        assert_eq!(enables.read_word(3), Ok(0x8000_0000));
+
+        let mut enabled = enables.iter_enabled::<LargeInterrupt>();
+        for number in [32, 48, 127] {
+            assert_eq!(enabled.next(), Some(LargeInterrupt(number)));
+        }
+        assert_eq!(enabled.next(), None);
+
+        enables.disable_all::<LargeInterrupt>();
+        assert_eq!(enables.iter_enabled::<LargeInterrupt>().next(), None);
+    }
 }
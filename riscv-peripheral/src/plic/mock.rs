@@ -0,0 +1,249 @@
+//! Memory-backed PLIC registers for host-side testing.
+//!
+//! [`MockPlic`] emulates the registers of a PLIC with a single context in regular memory.
+//! Unlike actual PLICs, its pending bits are writable, so the unsafe [`PENDINGS::try_pend`] can be used
+//! to inject external interrupts and unit test dispatch code paths without hardware.
+//!
+//! The mock also provides assertion helpers (e.g., [`MockPlic::assert_enabled`]), so HAL and application
+//! crates can check the interrupt configuration applied by their code in unit tests.
+//! This module is only available with the `mock` feature.
+//!
+//! # Note
+//!
+//! The mock does not emulate the PLIC logic (e.g., claim/complete does not update pending bits).
+//! Tests must update the registers to reproduce the expected behavior of the PLIC.
+//!
+//! As [`super::Plic`] requires a constant base address, the mock does not implement it.
+//! Instead, code under test should receive the register views of the PLIC (e.g., [`ENABLES`]).
+
+use super::{
+    claim::CLAIM, enables::ENABLES, pendings::PENDINGS, priorities::PRIORITIES,
+    threshold::THRESHOLD, ExternalInterruptNumber, PriorityNumber, N_SOURCES, N_WORDS,
+};
+use core::sync::atomic::AtomicU32;
+
+/// Cleared register, to initialize register arrays in constant contexts.
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU32 = AtomicU32::new(0);
+
+/// Memory-backed emulation of the registers of a PLIC with a single context.
+///
+/// Register views borrow the mock for `'static`, so it is usually declared as a `static`.
+/// Registers are backed by atomic integers, so the mock can be shared among test threads.
+///
+/// # Example
+///
+/// ```
+/// use riscv_peripheral::plic::mock::MockPlic;
+///
+/// static PLIC: MockPlic = MockPlic::new();
+///
+/// let pendings = PLIC.pendings();
+/// let claim = PLIC.claim();
+/// ```
+pub struct MockPlic {
+    priorities: [AtomicU32; N_SOURCES],
+    pendings: [AtomicU32; N_WORDS],
+    enables: [AtomicU32; N_WORDS],
+    threshold: AtomicU32,
+    claim: AtomicU32,
+}
+
+impl MockPlic {
+    /// Creates a new mock PLIC with all its registers cleared.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            priorities: [ZERO; N_SOURCES],
+            pendings: [ZERO; N_WORDS],
+            enables: [ZERO; N_WORDS],
+            threshold: ZERO,
+            claim: ZERO,
+        }
+    }
+
+    /// Returns the priorities register of the mock PLIC.
+    #[inline]
+    pub fn priorities(&'static self) -> PRIORITIES {
+        // SAFETY: valid memory address during the whole program execution
+        unsafe { PRIORITIES::new(self.priorities.as_ptr() as _) }
+    }
+
+    /// Returns the pendings register of the mock PLIC.
+    #[inline]
+    pub fn pendings(&'static self) -> PENDINGS {
+        // SAFETY: valid memory address during the whole program execution
+        unsafe { PENDINGS::new(self.pendings.as_ptr() as _) }
+    }
+
+    /// Returns the interrupts enable register of the mock PLIC context.
+    #[inline]
+    pub fn enables(&'static self) -> ENABLES {
+        // SAFETY: valid memory address during the whole program execution
+        unsafe { ENABLES::new(self.enables.as_ptr() as _) }
+    }
+
+    /// Returns the interrupt threshold register of the mock PLIC context.
+    #[inline]
+    pub fn threshold(&'static self) -> THRESHOLD {
+        // SAFETY: valid memory address during the whole program execution
+        unsafe { THRESHOLD::new(self.threshold.as_ptr() as _) }
+    }
+
+    /// Returns the interrupt claim/complete register of the mock PLIC context.
+    #[inline]
+    pub fn claim(&'static self) -> CLAIM {
+        // SAFETY: valid memory address during the whole program execution
+        unsafe { CLAIM::new(self.claim.as_ptr() as _) }
+    }
+
+    /// Asserts that the interrupt source `source` is enabled in the mock PLIC context.
+    #[track_caller]
+    pub fn assert_enabled<I: ExternalInterruptNumber>(&'static self, source: I) {
+        assert!(
+            self.enables().is_enabled(source),
+            "interrupt source {} is not enabled",
+            source.number()
+        );
+    }
+
+    /// Asserts that the interrupt source `source` is disabled in the mock PLIC context.
+    #[track_caller]
+    pub fn assert_disabled<I: ExternalInterruptNumber>(&'static self, source: I) {
+        assert!(
+            !self.enables().is_enabled(source),
+            "interrupt source {} is enabled",
+            source.number()
+        );
+    }
+
+    /// Asserts that the interrupt source `source` is pending in the mock PLIC.
+    #[track_caller]
+    pub fn assert_pending<I: ExternalInterruptNumber>(&'static self, source: I) {
+        assert!(
+            self.pendings().is_pending(source),
+            "interrupt source {} is not pending",
+            source.number()
+        );
+    }
+
+    /// Asserts that the interrupt source `source` is not pending in the mock PLIC.
+    #[track_caller]
+    pub fn assert_not_pending<I: ExternalInterruptNumber>(&'static self, source: I) {
+        assert!(
+            !self.pendings().is_pending(source),
+            "interrupt source {} is pending",
+            source.number()
+        );
+    }
+
+    /// Asserts that the priority level of the interrupt source `source` is `priority`.
+    #[track_caller]
+    pub fn assert_priority<I: ExternalInterruptNumber, P: PriorityNumber>(
+        &'static self,
+        source: I,
+        priority: P,
+    ) {
+        let actual = self.priorities().get_priority::<I, P>(source).number();
+        assert_eq!(
+            actual,
+            priority.number(),
+            "unexpected priority level of interrupt source {}",
+            source.number()
+        );
+    }
+
+    /// Asserts that the priority threshold of the mock PLIC context is `threshold`.
+    #[track_caller]
+    pub fn assert_threshold<P: PriorityNumber>(&'static self, threshold: P) {
+        let actual = self.threshold().get_threshold::<P>().number();
+        assert_eq!(actual, threshold.number(), "unexpected priority threshold");
+    }
+}
+
+impl Default for MockPlic {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{Interrupt, Priority};
+    use super::*;
+    use core::sync::atomic::Ordering;
+
+    /// Dispatches the pending interrupt with the highest priority, as an external handler would do.
+    fn dispatch(plic: &'static MockPlic) -> Option<Interrupt> {
+        let pendings = plic.pendings();
+        let priorities = plic.priorities();
+        let interrupt = [Interrupt::I1, Interrupt::I2, Interrupt::I3, Interrupt::I4]
+            .into_iter()
+            .filter(|&i| pendings.is_pending(i) && plic.enables().is_enabled(i))
+            .max_by_key(|&i| priorities.get_priority::<_, Priority>(i) as usize)?;
+        // emulate the claim logic of the PLIC
+        plic.claim().complete(interrupt);
+        let claimed = plic.claim().claim();
+        // clearing pending bits is only allowed in the mock
+        plic.pendings[0].fetch_and(!(1 << interrupt as u32), Ordering::Relaxed);
+        claimed
+    }
+
+    #[test]
+    fn test_mock_plic() {
+        static PLIC: MockPlic = MockPlic::new();
+
+        assert_eq!(dispatch(&PLIC), None);
+
+        // SAFETY: the mock pending bits are writable and only accessed by this test
+        unsafe {
+            assert_eq!(PLIC.pendings().try_pend(Interrupt::I2), Ok(()));
+            assert_eq!(PLIC.pendings().try_pend(Interrupt::I3), Ok(()));
+        }
+        // pending interrupts are not dispatched until enabled
+        assert_eq!(dispatch(&PLIC), None);
+
+        unsafe {
+            PLIC.enables().enable(Interrupt::I2);
+            PLIC.enables().enable(Interrupt::I3);
+            PLIC.priorities().set_priority(Interrupt::I2, Priority::P2);
+            PLIC.priorities().set_priority(Interrupt::I3, Priority::P1);
+        }
+        assert_eq!(dispatch(&PLIC), Some(Interrupt::I2));
+        assert_eq!(dispatch(&PLIC), Some(Interrupt::I3));
+        assert_eq!(dispatch(&PLIC), None);
+    }
+
+    #[test]
+    fn test_mock_plic_assertions() {
+        static PLIC: MockPlic = MockPlic::new();
+
+        PLIC.assert_disabled(Interrupt::I1);
+        PLIC.assert_not_pending(Interrupt::I1);
+        PLIC.assert_priority(Interrupt::I1, Priority::P0);
+        PLIC.assert_threshold(Priority::P0);
+
+        unsafe {
+            PLIC.enables().enable(Interrupt::I1);
+            PLIC.priorities().set_priority(Interrupt::I1, Priority::P3);
+            PLIC.threshold().set_threshold(Priority::P2);
+            assert_eq!(PLIC.pendings().try_pend(Interrupt::I1), Ok(()));
+        }
+
+        PLIC.assert_enabled(Interrupt::I1);
+        PLIC.assert_disabled(Interrupt::I2);
+        PLIC.assert_pending(Interrupt::I1);
+        PLIC.assert_not_pending(Interrupt::I2);
+        PLIC.assert_priority(Interrupt::I1, Priority::P3);
+        PLIC.assert_threshold(Priority::P2);
+    }
+
+    #[test]
+    #[should_panic(expected = "interrupt source 3 is not enabled")]
+    fn test_mock_plic_assert_enabled() {
+        static PLIC: MockPlic = MockPlic::new();
+
+        PLIC.assert_enabled(Interrupt::I3);
+    }
+}
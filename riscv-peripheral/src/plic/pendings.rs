@@ -1,13 +1,17 @@
 //! Interrupt pending bits register.
 
-use crate::common::{Reg, RO};
-use riscv_pac::ExternalInterruptNumber;
+use super::{iter_sources, N_WORDS};
+use crate::common::{Reg, RegArray, RO, RW};
+use riscv_pac::{
+    result::{Error, Result},
+    ExternalInterruptNumber,
+};
 
 /// Interrupts pending bits register.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct PENDINGS {
-    ptr: *mut u32,
+    regs: RegArray<u32, RO, N_WORDS, 4>,
 }
 
 impl PENDINGS {
@@ -18,29 +22,87 @@ impl PENDINGS {
     /// The base address must point to a valid Interrupts pending bits register.
     #[inline]
     pub(crate) const unsafe fn new(address: usize) -> Self {
-        Self { ptr: address as _ }
+        Self {
+            regs: RegArray::new(address as _),
+        }
     }
 
     #[cfg(test)]
     #[inline]
     pub(crate) fn address(self) -> usize {
-        self.ptr as _
+        self.regs.get_ptr() as _
+    }
+
+    /// Returns the register with the bit of a given interrupt source.
+    #[inline]
+    fn reg<I: ExternalInterruptNumber>(self, source: I) -> Reg<u32, RO> {
+        let offset = source.number() / u32::BITS as usize;
+        // SAFETY: valid interrupt number
+        unsafe { self.regs.get_unchecked(offset) }
     }
 
     /// Checks if an interrupt triggered by a given source is pending.
     #[inline]
     pub fn is_pending<I: ExternalInterruptNumber>(self, source: I) -> bool {
+        let reg = self.reg(source);
         let source = source.number();
-        let offset = (source / u32::BITS as usize) as _;
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RO> = unsafe { Reg::new(self.ptr.offset(offset)) };
         reg.read_bit(source % u32::BITS as usize)
     }
+
+    /// Returns the `index`th word of pending bits. Bit `i` of word `index` corresponds to the source `32 * index + i`.
+    ///
+    /// # Errors
+    ///
+    /// If `index` is not lower than 32, it returns [`Error::IndexOutOfBounds`].
+    #[inline]
+    pub fn read_word(self, index: usize) -> Result<u32> {
+        Ok(self.regs.get(index)?.read())
+    }
+
+    /// Returns an iterator over the pending interrupt sources, sorted by interrupt number.
+    ///
+    /// It only reads the words with bits of sources up to [`InterruptNumber::MAX_INTERRUPT_NUMBER`],
+    /// and skips the words with no pending sources. Bits that do not correspond to a valid source are ignored.
+    ///
+    /// [`InterruptNumber::MAX_INTERRUPT_NUMBER`]: riscv_pac::InterruptNumber::MAX_INTERRUPT_NUMBER
+    #[inline]
+    pub fn iter_pending<I: ExternalInterruptNumber>(self) -> impl Iterator<Item = I> {
+        iter_sources(self.regs)
+    }
+
+    /// Tries to pend an interrupt triggered by a given source.
+    ///
+    /// Pending bits are read-only in the PLIC specification. However, some platforms
+    /// (or shadow mechanisms, such as `plic::mock::MockPlic`) allow software to set them.
+    /// This method sets the pending bit and reads it back to check that the write took effect.
+    ///
+    /// # Errors
+    ///
+    /// If the pending bit is still cleared after the write, it returns [`Error::Unimplemented`].
+    ///
+    /// # Note
+    ///
+    /// It performs non-atomic read-modify-write operations, which may lead to **wrong** behavior.
+    ///
+    /// # Safety
+    ///
+    /// * The pending bits of the target platform must be writable by software.
+    /// * No other context (e.g., the PLIC gateways or another HART) may modify the pending word concurrently.
+    #[inline]
+    pub unsafe fn try_pend<I: ExternalInterruptNumber>(self, source: I) -> Result<()> {
+        let reg: Reg<u32, RW> = Reg::new(self.reg(source).get_ptr());
+        let source = source.number();
+        reg.set_bit(source % u32::BITS as usize);
+        match reg.read_bit(source % u32::BITS as usize) {
+            true => Ok(()),
+            false => Err(Error::Unimplemented),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::super::test::Interrupt;
+    use super::super::test::{Interrupt, LargeInterrupt};
     use super::*;
 
     #[test]
@@ -59,4 +121,57 @@ mod test {
             assert_eq!(pendings.is_pending(Interrupt::I4), i & 0x10 != 0);
         }
     }
+
+    #[test]
+    fn test_try_pend() {
+        // slice to emulate the interrupt pendings register
+        let mut raw_reg = [0u32; 32];
+        // SAFETY: valid memory address
+        let pendings = unsafe { PENDINGS::new(raw_reg.as_mut_ptr() as _) };
+
+        // SAFETY: the emulated pending bits are writable and not shared
+        assert_eq!(unsafe { pendings.try_pend(Interrupt::I1) }, Ok(()));
+        assert!(pendings.is_pending(Interrupt::I1));
+        assert!(!pendings.is_pending(Interrupt::I2));
+        // SAFETY: the emulated pending bits are writable and not shared
+        assert_eq!(unsafe { pendings.try_pend(Interrupt::I3) }, Ok(()));
+        assert!(pendings.is_pending(Interrupt::I3));
+        assert_eq!(raw_reg[0], 0b1010);
+    }
+
+    #[test]
+    fn test_iter_pending() {
+        // slice to emulate the interrupt pendings register of a target with 128 sources
+        let mut raw_reg = [0u32; 32];
+        raw_reg[0] = 0b1011; // source 0 is reserved
+        raw_reg[2] = 0x8000_0001;
+        raw_reg[3] = 0x8000_0000;
+        raw_reg[4] = 0xFFFF_FFFF; // beyond the last source
+                                  // SAFETY: valid memory address
+        let pendings = unsafe { PENDINGS::new(raw_reg.as_mut_ptr() as _) };
+
+        assert_eq!(pendings.read_word(0), Ok(0b1011));
+        assert_eq!(pendings.read_word(1), Ok(0));
+        assert_eq!(pendings.read_word(31), Ok(0));
+        assert_eq!(
+            pendings.read_word(32),
+            Err(Error::IndexOutOfBounds {
+                index: 32,
+                min: 0,
+                max: 31
+            })
+        );
+
+        let mut pending = pendings.iter_pending::<LargeInterrupt>();
+        for number in [1, 3, 64, 95, 127] {
+            assert_eq!(pending.next(), Some(LargeInterrupt(number)));
+        }
+        assert_eq!(pending.next(), None);
+
+        // Small targets only scan the first word
+        let mut pending = pendings.iter_pending::<Interrupt>();
+        assert_eq!(pending.next(), Some(Interrupt::I1));
+        assert_eq!(pending.next(), Some(Interrupt::I3));
+        assert_eq!(pending.next(), None);
+    }
 }
@@ -1,13 +1,14 @@
 //! Interrupts Priorities register.
 
-use crate::common::{Reg, RW};
+use super::N_SOURCES;
+use crate::common::{RegArray, RW};
 use riscv_pac::{ExternalInterruptNumber, PriorityNumber};
 
 /// Interrupts priorities register.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct PRIORITIES {
-    ptr: *mut u32,
+    regs: RegArray<u32, RW, N_SOURCES, 4>,
 }
 
 impl PRIORITIES {
@@ -18,20 +19,22 @@ impl PRIORITIES {
     /// The base address must point to a valid Interrupts priorities register.
     #[inline]
     pub(crate) const unsafe fn new(address: usize) -> Self {
-        Self { ptr: address as _ }
+        Self {
+            regs: RegArray::new(address as _),
+        }
     }
 
     #[cfg(test)]
     #[inline]
     pub(crate) fn address(self) -> usize {
-        self.ptr as _
+        self.regs.get_ptr() as _
     }
 
     /// Returns the priority assigned to a given interrupt source.
     #[inline]
     pub fn get_priority<I: ExternalInterruptNumber, P: PriorityNumber>(self, source: I) -> P {
         // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.add(source.number())) };
+        let reg = unsafe { self.regs.get_unchecked(source.number()) };
         P::from_number(reg.read() as _).unwrap()
     }
 
@@ -47,10 +50,36 @@ impl PRIORITIES {
         priority: P,
     ) {
         // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.add(source.number())) };
+        let reg = unsafe { self.regs.get_unchecked(source.number()) };
         reg.write(priority.number() as _);
     }
 
+    /// Returns the number of words required to save the priorities of all the interrupt sources.
+    #[inline]
+    pub(crate) const fn state_len<I: ExternalInterruptNumber>() -> usize {
+        I::MAX_INTERRUPT_NUMBER + 1
+    }
+
+    /// Saves the priority levels of the first `state.len()` interrupt sources into `state`.
+    #[inline]
+    pub(crate) fn save(self, state: &mut [u32]) {
+        for (reg, word) in self.regs.iter().zip(state) {
+            *word = reg.read();
+        }
+    }
+
+    /// Restores the priority levels of the first `state.len()` interrupt sources from `state`.
+    ///
+    /// # Safety
+    ///
+    /// Changing the priority levels can break priority-based critical sections.
+    #[inline]
+    pub(crate) unsafe fn restore(self, state: &[u32]) {
+        for (reg, &word) in self.regs.iter().zip(state) {
+            reg.write(word);
+        }
+    }
+
     /// Resets all the priority levels of all the external interrupt sources to 0.
     ///
     /// # Note
@@ -59,9 +88,7 @@ impl PRIORITIES {
     /// Thus, this method effectively disables the all the external interrupts.
     #[inline]
     pub fn reset<I: ExternalInterruptNumber>(self) {
-        for source in 0..=I::MAX_INTERRUPT_NUMBER as _ {
-            // SAFETY: interrupt number within range
-            let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr.offset(source)) };
+        for reg in self.regs.iter().take(I::MAX_INTERRUPT_NUMBER + 1) {
             reg.write(0);
         }
     }
@@ -1,9 +1,32 @@
 //! Priority threshold register.
 
 use crate::{common::unsafe_peripheral, plic::PriorityNumber};
+use riscv_pac::result::Result;
 
 unsafe_peripheral!(THRESHOLD, u32, RW);
 
+/// Policy for converting out-of-range numbers to priority levels.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Saturation {
+    /// Numbers greater than `P::MAX_PRIORITY_NUMBER` are rejected.
+    Strict,
+    /// Numbers greater than `P::MAX_PRIORITY_NUMBER` are clamped to the highest priority level.
+    Clamp,
+}
+
+/// Converts a raw threshold number to a priority level with the given saturation policy.
+///
+/// # Errors
+///
+/// If the (clamped) number is not a valid priority level, it returns the error of `P::from_number`.
+#[inline]
+pub fn to_priority<P: PriorityNumber>(number: usize, saturation: Saturation) -> Result<P> {
+    match saturation {
+        Saturation::Strict => P::from_number(number),
+        Saturation::Clamp => P::from_number(number.min(P::MAX_PRIORITY_NUMBER)),
+    }
+}
+
 impl THRESHOLD {
     /// Returns the priority threshold level.
     #[inline]
@@ -11,6 +34,17 @@ impl THRESHOLD {
         P::from_number(self.register.read() as _).unwrap()
     }
 
+    /// Returns the priority threshold level, or an error if the raw level is not a valid `P`.
+    ///
+    /// # Note
+    ///
+    /// Unlike [`THRESHOLD::get_threshold`], it does not panic if the register
+    /// was written with a number out of the range of `P` (e.g., by other software).
+    #[inline]
+    pub fn try_get_threshold<P: PriorityNumber>(self, saturation: Saturation) -> Result<P> {
+        to_priority(self.register.read() as _, saturation)
+    }
+
     /// Sets the priority threshold level.
     ///
     /// # Safety
@@ -21,6 +55,44 @@ impl THRESHOLD {
         self.register.write(threshold.number() as _)
     }
 
+    /// Sets the priority threshold level from a raw number with the given saturation policy.
+    /// It returns the priority level that has been written.
+    ///
+    /// # Errors
+    ///
+    /// If the number cannot be converted to a priority level, the register
+    /// is not modified and it returns the error of [`to_priority`].
+    ///
+    /// # Safety
+    ///
+    /// Changing the priority threshold can break priority-based critical sections.
+    #[inline]
+    pub unsafe fn set_threshold_number<P: PriorityNumber>(
+        self,
+        number: usize,
+        saturation: Saturation,
+    ) -> Result<P> {
+        let threshold = to_priority(number, saturation)?;
+        self.set_threshold(threshold);
+        Ok(threshold)
+    }
+
+    /// Returns the raw priority threshold level to save it.
+    #[inline]
+    pub(crate) fn save(self) -> u32 {
+        self.register.read()
+    }
+
+    /// Restores a raw priority threshold level returned by [`THRESHOLD::save`].
+    ///
+    /// # Safety
+    ///
+    /// Changing the priority threshold can break priority-based critical sections.
+    #[inline]
+    pub(crate) unsafe fn restore(self, state: u32) {
+        self.register.write(state)
+    }
+
     /// Resets the priority threshold level to 0.
     ///
     /// # Note
@@ -37,6 +109,7 @@ impl THRESHOLD {
 mod test {
     use super::super::test::Priority;
     use super::*;
+    use riscv_pac::result::Error;
 
     #[test]
     fn test_threshold() {
@@ -52,4 +125,49 @@ mod test {
         threshold.reset();
         assert_eq!(threshold.get_threshold::<Priority>(), Priority::P0);
     }
+
+    #[test]
+    fn test_saturation() {
+        let mut raw_reg = 0u32;
+        // SAFETY: valid memory address
+        let threshold = unsafe { THRESHOLD::new(&mut raw_reg as *mut _ as _) };
+
+        let max = Priority::MAX_PRIORITY_NUMBER;
+        assert_eq!(to_priority(max, Saturation::Strict), Ok(Priority::P3));
+        assert_eq!(
+            to_priority::<Priority>(max + 1, Saturation::Strict),
+            Err(Error::InvalidVariant(max + 1))
+        );
+        assert_eq!(to_priority(max + 1, Saturation::Clamp), Ok(Priority::P3));
+        assert_eq!(to_priority(usize::MAX, Saturation::Clamp), Ok(Priority::P3));
+
+        // SAFETY: no priority-based critical sections
+        unsafe {
+            assert_eq!(
+                threshold.set_threshold_number(2, Saturation::Strict),
+                Ok(Priority::P2)
+            );
+            assert_eq!(
+                threshold.set_threshold_number::<Priority>(max + 1, Saturation::Strict),
+                Err(Error::InvalidVariant(max + 1))
+            );
+            assert_eq!(threshold.get_threshold::<Priority>(), Priority::P2);
+            assert_eq!(
+                threshold.set_threshold_number(max + 1, Saturation::Clamp),
+                Ok(Priority::P3)
+            );
+        }
+        assert_eq!(threshold.get_threshold::<Priority>(), Priority::P3);
+
+        // out-of-range value written by other software
+        threshold.register.write(max as u32 + 1);
+        assert_eq!(
+            threshold.try_get_threshold::<Priority>(Saturation::Strict),
+            Err(Error::InvalidVariant(max + 1))
+        );
+        assert_eq!(
+            threshold.try_get_threshold(Saturation::Clamp),
+            Ok(Priority::P3)
+        );
+    }
 }
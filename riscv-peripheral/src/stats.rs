@@ -0,0 +1,156 @@
+//! Interrupt statistics.
+//!
+//! [`InterruptStats`] counts how many times each interrupt source has been handled, so systems can
+//! expose their interrupt load (e.g., to detect interrupt storms) without instrumenting every handler.
+//! Each source has its own counter, indexed by its number. Counters wrap around on overflow.
+//!
+//! External interrupts can be counted with [`CLAIM::dispatch_counted`](crate::plic::claim::CLAIM::dispatch_counted),
+//! and core interrupts with [`InterruptStats::record`] in the dispatcher of the runtime.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use riscv_peripheral::stats::InterruptStats;
+//!
+//! // PAC's external interrupts, numbered from 1 to `Interrupt::MAX_INTERRUPT_NUMBER`
+//! static EXTERNAL_STATS: InterruptStats<{ Interrupt::MAX_INTERRUPT_NUMBER + 1 }> = InterruptStats::new();
+//!
+//! #[riscv_rt::core_interrupt(CoreInterrupt::MachineExternal)]
+//! fn machine_external() {
+//!     let claim = PLIC::ctx0().claim();
+//!     claim.dispatch_counted(&EXTERNAL_STATS, |source: Interrupt| { /* handle the interrupt */ });
+//! }
+//!
+//! let snapshot = EXTERNAL_STATS.snapshot();
+//! EXTERNAL_STATS.reset();
+//! ```
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use riscv_pac::InterruptNumber;
+
+/// Counters of the interrupt sources numbered from 0 to `N - 1`.
+#[derive(Debug)]
+pub struct InterruptStats<const N: usize> {
+    counters: [AtomicUsize; N],
+}
+
+impl<const N: usize> Default for InterruptStats<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> InterruptStats<N> {
+    /// Creates a new set of counters, all set to 0.
+    #[inline]
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicUsize = AtomicUsize::new(0);
+        Self {
+            counters: [ZERO; N],
+        }
+    }
+
+    /// Returns the counter of an interrupt source.
+    ///
+    /// The build fails if `N` is not greater than `I::MAX_INTERRUPT_NUMBER`.
+    #[inline]
+    fn counter<I: InterruptNumber>(&self, source: I) -> &AtomicUsize {
+        #[allow(clippy::let_unit_value)]
+        let () = Check::<I, N>::FITS;
+        &self.counters[source.number()]
+    }
+
+    /// Increments the counter of an interrupt source.
+    ///
+    /// # Note
+    ///
+    /// In targets without atomic read-modify-write instructions (i.e., without the `A` extension),
+    /// the counter is incremented with interrupts disabled.
+    #[inline]
+    pub fn record<I: InterruptNumber>(&self, source: I) {
+        let counter = self.counter(source);
+        #[cfg(target_has_atomic = "ptr")]
+        counter.fetch_add(1, Ordering::Relaxed);
+        #[cfg(not(target_has_atomic = "ptr"))]
+        riscv::interrupt::free(|| {
+            counter.store(
+                counter.load(Ordering::Relaxed).wrapping_add(1),
+                Ordering::Relaxed,
+            )
+        });
+    }
+
+    /// Returns the number of times an interrupt source has been recorded.
+    #[inline]
+    pub fn count<I: InterruptNumber>(&self, source: I) -> usize {
+        self.counter(source).load(Ordering::Relaxed)
+    }
+
+    /// Returns the sum of all the counters.
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.counters
+            .iter()
+            .fold(0, |acc, c| acc.wrapping_add(c.load(Ordering::Relaxed)))
+    }
+
+    /// Returns a copy of all the counters, indexed by interrupt number.
+    ///
+    /// # Note
+    ///
+    /// Counters are read one by one. Thus, interrupts handled while taking
+    /// the snapshot may only be reflected in some of the counters.
+    #[inline]
+    pub fn snapshot(&self) -> [usize; N] {
+        core::array::from_fn(|i| self.counters[i].load(Ordering::Relaxed))
+    }
+
+    /// Sets all the counters to 0.
+    #[inline]
+    pub fn reset(&self) {
+        self.counters
+            .iter()
+            .for_each(|c| c.store(0, Ordering::Relaxed));
+    }
+}
+
+/// Compile-time check that the counters of [`InterruptStats`] fit all the sources of `I`.
+struct Check<I, const N: usize>(core::marker::PhantomData<I>);
+
+impl<I: InterruptNumber, const N: usize> Check<I, N> {
+    const FITS: () = assert!(
+        I::MAX_INTERRUPT_NUMBER < N,
+        "InterruptStats: N must be greater than MAX_INTERRUPT_NUMBER"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::plic::test::Interrupt;
+    use super::*;
+
+    #[test]
+    fn test_interrupt_stats() {
+        let stats = InterruptStats::<{ Interrupt::MAX_INTERRUPT_NUMBER + 1 }>::new();
+        assert_eq!(stats.total(), 0);
+
+        let (i1, i2) = (Interrupt::I1, Interrupt::I2);
+        stats.record(i1);
+        stats.record(i2);
+        stats.record(i2);
+        assert_eq!(stats.count(i1), 1);
+        assert_eq!(stats.count(i2), 2);
+        assert_eq!(stats.total(), 3);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot[0], 0);
+        assert_eq!(snapshot[1], 1);
+        assert_eq!(snapshot[2], 2);
+        assert_eq!(snapshot.iter().sum::<usize>(), 3);
+
+        stats.reset();
+        assert_eq!(stats.snapshot(), [0; Interrupt::MAX_INTERRUPT_NUMBER + 1]);
+    }
+}
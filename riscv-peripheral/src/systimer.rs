@@ -0,0 +1,154 @@
+//! System timer facade.
+//!
+//! Depending on the privilege mode and the platform, the next timer event is programmed
+//! through different mechanisms:
+//!
+//! - [`Backend::Clint`]: the `MTIMECMP` register of the (A)CLINT peripheral (M-mode).
+//! - [`Backend::Sstc`]: the `stimecmp` CSR of the Sstc extension (S-mode).
+//! - [`Backend::Sbi`]: the timer extension of the SBI (S-mode).
+//!
+//! [`SysTimer`] hides these differences behind a single [`SysTimer::schedule`] method,
+//! so portable kernels can program timer events without dealing with each mechanism.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use riscv_peripheral::systimer::SysTimer;
+//!
+//! // In S-mode, use Sstc if available, or the SBI timer extension otherwise
+//! let systimer = SysTimer::detect(None, true).unwrap();
+//! let now = riscv::register::time::read64();
+//! systimer.schedule(now + 10_000).unwrap();
+//! ```
+
+use crate::aclint::mtimer::MTIMECMP;
+use riscv::sbi;
+use riscv_pac::result::{Error, Result};
+
+/// Mechanism used to program the next timer event.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Backend {
+    /// `MTIMECMP` register of the (A)CLINT peripheral assigned to the current HART.
+    Clint(MTIMECMP),
+    /// `stimecmp` CSR (Sstc extension).
+    Sstc,
+    /// Timer extension of the SBI.
+    Sbi,
+}
+
+/// Timer facade that programs the next timer event of the current HART.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SysTimer {
+    backend: Backend,
+}
+
+impl SysTimer {
+    /// Creates a new system timer with the given backend.
+    #[inline]
+    pub const fn new(backend: Backend) -> Self {
+        Self { backend }
+    }
+
+    /// Creates a new system timer that uses the `MTIMECMP` register of the current HART.
+    #[inline]
+    pub const fn clint(mtimecmp: MTIMECMP) -> Self {
+        Self::new(Backend::Clint(mtimecmp))
+    }
+
+    /// Creates a new system timer that uses the `stimecmp` CSR.
+    #[inline]
+    pub const fn sstc() -> Self {
+        Self::new(Backend::Sstc)
+    }
+
+    /// Creates a new system timer that uses the SBI timer extension.
+    #[inline]
+    pub const fn sbi() -> Self {
+        Self::new(Backend::Sbi)
+    }
+
+    /// Selects the most direct backend available for the current HART.
+    ///
+    /// Backends are selected in the following order:
+    ///
+    /// 1. [`Backend::Clint`] if `mtimecmp` is `Some` (i.e., the caller runs in M-mode).
+    /// 2. [`Backend::Sstc`] if `sstc` is `true` (e.g., as reported by the device tree).
+    ///    The Sstc extension cannot be detected at runtime, as accessing `stimecmp` traps if it is not available.
+    /// 3. [`Backend::Sbi`] if the SBI implements the timer extension.
+    ///
+    /// # Errors
+    ///
+    /// If no backend is available, it returns [`Error::Unimplemented`].
+    ///
+    /// # Note
+    ///
+    /// Probing the SBI timer extension is an environment call. Thus, it can only be reached in S-mode.
+    #[inline]
+    pub fn detect(mtimecmp: Option<MTIMECMP>, sstc: bool) -> Result<Self> {
+        match (mtimecmp, sstc) {
+            (Some(mtimecmp), _) => Ok(Self::clint(mtimecmp)),
+            (None, true) => Ok(Self::sstc()),
+            (None, false) if sbi::probe_extension(sbi::EID_TIME) => Ok(Self::sbi()),
+            _ => Err(Error::Unimplemented),
+        }
+    }
+
+    /// Returns the backend of the system timer.
+    #[inline]
+    pub const fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Programs the next timer event of the current HART at `deadline` (absolute time, in ticks of `mtime`).
+    ///
+    /// Scheduling a new event replaces the previous one. If `deadline` already passed,
+    /// the timer interrupt becomes pending right away.
+    ///
+    /// # Errors
+    ///
+    /// If the SBI call fails, it returns the [`Error::Sbi`] error of the SEE.
+    ///
+    /// **WARNING**: [`Backend::Sstc`] and [`Backend::Sbi`] panic on non-`riscv` targets.
+    #[inline]
+    pub fn schedule(&self, deadline: u64) -> Result<()> {
+        match self.backend {
+            Backend::Clint(mtimecmp) => mtimecmp.write(deadline),
+            Backend::Sstc => riscv::register::stimecmp::write64(deadline),
+            Backend::Sbi => sbi::set_timer(deadline)?,
+        }
+        Ok(())
+    }
+
+    /// Cancels the next timer event of the current HART, clearing the pending timer interrupt.
+    ///
+    /// It schedules a timer event infinitely far into the future (i.e., `u64::MAX`).
+    #[inline]
+    pub fn cancel(&self) -> Result<()> {
+        self.schedule(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_systimer() {
+        let mut raw_mtimecmp = 0u64;
+        // SAFETY: valid memory address
+        let mtimecmp = unsafe { MTIMECMP::new(&mut raw_mtimecmp as *mut u64 as _) };
+
+        let systimer = SysTimer::detect(Some(mtimecmp), true).unwrap();
+        assert_eq!(systimer, SysTimer::clint(mtimecmp));
+        assert_eq!(systimer.backend(), Backend::Clint(mtimecmp));
+
+        assert_eq!(systimer.schedule(0x1234_5678_9abc), Ok(()));
+        assert_eq!(mtimecmp.read(), 0x1234_5678_9abc);
+        assert_eq!(systimer.cancel(), Ok(()));
+        assert_eq!(mtimecmp.read(), u64::MAX);
+
+        let systimer = SysTimer::detect(None, true).unwrap();
+        assert_eq!(systimer.backend(), Backend::Sstc);
+        assert_eq!(SysTimer::sbi().backend(), Backend::Sbi);
+    }
+}
@@ -0,0 +1,315 @@
+//! Millisecond tick service.
+//!
+//! The [`Tick`] trait abstracts a monotonic millisecond counter with periodic tick events,
+//! so simple applications can measure time (e.g., `millis()`) without programming timers.
+//! [`SysTick`] implements it with a [`SysTimer`] that triggers a timer interrupt every millisecond.
+//!
+//! The counter is 64 bits wide. Thus, it does not overflow in practice, even in 32-bit targets.
+//!
+//! # Default tick service
+//!
+//! When the `tick` feature is enabled, this crate provides the [`SYSTICK`] instance and a
+//! `MachineTimer` interrupt handler that drives it. Thus, applications only need to start it:
+//!
+//! ```ignore
+//! use riscv_peripheral::{clock::MtimeClock, systimer::SysTimer, tick::{Tick, SYSTICK}};
+//!
+//! let mtimer = CLINT::mtimer();
+//! let clock = MtimeClock::new(mtimer.mtime, CLINT::freq());
+//! unsafe {
+//!     SYSTICK.start(SysTimer::clint(mtimer.mtimecmp_mhartid()), &clock).unwrap();
+//!     riscv::interrupt::enable();
+//! }
+//! let start = SYSTICK.now_ms();
+//! ```
+//!
+//! # Note
+//!
+//! The `aclint-hal-async` feature also defines the `MachineTimer` interrupt handler.
+//! If both features are enabled, the handler of `aclint-hal-async` is used, and
+//! [`SYSTICK`] must be driven by calling [`SysTick::on_interrupt`] from elsewhere.
+//! In S-mode, users must call [`SysTick::on_interrupt`] from their `SupervisorTimer` handler.
+
+use crate::{
+    clock::Clock,
+    systimer::{Backend, SysTimer},
+};
+use core::cell::UnsafeCell;
+use riscv_pac::result::{Error, Result};
+
+/// Frequency (in Hz) of the tick events.
+pub const TICK_HZ: u64 = 1_000;
+
+/// Maximum number of callbacks subscribed to the tick events of a [`SysTick`].
+pub const MAX_SUBSCRIBERS: usize = 4;
+
+/// Monotonic millisecond counter with periodic tick events.
+pub trait Tick {
+    /// Returns the number of milliseconds since the tick service started.
+    fn now_ms(&self) -> u64;
+
+    /// Subscribes `callback` to the tick events. On every tick, `callback` is called
+    /// with the current number of milliseconds from the timer interrupt handler.
+    ///
+    /// # Errors
+    ///
+    /// If no more callbacks can be subscribed, it returns [`Error::IndexOutOfBounds`].
+    fn subscribe(&self, callback: fn(u64)) -> Result<()>;
+}
+
+/// State of a [`SysTick`].
+#[derive(Clone, Copy, Debug)]
+struct State {
+    /// System timer used to schedule the tick events, or `None` if the service is stopped.
+    systimer: Option<SysTimer>,
+    /// Number of ticks of the time counter per millisecond.
+    period: u64,
+    /// Deadline of the next tick event.
+    deadline: u64,
+    /// Number of milliseconds since the service started.
+    ms: u64,
+    /// Callbacks subscribed to the tick events.
+    subscribers: [Option<fn(u64)>; MAX_SUBSCRIBERS],
+}
+
+impl State {
+    /// Creates a new state of a stopped tick service.
+    const fn new() -> Self {
+        Self {
+            systimer: None,
+            period: 0,
+            deadline: 0,
+            ms: 0,
+            subscribers: [None; MAX_SUBSCRIBERS],
+        }
+    }
+
+    /// Schedules the first tick event at `now + period`, and resets the millisecond counter.
+    fn start(&mut self, systimer: SysTimer, period: u64, now: u64) -> Result<()> {
+        let deadline = now.wrapping_add(period);
+        systimer.schedule(deadline)?;
+        self.systimer = Some(systimer);
+        self.period = period;
+        self.deadline = deadline;
+        self.ms = 0;
+        Ok(())
+    }
+
+    /// Increments the millisecond counter and schedules the next tick event.
+    /// It returns the new value of the counter, or `None` if the service is stopped.
+    fn tick(&mut self) -> Option<u64> {
+        let systimer = self.systimer?;
+        self.ms += 1;
+        self.deadline = self.deadline.wrapping_add(self.period);
+        // Only the SBI backend can fail, and the previous schedule call succeeded
+        let _ = systimer.schedule(self.deadline);
+        Some(self.ms)
+    }
+
+    /// Subscribes `callback` to the tick events.
+    fn subscribe(&mut self, callback: fn(u64)) -> Result<()> {
+        match self.subscribers.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(callback);
+                Ok(())
+            }
+            None => Err(Error::IndexOutOfBounds {
+                index: MAX_SUBSCRIBERS,
+                min: 0,
+                max: MAX_SUBSCRIBERS - 1,
+            }),
+        }
+    }
+}
+
+/// Tick service driven by the timer interrupt of the current HART.
+///
+/// Each timer interrupt increments the millisecond counter and schedules the next interrupt
+/// one period after the previous deadline. Thus, the counter does not drift, and ticks missed
+/// due to long critical sections are caught up by consecutive timer interrupts.
+///
+/// The state is accessed with interrupts disabled, so it must only be used by a single HART.
+#[derive(Debug)]
+pub struct SysTick {
+    state: UnsafeCell<State>,
+}
+
+// SAFETY: the state is only accessed with interrupts disabled, and it is used by a single HART.
+unsafe impl Sync for SysTick {}
+
+impl Default for SysTick {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SysTick {
+    /// Creates a new stopped tick service.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            state: UnsafeCell::new(State::new()),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the state.
+    #[inline]
+    fn with_state<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+        // SAFETY: interrupts are disabled, so the state is not accessed concurrently
+        riscv::interrupt::free(|| f(unsafe { &mut *self.state.get() }))
+    }
+
+    /// Starts the tick service with the millisecond counter set to 0.
+    ///
+    /// The tick events are scheduled with `systimer`. `clock` must be the time counter compared
+    /// by `systimer` (i.e., `MTIME`). Once the first event is scheduled, it enables the
+    /// corresponding timer interrupt (`mie.MTIE` for [`Backend::Clint`], `sie.STIE` otherwise).
+    ///
+    /// # Errors
+    ///
+    /// - If the frequency of `clock` is lower than [`TICK_HZ`], it returns [`Error::InvalidValue`].
+    /// - If the first event cannot be scheduled, it returns the error of [`SysTimer::schedule`].
+    ///
+    /// # Safety
+    ///
+    /// Enabling the timer interrupt can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn start<C: Clock>(&self, systimer: SysTimer, clock: C) -> Result<()> {
+        let period = clock.freq() as u64 / TICK_HZ;
+        if period == 0 {
+            return Err(Error::InvalidValue {
+                value: clock.freq(),
+                bitmask: usize::MAX,
+            });
+        }
+        self.with_state(|state| state.start(systimer, period, clock.now_ticks()))?;
+        match systimer.backend() {
+            Backend::Clint(_) => riscv::register::mie::set_mtimer(),
+            Backend::Sstc | Backend::Sbi => riscv::register::sie::set_stimer(),
+        }
+        Ok(())
+    }
+
+    /// Stops the tick service and cancels the next tick event.
+    /// The millisecond counter keeps its current value.
+    ///
+    /// # Errors
+    ///
+    /// If the next event cannot be cancelled, it returns the error of [`SysTimer::cancel`].
+    #[inline]
+    pub fn stop(&self) -> Result<()> {
+        match self.with_state(|state| state.systimer.take()) {
+            Some(systimer) => systimer.cancel(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the tick service is running.
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.with_state(|state| state.systimer.is_some())
+    }
+
+    /// Handles a timer interrupt: it increments the millisecond counter, schedules the next
+    /// tick event, and calls the subscribed callbacks. It does nothing if the service is stopped.
+    ///
+    /// # Note
+    ///
+    /// This method must only be called from the timer interrupt handler of the current HART.
+    #[inline]
+    pub fn on_interrupt(&self) {
+        let tick = self.with_state(|state| state.tick().map(|ms| (ms, state.subscribers)));
+        if let Some((ms, subscribers)) = tick {
+            subscribers
+                .iter()
+                .flatten()
+                .for_each(|callback| callback(ms));
+        }
+    }
+}
+
+impl Tick for SysTick {
+    #[inline]
+    fn now_ms(&self) -> u64 {
+        self.with_state(|state| state.ms)
+    }
+
+    #[inline]
+    fn subscribe(&self, callback: fn(u64)) -> Result<()> {
+        self.with_state(|state| state.subscribe(callback))
+    }
+}
+
+/// Default tick service, driven by the `MachineTimer` interrupt handler of this crate.
+#[cfg(feature = "tick")]
+pub static SYSTICK: SysTick = SysTick::new();
+
+/// Machine-level timer interrupt handler. It drives the default tick service [`SYSTICK`].
+#[cfg(all(feature = "tick", not(feature = "aclint-hal-async")))]
+#[no_mangle]
+#[allow(non_snake_case)]
+fn MachineTimer() {
+    SYSTICK.on_interrupt();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aclint::mtimer::MTIMECMP;
+
+    #[test]
+    fn test_state() {
+        let mut raw_mtimecmp = 0u64;
+        // SAFETY: valid memory address
+        let mtimecmp = unsafe { MTIMECMP::new(&mut raw_mtimecmp as *mut u64 as _) };
+        let systimer = SysTimer::clint(mtimecmp);
+
+        let mut state = State::new();
+        assert_eq!(state.tick(), None);
+
+        // the first deadline wraps around
+        assert_eq!(state.start(systimer, 10, u64::MAX - 4), Ok(()));
+        assert_eq!(mtimecmp.read(), 5);
+        assert_eq!(state.tick(), Some(1));
+        assert_eq!(mtimecmp.read(), 15);
+        assert_eq!(state.tick(), Some(2));
+        assert_eq!(mtimecmp.read(), 25);
+
+        // restarting resets the counter
+        assert_eq!(state.start(systimer, 10, 100), Ok(()));
+        assert_eq!(mtimecmp.read(), 110);
+        assert_eq!(state.tick(), Some(1));
+
+        fn callback(_ms: u64) {}
+        for _ in 0..MAX_SUBSCRIBERS {
+            assert_eq!(state.subscribe(callback), Ok(()));
+        }
+        assert_eq!(
+            state.subscribe(callback),
+            Err(Error::IndexOutOfBounds {
+                index: MAX_SUBSCRIBERS,
+                min: 0,
+                max: MAX_SUBSCRIBERS - 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_start_invalid_freq() {
+        let mut raw_mtimecmp = 0u64;
+        // SAFETY: valid memory address
+        let mtimecmp = unsafe { MTIMECMP::new(&mut raw_mtimecmp as *mut u64 as _) };
+        let clock = crate::clock::TimeCsrClock::new(999);
+
+        let systick = SysTick::new();
+        assert_eq!(
+            // SAFETY: the first event is never scheduled
+            unsafe { systick.start(SysTimer::clint(mtimecmp), clock) },
+            Err(Error::InvalidValue {
+                value: 999,
+                bitmask: usize::MAX
+            })
+        );
+    }
+}
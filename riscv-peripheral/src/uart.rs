@@ -0,0 +1,213 @@
+//! 16550-compatible UART peripheral.
+//!
+//! Most RISC-V platforms (including the QEMU `virt` machine) have at least one UART compatible with
+//! the 16550 register layout. [`UART`] supports the subset needed for a polled console: 8N1 frames,
+//! FIFOs, and blocking or non-blocking byte transfers. Interrupts and modem control are not supported.
+//!
+//! PACs instantiate a [`UART`] with the base address of the peripheral and the distance between
+//! its registers (some platforms place the 8-bit registers at 32-bit boundaries):
+//!
+//! ```
+//! use riscv_peripheral::uart::UART;
+//!
+//! /// UART0 of the QEMU `virt` machine.
+//! pub const UART0: UART = unsafe { UART::new(0x1000_0000, 0) };
+//! ```
+//!
+//! [`UART`] implements [`core::fmt::Write`]. With the `embedded-io` feature, it also implements
+//! the `embedded-io` traits (see [`hal::uart`](crate::hal::uart)).
+
+use crate::common::{Reg, RW};
+
+/// 16550-compatible UART peripheral.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UART {
+    base: usize,
+    reg_shift: u8,
+}
+
+impl UART {
+    /// Receiver buffer (read) and transmitter holding (write) registers.
+    const RBR_THR: usize = 0;
+    /// Interrupt enable register.
+    const IER: usize = 1;
+    /// FIFO control register.
+    const FCR: usize = 2;
+    /// Line control register.
+    const LCR: usize = 3;
+    /// Modem control register.
+    const MCR: usize = 4;
+    /// Line status register.
+    const LSR: usize = 5;
+
+    /// Divisor latch access bit of `LCR`.
+    const LCR_DLAB: u8 = 1 << 7;
+    /// 8 data bits, no parity, and 1 stop bit.
+    const LCR_8N1: u8 = 0b11;
+    /// Enables and clears the FIFOs.
+    const FCR_ENABLE_CLEAR: u8 = 0b111;
+    /// Data terminal ready and request to send.
+    const MCR_DTR_RTS: u8 = 0b11;
+    /// Data ready bit of `LSR`.
+    const LSR_DR: u8 = 1 << 0;
+    /// Transmitter holding register empty bit of `LSR`.
+    const LSR_THRE: u8 = 1 << 5;
+    /// Transmitter empty bit of `LSR`.
+    const LSR_TEMT: u8 = 1 << 6;
+
+    /// Creates a new UART peripheral.
+    ///
+    /// `reg_shift` is the base-2 logarithm of the distance (in bytes) between two consecutive
+    /// registers (e.g., 0 for byte-spaced registers, and 2 for registers at 32-bit boundaries).
+    ///
+    /// # Safety
+    ///
+    /// The base address must point to a valid 16550-compatible UART with the given register spacing.
+    #[inline]
+    pub const unsafe fn new(base: usize, reg_shift: u8) -> Self {
+        Self { base, reg_shift }
+    }
+
+    /// Returns the base address of the peripheral.
+    #[inline]
+    pub const fn base(self) -> usize {
+        self.base
+    }
+
+    #[inline]
+    const fn reg(self, index: usize) -> Reg<u8, RW> {
+        // SAFETY: valid address
+        unsafe { Reg::new((self.base + (index << self.reg_shift)) as _) }
+    }
+
+    /// Initializes the UART with 8N1 frames and enabled FIFOs, and disables its interrupts.
+    ///
+    /// `divisor` is the baud rate divisor (i.e., `clock / (16 * baud_rate)`).
+    /// If it is 0, the divisor is not modified (e.g., when it was set by the firmware).
+    #[inline]
+    pub fn init(self, divisor: u16) {
+        self.reg(Self::IER).write(0);
+        if divisor != 0 {
+            let [low, high] = divisor.to_le_bytes();
+            self.reg(Self::LCR).write(Self::LCR_DLAB);
+            self.reg(Self::RBR_THR).write(low);
+            self.reg(Self::IER).write(high);
+        }
+        self.reg(Self::LCR).write(Self::LCR_8N1);
+        self.reg(Self::FCR).write(Self::FCR_ENABLE_CLEAR);
+        self.reg(Self::MCR).write(Self::MCR_DTR_RTS);
+    }
+
+    /// Returns `true` if a byte can be written without blocking.
+    #[inline]
+    pub fn is_tx_ready(self) -> bool {
+        self.reg(Self::LSR).read() & Self::LSR_THRE != 0
+    }
+
+    /// Returns `true` if a received byte is available.
+    #[inline]
+    pub fn is_rx_ready(self) -> bool {
+        self.reg(Self::LSR).read() & Self::LSR_DR != 0
+    }
+
+    /// Returns `true` if all the written bytes have been transmitted.
+    #[inline]
+    pub fn is_tx_done(self) -> bool {
+        self.reg(Self::LSR).read() & Self::LSR_TEMT != 0
+    }
+
+    /// Writes a byte if the transmitter is ready. It returns `false` otherwise.
+    #[inline]
+    pub fn try_write_byte(self, byte: u8) -> bool {
+        let ready = self.is_tx_ready();
+        if ready {
+            self.reg(Self::RBR_THR).write(byte);
+        }
+        ready
+    }
+
+    /// Reads a received byte, if any.
+    #[inline]
+    pub fn try_read_byte(self) -> Option<u8> {
+        match self.is_rx_ready() {
+            true => Some(self.reg(Self::RBR_THR).read()),
+            false => None,
+        }
+    }
+
+    /// Writes a byte, waiting until the transmitter is ready.
+    #[inline]
+    pub fn write_byte(self, byte: u8) {
+        while !self.try_write_byte(byte) {}
+    }
+
+    /// Reads a byte, waiting until it is received.
+    #[inline]
+    pub fn read_byte(self) -> u8 {
+        loop {
+            if let Some(byte) = self.try_read_byte() {
+                return byte;
+            }
+        }
+    }
+
+    /// Waits until all the written bytes have been transmitted.
+    #[inline]
+    pub fn flush(self) {
+        while !self.is_tx_done() {}
+    }
+}
+
+impl core::fmt::Write for UART {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        s.bytes().for_each(|byte| self.write_byte(byte));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Line status of an idle UART with a received byte.
+    const LSR_IDLE_RX: u8 = UART::LSR_DR | UART::LSR_THRE | UART::LSR_TEMT;
+
+    #[test]
+    fn test_uart_init() {
+        let mut raw_regs = [0u32; 8];
+        // SAFETY: valid memory addresses (registers at 32-bit boundaries)
+        let uart = unsafe { UART::new(raw_regs.as_mut_ptr() as _, 2) };
+        assert_eq!(uart.reg(UART::LSR).get_ptr() as usize, uart.base() + 20);
+
+        uart.init(0x1234);
+        // the divisor latch overlaps the receiver buffer and interrupt enable registers
+        assert_eq!(raw_regs[0] as u8, 0x34);
+        assert_eq!(raw_regs[1] as u8, 0x12);
+        assert_eq!(raw_regs[3] as u8, UART::LCR_8N1);
+        assert_eq!(raw_regs[2] as u8, UART::FCR_ENABLE_CLEAR);
+        assert_eq!(raw_regs[4] as u8, UART::MCR_DTR_RTS);
+    }
+
+    #[test]
+    fn test_uart_transfer() {
+        let mut raw_regs = [0u8; 8];
+        // SAFETY: valid memory addresses
+        let mut uart = unsafe { UART::new(raw_regs.as_mut_ptr() as _, 0) };
+
+        assert!(!uart.is_tx_ready());
+        assert!(!uart.try_write_byte(b'a'));
+        assert_eq!(uart.try_read_byte(), None);
+
+        uart.reg(UART::LSR).write(LSR_IDLE_RX);
+        assert!(uart.is_tx_ready() && uart.is_rx_ready() && uart.is_tx_done());
+        uart.reg(UART::RBR_THR).write(b'x');
+        assert_eq!(uart.try_read_byte(), Some(b'x'));
+        assert_eq!(uart.read_byte(), b'x');
+
+        core::fmt::Write::write_str(&mut uart, "ok").unwrap();
+        uart.flush();
+        assert_eq!(uart.reg(UART::RBR_THR).read(), b'k');
+    }
+}
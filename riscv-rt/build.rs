@@ -1,59 +1,424 @@
 // NOTE: Adapted from cortex-m/build.rs
 
-use riscv_target_parser::RiscvTarget;
+use riscv_target_parser::{CodeModel, Extension, RiscvTarget};
 use std::{env, fs, io, path::PathBuf};
 
 // List of all possible RISC-V configurations to check for in risv-rt
 const RISCV_CFG: [&str; 4] = ["riscvi", "riscvm", "riscvf", "riscvd"];
 
-fn add_linker_script(arch_width: u32) -> io::Result<()> {
-    // Read the file to a string and replace all occurrences of ${ARCH_WIDTH} with the arch width
-    let mut content = fs::read_to_string("link.x.in")?;
-    content = content.replace("${ARCH_WIDTH}", &arch_width.to_string());
+/// Flavors of the linker script: (file name, load region of `.data`, load region of `.trap`).
+///
+/// - `link.x`: `.data` is loaded in `REGION_RODATA` and copied to `REGION_DATA` at startup.
+///   With the `trap-ram` feature, `.trap` is loaded in `REGION_TEXT` and copied to `REGION_TRAP`.
+/// - `link-ram.x`: RAM-only targets. `.data` and `.trap` are loaded in place, so the copies are
+///   skipped at startup.
+const LINKER_SCRIPTS: [(&str, &str, &str); 2] = [
+    ("link.x", "AT > REGION_RODATA", "AT > REGION_TEXT"),
+    ("link-ram.x", "", ""),
+];
 
+fn add_linker_scripts(arch_width: u32, code_model: Option<CodeModel>) -> io::Result<()> {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
-
-    // Put the linker script somewhere the linker can find it
-    fs::write(out_dir.join("link.x"), content)?;
+    for (name, data_lma, trap_lma) in LINKER_SCRIPTS {
+        let content = linker_script(arch_width, code_model, data_lma, trap_lma)?;
+        // Put the linker script somewhere the linker can find it
+        fs::write(out_dir.join(name), content)?;
+    }
     println!("cargo:rustc-link-search={}", out_dir.display());
     println!("cargo:rerun-if-changed=link.x");
 
     Ok(())
 }
 
+fn linker_script(
+    arch_width: u32,
+    code_model: Option<CodeModel>,
+    data_lma: &str,
+    trap_lma: &str,
+) -> io::Result<String> {
+    // Read the file to a string
+    let mut content = fs::read_to_string("link.x.in")?;
+
+    // With the data2-region feature, the .data2 and .bss2 sections are placed in REGION_DATA2 and
+    // REGION_BSS2. As .data, the .data2 section is loaded at ${DATA_LMA}, which is replaced below.
+    if env::var_os("CARGO_FEATURE_DATA2_REGION").is_some() {
+        content = content.replace("${DATA2_SECTIONS}", DATA2_SECTIONS);
+        content.push_str(DATA2_REGION_ASSERTS);
+    } else {
+        content = content.replace("${DATA2_SECTIONS}", "");
+    }
+
+    // Replace all occurrences of ${ARCH_WIDTH} with the arch width
+    content = content.replace("${ARCH_WIDTH}", &arch_width.to_string());
+
+    content = content.replace("${DATA_LMA}", data_lma);
+
+    // With the trap-region feature, the trap code is placed in its own .trap output section.
+    // Otherwise, it is placed in the .text section.
+    if env::var_os("CARGO_FEATURE_TRAP_REGION").is_some() {
+        // With the trap-ram feature, the .trap section is copied to REGION_TRAP at startup.
+        let trap_lma = match env::var_os("CARGO_FEATURE_TRAP_RAM") {
+            Some(_) => trap_lma,
+            None => "",
+        };
+        content = content.replace("${TRAP_TEXT}", "");
+        content = content.replace(
+            "${TRAP_SECTION}",
+            &TRAP_SECTION.replace("${TRAP_LMA}", trap_lma),
+        );
+        content.push_str(TRAP_REGION_ASSERTS);
+    } else {
+        content = content.replace("${TRAP_TEXT}", TRAP_TEXT);
+        content = content.replace("${TRAP_SECTION}", "");
+    }
+
+    // With the vectors-region feature, the vector tables are placed in their own .vector_table
+    // output section in REGION_VECTORS. Otherwise, they are placed next to the trap code (in .text,
+    // or in .trap with the trap-region feature), so the `j` instructions of the tables can reach
+    // the trap handlers.
+    if env::var_os("CARGO_FEATURE_VECTORS_REGION").is_some() {
+        content = content.replace("${VECTOR_TABLE}", "");
+        content = content.replace(
+            "${VECTOR_TABLE_SECTION}",
+            &VECTOR_TABLE_SECTION.replace("${VECTORS_REGION}", "REGION_VECTORS"),
+        );
+        content.push_str(VECTORS_REGION_ASSERTS);
+    } else {
+        content = content.replace("${VECTOR_TABLE_SECTION}", "");
+    }
+    content = content.replace("${VECTOR_TABLE}", VECTOR_TABLE);
+
+    // With the app-image feature, the entry point is _app_entry instead of _start
+    if env::var_os("CARGO_FEATURE_APP_IMAGE").is_some() {
+        content.push_str(APP_IMAGE_ENTRY);
+    }
+
+    // If RISCV_RT_VECTOR_REPORT is set, add symbols to report which handlers fall back to defaults
+    println!("cargo:rerun-if-env-changed=RISCV_RT_VECTOR_REPORT");
+    if env::var_os("RISCV_RT_VECTOR_REPORT").is_some() {
+        content.push_str(&vector_report(&content));
+    }
+
+    // In 64-bit targets, check that the code model can reach the memory layout.
+    // Other code models (e.g., large) do not restrict the memory layout.
+    if arch_width == 64 {
+        content.push_str(match code_model {
+            Some(CodeModel::Medlow) => MEDLOW_ASSERTS,
+            Some(CodeModel::Medany) => MEDANY_ASSERTS,
+            None => "",
+        });
+    }
+
+    // With the pmp-wx feature, check that the PMP address ranges of the runtime sections are valid
+    if env::var_os("CARGO_FEATURE_PMP_WX").is_some() {
+        content.push_str(PMP_WX_ASSERTS);
+    }
+
+    Ok(content)
+}
+
+/// Input sections of the trap code when it is placed in the `.text` section.
+const TRAP_TEXT: &str = r#"${VECTOR_TABLE}
+    . = ALIGN(_trap_align);
+    __strap = .;
+    *(.trap.start);
+    *(.trap);
+    *(.trap.rust);
+    __etrap = .;"#;
+
+/// Input sections of the vector tables (only used when the `v-trap` feature is enabled).
+const VECTOR_TABLE: &str = r#"    . = ALIGN(4);
+    __svector_table = .;
+    KEEP(*(.trap.vector));
+    . = ALIGN(4);
+    KEEP(*(.trap.vector.*));
+    . = ALIGN(4);
+    __evector_table = .;"#;
+
+/// Output section of the vector tables when they are not placed next to the trap code.
+const VECTOR_TABLE_SECTION: &str = r#"
+  /* Interrupt vector tables (only used when the `v-trap` feature is enabled) */
+  .vector_table : ALIGN(4)
+  {
+${VECTOR_TABLE}
+  } > ${VECTORS_REGION}
+"#;
+
+/// Linker script assertions of the `vectors-region` feature.
+const VECTORS_REGION_ASSERTS: &str = r#"
+/* # Vector table checks (vectors-region) */
+ASSERT(ORIGIN(REGION_VECTORS) % 4 == 0, "
+ERROR(riscv-rt): the start of the REGION_VECTORS must be 4-byte aligned");
+
+ASSERT((__svector_table >= __strap ? __evector_table - __strap : __etrap - __svector_table) < 1M, "
+ERROR(riscv-rt): the vector tables must be within 1 MiB of the trap code, as their entries are
+`j` instructions. Place REGION_VECTORS closer to the trap code (REGION_TEXT or REGION_TRAP)");
+"#;
+
+/// Output section of the trap code when it is placed in `REGION_TRAP` (`trap-region` feature).
+const TRAP_SECTION: &str = r#"
+  /* Trap code (`trap-region` feature). It starts at `_strap`, which defaults to the
+     start of REGION_TRAP. With the `trap-ram` feature, it is copied to REGION_TRAP at startup. */
+  PROVIDE(_strap = ORIGIN(REGION_TRAP));
+
+  .trap _strap :
+  {
+    __strap = .;
+    *(.trap.start);
+    *(.trap);
+    *(.trap.rust);
+${VECTOR_TABLE}
+    . = ALIGN(4);
+    __etrap = .;
+  } > REGION_TRAP ${TRAP_LMA}
+
+  /* LMA of .trap */
+  __sitrap = LOADADDR(.trap);
+"#;
+
+/// Linker script assertions of the `trap-region` feature.
+const TRAP_REGION_ASSERTS: &str = r#"
+/* # Trap region checks (trap-region) */
+ASSERT(_strap % _trap_align == 0, "
+ERROR(riscv-rt): `_strap` must be `_trap_align`-byte aligned");
+
+ASSERT(_strap >= ORIGIN(REGION_TRAP), "
+ERROR(riscv-rt): The .trap section must be placed inside the REGION_TRAP region.
+Set _strap to an address greater than or equal to 'ORIGIN(REGION_TRAP)'");
+
+ASSERT(__etrap <= ORIGIN(REGION_TRAP) + LENGTH(REGION_TRAP), "
+ERROR(riscv-rt): The .trap section must be placed inside the REGION_TRAP region.
+Set _strap to an address smaller than 'ORIGIN(REGION_TRAP) + LENGTH(REGION_TRAP)'
+or increase the size of REGION_TRAP");
+
+ASSERT(__sitrap % 4 == 0, "
+BUG(riscv-rt): the LMA of .trap is not 4-byte aligned");
+"#;
+
+/// Output sections of the second RAM region (`data2-region` feature).
+const DATA2_SECTIONS: &str = r#"
+  /* .data and .bss sections of the second RAM region (`data2-region` feature) */
+  .data2 : ALIGN(${ARCH_WIDTH})
+  {
+    . = ALIGN(${ARCH_WIDTH});
+    __sdata2 = .;
+    *(.data2 .data2.*);
+    . = ALIGN(${ARCH_WIDTH});
+    __edata2 = .;
+  } > REGION_DATA2 ${DATA_LMA}
+
+  /* LMA of .data2 */
+  __sidata2 = LOADADDR(.data2);
+
+  .bss2 (NOLOAD) : ALIGN(${ARCH_WIDTH})
+  {
+    . = ALIGN(${ARCH_WIDTH});
+    __sbss2 = .;
+    *(.bss2 .bss2.*);
+    . = ALIGN(${ARCH_WIDTH});
+    __ebss2 = .;
+  } > REGION_BSS2
+"#;
+
+/// Linker script assertions of the `data2-region` feature.
+const DATA2_REGION_ASSERTS: &str = r#"
+/* # Second RAM region checks (data2-region) */
+ASSERT(ORIGIN(REGION_DATA2) % ${ARCH_WIDTH} == 0, "
+ERROR(riscv-rt): the start of the REGION_DATA2 must be ${ARCH_WIDTH}-byte aligned");
+
+ASSERT(ORIGIN(REGION_BSS2) % ${ARCH_WIDTH} == 0, "
+ERROR(riscv-rt): the start of the REGION_BSS2 must be ${ARCH_WIDTH}-byte aligned");
+
+ASSERT(__sidata2 % ${ARCH_WIDTH} == 0, "
+BUG(riscv-rt): the LMA of .data2 is not ${ARCH_WIDTH}-byte aligned");
+"#;
+
+/// Entry point of the linker script with the `app-image` feature.
+const APP_IMAGE_ENTRY: &str = r#"
+/* # Entry point (app-image) */
+ENTRY(_app_entry);
+"#;
+
+/// Linker script assertions of the `medlow` code model in 64-bit targets.
+///
+/// Symbols are addressed with `lui`, which sign-extends 32-bit values. Thus, all the runtime
+/// sections must be placed below 0x8000_0000 (negative addresses are not supported by riscv-rt).
+const MEDLOW_ASSERTS: &str = r#"
+/* # Code model checks (medlow) */
+ASSERT(__etext < 0x80000000 && __erodata < 0x80000000 && __ebss < 0x80000000
+    && __eheap < 0x80000000 && _stack_start < 0x80000000, "
+ERROR(riscv-rt): the medlow code model (-C code-model=small) can only address memory
+below 0x80000000 in 64-bit targets. Move all the memory regions below 0x80000000 or
+use the medany code model (-C code-model=medium).");
+"#;
+
+/// Linker script assertions of the `medany` code model in 64-bit targets.
+///
+/// Symbols are addressed relative to the program counter with `auipc`, which reaches +/-2 GiB.
+/// Thus, all the runtime sections must be placed in a 2 GiB address range.
+const MEDANY_ASSERTS: &str = r#"
+/* # Code model checks (medany) */
+ASSERT(MAX(MAX(MAX(__etext, __erodata), MAX(__ebss, __eheap)), _stack_start)
+    - MIN(MIN(_stext, __srodata), MIN(__sdata, __sheap)) < 0x80000000, "
+ERROR(riscv-rt): the medany code model (-C code-model=medium) can only address memory
+within 2 GiB of the code. All the memory regions must be placed in a 2 GiB address range.");
+"#;
+
+/// Linker script assertions of the `pmp-wx` feature.
+const PMP_WX_ASSERTS: &str = r#"
+/* # PMP W^X checks (pmp-wx) */
+ASSERT(__sdata <= __sstack, "
+ERROR(riscv-rt): the `pmp-wx` feature requires REGION_STACK to be placed after REGION_DATA");
+
+ASSERT(__sstack <= __stext || __sdata >= __etext, "
+ERROR(riscv-rt): the `pmp-wx` feature requires .text to be placed outside the RAM sections");
+
+ASSERT(__sstack <= __srodata || __sdata >= __erodata, "
+ERROR(riscv-rt): the `pmp-wx` feature requires .rodata to be placed outside the RAM sections");
+"#;
+
+/// Default handlers that can be overridden by user handlers.
+const DEFAULT_HANDLERS: [&str; 2] = ["ExceptionHandler", "DefaultHandler"];
+
+/// Generates the linker script symbols of the vector report.
+///
+/// For every `PROVIDE(handler = default);` statement of the linker script where `default` is one of
+/// the [`DEFAULT_HANDLERS`], it defines a `__riscv_rt_default_<handler>` symbol. Its value is 1 if
+/// `handler` resolved to `default`, and 0 if it resolved to a user handler.
+fn vector_report(content: &str) -> String {
+    let mut report = String::from("\n/* # Vector report (RISCV_RT_VECTOR_REPORT) */\n");
+    for line in content.lines() {
+        let provide = line
+            .trim()
+            .strip_prefix("PROVIDE(")
+            .and_then(|l| l.strip_suffix(");"))
+            .and_then(|l| l.split_once('='))
+            .map(|(handler, default)| (handler.trim(), default.trim()));
+        if let Some((handler, default)) = provide {
+            if DEFAULT_HANDLERS.contains(&default) {
+                report.push_str(&format!(
+                    "__riscv_rt_default_{handler} = ({handler} == {default}) ? 1 : 0;\n"
+                ));
+            }
+        }
+    }
+    report
+}
+
+/// Writes the number of per-hart trap trace buffers (`trap-trace` feature) to `trap_trace.rs`.
+///
+/// It is 1 if the `single-hart` feature is enabled. Otherwise, it is read from the
+/// `RISCV_RT_TRAP_TRACE_HARTS` environment variable (1 by default).
+fn add_trap_trace_harts() -> io::Result<()> {
+    println!("cargo:rerun-if-env-changed=RISCV_RT_TRAP_TRACE_HARTS");
+    let harts = match env::var_os("CARGO_FEATURE_SINGLE_HART") {
+        Some(_) => 1,
+        None => match env::var("RISCV_RT_TRAP_TRACE_HARTS") {
+            Ok(harts) => harts
+                .parse::<usize>()
+                .ok()
+                .filter(|&harts| harts > 0)
+                .expect("RISCV_RT_TRAP_TRACE_HARTS must be a positive integer"),
+            Err(_) => 1,
+        },
+    };
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(
+        out_dir.join("trap_trace.rs"),
+        format!(
+            "/// Number of per-hart trap trace buffers.\npub const TRACE_HARTS: usize = {harts};\n"
+        ),
+    )
+}
+
+/// Checks the target features of a RISC-V target for extensions required by riscv-rt.
+///
+/// Without this check, missing extensions surface as assembler errors in the runtime code.
+fn check_target_features(cargo_flags: &str) {
+    // LLVM implies Zicsr in the I and E base ISAs unless it is explicitly disabled
+    let no_zicsr = cargo_flags
+        .split(0x1fu8 as char)
+        .filter_map(|arg| arg.trim_start_matches("-C").strip_prefix("target-feature="))
+        .flat_map(|arg| arg.split(','))
+        .any(|feature| feature.eq_ignore_ascii_case("-zicsr"));
+    if no_zicsr {
+        panic!(
+            "\n\nERROR(riscv-rt): the target disables the Zicsr extension (`-zicsr`), but the startup \
+             and trap code of riscv-rt access CSRs. Remove `-zicsr` from the target features.\n"
+        );
+    }
+}
+
+/// Reports ISA and ABI combinations of the target that riscv-rt does not support with `cargo:warning`.
+fn check_target(target: &RiscvTarget) {
+    let flags = target.rustc_flags();
+    let has_fpu = flags.iter().any(|f| f == "riscvf" || f == "riscvd");
+    if target.base_extension() == Some(Extension::E) && has_fpu {
+        println!(
+            "cargo:warning=riscv-rt: the target combines the E base ISA with the F or D extensions. \
+             riscv-rt initializes the FPU at startup (`fscsr` and `f0`-`f31`), but the ILP32E and LP64E \
+             ABIs do not support hardware floating point. Use an I base target (e.g., `riscv32imafc`) \
+             or remove `+f` and `+d` from the target features"
+        );
+    }
+}
+
 fn main() {
     // Required until target_feature risc-v is stable and in-use (rust 1.75)
     for ext in RISCV_CFG.iter() {
         println!("cargo:rustc-check-cfg=cfg({ext})");
     }
 
+    if env::var_os("CARGO_FEATURE_TRAP_TRACE").is_some() {
+        add_trap_trace_harts().unwrap();
+    }
+
     let target = env::var("TARGET").unwrap();
     let cargo_flags = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap();
 
-    if let Ok(target) = RiscvTarget::build(&target, &cargo_flags) {
-        let width = target.width();
+    let is_riscv =
+        env::var("CARGO_CFG_TARGET_ARCH").map_or(false, |arch| arch.starts_with("riscv"));
+    if is_riscv {
+        check_target_features(&cargo_flags);
+    }
+    let target = match RiscvTarget::build(&target, &cargo_flags) {
+        Ok(target) => target,
+        // riscv-rt derives the base ISA and the `.attribute arch` directive (`RISCV_RT_LLVM_ARCH_PATCH`)
+        // from the target. Otherwise, the build fails later with `RISCV_RT_LLVM_ARCH_PATCH is not set`.
+        Err(err) if is_riscv => panic!(
+            "\n\nERROR(riscv-rt): could not parse the RISC-V target `{target}` ({err:?}).\n\
+             riscv-rt derives the ISA of the target from its name, so custom targets must be named \
+             `riscv{{32,64}}{{extensions}}-{{vendor}}-{{os}}[-{{bin}}]` (e.g., `riscv32imac-unknown-none-elf`), \
+             and target features must be RISC-V extensions supported by `riscv-target-parser` (e.g., `-C target-feature=+m,-c`).\n"
+        ),
+        // non-RISC-V targets (e.g., to build the documentation or run tests in the host)
+        Err(_) => return,
+    };
 
-        // set environmet variable RISCV_RT_BASE_ISA to the base ISA of the target.
-        println!(
-            "cargo:rustc-env=RISCV_RT_BASE_ISA={}",
-            target.llvm_base_isa()
-        );
-        // set environment variable RISCV_RT_LLVM_ARCH_PATCH to patch LLVM bug.
-        // (this env variable is temporary and will be removed after LLVM being fixed)
-        println!(
-            "cargo:rustc-env=RISCV_RT_LLVM_ARCH_PATCH={}",
-            target.llvm_arch_patch()
-        );
-        // make sure that these env variables are not changed without notice.
-        println!("cargo:rerun-if-env-changed=RISCV_RT_BASE_ISA");
-        println!("cargo:rerun-if-env-changed=RISCV_RT_LLVM_ARCH_PATCH");
-
-        for flag in target.rustc_flags() {
-            // Required until target_feature risc-v is stable and in-use
-            if RISCV_CFG.contains(&flag.as_str()) {
-                println!("cargo:rustc-cfg={flag}");
-            }
+    check_target(&target);
+
+    let width = target.width();
+
+    // set environmet variable RISCV_RT_BASE_ISA to the base ISA of the target.
+    println!(
+        "cargo:rustc-env=RISCV_RT_BASE_ISA={}",
+        target.llvm_base_isa()
+    );
+    // set environment variable RISCV_RT_LLVM_ARCH_PATCH to patch LLVM bug.
+    // (this env variable is temporary and will be removed after LLVM being fixed)
+    println!(
+        "cargo:rustc-env=RISCV_RT_LLVM_ARCH_PATCH={}",
+        target.llvm_arch_patch()
+    );
+    // make sure that these env variables are not changed without notice.
+    println!("cargo:rerun-if-env-changed=RISCV_RT_BASE_ISA");
+    println!("cargo:rerun-if-env-changed=RISCV_RT_LLVM_ARCH_PATCH");
+
+    for flag in target.rustc_flags() {
+        // Required until target_feature risc-v is stable and in-use
+        if RISCV_CFG.contains(&flag.as_str()) {
+            println!("cargo:rustc-cfg={flag}");
         }
-        add_linker_script(width.into()).unwrap();
     }
+    add_linker_scripts(width.into(), target.code_model()).unwrap();
 }
@@ -10,5 +10,9 @@ REGION_ALIAS("REGION_DATA", RAM);
 REGION_ALIAS("REGION_BSS", RAM);
 REGION_ALIAS("REGION_HEAP", RAM);
 REGION_ALIAS("REGION_STACK", RAM);
+REGION_ALIAS("REGION_VECTORS", FLASH);
+REGION_ALIAS("REGION_TRAP", RAM);
+REGION_ALIAS("REGION_DATA2", RAM);
+REGION_ALIAS("REGION_BSS2", RAM);
 
 INCLUDE link.x
@@ -0,0 +1,62 @@
+//! Awaits machine software and timer interrupts from an asynchronous `main` function.
+//!
+//! The main task pends a software interrupt via the CLINT `MSWI` peripheral and programs a timer
+//! interrupt via the CLINT `MTIMER` peripheral of the QEMU `virt` machine. It prints the sequence
+//! through semihosting, so the host can check that the executor wakes the task after each interrupt.
+//! Link with `examples/qemu.x` and run with
+//! `qemu-system-riscv{32,64} -machine virt -bios none -display none -serial null -monitor none -semihosting-config enable=on,target=native -kernel <elf>`.
+//! The same output is expected with and without the `v-trap` feature.
+#![no_std]
+#![no_main]
+
+use riscv::register::mhartid;
+use riscv_rt::{entry, executor};
+use riscv_semihosting::{debug, hprintln};
+
+/// Base address of the `MSIP` registers of the QEMU `virt` CLINT.
+const MSIP_BASE: usize = 0x0200_0000;
+
+/// Base address of the `MTIMECMP` registers of the QEMU `virt` CLINT.
+const MTIMECMP_BASE: usize = 0x0200_4000;
+
+/// Returns a pointer to the `MSIP` register of the current HART.
+fn msip() -> *mut u32 {
+    (MSIP_BASE + 4 * mhartid::read()) as *mut u32
+}
+
+/// Sets the `MTIMECMP` register of the current HART.
+fn set_mtimecmp(value: u64) {
+    let mtimecmp = (MTIMECMP_BASE + 8 * mhartid::read()) as *mut u32;
+    // Write the high half first to avoid spurious interrupts (also valid in RV32)
+    unsafe {
+        mtimecmp.add(1).write_volatile(u32::MAX);
+        mtimecmp.write_volatile(value as u32);
+        mtimecmp.add(1).write_volatile((value >> 32) as u32);
+    }
+}
+
+#[entry(async)]
+async fn main() {
+    hprintln!("0: main");
+
+    // Pend the software interrupt and wait for the handler to wake the task
+    unsafe { msip().write_volatile(1) };
+    executor::wait_software().await;
+    unsafe { msip().write_volatile(0) };
+    hprintln!("1: MachineSoft");
+
+    // The timer interrupt is pending as soon as MTIMECMP is lower than MTIME
+    set_mtimecmp(0);
+    executor::wait_timer().await;
+    set_mtimecmp(u64::MAX);
+    hprintln!("2: MachineTimer");
+
+    debug::exit(debug::EXIT_SUCCESS);
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    hprintln!("{}", info);
+    debug::exit(debug::EXIT_FAILURE);
+    loop {}
+}
@@ -0,0 +1,60 @@
+//! RAM-only program for the QEMU `virt` machine.
+//!
+//! The program is linked with `link-ram.x`, so the initial values of `.data` are loaded in place
+//! by QEMU and the runtime skips the copy of `.data` at startup. It checks that `.data` and `.bss`
+//! are properly initialized, and prints the results through semihosting. Link with `examples/qemu_ram.x`
+//! and run with
+//! `qemu-system-riscv{32,64} -machine virt -bios none -display none -serial null -monitor none -semihosting-config enable=on,target=native -kernel <elf>`.
+#![no_std]
+#![no_main]
+
+use core::ptr::{addr_of, addr_of_mut};
+
+use riscv_rt::entry;
+use riscv_semihosting::{debug, hprintln};
+
+/// Variable in `.data`.
+static mut DATA: [u32; 4] = [0xdead_beef, 1, 2, 3];
+
+/// Variable in `.bss`.
+static mut BSS: [u32; 4] = [0; 4];
+
+extern "C" {
+    static __sdata: u8;
+    static __sidata: u8;
+}
+
+#[entry]
+fn main() -> ! {
+    // SAFETY: single-threaded program, no interrupts enabled
+    let (data, bss) = unsafe {
+        (
+            addr_of!(DATA).read_volatile(),
+            addr_of!(BSS).read_volatile(),
+        )
+    };
+    let in_place = unsafe { addr_of!(__sdata) == addr_of!(__sidata) };
+
+    hprintln!(".data loaded in place: {}", in_place);
+    hprintln!(".data: {:x?}", data);
+    hprintln!(".bss: {:?}", bss);
+
+    // .data and .bss must be writable
+    unsafe {
+        addr_of_mut!(DATA).write_volatile([0; 4]);
+        addr_of_mut!(BSS).write_volatile([1; 4]);
+    }
+
+    match in_place && data == [0xdead_beef, 1, 2, 3] && bss == [0; 4] {
+        true => debug::exit(debug::EXIT_SUCCESS),
+        false => debug::exit(debug::EXIT_FAILURE),
+    }
+    loop {}
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    hprintln!("{}", info);
+    debug::exit(debug::EXIT_FAILURE);
+    loop {}
+}
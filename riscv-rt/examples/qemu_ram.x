@@ -0,0 +1,15 @@
+/* Memory layout of the QEMU `virt` machine when booting with `-bios none`, as a RAM-only target.
+   QEMU loads the whole program in RAM, so .data is loaded in place with link-ram.x. */
+MEMORY
+{
+    RAM : ORIGIN = 0x80000000, LENGTH = 16M
+}
+
+REGION_ALIAS("REGION_TEXT", RAM);
+REGION_ALIAS("REGION_RODATA", RAM);
+REGION_ALIAS("REGION_DATA", RAM);
+REGION_ALIAS("REGION_BSS", RAM);
+REGION_ALIAS("REGION_HEAP", RAM);
+REGION_ALIAS("REGION_STACK", RAM);
+
+INCLUDE link-ram.x
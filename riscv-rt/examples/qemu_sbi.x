@@ -0,0 +1,15 @@
+/* Memory layout of the QEMU `virt` machine when booting with the default SBI firmware (OpenSBI).
+   The firmware is loaded at 0x80000000, so the kernel is placed right after its reserved region. */
+MEMORY
+{
+    RAM : ORIGIN = 0x80400000, LENGTH = 16M
+}
+
+REGION_ALIAS("REGION_TEXT", RAM);
+REGION_ALIAS("REGION_RODATA", RAM);
+REGION_ALIAS("REGION_DATA", RAM);
+REGION_ALIAS("REGION_BSS", RAM);
+REGION_ALIAS("REGION_HEAP", RAM);
+REGION_ALIAS("REGION_STACK", RAM);
+
+INCLUDE link.x
@@ -0,0 +1,97 @@
+//! Two-hart (SMP) program for the QEMU `virt` machine.
+//!
+//! Hart 0 boots and initializes RAM, while hart 1 waits in `_mp_hook` for a machine software
+//! interrupt. Then, hart 0 writes a request to a mailbox in RAM and wakes hart 1 via the CLINT `MSWI`
+//! peripheral. Hart 1 reads the request and writes a reply that hart 0 checks. Both harts print
+//! their steps through semihosting in a deterministic order. Link with `examples/qemu_smp.x` and run with
+//! `qemu-system-riscv{32,64} -machine virt -smp 2 -bios none -display none -serial null -monitor none -semihosting-config enable=on,target=native -kernel <elf>`.
+//! The same output is expected with and without the `v-trap` feature.
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use riscv::{
+    asm::{fence, wfi},
+    register::{mie, mip},
+};
+use riscv_rt::entry;
+use riscv_semihosting::{debug, hprintln};
+
+/// Base address of the `MSIP` registers of the QEMU `virt` CLINT.
+const MSIP_BASE: usize = 0x0200_0000;
+
+/// Request sent by hart 0 to hart 1.
+const REQUEST: usize = 42;
+
+/// Mailbox with the request of hart 0 (0 if empty).
+static MAILBOX: AtomicUsize = AtomicUsize::new(0);
+
+/// Reply of hart 1 (0 if not replied yet).
+static REPLY: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a pointer to the `MSIP` register of a HART.
+fn msip(hartid: usize) -> *mut u32 {
+    (MSIP_BASE + 4 * hartid) as *mut u32
+}
+
+/// Hart 0 initializes RAM, while the other harts wait for a software interrupt.
+///
+/// `MSIP` registers are cleared at reset, so hart 1 must not clear its own before waiting:
+/// it could miss the interrupt sent by hart 0.
+#[export_name = "_mp_hook"]
+pub extern "Rust" fn mp_hook(hartid: usize) -> bool {
+    if hartid == 0 {
+        return true;
+    }
+    // Interrupts are globally disabled, but pending interrupts still wake up the hart
+    unsafe { mie::set_msoft() };
+    while !mip::read().msoft() {
+        wfi();
+    }
+    unsafe {
+        mie::clear_msoft();
+        msip(hartid).write_volatile(0);
+    }
+    false
+}
+
+#[entry]
+fn main(hartid: usize) -> ! {
+    match hartid {
+        0 => {
+            hprintln!("hart 0: sending {} to hart 1", REQUEST);
+            MAILBOX.store(REQUEST, Ordering::Release);
+            // Order the mailbox write before the device write that wakes hart 1
+            fence();
+            unsafe { msip(1).write_volatile(1) };
+
+            let reply = loop {
+                match REPLY.load(Ordering::Acquire) {
+                    0 => core::hint::spin_loop(),
+                    reply => break reply,
+                }
+            };
+            hprintln!("hart 0: hart 1 replied {}", reply);
+            match reply {
+                r if r == REQUEST + 1 => debug::exit(debug::EXIT_SUCCESS),
+                _ => debug::exit(debug::EXIT_FAILURE),
+            }
+        }
+        _ => {
+            let request = MAILBOX.load(Ordering::Acquire);
+            hprintln!("hart {}: received {}", hartid, request);
+            REPLY.store(request + 1, Ordering::Release);
+        }
+    }
+    loop {
+        wfi();
+    }
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    hprintln!("{}", info);
+    debug::exit(debug::EXIT_FAILURE);
+    loop {}
+}
@@ -0,0 +1,17 @@
+/* Memory layout of the QEMU `virt` machine with 2 harts (`-smp 2`) when booting with `-bios none` */
+MEMORY
+{
+    RAM : ORIGIN = 0x80000000, LENGTH = 16M
+}
+
+REGION_ALIAS("REGION_TEXT", RAM);
+REGION_ALIAS("REGION_RODATA", RAM);
+REGION_ALIAS("REGION_DATA", RAM);
+REGION_ALIAS("REGION_BSS", RAM);
+REGION_ALIAS("REGION_HEAP", RAM);
+REGION_ALIAS("REGION_STACK", RAM);
+
+/* Allocate a stack for each hart */
+_max_hart_id = 1;
+
+INCLUDE link.x
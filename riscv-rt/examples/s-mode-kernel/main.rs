@@ -0,0 +1,152 @@
+//! Minimal S-mode kernel running on top of the default SBI firmware of the QEMU `virt` machine.
+//!
+//! The kernel:
+//!
+//! 1. Disables address translation via `satp` (real kernels would set up their page tables here).
+//! 2. Programs a timer interrupt via the SBI timer extension and waits for it.
+//! 3. Drops to U-mode and serves the environment calls of the [`user`] program.
+//!
+//! Traps are dispatched by `riscv-rt` according to `scause`: the `SupervisorTimer` interrupt and
+//! the `UserEnvCall` exception have their own handlers, and any other exception ends up in
+//! `ExceptionHandler`. Every step is printed through semihosting, so the host can check the output.
+//!
+//! Build with the `s-mode` and `riscv/s-mode` features, link with `examples/qemu_sbi.x`, and run with
+//! `qemu-system-riscv{32,64} -machine virt -display none -serial null -monitor none -semihosting-config enable=on,target=native -kernel <elf>`.
+//! The same output is expected with and without the `v-trap` feature.
+#![no_std]
+#![no_main]
+
+mod user;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use riscv::{
+    asm::{sfence_vma_all, wfi},
+    interrupt::supervisor::Interrupt,
+    register::{
+        satp, scause, sepc, sie,
+        sstatus::{self, SPP},
+        stval, time,
+    },
+    sbi,
+};
+use riscv_rt::{core_interrupt, entry, exception, TrapFrame};
+use riscv_semihosting::{debug, hprintln};
+
+/// Timer ticks to wait before the first timer interrupt.
+const TIMER_DELAY: u64 = 10_000;
+
+/// Size (in bytes) of the U-mode stack.
+const USER_STACK_SIZE: usize = 16 * 1024;
+
+/// Stack of the U-mode program.
+#[repr(C, align(16))]
+struct UserStack([u8; USER_STACK_SIZE]);
+
+static mut USER_STACK: UserStack = UserStack([0; USER_STACK_SIZE]);
+
+/// Set by the `SupervisorTimer` handler.
+static TIMER_FIRED: AtomicBool = AtomicBool::new(false);
+
+#[entry]
+fn main() -> ! {
+    hprintln!("kernel: boot");
+    if !sbi::probe_extension(sbi::EID_TIME) {
+        panic!("SBI timer extension not available");
+    }
+
+    // Bare mode: no address translation nor protection
+    unsafe { satp::set(satp::Mode::Bare, 0, 0) };
+    sfence_vma_all();
+
+    unsafe {
+        sie::set_stimer();
+        riscv::interrupt::supervisor::enable();
+    }
+    sbi::set_timer(time::read64() + TIMER_DELAY).unwrap();
+    while !TIMER_FIRED.load(Ordering::Relaxed) {
+        wfi();
+    }
+    riscv::interrupt::supervisor::disable();
+    unsafe { sie::clear_stimer() };
+
+    hprintln!("kernel: entering U-mode");
+    enter_user(user::main)
+}
+
+/// Jumps to `entry` in U-mode.
+///
+/// Supervisor interrupts are always enabled in U-mode, so the caller must disable
+/// the interrupt sources that the kernel does not expect while the user program runs.
+fn enter_user(entry: extern "C" fn() -> !) -> ! {
+    // `riscv-rt` does not swap stacks on trap entry, so the trap frame of every
+    // trap coming from U-mode is stored in the user stack.
+    let stack_top = core::ptr::addr_of_mut!(USER_STACK) as usize + USER_STACK_SIZE;
+    unsafe {
+        sepc::write(entry as usize);
+        sstatus::set_spp(SPP::User);
+        enter(stack_top)
+    }
+}
+
+/// Sets the stack pointer to `stack_top` and returns to the privilege mode in `sstatus.SPP`.
+unsafe fn enter(stack_top: usize) -> ! {
+    core::arch::asm!("mv sp, {}", "sret", in(reg) stack_top, options(noreturn))
+}
+
+#[core_interrupt(Interrupt::SupervisorTimer)]
+fn supervisor_timer() {
+    hprintln!("kernel: SupervisorTimer");
+    // Clear the pending interrupt by requesting a timer event infinitely far into the future
+    sbi::set_timer(u64::MAX).unwrap();
+    TIMER_FIRED.store(true, Ordering::Relaxed);
+}
+
+#[exception(riscv::interrupt::supervisor::Exception::UserEnvCall)]
+fn user_env_call(trap_frame: &mut riscv_rt::TrapFrame) {
+    match trap_frame.a0 {
+        user::SYS_PRINT => {
+            let (ptr, len) = (trap_frame.a1 as *const u8, trap_frame.a2);
+            // The memory is identity-mapped, so user pointers are valid in S-mode
+            let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+            match core::str::from_utf8(bytes) {
+                Ok(msg) => {
+                    hprintln!("user: {}", msg);
+                    trap_frame.a0 = 0;
+                }
+                Err(_) => trap_frame.a0 = usize::MAX,
+            }
+        }
+        user::SYS_EXIT => {
+            hprintln!("kernel: user exited with code {}", trap_frame.a1);
+            match trap_frame.a1 {
+                0 => debug::exit(debug::EXIT_SUCCESS),
+                _ => debug::exit(debug::EXIT_FAILURE),
+            }
+        }
+        _ => trap_frame.a0 = usize::MAX,
+    }
+    // Resume the user program right after the `ecall` instruction
+    let mut sepc = sepc::Sepc::read();
+    sepc.step_over_instruction(|pc| unsafe { (pc as *const u16).read_volatile() });
+    sepc.write();
+}
+
+#[export_name = "ExceptionHandler"]
+fn exception_handler(_trap_frame: &TrapFrame) -> ! {
+    panic!(
+        "unexpected exception {:?} (sepc = {:#x}, stval = {:#x})",
+        scause::read().cause(),
+        sepc::read(),
+        stval::read()
+    );
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    hprintln!("{}", info);
+    debug::exit(debug::EXIT_FAILURE);
+    // Semihosting may not be available: try to shut down the system via SBI
+    sbi::system_reset(sbi::ResetType::Shutdown, sbi::ResetReason::SystemFailure);
+    loop {}
+}
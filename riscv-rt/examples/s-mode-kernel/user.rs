@@ -0,0 +1,46 @@
+//! U-mode program of the S-mode kernel example.
+//!
+//! It runs with user privileges, so it cannot access CSRs nor use semihosting directly.
+//! Instead, it requests services to the kernel via `ecall` with the syscall ID in `a0`
+//! and its arguments in `a1` and `a2`. The kernel writes the result in `a0`.
+
+/// Prints a UTF-8 string: `a1` is a pointer to the string, and `a2` is its length.
+pub const SYS_PRINT: usize = 0;
+/// Terminates the program: `a1` is the exit code.
+pub const SYS_EXIT: usize = 1;
+
+/// Issues a syscall to the kernel.
+fn syscall(id: usize, arg1: usize, arg2: usize) -> usize {
+    let ret;
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            inlateout("a0") id => ret,
+            in("a1") arg1,
+            in("a2") arg2,
+            options(nostack),
+        )
+    };
+    ret
+}
+
+fn print(msg: &str) -> usize {
+    syscall(SYS_PRINT, msg.as_ptr() as usize, msg.len())
+}
+
+fn exit(code: usize) -> ! {
+    syscall(SYS_EXIT, code, 0);
+    // The kernel never resumes a terminated program
+    loop {}
+}
+
+/// Entry point of the U-mode program.
+pub extern "C" fn main() -> ! {
+    let mut code = 0;
+    for msg in ["hello from U-mode", "bye"] {
+        if print(msg) != 0 {
+            code += 1;
+        }
+    }
+    exit(code)
+}
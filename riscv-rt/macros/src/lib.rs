@@ -2,13 +2,13 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::quote;
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     parse::{self, Parse},
     parse_macro_input, parse_quote,
     punctuated::Punctuated,
     spanned::Spanned,
-    FnArg, ItemFn, LitInt, LitStr, PatType, Path, ReturnType, Token, Type, Visibility,
+    Expr, FnArg, ItemFn, LitInt, LitStr, PatType, Path, ReturnType, Stmt, Token, Type, Visibility,
 };
 
 /// Attribute to declare the entry point of the program
@@ -42,6 +42,21 @@ use syn::{
 ///     }
 /// }
 /// ```
+///
+/// - Asynchronous entry point (requires the `async-main` feature of `riscv-rt`)
+///
+/// ``` ignore
+/// # #![no_main]
+/// # use riscv_rt_macros::entry;
+/// #[entry(async)]
+/// async fn main() {
+///     riscv_rt::executor::wait_timer().await;
+///     /* .. */
+/// }
+/// ```
+///
+/// The type of an asynchronous entry point must be `[unsafe] async fn()`. The returned future is
+/// polled by the executor of `riscv-rt` (see the `executor` module of `riscv-rt`).
 #[proc_macro_attribute]
 pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
     let f = parse_macro_input!(input as ItemFn);
@@ -100,6 +115,69 @@ pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     }
 
+    // `#[entry(async)]` marks an asynchronous main function polled by the runtime executor
+    let is_async = match args.to_string().as_str() {
+        "" => false,
+        "async" => true,
+        _ => {
+            return parse::Error::new(
+                Span::call_site(),
+                "This attribute accepts no arguments or `async`",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+    if is_async && !cfg!(feature = "async-main") {
+        return parse::Error::new(
+            Span::call_site(),
+            "`#[entry(async)]` requires the `async-main` feature of `riscv-rt`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if is_async {
+        let valid_signature = f.sig.constness.is_none()
+            && f.sig.asyncness.is_some()
+            && f.vis == Visibility::Inherited
+            && f.sig.abi.is_none()
+            && f.sig.generics.params.is_empty()
+            && f.sig.generics.where_clause.is_none()
+            && f.sig.variadic.is_none()
+            && f.sig.output == ReturnType::Default;
+
+        if !valid_signature {
+            let msg = "`#[entry(async)]` function must have signature `[unsafe] async fn([arg0: usize, ...])`";
+            return parse::Error::new(f.span(), msg).to_compile_error().into();
+        }
+
+        let attrs = f.attrs;
+        let unsafety = f.sig.unsafety;
+        let args = f.sig.inputs;
+        let stmts = f.block.stmts;
+        let (idents, types): (Vec<_>, Vec<_>) = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| match arg {
+                FnArg::Typed(arg) => (format_ident!("__arg{}", i), arg.ty.clone()),
+                FnArg::Receiver(_) => unreachable!(), // checked above
+            })
+            .unzip();
+        return quote!(
+            #[allow(non_snake_case)]
+            #[export_name = "main"]
+            pub unsafe fn __risc_v_rt__main(#(#idents: #types),*) -> ! {
+                #(#attrs)*
+                #unsafety async fn __risc_v_rt__user_main(#args) {
+                    #(#stmts)*
+                }
+                riscv_rt::executor::run(__risc_v_rt__user_main(#(#idents),*))
+            }
+        )
+        .into();
+    }
+
     // check the function signature
     let valid_signature = f.sig.constness.is_none()
         && f.sig.asyncness.is_none()
@@ -108,24 +186,19 @@ pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
         && f.sig.generics.params.is_empty()
         && f.sig.generics.where_clause.is_none()
         && f.sig.variadic.is_none()
-        && match f.sig.output {
-            ReturnType::Default => false,
-            ReturnType::Type(_, ref ty) => matches!(**ty, Type::Never(_)),
-        };
+        && (cfg!(feature = "test-entry")
+            || match f.sig.output {
+                ReturnType::Default => false,
+                ReturnType::Type(_, ref ty) => matches!(**ty, Type::Never(_)),
+            });
 
     if !valid_signature {
-        return parse::Error::new(
-            f.span(),
-            "`#[entry]` function must have signature `[unsafe] fn([arg0: usize, ...]) -> !`",
-        )
-        .to_compile_error()
-        .into();
-    }
-
-    if !args.is_empty() {
-        return parse::Error::new(Span::call_site(), "This attribute accepts no arguments")
-            .to_compile_error()
-            .into();
+        #[cfg(not(feature = "test-entry"))]
+        let msg = "`#[entry]` function must have signature `[unsafe] fn([arg0: usize, ...]) -> !`";
+        #[cfg(feature = "test-entry")]
+        let msg =
+            "`#[entry]` function must have signature `[unsafe] fn([arg0: usize, ...]) [-> T]`";
+        return parse::Error::new(f.span(), msg).to_compile_error().into();
     }
 
     // XXX should we blacklist other attributes?
@@ -134,6 +207,36 @@ pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = f.sig.inputs;
     let stmts = f.block.stmts;
 
+    // In `test-entry` mode, `main` may return. Its result is reported to `riscv_rt::exit`.
+    let returns = match f.sig.output {
+        ReturnType::Default => true,
+        ReturnType::Type(_, ref ty) => !matches!(**ty, Type::Never(_)),
+    };
+    if returns {
+        let output = f.sig.output;
+        let (idents, types): (Vec<_>, Vec<_>) = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| match arg {
+                FnArg::Typed(arg) => (format_ident!("__arg{}", i), arg.ty.clone()),
+                FnArg::Receiver(_) => unreachable!(), // checked above
+            })
+            .unzip();
+        return quote!(
+            #[allow(non_snake_case)]
+            #[export_name = "main"]
+            pub unsafe fn __risc_v_rt__main(#(#idents: #types),*) -> ! {
+                #(#attrs)*
+                #unsafety fn __risc_v_rt__user_main(#args) #output {
+                    #(#stmts)*
+                }
+                let result = __risc_v_rt__user_main(#(#idents),*);
+                riscv_rt::exit(riscv_rt::Termination::report(result))
+            }
+        )
+        .into();
+    }
+
     quote!(
         #[allow(non_snake_case)]
         #[export_name = "main"]
@@ -186,6 +289,19 @@ fn is_correct_type(ty: &Type, name: &str) -> bool {
 /// The function passed will be called before static variables are initialized. Any access of static
 /// variables will result in undefined behavior.
 ///
+/// # Naked functions
+///
+/// As the compiler may use the stack or static variables in the prologue of a regular Rust function,
+/// `#[pre_init(naked)]` accepts a naked function instead. The function must be marked as
+/// `#[unsafe(naked)]`, have the signature `unsafe extern "C" fn()`, and its body must be a single
+/// `naked_asm!` block (naked functions require Rust 1.88 or newer). The function is placed in the `.init.pre_init` section, next to the boot code
+/// in the `.init` section. The assembly code must:
+///
+/// - Return to the caller with `ret`.
+/// - Preserve `sp`, `ra` (or restore it before returning), and the callee-saved registers (`s0`-`s11`).
+///   Registers `a0`-`a2` hold the arguments of the boot code, and they are saved in the stack before the call.
+/// - Not access `.data` nor `.bss`, as they are not initialized yet.
+///
 /// # Examples
 ///
 /// ```
@@ -197,10 +313,36 @@ fn is_correct_type(ty: &Type, name: &str) -> bool {
 ///
 /// # fn main() {}
 /// ```
+///
+/// ```ignore
+/// #[riscv_rt::pre_init(naked)]
+/// #[unsafe(naked)]
+/// unsafe extern "C" fn before_main() {
+///     core::arch::naked_asm!("csrw mscratch, zero", "ret")
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn pre_init(args: TokenStream, input: TokenStream) -> TokenStream {
     let f = parse_macro_input!(input as ItemFn);
 
+    // `#[pre_init(naked)]` marks a naked function with a `naked_asm!` body
+    let is_naked = match args.to_string().as_str() {
+        "" => false,
+        "naked" => true,
+        _ => {
+            return parse::Error::new(
+                Span::call_site(),
+                "This attribute accepts no arguments or `naked`",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    if is_naked {
+        return pre_init_naked(f);
+    }
+
     // check the function signature
     let valid_signature = f.sig.constness.is_none()
         && f.sig.asyncness.is_none()
@@ -228,21 +370,185 @@ pub fn pre_init(args: TokenStream, input: TokenStream) -> TokenStream {
         .into();
     }
 
+    // XXX should we blacklist other attributes?
+    let attrs = f.attrs;
+    let ident = f.sig.ident;
+    let block = f.block;
+
+    quote!(
+        #[export_name = "__pre_init"]
+        #(#attrs)*
+        pub unsafe fn #ident() #block
+    )
+    .into()
+}
+
+/// Section of naked `#[pre_init]` functions.
+const PRE_INIT_SECTION: &str = ".init.pre_init";
+
+/// Expands a `#[pre_init(naked)]` function.
+fn pre_init_naked(f: ItemFn) -> TokenStream {
+    // check the function signature
+    let valid_signature = f.sig.constness.is_none()
+        && f.sig.asyncness.is_none()
+        && f.vis == Visibility::Inherited
+        && f.sig.unsafety.is_some()
+        && matches!(f.sig.abi, Some(syn::Abi { name: Some(ref name), .. }) if name.value() == "C")
+        && f.sig.inputs.is_empty()
+        && f.sig.generics.params.is_empty()
+        && f.sig.generics.where_clause.is_none()
+        && f.sig.variadic.is_none()
+        && f.sig.output == ReturnType::Default;
+
+    if !valid_signature {
+        return parse::Error::new(
+            f.span(),
+            "`#[pre_init(naked)]` function must have signature `unsafe extern \"C\" fn()`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // the function must be naked, so the compiler does not emit a prologue
+    let is_naked = f.attrs.iter().any(|attr| {
+        let meta = attr.meta.to_token_stream().to_string();
+        meta == "naked" || meta == "unsafe (naked)"
+    });
+    if !is_naked {
+        return parse::Error::new(
+            f.sig.ident.span(),
+            "`#[pre_init(naked)]` function must be marked as `#[unsafe(naked)]`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // the function must live in the `.init.pre_init` section
+    for attr in f.attrs.iter() {
+        let meta = attr.meta.to_token_stream().to_string();
+        if meta.starts_with("link_section") || meta.starts_with("unsafe (link_section") {
+            return parse::Error::new(
+                attr.span(),
+                "`#[pre_init(naked)]` function is placed in the `.init.pre_init` section, remove this attribute",
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+
+    // the body must be a single `naked_asm!` block
+    let is_naked_asm = |mac: &syn::Macro| matches!(mac.path.segments.last(), Some(segment) if segment.ident == "naked_asm");
+    let valid_body = match f.block.stmts.as_slice() {
+        [Stmt::Macro(stmt)] => is_naked_asm(&stmt.mac),
+        [Stmt::Expr(Expr::Macro(expr), _)] => is_naked_asm(&expr.mac),
+        _ => false,
+    };
+    if !valid_body {
+        return parse::Error::new(
+            f.block.span(),
+            "`#[pre_init(naked)]` function body must be a single `naked_asm!` block",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let attrs = f.attrs;
+    let ident = f.sig.ident;
+    let block = f.block;
+
+    quote!(
+        #[export_name = "__pre_init"]
+        #[link_section = #PRE_INIT_SECTION]
+        #(#attrs)*
+        pub unsafe extern "C" fn #ident() #block
+    )
+    .into()
+}
+
+/// Attribute to mark which function will be called after RAM initialization, right before `main`.
+///
+/// **IMPORTANT**: This attribute can appear at most *once* in the dependency graph.
+///
+/// The function must have the signature of `[unsafe] fn(&mut BootInfo)`.
+///
+/// The function is called with a valid stack, initialized `.data` and `.bss` sections, and
+/// (if available) the floating point unit enabled. Interrupts are disabled, and the trap vector
+/// is not set yet. The [`BootInfo`] structure contains the arguments passed to the boot hart
+/// by the previous boot stage. Changes to these arguments are forwarded to `main`.
+///
+/// # Examples
+///
+/// ```
+/// # use riscv_rt_macros::post_init;
+/// # #[repr(C)]
+/// # pub struct BootInfo(usize, usize, usize);
+/// #[post_init]
+/// fn before_main(boot_info: &mut BootInfo) {
+///     // do something here
+/// }
+///
+/// # fn main() {}
+/// ```
+///
+/// [`BootInfo`]: ../riscv_rt/struct.BootInfo.html
+#[proc_macro_attribute]
+pub fn post_init(args: TokenStream, input: TokenStream) -> TokenStream {
+    let f = parse_macro_input!(input as ItemFn);
+
+    // check the function arguments
+    let valid_arguments = f.sig.inputs.len() == 1
+        && match f.sig.inputs.first() {
+            Some(FnArg::Typed(arg)) => match *arg.ty {
+                Type::Reference(ref r) => {
+                    r.mutability.is_some() && is_correct_type(&r.elem, "BootInfo")
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+    // check the function signature
+    let valid_signature = f.sig.constness.is_none()
+        && f.sig.asyncness.is_none()
+        && f.vis == Visibility::Inherited
+        && f.sig.abi.is_none()
+        && valid_arguments
+        && f.sig.generics.params.is_empty()
+        && f.sig.generics.where_clause.is_none()
+        && f.sig.variadic.is_none()
+        && match f.sig.output {
+            ReturnType::Default => true,
+            ReturnType::Type(_, ref ty) => match **ty {
+                Type::Tuple(ref tuple) => tuple.elems.is_empty(),
+                _ => false,
+            },
+        };
+
+    if !valid_signature {
+        return parse::Error::new(
+            f.span(),
+            "`#[post_init]` function must have signature `[unsafe] fn(&mut BootInfo)`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     if !args.is_empty() {
         return parse::Error::new(Span::call_site(), "This attribute accepts no arguments")
             .to_compile_error()
             .into();
     }
 
-    // XXX should we blacklist other attributes?
     let attrs = f.attrs;
+    let unsafety = f.sig.unsafety;
     let ident = f.sig.ident;
+    let inputs = f.sig.inputs;
     let block = f.block;
 
     quote!(
-        #[export_name = "__pre_init"]
+        #[export_name = "__post_init"]
         #(#attrs)*
-        pub unsafe fn #ident() #block
+        pub #unsafety extern "C" fn #ident(#inputs) #block
     )
     .into()
 }
@@ -350,6 +656,34 @@ pub fn loop_global_asm(input: TokenStream) -> TokenStream {
     res.parse().unwrap()
 }
 
+/// Loops an asm expression n times, and returns the result as a string literal.
+///
+/// `loop_asm_str!` takes the same arguments as [`loop_global_asm!`]. Unlike [`loop_global_asm!`],
+/// it does not emit a `global_asm!` block. Instead, the resulting string literal can be used as
+/// one of the template strings of a larger `global_asm!` block.
+///
+/// # Examples
+///
+/// ```
+/// # use riscv_rt_macros::loop_asm_str;
+/// assert_eq!(loop_asm_str!("li x{}, 0", 1, 3), "li x1, 0\nli x2, 0");
+/// ```
+#[proc_macro]
+pub fn loop_asm_str(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as AsmLoopArgs);
+
+    let instructions = (args.count_from..args.count_to)
+        .map(|i| {
+            let i = i.to_string();
+            args.asm_template.replace("{}", &i)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let instructions = LitStr::new(&instructions, Span::call_site());
+    quote!(#instructions).into()
+}
+
 #[derive(Clone, Copy, Debug)]
 enum RiscvArch {
     Rv32I,
@@ -372,14 +706,19 @@ impl Parse for RiscvArch {
 }
 
 impl RiscvArch {
-    fn try_from_env() -> Option<Self> {
-        let arch = std::env::var("RISCV_RT_BASE_ISA").ok()?;
-        match arch.as_str() {
-            "rv32i" => Some(Self::Rv32I),
-            "rv32e" => Some(Self::Rv32E),
-            "rv64i" => Some(Self::Rv64I),
-            "rv64e" => Some(Self::Rv64E),
-            _ => None,
+    /// Returns the base ISA set by the build script of `riscv-rt` in `RISCV_RT_BASE_ISA`.
+    ///
+    /// If the variable is not set or is not valid, it returns a compile error explaining why.
+    fn try_from_env() -> Result<Self, TokenStream> {
+        match std::env::var("RISCV_RT_BASE_ISA").as_deref() {
+            Ok("rv32i") => Ok(Self::Rv32I),
+            Ok("rv32e") => Ok(Self::Rv32E),
+            Ok("rv64i") => Ok(Self::Rv64I),
+            Ok("rv64e") => Ok(Self::Rv64E),
+            Ok(arch) => Err(env_error(&format!(
+                "`RISCV_RT_BASE_ISA` has an invalid value `{arch}` (expected `rv32i`, `rv32e`, `rv64i`, or `rv64e`)"
+            ))),
+            Err(_) => Err(env_error("`RISCV_RT_BASE_ISA` is not set")),
         }
     }
 
@@ -404,8 +743,13 @@ impl RiscvArch {
         }
     }
 
+    /// Registers stored in the trap frame, in order.
+    ///
+    /// Registers starting with `_` are padding slots, which are neither stored nor loaded.
+    /// The `sp` slot stores the value of `sp` before allocating the trap frame, and it is never loaded.
     fn trap_frame(&self) -> Vec<&str> {
-        match self {
+        #[allow(unused_mut)]
+        let mut frame = match self {
             Self::Rv32I | Self::Rv64I => vec![
                 "ra", "t0", "t1", "t2", "t3", "t4", "t5", "t6", "a0", "a1", "a2", "a3", "a4", "a5",
                 "a6", "a7",
@@ -413,7 +757,14 @@ impl RiscvArch {
             Self::Rv32E | Self::Rv64E => {
                 vec!["ra", "t0", "t1", "t2", "a0", "a1", "a2", "a3", "a4", "a5"]
             }
+        };
+        #[cfg(feature = "full-regs")]
+        match self {
+            // padding keeps the trap frame 16-byte aligned
+            Self::Rv32I | Self::Rv64I => frame.extend(["gp", "tp", "sp", "_pad"]),
+            Self::Rv32E | Self::Rv64E => frame.extend(["gp", "tp", "sp"]),
         }
+        frame
     }
 
     /// Standard RISC-V ABI requires the stack to be 16-byte aligned.
@@ -427,6 +778,24 @@ impl RiscvArch {
             _ => 16,
         }
     }
+
+    /// Checks that allocating the trap frame keeps the stack pointer aligned.
+    fn check_trap_frame(&self) -> Result<(), TokenStream> {
+        let byte_alignment = self.byte_alignment();
+        match (self.trap_frame().len() * self.width()) % byte_alignment {
+            0 => Ok(()),
+            _ => {
+                let msg = format!(
+                    "the trap frame of {self:?} targets ({} bytes) must be {byte_alignment}-byte aligned \
+                     to keep the stack aligned in trap handlers",
+                    self.trap_frame().len() * self.width(),
+                );
+                Err(parse::Error::new(Span::call_site(), msg)
+                    .to_compile_error()
+                    .into())
+            }
+        }
+    }
 }
 
 /// Generate the assembly instructions to store the trap frame.
@@ -437,42 +806,172 @@ impl RiscvArch {
 /// This is useful to optimize the binary size in vectored interrupt mode, which divides the trap
 /// frame storage in two parts: the first part saves space in the stack and stores only the `a0` register,
 /// while the second part stores the remaining registers.
+///
+/// The `sp` slot is filled with the value of `sp` before allocating the trap frame.
+/// As it uses `t0` as a scratch register, `t0` must be stored before `sp`.
 fn store_trap<T: FnMut(&str) -> bool>(arch: RiscvArch, mut filter: T) -> String {
     let width = arch.width();
     let store = arch.store();
+    let trap_size = arch.trap_frame().len();
     arch.trap_frame()
         .iter()
         .enumerate()
         .filter(|(_, &reg)| !reg.starts_with('_') && filter(reg))
-        .map(|(i, reg)| format!("{store} {reg}, {i}*{width}(sp)"))
+        .map(|(i, reg)| match *reg {
+            "sp" => format!("addi t0, sp, {trap_size}*{width}\n    {store} t0, {i}*{width}(sp)"),
+            _ => format!("{store} {reg}, {i}*{width}(sp)"),
+        })
         .collect::<Vec<_>>()
         .join("\n    ")
 }
 
 /// Generate the assembly instructions to load the trap frame.
 /// The `arch` parameter is used to determine the width of the registers.
-fn load_trap(arch: RiscvArch) -> String {
+///
+/// The `filter` function is used to filter which registers to load.
+/// The `sp` slot is not loaded, as `sp` is restored by deallocating the trap frame.
+fn load_trap<T: FnMut(&str) -> bool>(arch: RiscvArch, mut filter: T) -> String {
     let width = arch.width();
     let load = arch.load();
     arch.trap_frame()
         .iter()
         .enumerate()
-        .filter(|(_, &reg)| !reg.starts_with('_'))
+        .filter(|(_, &reg)| !reg.starts_with('_') && reg != "sp" && filter(reg))
         .map(|(i, reg)| format!("{load} {reg}, {i}*{width}(sp)"))
         .collect::<Vec<_>>()
         .join("\n    ")
 }
 
+/// Generate the `_save_trap_frame` and `_restore_trap_frame` subroutines in assembly.
+///
+/// With the `compact-trap` feature, trap entry points call these subroutines instead of inlining
+/// the stores and loads of the trap frame. They are called with `jal ra, ...` once the trap frame
+/// is allocated. Thus, `ra` must be stored before calling `_save_trap_frame` and loaded after
+/// returning from `_restore_trap_frame`. `a0` is not stored by `_save_trap_frame`, as vectored
+/// interrupt trap handlers store it before loading the address of the interrupt handler in `a0`.
+///
+/// The `store` parameter contains the instructions to store the remaining registers.
+#[cfg(feature = "compact-trap")]
+fn compact_trap_subroutines(arch: RiscvArch, store: &str) -> String {
+    let load = load_trap(arch, |reg| reg != "ra");
+    format!(
+        r#".section .trap, \"ax\"
+.align 2
+.global _save_trap_frame
+_save_trap_frame:
+    {store}
+    ret
+
+.align 2
+.global _restore_trap_frame
+_restore_trap_frame:
+    {load}
+    ret"#
+    )
+}
+
+/// Generate the assembly instructions to store the trap frame with the `compact-trap` feature.
+///
+/// It stores `ra` and the registers selected by `filter` among `ra` and `a0`, and calls `_save_trap_frame`.
+#[cfg(feature = "compact-trap")]
+fn compact_store_trap<T: FnMut(&str) -> bool>(arch: RiscvArch, mut filter: T) -> String {
+    let store = store_trap(arch, |reg| (reg == "ra" || reg == "a0") && filter(reg));
+    format!("{store}\n    jal ra, _save_trap_frame")
+}
+
+/// Generate the assembly instructions to load the trap frame with the `compact-trap` feature.
+///
+/// It calls `_restore_trap_frame` and loads `ra`.
+#[cfg(feature = "compact-trap")]
+fn compact_load_trap(arch: RiscvArch) -> String {
+    let load = load_trap(arch, |reg| reg == "ra");
+    format!("jal ra, _restore_trap_frame\n    {load}")
+}
+
+/// Generate the assembly instructions to enter a trap with the hart context in `scratch`.
+///
+/// If a hart context is installed and the trap is not nested, the stack pointer is switched
+/// to the trap stack of the context (if any), and the nesting depth of the context is incremented.
+/// The interrupted stack pointer is pushed to a 16-byte slot on top of the new stack.
+/// If no hart context is installed, the slot is pushed to the interrupted stack.
+///
+/// The layout of the hart context must match `riscv_rt::hart_context::HartContext`:
+/// trap stack pointer, nesting depth, and scratch slot.
+#[cfg(feature = "hart-context")]
+fn hart_context_enter(arch: RiscvArch, scratch: &str) -> String {
+    let width = arch.width();
+    let store = arch.store();
+    let load = arch.load();
+    format!(
+        r#"csrrw sp, {scratch}, sp          // sp = hart context, {scratch} = interrupted sp
+    bnez sp, 1f
+    csrrw sp, {scratch}, sp          // no hart context: stay on the interrupted stack
+    addi sp, sp, -16
+    {store} t0, {width}(sp)
+    addi t0, sp, 16
+    {store} t0, 0(sp)                // push the interrupted sp
+    {load} t0, {width}(sp)
+    j 3f
+1:  {store} t0, 2*{width}(sp)          // save t0 in the scratch slot of the context
+    {load} t0, {width}(sp)
+    addi t0, t0, 1
+    {store} t0, {width}(sp)            // increment the nesting depth
+    addi t0, t0, -1
+    bnez t0, 2f                      // nested trap: stay on the interrupted stack
+    {load} t0, 0(sp)
+    bnez t0, 4f                      // switch to the trap stack (if any)
+2:  csrr t0, {scratch}
+4:  addi t0, t0, -16
+    csrrw sp, {scratch}, sp          // sp = interrupted sp, {scratch} = hart context
+    {store} sp, 0(t0)                // push the interrupted sp
+    add sp, t0, zero
+    csrr t0, {scratch}
+    {load} t0, 2*{width}(t0)           // restore t0
+3:"#
+    )
+}
+
+/// Generate the assembly instructions to decrement the nesting depth of the hart context in `scratch`.
+///
+/// They must be placed before restoring the trap frame, as they use `t0` and `t1`.
+/// Once the trap frame is deallocated, the interrupted stack pointer must be popped with `load sp, 0(sp)`.
+#[cfg(feature = "hart-context")]
+fn hart_context_exit(arch: RiscvArch, scratch: &str) -> String {
+    let width = arch.width();
+    let store = arch.store();
+    let load = arch.load();
+    format!(
+        r#"csrr t0, {scratch}
+    beqz t0, 1f
+    {load} t1, {width}(t0)
+    addi t1, t1, -1
+    {store} t1, {width}(t0)            // decrement the nesting depth
+1:"#
+    )
+}
+
+/// Returns a compile error for an environment variable that the build script of `riscv-rt` did not set.
+fn env_error(problem: &str) -> TokenStream {
+    let msg = format!(
+        "{problem}. The build script of `riscv-rt` sets it from the RISC-V target. \
+         These macros are only expanded by `riscv-rt` (do not call them from other crates), \
+         and the build script must run for a RISC-V target (e.g., `--target riscv32imac-unknown-none-elf`)"
+    );
+    parse::Error::new(Span::call_site(), msg)
+        .to_compile_error()
+        .into()
+}
+
 /// Temporary patch macro to deal with LLVM bug
 #[proc_macro]
 pub fn llvm_arch_patch(_input: TokenStream) -> TokenStream {
-    let q = if let Ok(arch) = std::env::var("RISCV_RT_LLVM_ARCH_PATCH") {
-        let patch = format!(".attribute arch,\"{arch}\"");
-        quote! { core::arch::global_asm!{#patch} }
-    } else {
-        quote!(compile_error!("RISCV_RT_LLVM_ARCH_PATCH is not set"))
-    };
-    q.into()
+    match std::env::var("RISCV_RT_LLVM_ARCH_PATCH") {
+        Ok(arch) => {
+            let patch = format!(".attribute arch,\"{arch}\"");
+            quote! { core::arch::global_asm!{#patch} }.into()
+        }
+        Err(_) => env_error("`RISCV_RT_LLVM_ARCH_PATCH` is not set"),
+    }
 }
 
 /// Generates weak `_start_trap` function in assembly.
@@ -481,39 +980,82 @@ pub fn llvm_arch_patch(_input: TokenStream) -> TokenStream {
 /// The trap frame is allocated on the stack and deallocated after the call.
 #[proc_macro]
 pub fn weak_start_trap(_input: TokenStream) -> TokenStream {
-    let arch = RiscvArch::try_from_env().unwrap();
+    let arch = match RiscvArch::try_from_env() {
+        Ok(arch) => arch,
+        Err(e) => return e,
+    };
+
+    if let Err(e) = arch.check_trap_frame() {
+        return e;
+    }
 
     let width = arch.width();
     let trap_size = arch.trap_frame().len();
-    let byte_alignment = arch.byte_alignment();
-    // ensure we do not break that sp is 16-byte aligned
-    if (trap_size * width) % byte_alignment != 0 {
-        return parse::Error::new(Span::call_site(), "Trap frame size must be 16-byte aligned")
-            .to_compile_error()
-            .into();
-    }
+    // With the `compact-trap` feature, `ra` and `a0` are stored inline, and the remaining
+    // registers are stored by the `_save_trap_frame` subroutine
+    #[cfg(not(feature = "compact-trap"))]
     let store = store_trap(arch, |_| true);
-    let load = load_trap(arch);
+    #[cfg(feature = "compact-trap")]
+    let store = store_trap(arch, |reg| reg != "ra" && reg != "a0");
+    #[cfg(not(feature = "compact-trap"))]
+    let load = load_trap(arch, |_| true);
+    #[cfg(feature = "compact-trap")]
+    let load = compact_load_trap(arch);
 
     #[cfg(feature = "s-mode")]
     let ret = "sret";
     #[cfg(not(feature = "s-mode"))]
     let ret = "mret";
 
+    #[cfg(feature = "hart-context")]
+    let (store, enter, exit, pop) = {
+        let scratch = if cfg!(feature = "s-mode") {
+            "sscratch"
+        } else {
+            "mscratch"
+        };
+        // The interrupted stack pointer is stored right above the trap frame
+        let store = store.replace(
+            &format!("addi t0, sp, {trap_size}*{width}"),
+            &format!("{} t0, {trap_size}*{width}(sp)", arch.load()),
+        );
+        (
+            store,
+            hart_context_enter(arch, scratch),
+            hart_context_exit(arch, scratch),
+            format!("{} sp, 0(sp)", arch.load()),
+        )
+    };
+    #[cfg(not(feature = "hart-context"))]
+    let (enter, exit, pop) = ("", "", "");
+
+    #[cfg(not(feature = "compact-trap"))]
+    let subroutines = String::new();
+    #[cfg(feature = "compact-trap")]
+    let (store, subroutines) = (
+        compact_store_trap(arch, |_| true),
+        compact_trap_subroutines(arch, &store),
+    );
+
     format!(
         r#"
 core::arch::global_asm!(
-".section .trap, \"ax\"
+".section .trap.start, \"ax\"
 .align {width}
 .weak _start_trap
 _start_trap:
+    {enter}
     addi sp, sp, - {trap_size} * {width}
     {store}
     add a0, sp, zero
     jal ra, _start_trap_rust
+    {exit}
     {load}
     addi sp, sp, {trap_size} * {width}
+    {pop}
     {ret}
+
+{subroutines}
 ");"#
     )
     .parse()
@@ -527,12 +1069,53 @@ _start_trap:
 /// jumps to the interrupt handler. The '_continue_interrupt_trap' function stores the trap frame
 /// partially (all registers except a0), jumps to the interrupt handler, and restores the trap frame.
 pub fn vectored_interrupt_trap(_input: TokenStream) -> TokenStream {
-    let arch = RiscvArch::try_from_env().unwrap();
+    let arch = match RiscvArch::try_from_env() {
+        Ok(arch) => arch,
+        Err(e) => return e,
+    };
+    if let Err(e) = arch.check_trap_frame() {
+        return e;
+    }
     let width = arch.width();
     let trap_size = arch.trap_frame().len();
     let store_start = store_trap(arch, |reg| reg == "a0");
-    let store_continue = store_trap(arch, |reg| reg != "a0");
-    let load = load_trap(arch);
+    #[cfg(not(feature = "compact-trap"))]
+    let (store_continue, load) = (
+        store_trap(arch, |reg| reg != "a0"),
+        load_trap(arch, |_| true),
+    );
+    #[cfg(feature = "compact-trap")]
+    let (store_continue, load) = (
+        compact_store_trap(arch, |reg| reg != "a0"),
+        compact_load_trap(arch),
+    );
+
+    // Record the trap preserving the interrupt handler address (a0) in a 16-byte stack slot
+    #[cfg(feature = "trap-trace")]
+    let record = format!(
+        "addi sp, sp, -16\n    {store} a0, 0(sp)\n    jal ra, _trap_trace_record\n    {load} a0, 0(sp)\n    addi sp, sp, 16",
+        store = arch.store(),
+        load = arch.load(),
+    );
+    #[cfg(not(feature = "trap-trace"))]
+    let record = "";
+
+    // Sample the cause and the cycle counter before the handler, and check its duration afterwards.
+    // Both values are kept in a 16-byte stack slot while the handler runs.
+    #[cfg(feature = "isr-budget")]
+    let (budget_start, budget_check) = {
+        #[cfg(feature = "s-mode")]
+        let (xcause, xcycle) = ("scause", "cycle");
+        #[cfg(not(feature = "s-mode"))]
+        let (xcause, xcycle) = ("mcause", "mcycle");
+        let (store, load) = (arch.store(), arch.load());
+        (
+            format!("addi sp, sp, -16\n    csrr t0, {xcause}\n    {store} t0, 0(sp)\n    csrr t0, {xcycle}\n    {store} t0, {width}(sp)"),
+            format!("{load} a0, 0(sp)\n    {load} a1, {width}(sp)\n    jal ra, _isr_budget_check\n    addi sp, sp, 16"),
+        )
+    };
+    #[cfg(not(feature = "isr-budget"))]
+    let (budget_start, budget_check) = ("", "");
 
     #[cfg(feature = "s-mode")]
     let ret = "sret";
@@ -555,7 +1138,10 @@ _start_DefaultHandler_trap:
 .global _continue_interrupt_trap
 _continue_interrupt_trap:
     {store_continue}                   // store trap partially (all registers except a0)
+    {record}
+    {budget_start}
     jalr ra, a0, 0                     // jump to corresponding interrupt handler (address stored in a0)
+    {budget_check}
     {load}                             // restore trap frame
     addi sp, sp, {trap_size} * {width} // deallocate space for trap frame
     {ret}                              // return from interrupt
@@ -565,6 +1151,54 @@ _continue_interrupt_trap:
     instructions.parse().unwrap()
 }
 
+#[cfg(feature = "rnmi")]
+#[proc_macro]
+/// Generates global `_start_rnmi_trap` and weak `RnmiHandler` functions in assembly.
+///
+/// The `_start_rnmi_trap` function swaps the stack pointer with `mnscratch`, stores the trap frame
+/// in the RNMI stack, and calls `RnmiHandler`. Then, it restores the trap frame and the stack pointer
+/// and returns with `mnret`. The default `RnmiHandler` implementation is an infinite loop.
+pub fn rnmi_start_trap(_input: TokenStream) -> TokenStream {
+    let arch = match RiscvArch::try_from_env() {
+        Ok(arch) => arch,
+        Err(e) => return e,
+    };
+    let width = arch.width();
+    let trap_size = arch.trap_frame().len();
+    // The stack pointer before the RNMI is in `mnscratch` (0x740) while the handler runs
+    let store = store_trap(arch, |_| true).replace(
+        &format!("addi t0, sp, {trap_size}*{width}"),
+        "csrr t0, 0x740",
+    );
+    let load = load_trap(arch, |_| true);
+
+    format!(
+        r#"
+core::arch::global_asm!(
+".section .trap.rnmi, \"ax\"
+.align 2
+.global _start_rnmi_trap
+_start_rnmi_trap:
+    csrrw sp, 0x740, sp                 // switch to the RNMI stack (mnscratch)
+    addi sp, sp, -{trap_size} * {width} // allocate space for trap frame
+    {store}
+    add a0, sp, zero
+    jal ra, RnmiHandler
+    {load}
+    addi sp, sp, {trap_size} * {width} // deallocate space for trap frame
+    csrrw sp, 0x740, sp                // switch back to the interrupted stack
+    .word 0x70200073                   // mnret
+
+.section .text.RnmiHandler, \"ax\"
+.weak RnmiHandler
+RnmiHandler:
+    j RnmiHandler
+");"#
+    )
+    .parse()
+    .unwrap()
+}
+
 #[derive(Clone, Copy, Debug)]
 enum RiscvPacItem {
     Exception,
@@ -649,7 +1283,7 @@ impl RiscvPacItem {
 /// }
 /// ```
 pub fn exception(args: TokenStream, input: TokenStream) -> TokenStream {
-    trap(args, input, RiscvPacItem::Exception, None)
+    trap(args, input, RiscvPacItem::Exception, false)
 }
 
 #[proc_macro_attribute]
@@ -670,12 +1304,8 @@ pub fn exception(args: TokenStream, input: TokenStream) -> TokenStream {
 /// }
 /// ```
 pub fn core_interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
-    let arch = if cfg!(feature = "v-trap") {
-        RiscvArch::try_from_env()
-    } else {
-        None
-    };
-    trap(args, input, RiscvPacItem::CoreInterrupt, arch)
+    let start_trap = cfg!(feature = "v-trap");
+    trap(args, input, RiscvPacItem::CoreInterrupt, start_trap)
 }
 
 #[proc_macro_attribute]
@@ -685,6 +1315,11 @@ pub fn core_interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// The argument of the macro must be a path to a variant of an enum that implements the `riscv_rt::ExternalInterruptNumber` trait.
 ///
+/// If the `v-trap` feature is enabled, the optional `fast` argument generates a dedicated trap handler
+/// in assembly (e.g., `_start_GPIO0_trap`), as the [`macro@core_interrupt`] attribute does. Targets whose
+/// interrupt controller directly vectors external interrupts can jump to this trap handler, skipping the
+/// generic external interrupt dispatch. Claiming and completing the interrupt (if needed) is up to the handler.
+///
 /// # Example
 ///
 /// ``` ignore,no_run
@@ -692,16 +1327,69 @@ pub fn core_interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
 /// fn gpio0() -> ! {
 ///     loop{};
 /// }
+///
+/// #[riscv_rt::external_interrupt(e310x::interrupt::Interrupt::GPIO1, fast)]
+/// fn gpio1() {
+///     // ...
+/// }
 /// ```
 pub fn external_interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
-    trap(args, input, RiscvPacItem::ExternalInterrupt, None)
+    if args.is_empty() {
+        return trap(args, input, RiscvPacItem::ExternalInterrupt, false);
+    }
+    let ExternalInterruptArgs { path, fast } = parse_macro_input!(args as ExternalInterruptArgs);
+    let start_trap = match fast {
+        Some(fast) if !cfg!(feature = "v-trap") => {
+            return parse::Error::new(
+                fast.span(),
+                "`#[external_interrupt]` fast path requires the `v-trap` feature",
+            )
+            .to_compile_error()
+            .into();
+        }
+        Some(_) => true,
+        None => false,
+    };
+    trap(
+        quote!(#path).into(),
+        input,
+        RiscvPacItem::ExternalInterrupt,
+        start_trap,
+    )
+}
+
+/// Arguments of the [`macro@external_interrupt`] attribute: `path[, fast]`.
+struct ExternalInterruptArgs {
+    path: Path,
+    fast: Option<syn::Ident>,
+}
+
+impl Parse for ExternalInterruptArgs {
+    fn parse(input: parse::ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut fast = None;
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if !input.is_empty() {
+                let ident: syn::Ident = input.parse()?;
+                if ident != "fast" {
+                    return Err(syn::Error::new(ident.span(), "expected `fast`"));
+                }
+                fast = Some(ident);
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+            }
+        }
+        Ok(Self { path, fast })
+    }
 }
 
 fn trap(
     args: TokenStream,
     input: TokenStream,
     pac_item: RiscvPacItem,
-    arch: Option<RiscvArch>,
+    start_trap: bool,
 ) -> TokenStream {
     let f = parse_macro_input!(input as ItemFn);
 
@@ -730,20 +1418,29 @@ fn trap(
     let int_ident = &int_path.segments.last().unwrap().ident;
     let export_name = format!("{:#}", int_ident);
 
-    let start_trap = match arch {
-        Some(arch) => {
-            let trap = start_interrupt_trap(int_ident, arch);
-            quote! {
-                #trap
-            }
-        }
-        None => proc_macro2::TokenStream::new(),
+    // The `cfg` attributes of the handler also apply to the items generated for it.
+    // Thus, handlers of different devices can share export names without colliding.
+    let cfgs = f
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect::<Vec<_>>();
+
+    // The trap handler is generated by `riscv-rt`, which selects the base ISA of the target.
+    // Note that this macro is expanded in other crates, where `RISCV_RT_BASE_ISA` is not set.
+    let start_trap = match start_trap {
+        true => quote! {
+            #(#cfgs)*
+            riscv_rt::__start_interrupt_trap!(#int_ident);
+        },
+        false => proc_macro2::TokenStream::new(),
     };
 
     let pac_trait = pac_item.impl_trait();
 
     quote!(
         // Compile-time check to ensure the trap path implements the trap trait
+        #(#cfgs)*
         const _: fn() = || {
             fn assert_impl<T: #pac_trait>(_arg: T) {}
             assert_impl(#int_path);
@@ -757,8 +1454,38 @@ fn trap(
     .into()
 }
 
-fn start_interrupt_trap(ident: &syn::Ident, arch: RiscvArch) -> proc_macro2::TokenStream {
-    let interrupt = ident.to_string();
+/// Arguments of the [`macro@start_interrupt_trap`] macro: `arch, interrupt`.
+#[cfg(feature = "v-trap")]
+struct StartInterruptTrapArgs {
+    arch: RiscvArch,
+    interrupt: syn::Ident,
+}
+
+#[cfg(feature = "v-trap")]
+impl Parse for StartInterruptTrapArgs {
+    fn parse(input: parse::ParseStream) -> syn::Result<Self> {
+        let arch = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let interrupt = input.parse()?;
+        Ok(Self { arch, interrupt })
+    }
+}
+
+#[cfg(feature = "v-trap")]
+#[doc(hidden)]
+#[proc_macro]
+/// Generates the `_start_<interrupt>_trap` function of an interrupt in vectored mode.
+///
+/// The first argument is the base ISA of the target (`rv32i`, `rv32e`, `rv64i`, or `rv64e`),
+/// which determines the layout of the trap frame. The second argument is the interrupt name.
+/// It is used by the `core_interrupt` and `external_interrupt` attributes via `riscv-rt`.
+pub fn start_interrupt_trap(input: TokenStream) -> TokenStream {
+    let StartInterruptTrapArgs { arch, interrupt } = parse_macro_input!(input);
+    if let Err(e) = arch.check_trap_frame() {
+        return e;
+    }
+
+    let interrupt = interrupt.to_string();
     let width = arch.width();
     let trap_size = arch.trap_frame().len();
     let store = store_trap(arch, |r| r == "a0");
@@ -0,0 +1,79 @@
+//! Typed reasons for aborting the execution.
+//!
+//! When the runtime cannot continue (e.g., an exception has no handler), it calls `__abort` with an
+//! [`AbortReason`] before jumping to `abort`, which loops forever. By default, `__abort` does nothing.
+//! Products can override it to log the reason, reset the device, or enter a safe mode:
+//!
+//! ``` no_run
+//! use riscv_rt::AbortReason;
+//!
+//! #[export_name = "__abort"]
+//! fn custom_abort(reason: AbortReason) {
+//!     match reason {
+//!         AbortReason::UnhandledException => { /* log the exception and reset the device */ }
+//!         _ => { /* enter safe mode */ }
+//!     }
+//! }
+//! ```
+//!
+//! If `__abort` returns, the runtime jumps to `abort`.
+//!
+//! # Note
+//!
+//! Harts with an ID greater than `_max_hart_id` do not have a stack. Thus, they do not call `__abort`,
+//! and jump to `abort` directly with [`AbortReason::HartIdTooLarge`] in `a0`. Custom `abort`
+//! implementations written in assembly can use `a0` for all the reasons.
+
+/// Reason for aborting the execution, passed to `__abort` (and to `abort` in `a0`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+#[repr(usize)]
+pub enum AbortReason {
+    /// The ID of the hart is greater than `_max_hart_id`.
+    HartIdTooLarge = 0,
+    /// An exception occurred, and the default `ExceptionHandler` was called.
+    UnhandledException = 1,
+    /// An interrupt occurred, and the default `DefaultHandler` was called.
+    UnhandledInterrupt = 2,
+    /// A panic occurred while handling a trap, and the default `PanicInTrap` was called.
+    PanicInTrap = 3,
+    /// The program exited, and the default `_exit` was called.
+    Exit = 4,
+    /// A double trap exception (code 16, e.g., reported by the Ssdbltrp extension) occurred, and the
+    /// default `ExceptionHandler` was called. The state of the trap handler that was interrupted is lost.
+    DoubleTrap = 5,
+    /// The data image (e.g., the initial values of `.data`) failed an integrity check.
+    ///
+    /// The runtime does not check the data image. Firmware that verifies it (e.g., in `__pre_init`
+    /// or at the beginning of `main`) can abort with this reason using [`abort_with`].
+    DataImageCorrupt = 6,
+}
+
+/// Aborts the execution with the given `reason`.
+///
+/// It calls `__abort` with `reason` and jumps to `abort`, as the default handlers of the runtime do.
+#[inline]
+pub fn abort_with(reason: AbortReason) -> ! {
+    // SAFETY: the caller runs Rust code, so it has a valid stack
+    unsafe { _abort(reason) }
+}
+
+/// Calls `__abort` with the reason for aborting and jumps to `abort`.
+///
+/// # Safety
+///
+/// This function must be called only from the default handlers of the runtime, with a valid stack.
+/// Do **NOT** call this function directly.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn _abort(reason: AbortReason) -> ! {
+    extern "Rust" {
+        fn __abort(reason: AbortReason);
+    }
+    extern "C" {
+        fn abort() -> !;
+    }
+
+    __abort(reason);
+    abort()
+}
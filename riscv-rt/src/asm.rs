@@ -1,17 +1,43 @@
 use core::arch::global_asm;
 
 /// Parse cfg attributes inside a global_asm call.
+///
+/// Each template string can be preceded by a `#[cfg(...)]` attribute. Several template strings
+/// can be grouped under the same `#[cfg(...)]` attribute with braces, and groups can be nested:
+///
+/// ``` ignore
+/// cfg_global_asm!(
+///     "always",
+///     #[cfg(feature = "a")]
+///     "only with feature a",
+///     #[cfg(feature = "b")]
+///     {
+///         "only with feature b",
+///         #[cfg(feature = "c")]
+///         "only with features b and c",
+///     },
+/// );
+/// ```
+///
+/// Template strings can also be macro invocations that expand to string literals
+/// (e.g., `concat!` or `riscv_rt_macros::loop_asm_str!`).
 macro_rules! cfg_global_asm {
     {@inner, [$($x:tt)*], } => {
         global_asm!{$($x)*}
     };
-    (@inner, [$($x:tt)*], #[cfg($meta:meta)] $asm:literal, $($rest:tt)*) => {
+    (@inner, [$($x:tt)*], #[cfg($meta:meta)] { $($inner:tt)* }, $($rest:tt)*) => {
+        #[cfg($meta)]
+        cfg_global_asm!{@inner, [$($x)*], $($inner)* $($rest)*}
+        #[cfg(not($meta))]
+        cfg_global_asm!{@inner, [$($x)*], $($rest)*}
+    };
+    (@inner, [$($x:tt)*], #[cfg($meta:meta)] $asm:expr, $($rest:tt)*) => {
         #[cfg($meta)]
         cfg_global_asm!{@inner, [$($x)* $asm,], $($rest)*}
         #[cfg(not($meta))]
         cfg_global_asm!{@inner, [$($x)*], $($rest)*}
     };
-    {@inner, [$($x:tt)*], $asm:literal, $($rest:tt)*} => {
+    {@inner, [$($x:tt)*], $asm:expr, $($rest:tt)*} => {
         cfg_global_asm!{@inner, [$($x)* $asm,], $($rest)*}
     };
     {$($asms:tt)*} => {
@@ -31,14 +57,22 @@ macro_rules! cfg_global_asm {
 // - https://github.com/llvm/llvm-project/issues/61991
 riscv_rt_macros::llvm_arch_patch!();
 
-// Entry point of all programs (_start). It initializes DWARF call frame information,
-// the stack pointer, the frame pointer (needed for closures to work in start_rust)
-// and the global pointer. Then it calls _start_rust.
+// Entry point of all programs (_start, or _app_entry with the `app-image` feature). It initializes
+// DWARF call frame information, the stack pointer, the frame pointer (needed for closures to work
+// in start_rust) and the global pointer. Then it calls _start_rust.
+//
+// All the boot code is emitted in a single `global_asm!` block. Otherwise, the compiler
+// may place each block in a different codegen unit, and the linker may not keep
+// `_start`, `_abs_start`, and the rest of the initialization code contiguous.
 cfg_global_asm!(
-    ".section .init, \"ax\"
-    .global _start
-
+    ".section .init, \"ax\"",
+    // With the `app-image` feature, the entry point is `_app_entry` instead of `_start`
+    #[cfg(not(feature = "app-image"))]
+    ".global _start
 _start:",
+    #[cfg(feature = "app-image")]
+    ".global _app_entry
+_app_entry:",
     #[cfg(target_arch = "riscv32")]
     "lui ra, %hi(_abs_start)
      jr %lo(_abs_start)(ra)",
@@ -65,60 +99,82 @@ _abs_start:
     #[cfg(not(feature = "s-mode"))]
     "csrw mie, 0
     csrw mip, 0",
+    // No hart context is installed until `HartContext::install` (the scratch register is unspecified at reset)
+    #[cfg(all(feature = "hart-context", feature = "s-mode"))]
+    "csrw sscratch, zero",
+    #[cfg(all(feature = "hart-context", not(feature = "s-mode")))]
+    "csrw mscratch, zero",
     // Set pre-init trap vector
     "la t0, _pre_init_trap",
     #[cfg(feature = "s-mode")]
     "csrw stvec, t0",
     #[cfg(not(feature = "s-mode"))]
     "csrw mtvec, t0",
-);
-
-// ZERO OUT GENERAL-PURPOSE REGISTERS
-riscv_rt_macros::loop_global_asm!("    li x{}, 0", 1, 10);
-// a0..a2 (x10..x12) skipped
-riscv_rt_macros::loop_global_asm!("    li x{}, 0", 13, 16);
-#[cfg(riscvi)]
-riscv_rt_macros::loop_global_asm!("    li x{}, 0", 16, 32);
-
-// INITIALIZE GLOBAL POINTER, STACK POINTER, AND FRAME POINTER
-cfg_global_asm!(
+    // ZERO OUT GENERAL-PURPOSE REGISTERS
+    riscv_rt_macros::loop_asm_str!("li x{}, 0", 1, 10),
+    // a0..a2 (x10..x12) skipped
+    riscv_rt_macros::loop_asm_str!("li x{}, 0", 13, 16),
+    #[cfg(riscvi)]
+    riscv_rt_macros::loop_asm_str!("li x{}, 0", 16, 32),
+    // In S-mode, the hartid is passed as parameter. Keep it in tp for `_riscv_hartid`
+    #[cfg(all(feature = "s-mode", not(feature = "u-boot")))]
+    "mv tp, a0",
+    // INITIALIZE GLOBAL POINTER, STACK POINTER, AND FRAME POINTER
     ".option push
     .option norelax
     la gp, __global_pointer$
     .option pop",
-);
-#[cfg(not(feature = "single-hart"))]
-cfg_global_asm!(
-    #[cfg(feature = "s-mode")]
-    "mv t2, a0 // the hartid is passed as parameter by SMODE",
-    #[cfg(not(feature = "s-mode"))]
-    "csrr t2, mhartid",
-    "lui t0, %hi(_max_hart_id)
-    add t0, t0, %lo(_max_hart_id)
-    bgtu t2, t0, abort
-    lui t0, %hi(_hart_stack_size)
-    add t0, t0, %lo(_hart_stack_size)",
-    #[cfg(riscvm)]
-    "mul t0, t2, t0",
-    #[cfg(not(riscvm))]
-    "beqz t2, 2f  // skip if hart ID is 0
-    mv t1, t0
+    #[cfg(not(feature = "single-hart"))]
+    {
+        #[cfg(feature = "s-mode")]
+        "mv t2, a0 // the hartid is passed as parameter by SMODE",
+        #[cfg(not(feature = "s-mode"))]
+        "csrr t2, mhartid",
+        "lui t0, %hi(_max_hart_id)
+        add t0, t0, %lo(_max_hart_id)
+        bleu t2, t0, 3f
+        li a0, 0 // AbortReason::HartIdTooLarge
+        j abort
+3:      lui t0, %hi(_hart_stack_size)
+        add t0, t0, %lo(_hart_stack_size)",
+        #[cfg(riscvm)]
+        "mul t0, t2, t0",
+        #[cfg(not(riscvm))]
+        "beqz t2, 2f  // skip if hart ID is 0
+        mv t1, t0
 1:
-    add t0, t0, t1
-    addi t2, t2, -1
-    bnez t2, 1b
+        add t0, t0, t1
+        addi t2, t2, -1
+        bnez t2, 1b
 2:  ",
-);
-cfg_global_asm!(
+    },
     "la t1, _stack_start",
     #[cfg(not(feature = "single-hart"))]
     "sub t1, t1, t0",
+    // Fill the stack area of the hart with `_stack_paint_pattern` (t1 points to its top)
+    #[cfg(feature = "stack-paint")]
+    "lui t0, %hi(_hart_stack_size)
+    add t0, t0, %lo(_hart_stack_size)
+    sub t0, t1, t0
+    addi t0, t0, 3
+    andi t0, t0, -4 // align the bottom of the stack area to 4 bytes
+    la t2, _stack_paint_word
+    lw t2, 0(t2)
+    bgeu t0, t1, 2f
+1:  sw t2, 0(t0)
+    addi t0, t0, 4
+    bltu t0, t1, 1b
+2:  ",
+    // The top of the hart stack is reserved for the RNMI handler, and mnscratch points to it
+    #[cfg(feature = "rnmi")]
+    "andi t1, t1, -16
+    csrw 0x740, t1 // mnscratch
+    lui t0, %hi(_rnmi_stack_size)
+    add t0, t0, %lo(_rnmi_stack_size)
+    sub t1, t1, t0",
     "andi sp, t1, -16 // align stack to 16-bytes
     add s0, sp, zero",
-);
-
-// STORE A0..A2 IN THE STACK, AS THEY WILL BE NEEDED LATER BY main
-cfg_global_asm!(
+    // STORE A0..A2 IN THE STACK, AS THEY WILL BE NEEDED LATER BY main
     #[cfg(target_arch = "riscv32")]
     "addi sp, sp, -4 * 4 // we must keep stack aligned to 16-bytes
     sw a0, 4 * 0(sp)
@@ -129,26 +185,74 @@ cfg_global_asm!(
     sd a0, 8 * 0(sp)
     sd a1, 8 * 1(sp)
     sd a2, 8 * 2(sp)",
-);
-
-// SKIP RAM INITIALIZATION IF CURRENT HART IS NOT THE BOOT HART
-#[cfg(not(feature = "single-hart"))]
-cfg_global_asm!(
-    #[cfg(not(feature = "s-mode"))]
-    "csrr a0, mhartid",
-    "call _mp_hook
-    mv t0, a0
+    // SKIP RAM INITIALIZATION IF CURRENT HART IS NOT THE BOOT HART
+    #[cfg(not(feature = "single-hart"))]
+    {
+        #[cfg(not(feature = "s-mode"))]
+        "csrr a0, mhartid",
+        "call _mp_hook
+        mv t0, a0
 
-    beqz a0, 4f",
-);
-// IF CURRENT HART IS THE BOOT HART CALL __pre_init AND INITIALIZE RAM
-cfg_global_asm!(
-    "call __pre_init
-    // Copy .data from flash to RAM
+        beqz a0, 4f",
+    },
+    // IF CURRENT HART IS THE BOOT HART CALL __pre_init AND INITIALIZE RAM
+    "call __pre_init",
+    // Copy .trap from flash to RAM
+    #[cfg(feature = "trap-ram")]
+    "la t0, __strap
+    la a0, __etrap
+    la t1, __sitrap
+    bgeu t0, a0, 6f
+    beq t0, t1, 6f // skip if .trap is loaded in place (e.g., link-ram.x)
+5:  lw t2, 0(t1)
+    addi t1, t1, 4
+    sw t2, 0(t0)
+    addi t0, t0, 4
+    bltu t0, a0, 5b
+6:  ",
+    // Copy .data2 from flash to RAM and zero out .bss2
+    #[cfg(feature = "data2-region")]
+    {
+        "la t0, __sdata2
+        la a0, __edata2
+        la t1, __sidata2
+        bgeu t0, a0, 8f
+        beq t0, t1, 8f // skip if .data2 is loaded in place (e.g., link-ram.x)
+7:  ",
+        #[cfg(target_arch = "riscv32")]
+        "lw t2, 0(t1)
+        addi t1, t1, 4
+        sw t2, 0(t0)
+        addi t0, t0, 4
+        bltu t0, a0, 7b",
+        #[cfg(target_arch = "riscv64")]
+        "ld t2, 0(t1)
+        addi t1, t1, 8
+        sd t2, 0(t0)
+        addi t0, t0, 8
+        bltu t0, a0, 7b",
+        "
+8:  la t0, __sbss2
+        la t2, __ebss2
+        bgeu t0, t2, 10f
+9:  ",
+        #[cfg(target_arch = "riscv32")]
+        "sw zero, 0(t0)
+        addi t0, t0, 4
+        bltu t0, t2, 9b",
+        #[cfg(target_arch = "riscv64")]
+        "sd zero, 0(t0)
+        addi t0, t0, 8
+        bltu t0, t2, 9b",
+        "
+10: ",
+    },
+    "// Copy .data from flash to RAM
     la t0, __sdata
     la a0, __edata
     la t1, __sidata
     bgeu t0, a0, 2f
+    beq t0, t1, 2f // skip if .data is loaded in place (e.g., link-ram.x)
 1:  ",
     #[cfg(target_arch = "riscv32")]
     "lw t2, 0(t1)
@@ -178,34 +282,37 @@ cfg_global_asm!(
     bltu t0, t2, 3b",
     "
 4: // RAM initilized",
-);
-
-// INITIALIZE FLOATING POINT UNIT
-#[cfg(any(riscvf, riscvd))]
-cfg_global_asm!(
-    "
-    li t0, 0x4000 // bit 14 is FS most significant bit
-    li t2, 0x2000 // bit 13 is FS least significant bit
-    ",
-    #[cfg(feature = "s-mode")]
-    "csrrc x0, sstatus, t0
-    csrrs x0, sstatus, t2",
-    #[cfg(not(feature = "s-mode"))]
-    "csrrc x0, mstatus, t0
-    csrrs x0, mstatus, t2",
-    "fscsr x0",
-);
-// ZERO OUT FLOATING POINT REGISTERS
-#[cfg(all(target_arch = "riscv32", riscvd))]
-riscv_rt_macros::loop_global_asm!("    fcvt.d.w f{}, x0", 32);
-#[cfg(all(target_arch = "riscv64", riscvd))]
-riscv_rt_macros::loop_global_asm!("    fmv.d.x f{}, x0", 32);
-#[cfg(all(riscvf, not(riscvd)))]
-riscv_rt_macros::loop_global_asm!("    fmv.w.x f{}, x0", 32);
-
-// SET UP INTERRUPTS, RESTORE a0..a2, AND JUMP TO MAIN RUST FUNCTION
-cfg_global_asm!(
-    "call _setup_interrupts",
+    // Synchronize the instruction and data streams, as the trap code has been copied to RAM
+    #[cfg(feature = "trap-ram")]
+    "fence.i",
+    // INITIALIZE FLOATING POINT UNIT
+    #[cfg(any(riscvf, riscvd))]
+    {
+        "
+        li t0, 0x4000 // bit 14 is FS most significant bit
+        li t2, 0x2000 // bit 13 is FS least significant bit
+        ",
+        #[cfg(feature = "s-mode")]
+        "csrrc x0, sstatus, t0
+        csrrs x0, sstatus, t2",
+        #[cfg(not(feature = "s-mode"))]
+        "csrrc x0, mstatus, t0
+        csrrs x0, mstatus, t2",
+        "fscsr x0",
+    },
+    // ZERO OUT FLOATING POINT REGISTERS
+    #[cfg(all(target_arch = "riscv32", riscvd))]
+    riscv_rt_macros::loop_asm_str!("fcvt.d.w f{}, x0", 32),
+    #[cfg(all(target_arch = "riscv64", riscvd))]
+    riscv_rt_macros::loop_asm_str!("fmv.d.x f{}, x0", 32),
+    #[cfg(all(riscvf, not(riscvd)))]
+    riscv_rt_macros::loop_asm_str!("fmv.w.x f{}, x0", 32),
+    // PROGRAM PMP ENTRIES (pmp-wx), CALL __post_init WITH A POINTER TO a0..a2 (BootInfo), SET UP INTERRUPTS, RESTORE a0..a2, AND JUMP TO MAIN RUST FUNCTION
+    #[cfg(feature = "pmp-wx")]
+    "call _setup_pmp",
+    "mv a0, sp
+    call __post_init
+    call _setup_interrupts",
     #[cfg(target_arch = "riscv32")]
     "lw a0, 4 * 0(sp)
     lw a1, 4 * 1(sp)
@@ -218,13 +325,34 @@ cfg_global_asm!(
     addi sp, sp, 8 * 4",
     "jal zero, main
     .cfi_endproc",
-);
-
-cfg_global_asm!(
     // Default implementation of `__pre_init` does nothing.
     // Users can override this function with the [`#[pre_init]`] macro.
     ".weak __pre_init
 __pre_init:
+    ret",
+    // Default implementation of `__post_init` does nothing.
+    // Users can override this function with the [`#[post_init]`] macro.
+    ".weak __post_init
+__post_init:
+    ret",
+    // Default implementation of `_riscv_hartid` returns the hartid kept in tp at boot.
+    // Users can override this function by defining their own `_riscv_hartid`.
+    #[cfg(feature = "s-mode")]
+    ".weak _riscv_hartid
+_riscv_hartid:
+    mv a0, tp
+    ret",
+    // Default implementation of `__idle` waits for an interrupt.
+    // Users can override this function by defining their own `__idle`.
+    ".weak __idle
+__idle:
+    wfi
+    ret",
+    // Default implementation of `__isr_budget_exceeded` does nothing.
+    // Users can override this function by defining their own `__isr_budget_exceeded`.
+    #[cfg(feature = "isr-budget")]
+    ".weak __isr_budget_exceeded
+__isr_budget_exceeded:
     ret",
     #[cfg(not(feature = "single-hart"))]
     // Default implementation of `_mp_hook` wakes hart 0 and busy-loops all the other harts.
@@ -250,17 +378,69 @@ _setup_interrupts:",
     "csrw stvec, t0",
     #[cfg(not(feature = "s-mode"))]
     "csrw mtvec, t0",
+    // In M-mode, if a secondary vector table for S-mode is provided, it is set in vectored mode
+    #[cfg(all(feature = "v-trap", not(feature = "s-mode")))]
+    ".weak _vector_table_s",
+    #[cfg(all(feature = "v-trap", not(feature = "s-mode"), target_arch = "riscv32"))]
+    "lui t0, %hi(_vector_table_s)
+    addi t0, t0, %lo(_vector_table_s)",
+    #[cfg(all(feature = "v-trap", not(feature = "s-mode"), target_arch = "riscv64"))]
+    ".option push
+    .option norelax
+1:
+    auipc t0, %pcrel_hi(2f)
+    ld t0, %pcrel_lo(1b)(t0)
+    .option pop
+    .pushsection .rodata._vector_table_s, \"a\"
+    .balign 8
+2:
+    .dword _vector_table_s
+    .popsection",
+    #[cfg(all(feature = "v-trap", not(feature = "s-mode")))]
+    "beqz t0, 3f // skip if there is no S-mode vector table
+    ori t0, t0, 0x1
+    csrw stvec, t0
+3:  ",
     "ret",
-    // Default implementation of `ExceptionHandler` is an infinite loop.
+    // Default implementation of `ExceptionHandler` aborts with `AbortReason::DoubleTrap` for double trap
+    // exceptions (code 16), and with `AbortReason::UnhandledException` otherwise.
     // Users can override this function by defining their own `ExceptionHandler`
     ".weak ExceptionHandler
 ExceptionHandler:
-    j ExceptionHandler",
-    // Default implementation of `DefaultHandler` is an infinite loop.
+    li a0, 1",
+    #[cfg(feature = "s-mode")]
+    "csrr t0, scause",
+    #[cfg(not(feature = "s-mode"))]
+    "csrr t0, mcause",
+    "li t1, 16
+    bne t0, t1, 1f
+    li a0, 5
+1:  tail _abort",
+    // Default implementation of `DefaultHandler` aborts with `AbortReason::UnhandledInterrupt`.
     // Users can override this function by defining their own `DefaultHandler`
     ".weak DefaultHandler
 DefaultHandler:
-    j DefaultHandler",
+    li a0, 2
+    tail _abort",
+    // Default implementation of `__abort` does nothing.
+    // Users can override this function by defining their own `__abort`
+    ".weak __abort
+__abort:
+    ret",
+    #[cfg(all(feature = "panic-in-trap", feature = "s-mode"))]
+    // Default implementation of `PanicInTrap` disables interrupts and aborts with `AbortReason::PanicInTrap`.
+    // Users can override this function by defining their own `PanicInTrap`
+    ".weak PanicInTrap
+PanicInTrap:
+    csrci sstatus, 0x2
+    li a0, 3
+    tail _abort",
+    #[cfg(all(feature = "panic-in-trap", not(feature = "s-mode")))]
+    ".weak PanicInTrap
+PanicInTrap:
+    csrci mstatus, 0x8
+    li a0, 3
+    tail _abort",
     // Default implementation of `_pre_init_trap` is an infinite loop.
     // Users can override this function by defining their own `_pre_init_trap`
     // If the execution reaches this point, it means that there is a bug in the boot code.
@@ -268,6 +448,27 @@ DefaultHandler:
     .weak _pre_init_trap
 _pre_init_trap:
     j _pre_init_trap",
+    // Default implementation of `_exit` aborts with `AbortReason::Exit`.
+    // Users can override this function by defining their own `_exit`
+    #[cfg(feature = "test-entry")]
+    ".section .text.abort
+    .weak _exit
+_exit:
+    li a0, 4
+    tail _abort",
+    // Make sure there is an abort symbol when linking
+    ".section .text.abort
+    .weak abort
+abort:
+    j abort",
+    // `_stack_paint_pattern` is an absolute symbol that may not fit a sign-extended `lui` immediate
+    // in RV64 targets (e.g., 0xCCCCCCCC). Thus, the code loads it from this word instead.
+    #[cfg(feature = "stack-paint")]
+    ".section .rodata._stack_paint_word, \"a\"
+    .balign 4
+    .global _stack_paint_word
+_stack_paint_word:
+    .word _stack_paint_pattern",
 );
 
 riscv_rt_macros::weak_start_trap!();
@@ -275,10 +476,5 @@ riscv_rt_macros::weak_start_trap!();
 #[cfg(feature = "v-trap")]
 riscv_rt_macros::vectored_interrupt_trap!();
 
-#[rustfmt::skip]
-global_asm!(
-    ".section .text.abort
-.weak abort
-abort:  // make sure there is an abort symbol when linking
-    j abort"
-);
+#[cfg(feature = "rnmi")]
+riscv_rt_macros::rnmi_start_trap!();
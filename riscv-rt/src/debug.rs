@@ -0,0 +1,140 @@
+//! Watchpoints to find the code that corrupts a variable.
+//!
+//! When the `watchpoint` feature is enabled, [`watch_address`] arms a trigger of the trigger module
+//! (Sdtrig extension) that raises a breakpoint exception when a store writes to a memory region.
+//! The runtime defines the `Breakpoint` exception handler, which calls the callback passed to
+//! [`watch_address`] with the [`TrapFrame`] of the store. Thus, firmware can find out who is
+//! scribbling on a variable (e.g., by printing `ra` and `mepc`) without an external debugger.
+//!
+//! The watchpoint is disarmed before the callback is called, as the exception may be raised before
+//! the store is performed (i.e., returning to the store would raise the exception again). Call
+//! [`watch_address`] again after the store (e.g., in the main loop) to keep watching the region.
+//!
+//! # Example
+//!
+//! ``` ignore
+//! use riscv_rt::{debug, TrapFrame};
+//!
+//! static mut COUNTER: u32 = 0;
+//!
+//! fn on_write(trap_frame: &TrapFrame) {
+//!     let pc = riscv::register::mepc::read();
+//!     // ... log the store at `pc` and the return address in `trap_frame.ra` ...
+//! }
+//!
+//! debug::watch_address(core::ptr::addr_of!(COUNTER) as usize, 4, on_write).unwrap();
+//! ```
+//!
+//! # Note
+//!
+//! The watchpoint uses the trigger [`TRIGGER`] of the current hart, and it fires for stores executed
+//! in M-mode, S-mode, or U-mode. In M-mode, triggers that raise breakpoint exceptions only fire if
+//! `tcontrol.mte` is set (if `tcontrol` is implemented). Regions are rounded up to a naturally aligned
+//! power-of-two region, so the start address must be aligned to the rounded length (check
+//! [`riscv::debug::store_match_tdata`] for more details).
+//!
+//! Breakpoint exceptions that are not caused by the watchpoint (e.g., `ebreak` instructions) are
+//! forwarded to `ExceptionHandler`. As this feature defines the `Breakpoint` symbol, users must not
+//! define their own handler for this exception.
+
+use crate::TrapFrame;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use riscv::debug::{self, Modes};
+use riscv::register::{mepc, mtval};
+use riscv_pac::result::Result;
+
+/// Index of the trigger used by the watchpoint.
+pub const TRIGGER: usize = 0;
+
+/// Signature of watchpoint callbacks.
+pub type Callback = fn(&TrapFrame);
+
+/// Callback of the armed watchpoint (0 if the watchpoint is not armed).
+static CALLBACK: AtomicUsize = AtomicUsize::new(0);
+/// Start address of the watched region.
+static START: AtomicUsize = AtomicUsize::new(0);
+/// Length (in bytes) of the watched region.
+static LEN: AtomicUsize = AtomicUsize::new(0);
+
+/// Arms the watchpoint to call `on_write` when a store writes to the `len` bytes starting at `addr`.
+///
+/// A previously armed watchpoint is replaced.
+///
+/// # Errors
+///
+/// It returns the error of [`riscv::debug::watch_store`] if the trigger cannot be armed.
+#[inline]
+pub fn watch_address(addr: usize, len: usize, on_write: Callback) -> Result<()> {
+    START.store(addr, Ordering::Relaxed);
+    LEN.store(len, Ordering::Relaxed);
+    CALLBACK.store(on_write as usize, Ordering::Release);
+    // SAFETY: the runtime handles the breakpoint exception, and it disarms the trigger before returning
+    let res = unsafe {
+        debug::watch_store(
+            TRIGGER,
+            addr,
+            len,
+            Modes::MACHINE.union(Modes::SUPERVISOR).union(Modes::USER),
+        )
+    };
+    if res.is_err() {
+        CALLBACK.store(0, Ordering::Release);
+    }
+    res
+}
+
+/// Disarms the watchpoint.
+///
+/// # Errors
+///
+/// It returns the error of [`riscv::debug::disarm`] if the trigger cannot be disarmed.
+#[inline]
+pub fn unwatch() -> Result<()> {
+    CALLBACK.store(0, Ordering::Release);
+    debug::disarm(TRIGGER)
+}
+
+/// Returns `true` if the breakpoint exception was raised by the watchpoint.
+///
+/// The hit bits of the trigger and the address in `mtval` are optional. If none of them identify
+/// the watchpoint, any breakpoint exception not raised by an `ebreak` instruction is attributed to it.
+#[inline]
+unsafe fn is_watchpoint() -> bool {
+    if debug::hit(TRIGGER) == Ok(true) {
+        return true;
+    }
+    let addr = mtval::read();
+    let start = START.load(Ordering::Relaxed);
+    if addr.wrapping_sub(start) < LEN.load(Ordering::Relaxed).max(1) {
+        return true;
+    }
+    let parcel = |addr: usize| (addr as *const u16).read_volatile();
+    let pc = mepc::read();
+    let low = parcel(pc);
+    let insn = match low & 0b11 {
+        0b11 => low as u32 | (parcel(pc + 2) as u32) << 16,
+        _ => low as u32,
+    };
+    // ebreak and c.ebreak
+    !matches!(insn, 0x0010_0073 | 0x9002)
+}
+
+/// Calls the watchpoint callback, or `ExceptionHandler` if the exception was not raised by the watchpoint.
+#[export_name = "Breakpoint"]
+unsafe extern "C" fn breakpoint(trap_frame: &TrapFrame) {
+    extern "C" {
+        fn ExceptionHandler(trap_frame: &TrapFrame);
+    }
+    match CALLBACK.load(Ordering::Acquire) {
+        0 => ExceptionHandler(trap_frame),
+        _ if !is_watchpoint() => ExceptionHandler(trap_frame),
+        callback => {
+            CALLBACK.store(0, Ordering::Release);
+            // The store may not have been performed yet, so the trigger must not fire again
+            let _ = debug::disarm(TRIGGER);
+            // SAFETY: non-zero values of `CALLBACK` are `Callback` pointers stored by `watch_address`
+            let callback = core::mem::transmute::<usize, Callback>(callback);
+            callback(trap_frame);
+        }
+    }
+}
@@ -10,8 +10,22 @@
 //! If your target has custom exception sources, the target PAC might provide equivalent
 //! code to adapt for the target needs. In this case, you may need to opt out this module.
 //! To do so, activate the `no-exceptions` feature of the `riscv-rt` crate.
+//!
+//! # Runtime redirection
+//!
+//! When the `exception-redirect` feature is enabled, exception handlers can be replaced at
+//! runtime with the [`redirect`] function (e.g., in bootloader to application transitions or
+//! test harnesses). Redirected handlers are stored in a RAM table that is checked before
+//! the static [`__EXCEPTIONS`] array.
 
 use crate::TrapFrame;
+#[cfg(feature = "exception-redirect")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "exception-redirect")]
+use riscv_pac::result::{Error, Result};
+
+/// Signature of exception handlers.
+pub type Handler = unsafe extern "C" fn(&TrapFrame);
 
 extern "C" {
     fn InstructionMisaligned(trap_frame: &TrapFrame);
@@ -32,7 +46,7 @@ extern "C" {
 
 /// Array with all the exception handlers sorted according to their exception source code.
 #[no_mangle]
-pub static __EXCEPTIONS: [Option<unsafe extern "C" fn(&TrapFrame)>; 16] = [
+pub static __EXCEPTIONS: [Option<Handler>; 16] = [
     Some(InstructionMisaligned),
     Some(InstructionFault),
     Some(IllegalInstruction),
@@ -51,6 +65,65 @@ pub static __EXCEPTIONS: [Option<unsafe extern "C" fn(&TrapFrame)>; 16] = [
     Some(StorePageFault),
 ];
 
+/// Empty entry of the redirection table.
+#[cfg(feature = "exception-redirect")]
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_REDIRECT: AtomicUsize = AtomicUsize::new(0);
+
+/// RAM table with the addresses of the redirected exception handlers.
+/// Entries set to 0 fall back to the [`__EXCEPTIONS`] array.
+#[cfg(feature = "exception-redirect")]
+static REDIRECTS: [AtomicUsize; 16] = [NO_REDIRECT; 16];
+
+/// Redirects the exception with source code `code` to `handler`.
+/// If `handler` is [`None`], the exception handler from [`__EXCEPTIONS`] is restored.
+///
+/// The entry is replaced atomically, so the exception is dispatched either to the previous
+/// or to the new handler, even if it is triggered while redirecting.
+///
+/// # Errors
+///
+/// It returns [`Error::IndexOutOfBounds`] if `code` is not a valid index of [`__EXCEPTIONS`].
+///
+/// # Example
+///
+/// ``` no_run
+/// use riscv::interrupt::Exception;
+/// use riscv_rt::{exceptions, TrapFrame};
+///
+/// unsafe extern "C" fn test_breakpoint(_trap_frame: &TrapFrame) {
+///     // ...
+/// }
+///
+/// exceptions::redirect(Exception::Breakpoint as usize, Some(test_breakpoint)).unwrap();
+/// ```
+#[cfg(feature = "exception-redirect")]
+#[inline]
+pub fn redirect(code: usize, handler: Option<Handler>) -> Result<()> {
+    let entry = REDIRECTS.get(code).ok_or(Error::IndexOutOfBounds {
+        index: code,
+        min: 0,
+        max: REDIRECTS.len() - 1,
+    })?;
+    entry.store(handler.map_or(0, |h| h as usize), Ordering::Release);
+    Ok(())
+}
+
+/// Returns the exception handler for the exception with source code `code`.
+///
+/// Redirected handlers take precedence over the handlers in [`__EXCEPTIONS`].
+#[inline]
+fn handler(code: usize) -> Option<Handler> {
+    #[cfg(feature = "exception-redirect")]
+    if let Some(addr) = REDIRECTS.get(code).map(|e| e.load(Ordering::Acquire)) {
+        if addr != 0 {
+            // SAFETY: non-zero entries are only set by `redirect` from valid exception handlers
+            return Some(unsafe { core::mem::transmute::<usize, Handler>(addr) });
+        }
+    }
+    __EXCEPTIONS.get(code).copied().flatten()
+}
+
 /// It calls the corresponding exception handler depending on the exception source code.
 ///
 /// # Safety
@@ -63,8 +136,8 @@ pub unsafe extern "C" fn _dispatch_exception(trap_frame: &TrapFrame, code: usize
     extern "C" {
         fn ExceptionHandler(trap_frame: &TrapFrame);
     }
-    match __EXCEPTIONS.get(code) {
-        Some(Some(handler)) => handler(trap_frame),
-        _ => ExceptionHandler(trap_frame),
+    match handler(code) {
+        Some(handler) => handler(trap_frame),
+        None => ExceptionHandler(trap_frame),
     }
 }
@@ -0,0 +1,234 @@
+//! Minimal executor for asynchronous `main` functions.
+//!
+//! When the `async-main` feature is enabled, the function marked with [`#[entry(async)]`](crate::entry)
+//! may be an `async fn`. The runtime polls it with [`block_on`] in a low-power loop: after each poll,
+//! the hart sleeps in [`crate::idle`] until a waker is woken (e.g., by an interrupt handler).
+//!
+//! ``` ignore,no_run
+//! #[riscv_rt::entry(async)]
+//! async fn main() {
+//!     // program the CLINT MTIMECMP register of the current hart
+//!     riscv_rt::executor::wait_timer().await;
+//!     // ...
+//! }
+//! ```
+//!
+//! The executor runs a single task. Thus, all the wakers wake the same task, and interrupt handlers
+//! can wake it with [`wake`]. Interrupts are enabled before polling the `main` future for the first time.
+//! If the future completes, the hart sleeps forever (or calls [`crate::exit`] with the `test-entry` feature).
+//!
+//! # Timer and software interrupts
+//!
+//! The runtime provides the handlers of the timer and software interrupts (`MachineTimer` and
+//! `MachineSoft`, or `SupervisorTimer` and `SupervisorSoft` in S-mode). Thus, these interrupts
+//! cannot be handled with [`crate::core_interrupt`]. [`wait_timer`] and [`wait_software`] enable the
+//! interrupt source in `mie` (`sie` in S-mode) and wait for the handler to run. The handler disables
+//! the interrupt source again, so the interrupt does not fire while the pending condition is cleared:
+//!
+//! - Before awaiting [`wait_timer`], programs must set the timer compare register (e.g., CLINT `MTIMECMP`)
+//!   to the deadline. The timer interrupt remains pending until the compare register is updated again.
+//! - Once [`wait_software`] completes, programs must clear the software interrupt (e.g., CLINT `MSIP`).
+//!
+//! # Note
+//!
+//! The executor state is shared by all the harts. Multi-hart targets should only run the executor
+//! on one hart.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+#[cfg(feature = "s-mode")]
+use riscv::{interrupt::supervisor as xinterrupt, register::sie as xie};
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::{interrupt::machine as xinterrupt, register::mie as xie};
+
+/// Set when the task must be polled again.
+static WOKEN: AtomicBool = AtomicBool::new(false);
+
+/// Virtual table of the wakers of the executor. All the wakers wake the same task.
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake_raw, wake_raw, drop);
+
+fn clone(_: *const ()) -> RawWaker {
+    RawWaker::new(core::ptr::null(), &VTABLE)
+}
+
+fn wake_raw(_: *const ()) {
+    wake();
+}
+
+fn drop(_: *const ()) {}
+
+/// Clears `flag`, and returns `true` if it was set.
+///
+/// In targets without atomic read-modify-write instructions (i.e., without the `A` extension),
+/// the flag is checked and cleared with interrupts disabled, so interrupt handlers cannot set it
+/// in between. In these targets, the flag must not be shared with other harts.
+#[inline]
+fn take(flag: &AtomicBool) -> bool {
+    match () {
+        #[cfg(target_has_atomic = "8")]
+        () => flag.swap(false, Ordering::Acquire),
+        #[cfg(not(target_has_atomic = "8"))]
+        () => xinterrupt::free(|| {
+            let set = flag.load(Ordering::Acquire);
+            flag.store(false, Ordering::Relaxed);
+            set
+        }),
+    }
+}
+
+/// Wakes the task of the executor, so it is polled again.
+///
+/// It can be called from interrupt handlers.
+#[inline]
+pub fn wake() {
+    WOKEN.store(true, Ordering::Release);
+}
+
+/// Polls `future` until it completes, sleeping with [`crate::idle`] while it is not woken.
+///
+/// This function must be called with interrupts enabled for the handlers that wake the task to run.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = future;
+    // SAFETY: `future` is shadowed, so it is never moved again
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    // SAFETY: the vtable functions do not access the data pointer
+    let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        while !take(&WOKEN) {
+            crate::idle(|| WOKEN.load(Ordering::Relaxed));
+        }
+    }
+}
+
+/// Runs the `main` future generated by [`#[entry(async)]`](crate::entry).
+///
+/// # Safety
+///
+/// It enables interrupts. Do **NOT** call this function directly.
+#[doc(hidden)]
+pub unsafe fn run<F: Future<Output = ()>>(future: F) -> ! {
+    xinterrupt::enable();
+    block_on(future);
+    #[cfg(feature = "test-entry")]
+    crate::exit(0);
+    #[cfg(not(feature = "test-entry"))]
+    loop {
+        crate::idle(|| false);
+    }
+}
+
+/// Interrupt signal set by an interrupt handler and cleared by the task that waits for it.
+struct Signal(AtomicBool);
+
+impl Signal {
+    /// Sets the signal and wakes the task.
+    #[inline]
+    fn signal(&self) {
+        self.0.store(true, Ordering::Release);
+        wake();
+    }
+
+    /// Waits for the signal, and clears it.
+    #[inline]
+    const fn wait(&'static self) -> Wait {
+        Wait(self)
+    }
+}
+
+/// Future returned by [`Signal::wait`].
+struct Wait(&'static Signal);
+
+impl Future for Wait {
+    type Output = ();
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        match take(&self.0 .0) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+/// Signal of the timer interrupt.
+static TIMER: Signal = Signal(AtomicBool::new(false));
+
+/// Signal of the software interrupt.
+static SOFTWARE: Signal = Signal(AtomicBool::new(false));
+
+/// Waits for the timer interrupt (`MachineTimer`, or `SupervisorTimer` in S-mode).
+///
+/// It enables the timer interrupt source, which is disabled again by its handler.
+/// The timer compare register must be programmed before awaiting this function.
+pub async fn wait_timer() {
+    #[cfg(feature = "s-mode")]
+    // SAFETY: the handler is provided by the runtime
+    unsafe {
+        xie::set_stimer()
+    };
+    #[cfg(not(feature = "s-mode"))]
+    // SAFETY: the handler is provided by the runtime
+    unsafe {
+        xie::set_mtimer()
+    };
+    TIMER.wait().await
+}
+
+/// Waits for the software interrupt (`MachineSoft`, or `SupervisorSoft` in S-mode).
+///
+/// It enables the software interrupt source, which is disabled again by its handler.
+/// The software interrupt must be cleared once this function completes.
+pub async fn wait_software() {
+    #[cfg(feature = "s-mode")]
+    // SAFETY: the handler is provided by the runtime
+    unsafe {
+        xie::set_ssoft()
+    };
+    #[cfg(not(feature = "s-mode"))]
+    // SAFETY: the handler is provided by the runtime
+    unsafe {
+        xie::set_msoft()
+    };
+    SOFTWARE.wait().await
+}
+
+/// Handler of the timer interrupt. It disables the interrupt source and wakes the task.
+#[cfg_attr(not(feature = "s-mode"), export_name = "MachineTimer")]
+#[cfg_attr(feature = "s-mode", export_name = "SupervisorTimer")]
+unsafe extern "C" fn timer_handler() {
+    #[cfg(feature = "s-mode")]
+    xie::clear_stimer();
+    #[cfg(not(feature = "s-mode"))]
+    xie::clear_mtimer();
+    TIMER.signal();
+}
+
+/// Handler of the software interrupt. It disables the interrupt source and wakes the task.
+#[cfg_attr(not(feature = "s-mode"), export_name = "MachineSoft")]
+#[cfg_attr(feature = "s-mode", export_name = "SupervisorSoft")]
+unsafe extern "C" fn software_handler() {
+    #[cfg(feature = "s-mode")]
+    xie::clear_ssoft();
+    #[cfg(not(feature = "s-mode"))]
+    xie::clear_msoft();
+    SOFTWARE.signal();
+}
+
+#[cfg(all(feature = "v-trap", not(feature = "s-mode")))]
+crate::__start_interrupt_trap!(MachineTimer);
+#[cfg(all(feature = "v-trap", not(feature = "s-mode")))]
+crate::__start_interrupt_trap!(MachineSoft);
+#[cfg(all(feature = "v-trap", feature = "s-mode"))]
+crate::__start_interrupt_trap!(SupervisorTimer);
+#[cfg(all(feature = "v-trap", feature = "s-mode"))]
+crate::__start_interrupt_trap!(SupervisorSoft);
@@ -0,0 +1,167 @@
+//! Per-hart context pointed to by `mscratch`/`sscratch`.
+//!
+//! When the `hart-context` feature is enabled, the runtime reserves the `mscratch` register
+//! (`sscratch` in S-mode) to hold a pointer to the [`HartContext`] of the current hart.
+//! RTOSes and other runtime extensions must use this context instead of claiming the scratch
+//! register for themselves, so they can all work on top of the same mechanism.
+//!
+//! The `_start_trap` trap entry point uses the context as follows:
+//!
+//! 1. If no context is installed (i.e., the scratch register is 0), traps are handled on the
+//!    interrupted stack. The boot code clears the scratch register, so traps taken before
+//!    [`HartContext::install`] is called are handled this way.
+//! 2. Otherwise, `_start_trap` increments the nesting depth of the context. If the trap is not nested
+//!    and the context has a trap stack, the trap is handled on the trap stack. Nested traps are handled
+//!    on the interrupted stack (i.e., the trap stack).
+//! 3. Once the trap is handled, `_start_trap` decrements the nesting depth and restores the interrupted stack.
+//!
+//! The scratch register always points to the context, even while a trap is being handled.
+//! Thus, trap handlers can use [`HartContext::current`] to access the context of the current hart.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use riscv_rt::hart_context::HartContext;
+//!
+//! static CONTEXT: HartContext = HartContext::new();
+//! static mut TRAP_STACK: [u128; 256] = [0; 256];
+//!
+//! # fn main() {
+//! unsafe {
+//!     let top = core::ptr::addr_of_mut!(TRAP_STACK) as usize + core::mem::size_of::<[u128; 256]>();
+//!     CONTEXT.set_trap_sp(top);
+//!     CONTEXT.install();
+//! }
+//! # }
+//! ```
+//!
+//! # Note
+//!
+//! In vectored mode (i.e., `v-trap` feature enabled), core interrupts do not go through `_start_trap`.
+//! Thus, this feature is not compatible with the `v-trap` feature. Users that define their own
+//! `_start_trap` must also implement the convention described above.
+
+use core::cell::UnsafeCell;
+
+#[cfg(feature = "s-mode")]
+use riscv::register::sscratch as xscratch;
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::register::mscratch as xscratch;
+
+/// Context of a hart, pointed to by `mscratch`/`sscratch`.
+///
+/// # ABI
+///
+/// The layout of this struct is part of the public ABI of this crate, as it is accessed by `_start_trap`:
+/// fields are stored in declaration order, each taking `XLEN` bits.
+/// Assembly code can use the `*_OFFSET` constants instead of hard-coding field offsets.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct HartContext {
+    /// Top of the stack used to handle non-nested traps, or 0 to use the interrupted stack.
+    trap_sp: UnsafeCell<usize>,
+    /// Number of traps being handled with this context.
+    depth: UnsafeCell<usize>,
+    /// Slot used by `_start_trap` to save `t0` while switching stacks.
+    scratch: UnsafeCell<usize>,
+    /// Pointer to user data (e.g., the RTOS per-hart state).
+    data: UnsafeCell<usize>,
+}
+
+// SAFETY: the context is only modified by its hart, either in `_start_trap` (with interrupts
+// disabled) or via unsafe methods whose callers must guarantee exclusive access.
+unsafe impl Sync for HartContext {}
+
+impl HartContext {
+    /// Offset of the trap stack pointer in bytes.
+    pub const TRAP_SP_OFFSET: usize = 0;
+
+    /// Offset of the nesting depth in bytes.
+    pub const DEPTH_OFFSET: usize = Self::TRAP_SP_OFFSET + core::mem::size_of::<usize>();
+
+    /// Offset of the scratch slot in bytes.
+    pub const SCRATCH_OFFSET: usize = Self::DEPTH_OFFSET + core::mem::size_of::<usize>();
+
+    /// Offset of the user data pointer in bytes.
+    pub const DATA_OFFSET: usize = Self::SCRATCH_OFFSET + core::mem::size_of::<usize>();
+
+    /// Creates a new context without trap stack nor user data.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            trap_sp: UnsafeCell::new(0),
+            depth: UnsafeCell::new(0),
+            scratch: UnsafeCell::new(0),
+            data: UnsafeCell::new(0),
+        }
+    }
+
+    /// Returns the top of the trap stack, or 0 if traps are handled on the interrupted stack.
+    #[inline]
+    pub fn trap_sp(&self) -> usize {
+        unsafe { self.trap_sp.get().read_volatile() }
+    }
+
+    /// Sets the top of the trap stack. If `sp` is 0, traps are handled on the interrupted stack.
+    ///
+    /// # Safety
+    ///
+    /// `sp` must be 16-byte aligned, and the trap stack must be large enough to handle
+    /// all the nested traps. The trap stack must not be modified while a trap is being handled.
+    #[inline]
+    pub unsafe fn set_trap_sp(&self, sp: usize) {
+        self.trap_sp.get().write_volatile(sp);
+    }
+
+    /// Returns the number of traps being handled with this context.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        unsafe { self.depth.get().read_volatile() }
+    }
+
+    /// Returns the user data pointer.
+    #[inline]
+    pub fn data(&self) -> *mut () {
+        unsafe { self.data.get().read_volatile() as *mut () }
+    }
+
+    /// Sets the user data pointer.
+    ///
+    /// # Safety
+    ///
+    /// Trap handlers may read the user data pointer at any time,
+    /// so it must be set before enabling the interrupts that use it.
+    #[inline]
+    pub unsafe fn set_data(&self, data: *mut ()) {
+        self.data.get().write_volatile(data as usize);
+    }
+
+    /// Installs this context as the context of the current hart.
+    ///
+    /// # Safety
+    ///
+    /// - The context must not be installed in any other hart.
+    /// - This function must not be called while a trap is being handled.
+    #[inline]
+    pub unsafe fn install(&'static self) {
+        xscratch::write(self as *const Self as usize);
+    }
+
+    /// Uninstalls the context of the current hart. Traps are then handled on the interrupted stack.
+    ///
+    /// # Safety
+    ///
+    /// This function must not be called while a trap is being handled.
+    #[inline]
+    pub unsafe fn uninstall() {
+        xscratch::write(0);
+    }
+
+    /// Returns the context of the current hart, or [`None`] if no context is installed.
+    #[inline]
+    pub fn current() -> Option<&'static Self> {
+        // SAFETY: `mscratch`/`sscratch` is 0 or points to a context installed with `install`
+        unsafe { (xscratch::read() as *const Self).as_ref() }
+    }
+}
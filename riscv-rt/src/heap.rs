@@ -0,0 +1,177 @@
+//! Initialization of heap allocators.
+//!
+//! With the `heap` feature, [`init_allocator`] hands the heap region of the runtime (i.e.,
+//! [`heap_start`] and `_heap_size` bytes) to a global allocator. The region can only be handed
+//! once: the function disables interrupts while it checks and sets a flag, so subsequent calls
+//! (e.g., from another hart or an interrupt handler) return [`Error::AlreadyInitialized`]
+//! instead of handing the same memory to the allocator twice.
+//!
+//! Allocators implement [`InitFromRegion`] (and [`AddRegion`] to use [`init_allocator_regions`]).
+//! Allocators defined in other crates (e.g., `embedded_alloc::LlffHeap`) cannot implement these
+//! traits. Use [`init_with`] for them instead.
+//!
+//! # Example
+//!
+//! ``` ignore
+//! use embedded_alloc::LlffHeap as Heap;
+//!
+//! #[global_allocator]
+//! static HEAP: Heap = Heap::empty();
+//!
+//! #[riscv_rt::entry]
+//! fn main() -> ! {
+//!     // SAFETY: `HEAP` is not used before it is initialized
+//!     riscv_rt::heap::init_with(|start, size| unsafe { HEAP.init(start, size) }).unwrap();
+//!     // ...
+//! }
+//! ```
+
+use crate::heap_start;
+use core::{
+    alloc::GlobalAlloc,
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use riscv_pac::result::{Error, Result};
+
+#[cfg(feature = "s-mode")]
+use riscv::interrupt::supervisor as xinterrupt;
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::interrupt::machine as xinterrupt;
+
+/// Set once the heap region has been handed to an allocator.
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Allocators that can be initialized with a memory region.
+///
+/// # Safety
+///
+/// After [`InitFromRegion::init`], the allocator must only allocate memory of the given region.
+pub unsafe trait InitFromRegion {
+    /// Initializes the allocator with the memory region of `size` bytes starting at `start`.
+    ///
+    /// # Safety
+    ///
+    /// The region must be valid for reads and writes, and not used by anything else.
+    /// This function is called at most once.
+    unsafe fn init(&self, start: usize, size: usize);
+}
+
+/// Allocators that can manage several memory regions.
+///
+/// # Safety
+///
+/// After [`AddRegion::add_region`], the allocator must only allocate memory of the regions
+/// added so far.
+pub unsafe trait AddRegion: InitFromRegion {
+    /// Adds the memory region of `size` bytes starting at `start` to an initialized allocator.
+    ///
+    /// # Safety
+    ///
+    /// The region must be valid for reads and writes, and not used by anything else.
+    /// It must not overlap with the other regions of the allocator.
+    unsafe fn add_region(&self, start: usize, size: usize);
+}
+
+/// Returns the heap region of the runtime, which starts at [`heap_start`] and spans `_heap_size` bytes.
+#[inline]
+pub fn heap_region() -> Range<usize> {
+    extern "C" {
+        static _heap_size: u8;
+    }
+
+    let start = heap_start() as usize;
+    // `_heap_size` is an absolute symbol, so its address is its value
+    #[allow(unused_unsafe)] // no longer unsafe since rust 1.82.0
+    let size = unsafe { core::ptr::addr_of!(_heap_size) } as usize;
+    start..start + size
+}
+
+/// Sets the initialization flag, and returns `true` if it was already set.
+///
+/// In targets without atomic read-modify-write instructions (i.e., without the `A` extension),
+/// the flag is checked and set with interrupts disabled by the caller. In these targets, the heap
+/// must not be initialized concurrently from multiple harts.
+#[inline]
+fn take_heap() -> bool {
+    match () {
+        #[cfg(target_has_atomic = "8")]
+        () => INITIALIZED.swap(true, Ordering::AcqRel),
+        #[cfg(not(target_has_atomic = "8"))]
+        () => {
+            let initialized = INITIALIZED.load(Ordering::Acquire);
+            INITIALIZED.store(true, Ordering::Release);
+            initialized
+        }
+    }
+}
+
+/// Calls `f` with the start address and the size (in bytes) of the heap region, only once per program.
+///
+/// `f` runs with interrupts disabled in the current hart.
+///
+/// # Errors
+///
+/// - [`Error::EmptyRegion`] if `_heap_size` is 0. `f` is not called.
+/// - [`Error::AlreadyInitialized`] if the heap region was already handed to an allocator.
+pub fn init_with<F: FnOnce(usize, usize)>(f: F) -> Result<()> {
+    let region = heap_region();
+    if region.is_empty() {
+        return Err(Error::EmptyRegion);
+    }
+    xinterrupt::free(|| match take_heap() {
+        true => Err(Error::AlreadyInitialized),
+        false => {
+            f(region.start, region.len());
+            Ok(())
+        }
+    })
+}
+
+/// Initializes `allocator` with the heap region, only once per program.
+///
+/// # Errors
+///
+/// See [`init_with`].
+#[inline]
+pub fn init_allocator<A: GlobalAlloc + InitFromRegion>(allocator: &A) -> Result<()> {
+    // SAFETY: the heap region is only used by the allocator, and it is handed only once
+    init_with(|start, size| unsafe { allocator.init(start, size) })
+}
+
+/// Initializes `allocator` with the heap region and the additional memory `regions`, only once per program.
+///
+/// Empty regions are skipped. If the heap region is empty, the allocator is initialized with the
+/// first non-empty region of `regions`.
+///
+/// # Errors
+///
+/// - [`Error::EmptyRegion`] if the heap region and all the `regions` are empty.
+/// - [`Error::AlreadyInitialized`] if the heap region was already handed to an allocator.
+///
+/// # Safety
+///
+/// `regions` must be valid for reads and writes, not be used by anything else, and not overlap
+/// with each other nor with the heap region.
+pub unsafe fn init_allocator_regions<A: GlobalAlloc + AddRegion>(
+    allocator: &A,
+    regions: &[Range<usize>],
+) -> Result<()> {
+    let heap = heap_region();
+    let mut regions = core::iter::once(&heap)
+        .chain(regions)
+        .filter(|region| !region.is_empty());
+    let first = regions.next().ok_or(Error::EmptyRegion)?;
+    xinterrupt::free(|| match take_heap() {
+        true => Err(Error::AlreadyInitialized),
+        false => {
+            allocator.init(first.start, first.len());
+            for region in regions {
+                allocator.add_region(region.start, region.len());
+            }
+            Ok(())
+        }
+    })
+}
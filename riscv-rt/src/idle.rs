@@ -0,0 +1,75 @@
+//! Low-power idle loop.
+//!
+//! Putting a hart to sleep until there is work to do is prone to race conditions: if an interrupt
+//! handler pushes new work after the application checks its work queue but before it executes `wfi`,
+//! the hart may sleep with pending work. [`idle`] implements the canonical race-free sequence:
+//!
+//! 1. Disable interrupts (`mstatus.MIE` or `sstatus.SIE` in S-mode).
+//! 2. Check if there is pending work. If so, skip the next step.
+//! 3. Call `__idle`, which executes `wfi` by default. Pending interrupts wake up the hart even if
+//!    they are globally disabled, so an interrupt that arrives after step 1 is not lost.
+//! 4. Restore the previous interrupt state. If interrupts were enabled, pending interrupts are handled now.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::sync::atomic::{AtomicBool, Ordering};
+//!
+//! static WORK: AtomicBool = AtomicBool::new(false);
+//!
+//! # fn main() {
+//! loop {
+//!     if WORK.swap(false, Ordering::Relaxed) {
+//!         // do the work pushed by interrupt handlers
+//!     }
+//!     riscv_rt::idle(|| WORK.load(Ordering::Relaxed));
+//! }
+//! # }
+//! ```
+//!
+//! # Custom idle hook
+//!
+//! Users can override `__idle` to enter deeper low-power states (e.g., by configuring the power
+//! controller of the target before `wfi`). It is called with interrupts disabled, and it must not
+//! enable them:
+//!
+//! ```no_run
+//! #[export_name = "__idle"]
+//! fn my_idle() {
+//!     // configure the power controller
+//!     riscv::asm::wfi();
+//! }
+//! ```
+
+#[cfg(feature = "s-mode")]
+use riscv::{interrupt::supervisor as xinterrupt, register::sstatus as xstatus};
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::{interrupt::machine as xinterrupt, register::mstatus as xstatus};
+
+/// Puts the current hart to sleep if `has_work` returns `false`.
+///
+/// `has_work` is called with interrupts disabled, so interrupt handlers cannot push work between
+/// the check and the sleep. The previous interrupt state is restored before returning. Thus, this
+/// function must be called with interrupts enabled for the handlers of the wake-up interrupt to run.
+#[inline]
+pub fn idle<F: FnOnce() -> bool>(has_work: F) {
+    extern "C" {
+        fn __idle();
+    }
+
+    #[cfg(feature = "s-mode")]
+    let enabled = xstatus::read().sie();
+    #[cfg(not(feature = "s-mode"))]
+    let enabled = xstatus::read().mie();
+
+    xinterrupt::disable();
+    if !has_work() {
+        // SAFETY: `__idle` is provided by the runtime or overridden by the user
+        unsafe { __idle() };
+    }
+    if enabled {
+        // SAFETY: interrupts were enabled before calling this function
+        unsafe { xinterrupt::enable() };
+    }
+}
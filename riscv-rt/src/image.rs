@@ -0,0 +1,84 @@
+//! Hand-off between images (e.g., from a bootloader to an application).
+//!
+//! A bootloader built on top of `riscv-rt` can start another image with [`jump_to_image`].
+//! The application image is usually built with the `app-image` feature, so its entry point is
+//! `_app_entry` instead of `_start`, and it is linked at its own address with `_stext` or a
+//! dedicated `REGION_TEXT` in its `memory.x` file.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! /// Address of `_app_entry` in the application image (i.e., its `_stext`).
+//! const APP_ENTRY: usize = 0x2001_0000;
+//!
+//! # fn main() {
+//! // SAFETY: a valid application image is placed at `APP_ENTRY`
+//! unsafe { riscv_rt::image::jump_to_image(APP_ENTRY, 0, 0, 0) };
+//! # }
+//! ```
+
+/// Sanitizes the state of the current hart and jumps to the entry point of another image.
+///
+/// Before jumping to `address`, this function:
+///
+/// 1. Disables interrupts (`mstatus.MIE`), and clears `mie` and `mip`.
+/// 2. Resets `mtvec` to 0, so the new image does not inherit the trap handlers of the current one.
+/// 3. Clears any pending load reservation (only in targets with the `A` extension).
+/// 4. Synchronizes the data and instruction streams with `fence` and `fence.i`, as the image
+///    may have been copied to memory by the current program.
+///
+/// Then, it jumps to `address` with `a0`, `a1`, and `a2` in the corresponding registers.
+/// In S-mode, the equivalent supervisor registers (`sstatus`, `sie`, `sip`, and `stvec`) are used.
+///
+/// # Safety
+///
+/// - `address` must be the entry point of a valid image for the current hart.
+/// - The new image must not rely on any state of the current program (e.g., its stack).
+/// - In multi-hart targets, the other harts must be parked or handed off to the new image.
+#[inline]
+pub unsafe fn jump_to_image(address: usize, a0: usize, a1: usize, a2: usize) -> ! {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            #[cfg(not(feature = "s-mode"))]
+            core::arch::asm!(
+                "csrci mstatus, 0x8",
+                "csrw mie, zero",
+                "csrw mip, zero",
+                "csrw mtvec, zero",
+                options(nostack)
+            );
+            #[cfg(feature = "s-mode")]
+            core::arch::asm!(
+                "csrci sstatus, 0x2",
+                "csrw sie, zero",
+                "csrw sip, zero",
+                "csrw stvec, zero",
+                options(nostack)
+            );
+            // Store back the same value, so the reservation is cleared without side effects
+            #[cfg(target_feature = "a")]
+            core::arch::asm!(
+                "lw {tmp}, 0(sp)",
+                "sc.w zero, {tmp}, (sp)",
+                tmp = out(reg) _,
+                options(nostack)
+            );
+            core::arch::asm!(
+                "fence",
+                "fence.i",
+                "jr {address}",
+                address = in(reg) address,
+                in("a0") a0,
+                in("a1") a1,
+                in("a2") a2,
+                options(noreturn)
+            );
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => {
+            let _ = (address, a0, a1, a2);
+            unimplemented!()
+        }
+    }
+}
@@ -7,6 +7,9 @@
 //!
 //! In vectored mode (i.e., `v-trap` feature enabled), interrupt dispatching is handled by hardware.
 //! To support this mode, we provide inline assembly code that defines the interrupt vector table.
+//! The vector table is placed in the `.trap.vector` section, which is allocated next to the trap code
+//! (or in the `REGION_VECTORS` memory region with the `vectors-region` feature). Additional vector
+//! tables (e.g., `_vector_table_s`) must be placed in `.trap.vector.*` sections to be allocated with it.
 //!
 //! # Note
 //!
@@ -70,13 +73,15 @@ pub unsafe extern "C" fn _dispatch_core_interrupt(code: usize) {
     }
 }
 
-// In vectored mode, we also must provide a vector table
+// In vectored mode, we also must provide a vector table.
+// The table is placed in the `.trap.vector` section, which the linker script
+// allocates next to the trap code (or in `REGION_VECTORS` with the `vectors-region` feature).
 #[cfg(all(
     any(target_arch = "riscv32", target_arch = "riscv64"),
     feature = "v-trap"
 ))]
 core::arch::global_asm!(
-    r#" .section .trap, "ax"
+    r#" .section .trap.vector, "ax"
         .weak _vector_table
         .type _vector_table, @function
         
@@ -0,0 +1,118 @@
+//! Interrupt-safe text output routed to a global writer.
+//!
+//! When the `io` feature is enabled, the [`rprint!`](crate::rprint) and [`rprintln!`](crate::rprintln)
+//! macros format their arguments and send the resulting text to a global writer. Thus, examples and
+//! libraries can log messages without knowing the board (e.g., a UART or a debug channel) or the
+//! privilege mode they run on. The global writer is set with [`set_global_writer`]:
+//!
+//! ``` ignore,no_run
+//! use riscv_rt::io::{set_global_writer, Write};
+//!
+//! struct Uart;
+//!
+//! impl Write for Uart {
+//!     fn write_str(&self, s: &str) {
+//!         // send the bytes of `s` through the UART
+//!     }
+//! }
+//!
+//! static UART: Uart = Uart;
+//!
+//! set_global_writer(&UART);
+//! riscv_rt::rprintln!("Hello, {}!", "world");
+//! ```
+//!
+//! Messages are written inside a critical section (see the [`critical_section`] crate),
+//! so messages printed by trap handlers are never interleaved with other messages.
+//! Consequently, the target must provide a `critical-section` implementation (e.g., by enabling
+//! the `critical-section-single-hart` feature of the `riscv` crate), and writers should be fast.
+//!
+//! If no global writer is set, messages are sent to the host via semihosting when the `semihosting`
+//! feature is enabled. Otherwise, messages are discarded.
+
+use core::{cell::Cell, fmt};
+use critical_section::Mutex;
+
+/// Destination of the text printed with [`rprint!`](crate::rprint) and [`rprintln!`](crate::rprintln).
+///
+/// Writers receive a shared reference, as they may be called from any trap handler.
+/// Calls to [`Write::write_str`] are always performed inside a critical section.
+pub trait Write {
+    /// Writes a string slice. Errors must be handled (or ignored) by the writer.
+    fn write_str(&self, s: &str);
+}
+
+/// Global writer. If `None`, the default writer is used.
+static WRITER: Mutex<Cell<Option<&'static (dyn Write + Sync)>>> = Mutex::new(Cell::new(None));
+
+/// Sets the global writer used by [`rprint!`](crate::rprint) and [`rprintln!`](crate::rprintln).
+#[inline]
+pub fn set_global_writer(writer: &'static (dyn Write + Sync)) {
+    critical_section::with(|cs| WRITER.borrow(cs).set(Some(writer)));
+}
+
+/// Removes the global writer, so the default writer is used again.
+#[inline]
+pub fn clear_global_writer() {
+    critical_section::with(|cs| WRITER.borrow(cs).set(None));
+}
+
+/// Adapter to format arguments into a [`Write`] writer.
+struct Adapter<'a>(&'a (dyn Write + Sync));
+
+impl fmt::Write for Adapter<'_> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s);
+        Ok(())
+    }
+}
+
+/// Writes formatted arguments to the default writer.
+#[cfg(feature = "semihosting")]
+#[inline]
+fn default_write(args: fmt::Arguments) {
+    if let Ok(mut hstdout) = riscv_semihosting::hio::hstdout() {
+        fmt::Write::write_fmt(&mut hstdout, args).ok();
+    }
+}
+
+/// Writes formatted arguments to the default writer.
+#[cfg(not(feature = "semihosting"))]
+#[inline]
+fn default_write(_args: fmt::Arguments) {}
+
+/// Writes formatted arguments to the global writer. Do **NOT** call this function directly.
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    critical_section::with(|cs| match WRITER.borrow(cs).get() {
+        Some(writer) => {
+            fmt::Write::write_fmt(&mut Adapter(writer), args).ok();
+        }
+        None => default_write(args),
+    });
+}
+
+/// Prints to the global writer of [`io`](crate::io).
+///
+/// Equivalent to the [`rprintln!`](crate::rprintln) macro except that a newline is not printed at
+/// the end of the message. It is interrupt-safe, so it can be used in trap handlers.
+#[macro_export]
+macro_rules! rprint {
+    ($($arg:tt)*) => {
+        $crate::io::_print(core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to the global writer of [`io`](crate::io), with a newline.
+///
+/// It uses the same syntax as `std::println!`, and it is interrupt-safe, so it can be used in trap handlers.
+#[macro_export]
+macro_rules! rprintln {
+    () => {
+        $crate::io::_print(core::format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::io::_print(core::format_args!("{}\n", core::format_args!($($arg)*)))
+    };
+}
@@ -0,0 +1,140 @@
+//! Interrupt handler duration watchdog for debugging purposes.
+//!
+//! When the `isr-budget` feature is enabled, the runtime samples the cycle counter (`mcycle` in M-mode,
+//! `cycle` in S-mode) right before calling a core interrupt handler and right after it returns.
+//! If the handler of an interrupt runs for more cycles than the budget of the interrupt,
+//! the runtime calls `__isr_budget_exceeded` with the interrupt code and the elapsed cycles.
+//! This helps tracking down sporadic latency regressions in the field.
+//!
+//! Budgets are set per interrupt code with [`set_budget`]. By default, all the budgets are 0,
+//! which means that the interrupt is not checked. Budgets are shared by all the harts.
+//! Exceptions are never checked.
+//!
+//! In direct mode, handlers are measured by [`crate::start_trap_rust`]. In vectored mode
+//! (i.e., `v-trap` feature enabled), they are measured by the `_continue_interrupt_trap`
+//! assembly routine. Thus, the measured cycles include the dispatch of the interrupt.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use riscv::interrupt::Interrupt;
+//!
+//! riscv_rt::isr_budget::set_budget(Interrupt::MachineTimer, 10_000).unwrap();
+//! ```
+//!
+//! # Custom budget hook
+//!
+//! By default, `__isr_budget_exceeded` does nothing. Users can override it to report the event
+//! (e.g., by logging it or by incrementing a counter). It is called from the trap handler, right
+//! after the offending interrupt handler returns:
+//!
+//! ```no_run
+//! #[export_name = "__isr_budget_exceeded"]
+//! fn isr_budget_exceeded(code: usize, cycles: usize) {
+//!     // report the interrupt code and the elapsed cycles
+//! }
+//! ```
+//!
+//! # Note
+//!
+//! Only the lower `XLEN` bits of the cycle counter are sampled. Thus, handlers that run for more
+//! than `2^XLEN` cycles are not reliably detected.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use riscv_pac::{
+    result::{Error, Result},
+    CoreInterruptNumber,
+};
+
+#[cfg(feature = "s-mode")]
+use riscv::register::cycle as xcycle;
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::register::mcycle as xcycle;
+
+/// Number of interrupt codes with a configurable budget.
+pub const BUDGETS_LEN: usize = 64;
+
+/// Budgets (in cycles) of the interrupt codes. A budget of 0 disables the check.
+static BUDGETS: [AtomicUsize; BUDGETS_LEN] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+    [ZERO; BUDGETS_LEN]
+};
+
+/// Returns the index of the budget of an interrupt.
+#[inline]
+fn index<I: CoreInterruptNumber>(interrupt: I) -> Result<usize> {
+    let index = interrupt.number();
+    if index < BUDGETS_LEN {
+        Ok(index)
+    } else {
+        Err(Error::IndexOutOfBounds {
+            index,
+            min: 0,
+            max: BUDGETS_LEN - 1,
+        })
+    }
+}
+
+/// Sets the budget (in cycles) of an interrupt. A budget of 0 disables the check.
+///
+/// # Errors
+///
+/// If the interrupt number is not lower than [`BUDGETS_LEN`], it returns [`Error::IndexOutOfBounds`].
+#[inline]
+pub fn set_budget<I: CoreInterruptNumber>(interrupt: I, cycles: usize) -> Result<()> {
+    BUDGETS[index(interrupt)?].store(cycles, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Returns the budget (in cycles) of an interrupt. A budget of 0 means that the interrupt is not checked.
+///
+/// # Errors
+///
+/// If the interrupt number is not lower than [`BUDGETS_LEN`], it returns [`Error::IndexOutOfBounds`].
+#[inline]
+pub fn budget<I: CoreInterruptNumber>(interrupt: I) -> Result<usize> {
+    Ok(BUDGETS[index(interrupt)?].load(Ordering::Relaxed))
+}
+
+/// Returns the lower `XLEN` bits of the cycle counter.
+#[inline]
+pub(crate) fn cycles() -> usize {
+    xcycle::read()
+}
+
+/// Checks the duration of an interrupt handler.
+///
+/// `cause` is the value of `mcause`/`scause` when the handler was called, and `start` is the value of
+/// the cycle counter right before calling it. If the trap is an interrupt and the handler ran for more
+/// cycles than its budget, it calls `__isr_budget_exceeded` with the interrupt code and the elapsed cycles.
+///
+/// # Safety
+///
+/// This function must be called only from trap entry routines, right after the interrupt handler returns.
+/// Do **NOT** call this function directly.
+#[doc(hidden)]
+#[cfg_attr(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    link_section = ".trap.rust"
+)]
+#[no_mangle]
+pub unsafe extern "C" fn _isr_budget_check(cause: usize, start: usize) {
+    extern "Rust" {
+        fn __isr_budget_exceeded(code: usize, cycles: usize);
+    }
+
+    let elapsed = cycles().wrapping_sub(start);
+    let interrupt_bit = 1 << (usize::BITS - 1);
+    if cause & interrupt_bit == 0 {
+        return; // exceptions are not checked
+    }
+    let code = cause & !interrupt_bit;
+    if let Some(budget) = BUDGETS.get(code) {
+        let budget = budget.load(Ordering::Relaxed);
+        if budget != 0 && elapsed > budget {
+            __isr_budget_exceeded(code, elapsed);
+        }
+    }
+}
@@ -24,11 +24,13 @@
 //!
 //! - [`#[entry]`][attr-entry] to declare the entry point of the program
 //! - [`#[pre_init]`][attr-pre-init]to run code *before* `static` variables are initialized
+//! - [`#[post_init]`][attr-post-init] to run code *after* `static` variables are initialized, right before `main`
 //! - [`#[exception]`][attr-exception] to override an exception handler.
 //! - [`#[core_interrupt]`][attr-core-interrupt] to override a core interrupt handler.
 //! - [`#[external_interrupt]`][attr-external-interrupt] to override an external interrupt handler.
 //!
-//! If not overridden, all exception and interrupt handlers default to an infinite loop.
+//! If not overridden, all exception and interrupt handlers call [`__abort`](#__abort) with an [`AbortReason`]
+//! and then jump to `abort`, which is an infinite loop.
 //!
 //! The documentation for these attributes can be found in the [Attribute Macros](#attributes)
 //! section.
@@ -56,10 +58,51 @@
 //! - `REGION_BSS` - for `.bss` section
 //! - `REGION_HEAP` - for the heap area
 //! - `REGION_STACK` - for hart stacks
+//! - `REGION_VECTORS` - for interrupt vector tables (only required if the `vectors-region` feature is enabled)
+//! - `REGION_TRAP` - for the `.trap` section (only required if the `trap-region` feature is enabled)
+//! - `REGION_DATA2` - for the `.data2` section (only required if the `data2-region` feature is enabled)
+//! - `REGION_BSS2` - for the `.bss2` section (only required if the `data2-region` feature is enabled)
 //!
 //! These aliases must be mapped to a valid `MEMORY` region. Usually, `REGION_TEXT` and
 //! `REGION_RODATA` are mapped to the flash memory, while `REGION_DATA`, `REGION_BSS`,
-//! `REGION_HEAP`, and `REGION_STACK` are mapped to the RAM.
+//! `REGION_HEAP`, and `REGION_STACK` are mapped to the RAM. `REGION_VECTORS` is usually
+//! mapped to a fast memory (e.g., ITCM) in latency-critical systems.
+//!
+//! In 64-bit targets, the memory layout must also be reachable with the code model of the target.
+//! With the `medlow` code model (`-C code-model=small`), all the regions must be placed below
+//! `0x8000_0000`. With the `medany` code model (`-C code-model=medium`, default in Rust targets),
+//! all the regions must be placed in a 2 GiB address range. These constraints are checked at
+//! link time, so layouts that exceed the reach of the code model fail with a descriptive error
+//! instead of relocation errors (e.g., `R_RISCV_HI20 out of range`). Other code models (e.g., `large`)
+//! are not checked.
+//!
+//! ### RAM-only targets
+//!
+//! With `link.x`, the initial values of `.data` are stored in `REGION_RODATA` and copied to
+//! `REGION_DATA` at startup, which assumes that the program is loaded in non-volatile memory.
+//! Targets with no flash memory (e.g., soft cores with RAM preloaded by a bootloader or a debugger)
+//! should link with `link-ram.x` instead. This linker script is identical to `link.x`, but `.data`
+//! is loaded in place (i.e., its LMA and VMA are equal), so the copy is skipped at startup.
+//! Usually, all the region aliases are mapped to the same RAM region, and `_stext` defaults
+//! to the start of the RAM:
+//!
+//! ``` text
+//! /* memory.x */
+//! MEMORY
+//! {
+//!   RAM : ORIGIN = 0x40000000, LENGTH = 64K
+//! }
+//!
+//! REGION_ALIAS("REGION_TEXT", RAM);
+//! REGION_ALIAS("REGION_RODATA", RAM);
+//! REGION_ALIAS("REGION_DATA", RAM);
+//! REGION_ALIAS("REGION_BSS", RAM);
+//! REGION_ALIAS("REGION_HEAP", RAM);
+//! REGION_ALIAS("REGION_STACK", RAM);
+//! ```
+//!
+//! Then, link with `-C link-arg=-Tmemory.x -C link-arg=-Tlink-ram.x`, or add `INCLUDE link-ram.x`
+//! at the end of `memory.x`. The `qemu_ram` example shows a RAM-only program for the QEMU `virt` machine.
 //!
 //! ### `_stext`
 //!
@@ -67,6 +110,7 @@
 //! to override the loading address of the firmware (for example, in case of bootloader present).
 //!
 //! If omitted this symbol value will default to `ORIGIN(REGION_TEXT)`.
+//! The resulting `.text` section must be placed inside the `REGION_TEXT` region.
 //!
 //! ### `_heap_size`
 //!
@@ -80,7 +124,7 @@
 //! ### `_max_hart_id`
 //!
 //! This symbol defines the maximum hart id supported. All harts with id
-//! greater than `_max_hart_id` will be redirected to `abort()`.
+//! greater than `_max_hart_id` will be redirected to `abort()` with [`AbortReason::HartIdTooLarge`] in `a0`.
 //!
 //! This symbol is supposed to be redefined in platform support crates for
 //! multi-core targets.
@@ -93,6 +137,20 @@
 //!
 //! If omitted this symbol value will default to 2K.
 //!
+//! ### `_rnmi_stack_size`
+//!
+//! This symbol defines the size of the RNMI stack of *one* hart when the `rnmi` feature is enabled.
+//! The RNMI stack is carved from the top of the hart stack area (see [`rnmi`](#rnmi)).
+//!
+//! If omitted this symbol value will default to 512 bytes.
+//!
+//! ### `_stack_paint_pattern`
+//!
+//! This symbol defines the 32-bit pattern used to fill the hart stacks at startup when the
+//! `stack-paint` feature is enabled (see [`stack-paint`](#stack-paint)).
+//!
+//! If omitted this symbol value will default to `0xCCCCCCCC`.
+//!
 //! ### `_stack_start`
 //!
 //! This symbol provides the address at which the call stack will be allocated.
@@ -105,6 +163,22 @@
 //!
 //! If omitted this symbol value will default to `ORIGIN(REGION_STACK) + LENGTH(REGION_STACK)`.
 //!
+//! ### `_trap_align`
+//!
+//! This symbol defines the alignment (in bytes) of the trap code, and thus of `_start_trap`.
+//! It must be a power of 2 greater than or equal to 4. This is useful for targets that require
+//! a stricter alignment of the trap vector (e.g., CLIC targets or vendor boot ROMs).
+//!
+//! If omitted this symbol value will default to 4.
+//!
+//! ### `_strap`
+//!
+//! This symbol provides the start address of the `.trap` section. It is only used
+//! when the `trap-region` feature is enabled, and it must be `_trap_align`-byte aligned.
+//!
+//! If omitted this symbol value will default to `ORIGIN(REGION_TRAP)`.
+//! The resulting `.trap` section must be placed inside the `REGION_TRAP` region.
+//!
 //! ### Example of a fully featured `memory.x` file
 //!
 //! Next, we present a `memory.x` file that includes all the symbols
@@ -283,6 +357,24 @@
 //! }
 //! ```
 //!
+//! With the `heap` feature, `riscv_rt::heap::init_allocator` (or `riscv_rt::heap::init_with` for
+//! allocators of other crates) does the same, and it returns an error instead of handing the heap
+//! region to an allocator twice:
+//!
+//! ``` ignore
+//! use embedded_alloc::LlffHeap as Heap;
+//!
+//! #[global_allocator]
+//! static HEAP: Heap = Heap::empty();
+//!
+//! #[riscv_rt::entry]
+//! fn main() -> ! {
+//!     // SAFETY: `HEAP` is not used before it is initialized
+//!     riscv_rt::heap::init_with(|start, size| unsafe { HEAP.init(start, size) }).unwrap();
+//!     // ...
+//! }
+//! ```
+//!
 //! # Additional weak functions
 //!
 //! This crate uses additional functions to control the behavior of the runtime.
@@ -332,10 +424,63 @@
 //!
 //! Default implementation sets the trap vector to `_start_trap` in direct mode.
 //! If the `v-trap` feature is enabled, the trap vector is set to `_vector_table`
-//! in vectored mode. Users can override this function by defining their own `_setup_interrupts`.
+//! in vectored mode. Additionally, in M-mode, if a secondary `_vector_table_s` symbol is
+//! defined, it is set as the S-mode trap vector (`stvec`) in vectored mode.
+//! Users can override this function by defining their own `_setup_interrupts`.
+//! With the `reinit-interrupts` feature, this function can be called again after boot. Thus,
+//! custom implementations must not assume that they run only once.
+//!
+//! ## `__idle`
+//!
+//! This function is called by [`idle`] with interrupts disabled when there is no pending work.
+//!
+//! Default implementation executes `wfi`. Users can override this function by defining their own
+//! `__idle` (e.g., to enter deeper low-power states). Check the `idle` module documentation for more details.
+//!
+//! ## `_riscv_hartid`
+//!
+//! This function is only provided with the `s-mode` feature. It returns the ID of the current hart,
+//! and it is called by `riscv::hartid` when the `s-mode` feature of the `riscv` crate is enabled.
+//!
+//! At boot, the runtime keeps the hart ID passed as parameter by the SBI implementation in the `tp`
+//! register, and the default implementation returns it. Thus, code that runs on top of the runtime
+//! must not modify `tp` (e.g., context switchers must not restore the `tp` of a task in another hart).
+//! With the `u-boot` feature, `a0` holds `argc`, so `tp` is 0. Users can override this function by
+//! defining their own `_riscv_hartid` (e.g., to read the hart ID from a per-hart context).
+//!
+//! ## `__isr_budget_exceeded`
+//!
+//! This function is called when the `isr-budget` feature is enabled and a core interrupt handler
+//! runs for more cycles than its budget. It receives the interrupt code and the elapsed cycles.
+//!
+//! Default implementation does nothing. Users can override this function by defining their own
+//! `__isr_budget_exceeded`. Check the `isr_budget` module documentation for more details.
+//!
+//! ## `__abort`
+//!
+//! This function is called with an [`AbortReason`] when the runtime cannot continue (e.g., the default
+//! `ExceptionHandler` is called). Once it returns, the runtime jumps to `abort`, which is an infinite loop.
+//!
+//! Default implementation does nothing. Users can override this function by defining their own
+//! `__abort` (e.g., to log the reason or to reset the device). Check the `abort` module documentation
+//! for more details.
 //!
 //! # Attributes
 //!
+//! The `cfg` attributes placed below the [`exception`], [`core_interrupt`], and [`external_interrupt`]
+//! attributes also apply to the code generated for the handler (e.g., trap handlers in vectored mode).
+//! Thus, firmware for several devices can define handlers per device with the same interrupt name:
+//!
+//! ``` ignore,no_run
+//! #[riscv_rt::external_interrupt(board_a::Interrupt::UART0)]
+//! #[cfg(feature = "board-a")]
+//! fn uart0() {}
+//!
+//! #[riscv_rt::external_interrupt(board_b::Interrupt::UART0)]
+//! #[cfg(feature = "board-b")]
+//! fn uart0() {}
+//! ```
+//!
 //! ## Core exception handlers
 //!
 //! This functions are called when corresponding exception occurs.
@@ -425,6 +570,12 @@
 //! external interrupt source for the given handler.
 //! Note that external interrupts are target-specific and may not be available on all platforms.
 //!
+//! In vectored mode, the optional `fast` argument generates a proper trap handler for the interrupt,
+//! as the [`core_interrupt`] attribute does. For example, `#[external_interrupt(Interrupt::GPIO0, fast)]`
+//! generates a `_start_GPIO0_trap` trap handler. Targets whose interrupt controller directly vectors
+//! external interrupts (e.g., with a hardware-assisted claim) can jump to this trap handler, skipping
+//! the generic two-level dispatch. Note that the handler must claim and complete the interrupt if required.
+//!
 //! If interrupt handler is not explicitly defined, `DefaultHandler` is called.
 //!
 //! ### `DefaultHandler`
@@ -452,6 +603,25 @@
 //!
 //! Default implementation of this function stucks in a busy-loop.
 //!
+//! # Vector report
+//!
+//! Handlers whose export name contains a typo silently fall back to `ExceptionHandler` or
+//! `DefaultHandler`. To detect this, set the `RISCV_RT_VECTOR_REPORT` environment variable
+//! when building your application. The linker script then defines a `__riscv_rt_default_<handler>`
+//! symbol for each core exception and interrupt handler. Its value is 1 if the handler resolved
+//! to the default handler, and 0 if it resolved to a user handler. You can inspect these symbols
+//! with tools like `nm`:
+//!
+//! ``` text
+//! $ RISCV_RT_VECTOR_REPORT=1 cargo build
+//! $ nm target/riscv32imac-unknown-none-elf/debug/app | grep __riscv_rt_default_
+//! 00000001 A __riscv_rt_default_Breakpoint
+//! 00000000 A __riscv_rt_default_MachineTimer
+//! ...
+//! ```
+//!
+//! Note that external interrupt handlers provided by PACs (e.g., in `device.x`) are not included.
+//!
 //! # Cargo Features
 //!
 //! ## `single-hart`
@@ -479,6 +649,8 @@
 //! While most registers/instructions have variants for
 //! both `mcause` and `scause`, the `mhartid` hardware thread register is not available in supervisor
 //! mode. Instead, the hartid is passed as parameter by a bootstrapping firmware (i.e., SBI).
+//! The runtime keeps it in the `tp` register, so libraries can get it with `riscv::hartid` (with the
+//! `s-mode` feature of the `riscv` crate) instead of threading it from `main`. See [`_riscv_hartid`](#_riscv_hartid).
 //!
 //! Use case: QEMU supports [OpenSBI](https://github.com/riscv-software-src/opensbi) as default firmware.
 //! Using the SBI requires riscv-rt to be run in supervisor mode instead of machine mode.
@@ -508,6 +680,318 @@
 //! When the vectored trap feature is enabled, the trap vector is set to `_vector_table` in vectored mode.
 //! This table is a list of `j _start_INTERRUPT_trap` instructions, where `INTERRUPT` is the name of the core interrupt.
 //!
+//! ### Vector table placement
+//!
+//! Vector tables are composed of `j` instructions, which can only reach addresses within ±1 MiB.
+//! Thus, vector tables are placed next to the trap code by default (i.e., in `REGION_TEXT`).
+//!
+//! With the `vectors-region` feature (which implies `v-trap`), vector tables are placed in the
+//! `.vector_table` output section, which is allocated in the `REGION_VECTORS` memory region.
+//! This allows placing the vector tables in a different memory region (e.g., ITCM). In this case,
+//! you must define this region alias in your `memory.x` file:
+//!
+//! ``` text
+//! REGION_ALIAS("REGION_VECTORS", ITCM);
+//! ```
+//!
+//! `REGION_VECTORS` must be close enough to the trap handlers in `REGION_TEXT`. This is checked
+//! at link time. Also, if `REGION_VECTORS` is not a persistent memory, it must be loaded before the
+//! `_setup_interrupts` function is called (e.g., by a bootloader).
+//!
+//! ### Multiple vector tables
+//!
+//! In M-mode, targets that also run code in S-mode can provide a secondary vector table
+//! named `_vector_table_s`. Place it in a `.trap.vector.*` section (e.g., `.trap.vector.s`) so it
+//! is allocated next to `_vector_table`. If this symbol is defined, the default `_setup_interrupts`
+//! implementation sets `stvec` to `_vector_table_s` in vectored mode.
+//!
+//! ## `full-regs`
+//!
+//! The full registers feature (`full-regs`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["full-regs"] }
+//! ```
+//!
+//! When the `full-regs` feature is enabled, the trap handler also stores `gp`, `tp`, and the
+//! stack pointer before the trap in the [`TrapFrame`]. This is useful for RTOS context switchers
+//! and stack overflow diagnostics in exception handlers. Note that the trap frame grows accordingly.
+//! Check the [`TrapFrame`] documentation for more details about its layout.
+//!
+//! ## `compact-trap`
+//!
+//! The compact trap feature (`compact-trap`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["compact-trap"] }
+//! ```
+//!
+//! By default, every trap entry point (`_start_trap` and, in vectored mode, `_continue_interrupt_trap`)
+//! inlines the stores and loads of the [`TrapFrame`]. When the `compact-trap` feature is enabled, these
+//! entry points only store `ra` (and `a0` in `_start_trap`) and call the shared `_save_trap_frame`
+//! and `_restore_trap_frame` subroutines. This trades a few cycles per trap for code size, which is
+//! useful in targets with small flash memories. The layout of the [`TrapFrame`] does not change.
+//!
+//! Note that the `_start_<interrupt>_trap` functions of vectored mode already jump to
+//! `_continue_interrupt_trap` after storing `a0`. The `_start_rnmi_trap` entry point of the
+//! `rnmi` feature always inlines the trap frame stores and loads.
+//!
+//! ## `exception-redirect`
+//!
+//! The exception redirection feature (`exception-redirect`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["exception-redirect"] }
+//! ```
+//!
+//! When the `exception-redirect` feature is enabled, `exceptions::redirect` replaces exception
+//! handlers at runtime (e.g., in bootloader to application transitions or test harnesses).
+//! Redirected handlers are stored in a table in RAM, and the exception dispatcher falls back to
+//! the static `__EXCEPTIONS` array for exceptions that are not redirected. When this feature is
+//! disabled, exceptions are only dispatched via the static `__EXCEPTIONS` array.
+//!
+//! This feature has no effect if the `no-exceptions` feature is enabled.
+//!
+//! ## `rnmi`
+//!
+//! The resumable NMI feature (`rnmi`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//! It is only available in M-mode, for targets implementing the Smrnmi extension.
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["rnmi"] }
+//! ```
+//!
+//! Resumable NMIs (e.g., watchdog or ECC events) are taken even when interrupts are disabled, so
+//! they must not share the normal trap path. When the `rnmi` feature is enabled:
+//!
+//! - The top `_rnmi_stack_size` bytes of every hart stack area are reserved for the RNMI handler,
+//!   and `_start` makes `mnscratch` point to the top of this RNMI stack.
+//! - A global `_start_rnmi_trap` entry point is provided. It swaps `sp` and `mnscratch`, stores a
+//!   [`TrapFrame`] in the RNMI stack, calls `RnmiHandler`, and returns with `mnret`.
+//!   The `sp` slot of the trap frame (`full-regs` feature) holds the interrupted stack pointer.
+//!
+//! The RNMI trap vector is implementation-defined, so platforms must jump to `_start_rnmi_trap`
+//! from it (e.g., placing a `j _start_rnmi_trap` instruction at the RNMI trap vector address).
+//! `mnstatus.NMIE` is cleared on reset, so applications must call
+//! [`riscv::register::mnstatus::set_nmie`] once they are ready to handle RNMIs.
+//!
+//! `RnmiHandler` defaults to an infinite loop. It can be redefined in the following way:
+//!
+//! ``` no_run
+//! #[export_name = "RnmiHandler"]
+//! fn rnmi_handler(trap_frame: &mut riscv_rt::TrapFrame) {
+//!     // The cause of the RNMI is implementation-defined
+//!     let cause = riscv::register::mncause::read().code();
+//!     // ...
+//! }
+//! ```
+//!
+//! ## `panic-in-trap`
+//!
+//! The panic-in-trap detection feature (`panic-in-trap`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["panic-in-trap"] }
+//! ```
+//!
+//! When the `panic-in-trap` feature is enabled, [`start_trap_rust`] records the trap frame of the
+//! trap being handled. Panic handlers can then call `panic_in_trap::check` to route panics that
+//! occur while handling a trap to the `PanicInTrap` handler, avoiding deadlocks when the panic
+//! handler relies on interrupts or semihosting. By default, `PanicInTrap` disables interrupts
+//! and aborts with [`AbortReason::PanicInTrap`]. Check the `panic_in_trap` module documentation for more details.
+//!
+//! ## `trap-trace`
+//!
+//! The trap tracing feature (`trap-trace`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["trap-trace"] }
+//! ```
+//!
+//! When the `trap-trace` feature is enabled, the cause, the exception program counter, and the cycle
+//! counter of every trap are recorded in a per-hart ring buffer on trap entry. The `trap_trace::snapshot`
+//! function returns a copy of the ring buffer of a hart, which is useful for debugging spurious
+//! interrupt storms. In multi-hart targets, the `RISCV_RT_TRAP_TRACE_HARTS` environment variable sets
+//! the number of traced harts (1 by default). In S-mode, this feature requires the `single-hart` feature.
+//! Check the `trap_trace` module documentation for more details.
+//!
+//! ## `isr-budget`
+//!
+//! The interrupt handler duration watchdog feature (`isr-budget`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["isr-budget"] }
+//! ```
+//!
+//! When the `isr-budget` feature is enabled, the runtime measures the cycles spent by every core interrupt
+//! handler. Budgets are set per interrupt with `isr_budget::set_budget`, and the runtime calls
+//! [`__isr_budget_exceeded`](#__isr_budget_exceeded) when a handler exceeds its budget. This feature is
+//! meant for debugging sporadic latency regressions. Check the `isr_budget` module documentation for more details.
+//!
+//! ## `io` and `semihosting`
+//!
+//! The interrupt-safe output feature (`io`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["io"] }
+//! ```
+//!
+//! When the `io` feature is enabled, the `rprint!` and `rprintln!` macros print formatted text to the global
+//! writer set with `io::set_global_writer`. Messages are written inside a critical section, so the target
+//! must provide a `critical-section` implementation. The `semihosting` feature implies `io` and sends the
+//! messages to the host via semihosting when no global writer is set. Check the `io` module documentation
+//! for more details.
+//!
+//! ## `pmp-wx`
+//!
+//! The W^X hardening feature (`pmp-wx`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//! It is only available in M-mode.
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["pmp-wx"] }
+//! ```
+//!
+//! When the `pmp-wx` feature is enabled, `_start` programs locked PMP entries right after initializing
+//! RAM, so `.text` is read+execute-only, `.rodata` is read-only, and `.data`, `.bss`, heap, and stack
+//! are read+write but not executable, even in M-mode. The first `pmp_wx::ENTRIES` PMP entries are used
+//! by the runtime. In targets implementing Smepmp, set the `_pmp_mml` linker symbol to 1 to also set
+//! `mseccfg.MML`. Check the `pmp_wx` module documentation for more details.
+//!
+//! ## `emulate-misaligned`
+//!
+//! The misaligned access emulation feature (`emulate-misaligned`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["emulate-misaligned"] }
+//! ```
+//!
+//! When the `emulate-misaligned` feature is enabled, the runtime defines the `LoadMisaligned` and
+//! `StoreMisaligned` exception handlers. They decode the faulting load or store instruction, emulate
+//! it byte by byte using the registers of the [`TrapFrame`], and resume execution after the instruction.
+//! Instructions that cannot be emulated are forwarded to `ExceptionHandler`. This is useful for cores
+//! without hardware support for misaligned accesses. Check the `misaligned` module documentation for
+//! more details.
+//!
+//! ## `hart-context`
+//!
+//! The hart context feature (`hart-context`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//! It is not compatible with the `v-trap` feature.
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["hart-context"] }
+//! ```
+//!
+//! When the `hart-context` feature is enabled, `mscratch` (`sscratch` in S-mode) holds a pointer to the
+//! `hart_context::HartContext` of the current hart, which is installed with `HartContext::install`.
+//! `_start_trap` uses this context to switch to a dedicated trap stack and to track the nesting depth
+//! of traps, and trap handlers can access it with `HartContext::current`. RTOSes and other extensions
+//! must use this context instead of claiming the scratch register for themselves.
+//! Check the `hart_context` module documentation for more details.
+//!
+//! ## `trap-region` and `trap-ram`
+//!
+//! The trap region features (`trap-region` and `trap-ram`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//! They are not compatible with the `pmp-wx` feature.
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["trap-region"] }
+//! ```
+//!
+//! By default, the trap code (i.e., `_start_trap` and the `.trap` input sections) is placed in the `.text`
+//! section. When the `trap-region` feature is enabled, it is placed in its own `.trap` output section,
+//! which starts at [`_strap`](#_strap) in the `REGION_TRAP` region. This allows placing the trap code at
+//! the fixed address expected by some boot ROMs. Users must define `REGION_TRAP` in their `memory.x` file:
+//!
+//! ``` text
+//! REGION_ALIAS("REGION_TRAP", FLASH);
+//! _strap = 0x20000100; /* address expected by the boot ROM */
+//! ```
+//!
+//! The `trap-ram` feature implies `trap-region`. With `link.x`, the `.trap` section is loaded in `REGION_TEXT`
+//! and copied to `REGION_TRAP` by the boot hart at startup (e.g., to run the trap code from RAM). With
+//! `link-ram.x`, the `.trap` section is loaded in place and the copy is skipped. All the harts execute
+//! `fence.i` after the RAM initialization. In multi-hart targets, `_mp_hook` must not release the other
+//! harts until the boot hart is done.
+//!
+//! With the `v-trap` feature, the vector tables are placed at the end of the `.trap` output section,
+//! so their `j` instructions can reach the trap handlers (and `trap-ram` copies them too).
+//!
+//! ## `data2-region`
+//!
+//! The second RAM region feature (`data2-region`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["data2-region"] }
+//! ```
+//!
+//! When the `data2-region` feature is enabled, the linker script adds a `.data2` output section in `REGION_DATA2`
+//! and a `.bss2` output section in `REGION_BSS2`. This allows placing some variables in a second RAM region
+//! (e.g., a tightly-coupled memory) while the rest remain in `REGION_DATA` and `REGION_BSS`. Users must define
+//! both aliases in their `memory.x` file:
+//!
+//! ``` text
+//! REGION_ALIAS("REGION_DATA2", DTCM);
+//! REGION_ALIAS("REGION_BSS2", DTCM);
+//! ```
+//!
+//! Variables are placed in these sections with the `link_section` attribute. Initialized variables must be
+//! placed in `.data2` (or `.data2.*`), and zero-initialized variables in `.bss2` (or `.bss2.*`):
+//!
+//! ``` no_run
+//! #[link_section = ".data2.samples"]
+//! static mut SAMPLES: [u16; 4] = [1, 2, 3, 4];
+//!
+//! #[link_section = ".bss2.buffer"]
+//! static mut BUFFER: [u8; 1024] = [0; 1024];
+//! ```
+//!
+//! As `.data`, `.data2` is loaded in `REGION_RODATA` with `link.x` (or in place with `link-ram.x`), and
+//! the boot hart copies it from `__sidata2` to `__sdata2`..`__edata2` at startup. Then, it zeroes out
+//! `__sbss2`..`__ebss2`. Both loops run before `.data` and `.bss` are initialized.
+//!
+//! ## `app-image`
+//!
+//! The application image feature (`app-image`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["app-image"] }
+//! ```
+//!
+//! When the `app-image` feature is enabled, the entry point of the program is `_app_entry` instead of `_start`.
+//! This allows linking secondary images (e.g., an application started by a bootloader) without claiming the
+//! default entry point of the target. The image must be linked at the address expected by the bootloader
+//! (e.g., by setting [`_stext`](#_stext)), and `_app_entry` is placed at the start of its `.text` section.
+//! The bootloader can start the image with [`image::jump_to_image`], which sanitizes the state of the hart
+//! before jumping. Check the `image` module documentation for more details.
+//!
 //! ## `u-boot`
 //!
 //! The U-boot support feature (`u-boot`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
@@ -522,33 +1006,287 @@
 //! because when booting from elf, U-boot passes `argc` and `argv`. This feature also implies `single-hart`.
 //! The only way to get boot-hart is through fdt, so other harts initialization is up to you.
 //!
+//! ## `test-entry`
+//!
+//! The test entry feature (`test-entry`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dev-dependencies]
+//! riscv-rt = { features = ["test-entry"] }
+//! ```
+//!
+//! When the `test-entry` feature is enabled, the function marked with [`#[entry]`][attr-entry] may return
+//! any type that implements [`Termination`] (e.g., `()` or `Result<(), E>`). When `main` returns, the runtime
+//! calls [`exit`] with the corresponding exit code. By default, `_exit` aborts with [`AbortReason::Exit`], but test programs
+//! can override it to report the result to the host (e.g., with semihosting). Check the `test_entry` module
+//! documentation for more details. This feature is meant for test programs: normal builds should keep
+//! the never-returning `main`, which is still accepted in `test-entry` mode.
+//!
+//! ## `stack-paint`
+//!
+//! The stack painting feature (`stack-paint`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["stack-paint"] }
+//! ```
+//!
+//! When the `stack-paint` feature is enabled, every hart fills its stack area with
+//! [`_stack_paint_pattern`](#_stack_paint_pattern) before using the stack. Then, [`stack_usage`] returns
+//! the maximum stack depth reached by a hart. Check the `stack_paint` module documentation for more details.
+//!
+//! ## `async-main`
+//!
+//! The asynchronous main feature (`async-main`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["async-main"] }
+//! ```
+//!
+//! When the `async-main` feature is enabled, the [`#[entry(async)]`][attr-entry] attribute accepts an
+//! `async fn main()`. The runtime enables interrupts and polls the future with a minimal single-task
+//! executor that sleeps with [`idle`] until an interrupt handler wakes it. The runtime also provides
+//! the timer and software interrupt handlers, which wake the tasks awaiting `executor::wait_timer`
+//! and `executor::wait_software`. Thus, users must not define these handlers. Check the `executor`
+//! module documentation for more details.
+//!
+//! ## `reinit-interrupts`
+//!
+//! The interrupt re-initialization feature (`reinit-interrupts`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["reinit-interrupts"] }
+//! ```
+//!
+//! When the `reinit-interrupts` feature is enabled, [`reinit_interrupts`] calls
+//! [`_setup_interrupts`](#_setup_interrupts) again after boot (e.g., after a firmware update changes
+//! the trap layout). It synchronizes the instruction stream, checks the trap mode and the alignment of
+//! the new trap vector, and restores the previous one if the checks fail. Check the `reinit` module
+//! documentation for more details.
+//!
+//! ## `heap`
+//!
+//! The heap initialization feature (`heap`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["heap"] }
+//! ```
+//!
+//! When the `heap` feature is enabled, the `heap` module provides functions that hand the heap
+//! region ([`heap_start`] and `_heap_size`) to a global allocator only once per program. See the
+//! [Using the heap](#using-the-heap) section for more details.
+//!
+//! ## `watchpoint`
+//!
+//! The watchpoint feature (`watchpoint`) can be activated via [Cargo features](https://doc.rust-lang.org/cargo/reference/features.html).
+//! It is only available in M-mode.
+//!
+//! For example:
+//! ``` text
+//! [dependencies]
+//! riscv-rt = { features = ["watchpoint"] }
+//! ```
+//!
+//! When the `watchpoint` feature is enabled, `debug::watch_address` arms a trigger (Sdtrig extension)
+//! that raises a breakpoint exception when a store writes to a memory region, and the runtime defines
+//! the `Breakpoint` exception handler to call a user callback with the [`TrapFrame`] of the store.
+//! Other breakpoint exceptions are forwarded to `ExceptionHandler`. Check the `debug` module
+//! documentation for more details.
+//!
+//! ## Feature compatibility
+//!
+//! Some features change the same parts of the runtime (e.g., the trap entry point or the boot code).
+//! The following combinations are not supported, and they fail at compile time with an error message:
+//!
+//! | Feature        | Requires                 | Not compatible with |
+//! |----------------|--------------------------|---------------------|
+//! | `rnmi`         |                          | `s-mode`            |
+//! | `watchpoint`   |                          | `s-mode`            |
+//! | `pmp-wx`       |                          | `s-mode`, `trap-region` |
+//! | `trap-trace`   | `single-hart` (S-mode)   |                     |
+//! | `hart-context` |                          | `v-trap`            |
+//! | `trap-region`  |                          | `pmp-wx`            |
+//! | `u-boot`       | `single-hart` (implied)  |                     |
+//! | `trap-ram`     | `trap-region` (implied)  |                     |
+//!
+//! Other combinations are supported. In particular, `s-mode`, `v-trap`, and `u-boot` can be combined
+//! freely: the `xtask` QEMU jobs run the examples and check the boot code with combinations of them.
+//!
 //! [attr-entry]: attr.entry.html
 //! [attr-exception]: attr.exception.html
 //! [attr-external-interrupt]: attr.external_interrupt.html
 //! [attr-core-interrupt]: attr.core_interrupt.html
 //! [attr-pre-init]: attr.pre_init.html
+//! [attr-post-init]: attr.post_init.html
 
 // NOTE: Adapted from cortex-m/src/lib.rs
 #![no_std]
 #![deny(missing_docs)]
 
+// Unsupported feature combinations (see the feature compatibility table in the crate documentation)
+#[cfg(all(feature = "rnmi", feature = "s-mode"))]
+compile_error!("The `rnmi` feature is only available in M-mode");
+
+#[cfg(all(
+    feature = "trap-trace",
+    feature = "s-mode",
+    not(feature = "single-hart")
+))]
+compile_error!("The `trap-trace` feature requires the `single-hart` feature in S-mode");
+
+#[cfg(all(feature = "watchpoint", feature = "s-mode"))]
+compile_error!("The `watchpoint` feature is only available in M-mode");
+
+#[cfg(all(feature = "pmp-wx", feature = "s-mode"))]
+compile_error!("The `pmp-wx` feature is only available in M-mode");
+
+#[cfg(all(feature = "hart-context", feature = "v-trap"))]
+compile_error!("The `hart-context` feature is not compatible with the `v-trap` feature");
+
+#[cfg(all(feature = "trap-region", feature = "pmp-wx"))]
+compile_error!("The `trap-region` feature is not compatible with the `pmp-wx` feature");
+
 #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
 mod asm;
 
+pub mod abort;
+
 #[cfg(not(feature = "no-exceptions"))]
 pub mod exceptions;
 
 #[cfg(not(feature = "no-interrupts"))]
 pub mod interrupts;
 
+#[cfg(feature = "panic-in-trap")]
+pub mod panic_in_trap;
+
+#[cfg(feature = "trap-trace")]
+pub mod trap_trace;
+
+#[cfg(feature = "isr-budget")]
+pub mod isr_budget;
+
+#[cfg(feature = "io")]
+pub mod io;
+
+#[cfg(feature = "pmp-wx")]
+pub mod pmp_wx;
+
+#[cfg(feature = "emulate-misaligned")]
+pub mod misaligned;
+
+#[cfg(feature = "hart-context")]
+pub mod hart_context;
+
+pub mod idle;
+
+pub mod image;
+
+#[cfg(feature = "stack-paint")]
+pub mod stack_paint;
+
+#[cfg(feature = "test-entry")]
+pub mod test_entry;
+
+#[cfg(feature = "async-main")]
+pub mod executor;
+
+#[cfg(feature = "reinit-interrupts")]
+pub mod reinit;
+
+#[cfg(feature = "heap")]
+pub mod heap;
+
+#[cfg(feature = "watchpoint")]
+pub mod debug;
+
+pub mod vectors;
+
 #[cfg(feature = "s-mode")]
 use riscv::register::scause as xcause;
 
 #[cfg(not(feature = "s-mode"))]
 use riscv::register::mcause as xcause;
 
+pub use abort::{abort_with, AbortReason};
+pub use idle::idle;
 pub use riscv_pac::*;
-pub use riscv_rt_macros::{core_interrupt, entry, exception, external_interrupt, pre_init};
+pub use riscv_rt_macros::{
+    core_interrupt, entry, exception, external_interrupt, post_init, pre_init,
+};
+pub use vectors::{vectors_crc, verify_vectors};
+
+#[cfg(feature = "stack-paint")]
+pub use stack_paint::stack_usage;
+
+#[cfg(feature = "test-entry")]
+pub use test_entry::{exit, Termination};
+
+#[cfg(feature = "reinit-interrupts")]
+pub use reinit::reinit_interrupts;
+
+#[cfg(feature = "v-trap")]
+#[doc(hidden)]
+pub use riscv_rt_macros::start_interrupt_trap;
+
+/// Generates the `_start_<interrupt>_trap` function of an interrupt in vectored mode.
+///
+/// The [`core_interrupt`] and [`external_interrupt`] attributes are expanded in user crates, which
+/// do not know the base ISA of the target. Thus, the trap frame layout (RVI or RVE) is selected here.
+#[cfg(all(feature = "v-trap", target_arch = "riscv32", riscvi))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __start_interrupt_trap {
+    ($interrupt:ident) => {
+        $crate::start_interrupt_trap!(rv32i, $interrupt);
+    };
+}
+
+#[cfg(all(feature = "v-trap", target_arch = "riscv32", not(riscvi)))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __start_interrupt_trap {
+    ($interrupt:ident) => {
+        $crate::start_interrupt_trap!(rv32e, $interrupt);
+    };
+}
+
+#[cfg(all(feature = "v-trap", target_arch = "riscv64", riscvi))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __start_interrupt_trap {
+    ($interrupt:ident) => {
+        $crate::start_interrupt_trap!(rv64i, $interrupt);
+    };
+}
+
+#[cfg(all(feature = "v-trap", target_arch = "riscv64", not(riscvi)))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __start_interrupt_trap {
+    ($interrupt:ident) => {
+        $crate::start_interrupt_trap!(rv64e, $interrupt);
+    };
+}
+
+/// In non-RISC-V targets (e.g., host tests), no trap handler is generated.
+#[cfg(all(
+    feature = "v-trap",
+    not(any(target_arch = "riscv32", target_arch = "riscv64"))
+))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __start_interrupt_trap {
+    ($interrupt:ident) => {};
+}
 
 /// We export this static with an informative name so that if an application attempts to link
 /// two copies of riscv-rt together, linking will fail. We also declare a links key in
@@ -559,6 +1297,19 @@ pub use riscv_rt_macros::{core_interrupt, entry, exception, external_interrupt,
 pub static __ONCE__: () = ();
 
 /// Registers saved in trap handler
+///
+/// # ABI
+///
+/// The trap frame is allocated on the stack by the `_start_trap` assembly routine, and its
+/// layout is part of the public ABI of this crate: fields are stored in declaration order, each
+/// taking `XLEN` bits. Fields `t3`-`t6`, `a6`, and `a7` are only present in RVI targets (not in RVE).
+/// Assembly code can use [`TrapFrame::SIZE`] instead of hard-coding the trap frame size.
+///
+/// When the `full-regs` feature is enabled, `gp`, `tp`, and `sp` are appended to the trap frame.
+/// In RVI targets, an additional reserved slot keeps the trap frame size 16-byte aligned.
+/// Changes to `gp` and `tp` are restored when returning from the trap, while `sp` is read-only:
+/// it holds the value of the stack pointer before the trap frame was allocated.
+/// The `GP_OFFSET`, `TP_OFFSET`, and `SP_OFFSET` constants provide the offsets of these fields.
 #[repr(C)]
 #[derive(Debug)]
 pub struct TrapFrame {
@@ -600,6 +1351,85 @@ pub struct TrapFrame {
     #[cfg(riscvi)]
     /// `x17`: argument register `a7`. Used to pass the eighth argument to a function.
     pub a7: usize,
+    /// `x3`: global pointer `gp`. Points to the middle of the small data section.
+    #[cfg(feature = "full-regs")]
+    pub gp: usize,
+    /// `x4`: thread pointer `tp`. Points to thread-local data.
+    #[cfg(feature = "full-regs")]
+    pub tp: usize,
+    /// `x2`: stack pointer `sp` before the trap. Changes to this field are ignored.
+    #[cfg(feature = "full-regs")]
+    pub sp: usize,
+    #[cfg(all(feature = "full-regs", riscvi))]
+    _reserved: usize,
+}
+
+impl TrapFrame {
+    /// Size of the trap frame in bytes.
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Number of registers stored before `gp`.
+    #[cfg(feature = "full-regs")]
+    const N_BASE_REGS: usize = if cfg!(riscvi) { 16 } else { 10 };
+
+    /// Offset of the `gp` field in bytes.
+    #[cfg(feature = "full-regs")]
+    pub const GP_OFFSET: usize = Self::N_BASE_REGS * core::mem::size_of::<usize>();
+
+    /// Offset of the `tp` field in bytes.
+    #[cfg(feature = "full-regs")]
+    pub const TP_OFFSET: usize = Self::GP_OFFSET + core::mem::size_of::<usize>();
+
+    /// Offset of the `sp` field in bytes.
+    #[cfg(feature = "full-regs")]
+    pub const SP_OFFSET: usize = Self::TP_OFFSET + core::mem::size_of::<usize>();
+}
+
+/// Boot arguments passed to the [`#[post_init]`][attr-post-init] function.
+///
+/// They correspond to the values of `a0`, `a1`, and `a2` at `_start`, which are passed to `main`.
+/// The meaning of these arguments depends on the previous boot stage. For instance, SBI firmwares
+/// pass the hart ID in `a0` and a pointer to the device tree blob in `a1`, while U-Boot passes
+/// `argc` and `argv` in `a0` and `a1`, respectively.
+///
+/// # ABI
+///
+/// The boot arguments are stored in the stack of the hart by `_start`. Changes performed by the
+/// `#[post_init]` function are forwarded to `main`.
+///
+/// [attr-post-init]: attr.post_init.html
+#[repr(C)]
+#[derive(Debug)]
+pub struct BootInfo {
+    /// Value of `a0` at `_start`.
+    pub a0: usize,
+    /// Value of `a1` at `_start`.
+    pub a1: usize,
+    /// Value of `a2` at `_start`.
+    pub a2: usize,
+}
+
+impl BootInfo {
+    /// Returns the ID of the current hart.
+    ///
+    /// In M-mode, it reads the `mhartid` CSR. In S-mode, it assumes that the
+    /// previous boot stage follows the SBI convention (i.e., the hart ID is in `a0`).
+    #[inline]
+    pub fn hart_id(&self) -> usize {
+        match () {
+            #[cfg(feature = "s-mode")]
+            () => self.a0,
+            #[cfg(not(feature = "s-mode"))]
+            () => riscv::register::mhartid::read(),
+        }
+    }
+
+    /// Returns the address of the device tree blob, assuming that the previous
+    /// boot stage follows the SBI convention (i.e., the address is in `a1`).
+    #[inline]
+    pub const fn dtb(&self) -> usize {
+        self.a1
+    }
 }
 
 /// Trap entry point rust (_start_trap_rust)
@@ -628,6 +1458,12 @@ pub struct TrapFrame {
 /// interrupt. If this abnormal situation happens, this function will directly call the
 /// `DefaultHandler` function.
 ///
+/// When the `panic-in-trap` feature is enabled, the trap frame is recorded while the trap
+/// is handled, so panics occurring in trap handlers are detected (see the `panic_in_trap` module).
+///
+/// When the `isr-budget` feature is enabled, the duration of core interrupt handlers is checked
+/// against their budget (see the `isr_budget` module).
+///
 /// # Safety
 ///
 /// This function must be called only from assembly `_start_trap` function.
@@ -646,13 +1482,30 @@ pub unsafe extern "C" fn start_trap_rust(trap_frame: *const TrapFrame) {
         fn _dispatch_exception(trap_frame: &TrapFrame, code: usize);
     }
 
-    match xcause::read().cause() {
+    #[cfg(feature = "trap-trace")]
+    trap_trace::_trap_trace_record();
+
+    #[cfg(feature = "panic-in-trap")]
+    let previous = panic_in_trap::enter(trap_frame);
+
+    let cause = xcause::read();
+
+    #[cfg(feature = "isr-budget")]
+    let start = isr_budget::cycles();
+
+    match cause.cause() {
         #[cfg(not(feature = "v-trap"))]
         xcause::Trap::Interrupt(code) => _dispatch_core_interrupt(code),
         #[cfg(feature = "v-trap")]
         xcause::Trap::Interrupt(_) => DefaultHandler(),
         xcause::Trap::Exception(code) => _dispatch_exception(&*trap_frame, code),
     }
+
+    #[cfg(feature = "isr-budget")]
+    isr_budget::_isr_budget_check(cause.bits(), start);
+
+    #[cfg(feature = "panic-in-trap")]
+    panic_in_trap::exit(previous);
 }
 
 /// Returns a pointer to the start of the heap
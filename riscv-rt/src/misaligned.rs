@@ -0,0 +1,399 @@
+//! Emulation of misaligned loads and stores.
+//!
+//! Some cores do not support misaligned memory accesses in hardware, and raise `LoadMisaligned`
+//! and `StoreMisaligned` exceptions instead. When the `emulate-misaligned` feature is enabled,
+//! the runtime provides handlers for these exceptions that decode the faulting instruction,
+//! perform the memory access byte by byte, and resume execution after the instruction.
+//! This is useful for code that occasionally misaligns accesses (e.g., packed structs or DMA descriptors).
+//!
+//! The following instructions are emulated:
+//!
+//! - `lh`, `lhu`, `lw`, `sh`, and `sw`.
+//! - `ld`, `lwu`, and `sd` in 64-bit targets.
+//! - `c.lw`, `c.lwsp`, `c.sw`, and `c.swsp`.
+//! - `c.ld`, `c.ldsp`, `c.sd`, and `c.sdsp` in 64-bit targets.
+//!
+//! If the instruction cannot be emulated, the exception is forwarded to `ExceptionHandler`.
+//! Users can also call [`emulate`] from their own exception handlers.
+//!
+//! # Note
+//!
+//! The emulation only has access to the registers stored in the [`TrapFrame`]. Thus, instructions
+//! using a callee-saved register (i.e., `s0`-`s11`) or `sp` (unless the `full-regs` feature is enabled)
+//! as the base address register can only be emulated if the core writes the faulting address to
+//! `mtval`/`stval`. Instructions using callee-saved registers as data registers are not emulated.
+//!
+//! The memory access is performed with the privileges of the exception handler. Emulated accesses
+//! are not atomic, so they must not be used for memory shared with other harts or for peripherals.
+//!
+//! As this feature defines the `LoadMisaligned` and `StoreMisaligned` symbols,
+//! users must not define their own handlers for these exceptions.
+
+use crate::TrapFrame;
+use riscv_pac::result::{Error, Result};
+
+#[cfg(feature = "s-mode")]
+use riscv::register::{sepc::Sepc as Xepc, stval as xtval};
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::register::{mepc::Mepc as Xepc, mtval as xtval};
+
+/// Width of the general purpose registers in bytes.
+const XLEN: usize = core::mem::size_of::<usize>();
+
+/// Kind of memory access.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    /// Load to register `rd`. Narrow values are sign-extended if `signed` is `true`.
+    Load { rd: usize, signed: bool },
+    /// Store of register `rs2`.
+    Store { rs2: usize },
+}
+
+/// Decoded load or store instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Access {
+    kind: Kind,
+    /// Width of the access in bytes.
+    width: usize,
+    /// Base address register.
+    rs1: usize,
+    /// Offset added to the base address.
+    offset: isize,
+}
+
+/// Decodes a load or store instruction. Compressed instructions are in the lower 16 bits of `insn`.
+///
+/// It returns [`None`] if `insn` is not a load or store instruction supported by the emulation.
+fn decode(insn: u32) -> Option<Access> {
+    let field = |hi: u32, lo: u32| ((insn >> lo) & ((1 << (hi - lo + 1)) - 1)) as usize;
+    let load = |rd: usize| Kind::Load { rd, signed: true };
+    let store = |rs2: usize| Kind::Store { rs2 };
+
+    let (kind, width, rs1, offset) = match (insn & 0b11, field(15, 13)) {
+        (0b11, _) => {
+            let funct3 = field(14, 12);
+            match insn & 0x7f {
+                // LOAD
+                0b000_0011 => {
+                    let (width, signed) = match funct3 {
+                        1 => (2, true),
+                        2 => (4, true),
+                        3 if XLEN == 8 => (8, true),
+                        5 => (2, false),
+                        6 if XLEN == 8 => (4, false),
+                        _ => return None,
+                    };
+                    let rd = field(11, 7);
+                    let offset = (insn as i32) >> 20;
+                    (Kind::Load { rd, signed }, width, field(19, 15), offset)
+                }
+                // STORE
+                0b010_0011 => {
+                    let width = match funct3 {
+                        1 => 2,
+                        2 => 4,
+                        3 if XLEN == 8 => 8,
+                        _ => return None,
+                    };
+                    let offset = (((insn as i32) >> 25) << 5) | field(11, 7) as i32;
+                    (store(field(24, 20)), width, field(19, 15), offset)
+                }
+                _ => return None,
+            }
+        }
+        // C.LW, C.LD, C.SW, C.SD
+        (0b00, funct3) => {
+            let (rs1, reg) = (field(9, 7) + 8, field(4, 2) + 8);
+            let offset_w = (field(12, 10) << 3) | (field(6, 6) << 2) | (field(5, 5) << 6);
+            let offset_d = (field(12, 10) << 3) | (field(6, 5) << 6);
+            match funct3 {
+                2 => (load(reg), 4, rs1, offset_w as i32),
+                3 if XLEN == 8 => (load(reg), 8, rs1, offset_d as i32),
+                6 => (store(reg), 4, rs1, offset_w as i32),
+                7 if XLEN == 8 => (store(reg), 8, rs1, offset_d as i32),
+                _ => return None,
+            }
+        }
+        // C.LWSP, C.LDSP, C.SWSP, C.SDSP
+        (0b10, funct3) => {
+            let (rd, rs2) = (field(11, 7), field(6, 2));
+            match funct3 {
+                2 if rd != 0 => {
+                    let offset = (field(12, 12) << 5) | (field(6, 4) << 2) | (field(3, 2) << 6);
+                    (load(rd), 4, 2, offset as i32)
+                }
+                3 if XLEN == 8 && rd != 0 => {
+                    let offset = (field(12, 12) << 5) | (field(6, 5) << 3) | (field(4, 2) << 6);
+                    (load(rd), 8, 2, offset as i32)
+                }
+                6 => {
+                    let offset = (field(12, 9) << 2) | (field(8, 7) << 6);
+                    (store(rs2), 4, 2, offset as i32)
+                }
+                7 if XLEN == 8 => {
+                    let offset = (field(12, 10) << 3) | (field(9, 7) << 6);
+                    (store(rs2), 8, 2, offset as i32)
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(Access {
+        kind,
+        width,
+        rs1,
+        offset: offset as isize,
+    })
+}
+
+/// Returns a mutable reference to the register `x` in the trap frame.
+///
+/// It returns [`Error::InvalidFieldVariant`] if the register is not stored in the trap frame.
+fn reg_mut<'a>(
+    trap_frame: &'a mut TrapFrame,
+    field: &'static str,
+    x: usize,
+) -> Result<&'a mut usize> {
+    match x {
+        1 => Ok(&mut trap_frame.ra),
+        #[cfg(feature = "full-regs")]
+        3 => Ok(&mut trap_frame.gp),
+        #[cfg(feature = "full-regs")]
+        4 => Ok(&mut trap_frame.tp),
+        5 => Ok(&mut trap_frame.t0),
+        6 => Ok(&mut trap_frame.t1),
+        7 => Ok(&mut trap_frame.t2),
+        10 => Ok(&mut trap_frame.a0),
+        11 => Ok(&mut trap_frame.a1),
+        12 => Ok(&mut trap_frame.a2),
+        13 => Ok(&mut trap_frame.a3),
+        14 => Ok(&mut trap_frame.a4),
+        15 => Ok(&mut trap_frame.a5),
+        #[cfg(riscvi)]
+        16 => Ok(&mut trap_frame.a6),
+        #[cfg(riscvi)]
+        17 => Ok(&mut trap_frame.a7),
+        #[cfg(riscvi)]
+        28 => Ok(&mut trap_frame.t3),
+        #[cfg(riscvi)]
+        29 => Ok(&mut trap_frame.t4),
+        #[cfg(riscvi)]
+        30 => Ok(&mut trap_frame.t5),
+        #[cfg(riscvi)]
+        31 => Ok(&mut trap_frame.t6),
+        _ => Err(Error::InvalidFieldVariant { field, value: x }),
+    }
+}
+
+/// Returns the value of the register `x` before the trap.
+fn read_reg(trap_frame: &mut TrapFrame, field: &'static str, x: usize) -> Result<usize> {
+    match x {
+        0 => Ok(0),
+        #[cfg(feature = "full-regs")]
+        2 => Ok(trap_frame.sp),
+        _ => reg_mut(trap_frame, field, x).map(|reg| *reg),
+    }
+}
+
+/// Emulates the misaligned load or store instruction that caused the current exception,
+/// and advances `mepc`/`sepc` to the next instruction.
+///
+/// # Errors
+///
+/// - [`Error::InvalidVariant`] with the instruction if it is not a supported load or store instruction.
+/// - [`Error::InvalidFieldVariant`] if a register used by the instruction is not stored in the trap frame.
+///
+/// # Safety
+///
+/// This function must be called only from `LoadMisaligned` and `StoreMisaligned` exception handlers,
+/// with the trap frame of the exception. The memory accessed by the instruction must be valid.
+pub unsafe fn emulate(trap_frame: &mut TrapFrame) -> Result<()> {
+    let mut epc = Xepc::read();
+    let parcel = |addr: usize| (addr as *const u16).read_volatile();
+    let low = parcel(epc.pc());
+    let insn = match low & 0b11 {
+        0b11 => low as u32 | (parcel(epc.pc() + 2) as u32) << 16,
+        _ => low as u32,
+    };
+    let access = decode(insn).ok_or(Error::InvalidVariant(insn as usize))?;
+
+    // Cores may not report the faulting address. Note that 0 is never a misaligned address
+    let addr = match xtval::read() {
+        0 => read_reg(trap_frame, "rs1", access.rs1)?.wrapping_add(access.offset as usize),
+        addr => addr,
+    };
+    let ptr = addr as *mut u8;
+
+    match access.kind {
+        Kind::Load { rd, signed } => {
+            let mut value = 0u64;
+            for i in 0..access.width {
+                value |= (ptr.add(i).read_volatile() as u64) << (8 * i);
+            }
+            if signed {
+                let shift = 64 - 8 * access.width as u32;
+                value = (((value << shift) as i64) >> shift) as u64;
+            }
+            // Loads to x0 only have the side effects of the memory access
+            if rd != 0 {
+                *reg_mut(trap_frame, "rd", rd)? = value as usize;
+            }
+        }
+        Kind::Store { rs2 } => {
+            let value = read_reg(trap_frame, "rs2", rs2)? as u64;
+            for i in 0..access.width {
+                ptr.add(i).write_volatile((value >> (8 * i)) as u8);
+            }
+        }
+    }
+
+    epc.step_over_instruction(|_| low);
+    epc.write();
+    Ok(())
+}
+
+/// Emulates misaligned loads, or calls `ExceptionHandler` if the instruction cannot be emulated.
+#[export_name = "LoadMisaligned"]
+unsafe extern "C" fn load_misaligned(trap_frame: &mut TrapFrame) {
+    handle(trap_frame)
+}
+
+/// Emulates misaligned stores, or calls `ExceptionHandler` if the instruction cannot be emulated.
+#[export_name = "StoreMisaligned"]
+unsafe extern "C" fn store_misaligned(trap_frame: &mut TrapFrame) {
+    handle(trap_frame)
+}
+
+#[inline]
+unsafe fn handle(trap_frame: &mut TrapFrame) {
+    extern "C" {
+        fn ExceptionHandler(trap_frame: &TrapFrame);
+    }
+    if emulate(trap_frame).is_err() {
+        ExceptionHandler(trap_frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(rd: usize, signed: bool, width: usize, rs1: usize, offset: isize) -> Option<Access> {
+        Some(Access {
+            kind: Kind::Load { rd, signed },
+            width,
+            rs1,
+            offset,
+        })
+    }
+
+    fn store(rs2: usize, width: usize, rs1: usize, offset: isize) -> Option<Access> {
+        Some(Access {
+            kind: Kind::Store { rs2 },
+            width,
+            rs1,
+            offset,
+        })
+    }
+
+    #[test]
+    fn test_decode() {
+        // lh a0, -2(a1)
+        assert_eq!(decode(0xffe5_9503), load(10, true, 2, 11, -2));
+        // lhu t0, 6(t1)
+        assert_eq!(decode(0x0063_5283), load(5, false, 2, 6, 6));
+        // lw a5, 2047(a2)
+        assert_eq!(decode(0x7ff6_2783), load(15, true, 4, 12, 2047));
+        // sh a0, -3(a1)
+        assert_eq!(decode(0xfea5_9ea3), store(10, 2, 11, -3));
+        // sw t2, 5(t0)
+        assert_eq!(decode(0x0072_a2a3), store(7, 4, 5, 5));
+        // lb a0, 1(a1)
+        assert_eq!(decode(0x0015_8503), None);
+        // addi a0, a0, 1
+        assert_eq!(decode(0x0015_0513), None);
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            // ld ra, -2048(sp)
+            assert_eq!(decode(0x8001_3083), load(1, true, 8, 2, -2048));
+            // lwu a3, 10(a4)
+            assert_eq!(decode(0x00a7_6683), load(13, false, 4, 14, 10));
+            // sd a4, 1000(a2)
+            assert_eq!(decode(0x3ee6_3423), store(14, 8, 12, 1000));
+        }
+    }
+
+    #[test]
+    fn test_decode_compressed() {
+        // c.lw a0, 124(a1)
+        assert_eq!(decode(0x5de8), load(10, true, 4, 11, 124));
+        // c.sw a2, 64(a3)
+        assert_eq!(decode(0xc2b0), store(12, 4, 13, 64));
+        // c.lwsp t0, 252(sp)
+        assert_eq!(decode(0x52fe), load(5, true, 4, 2, 252));
+        // c.swsp a3, 12(sp)
+        assert_eq!(decode(0xc636), store(13, 4, 2, 12));
+        // c.lwsp with rd = x0 is reserved
+        assert_eq!(decode(0x4002), None);
+        // c.addi4spn with a zero immediate (illegal instruction)
+        assert_eq!(decode(0x0000), None);
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            // c.ld a5, 248(s1)
+            assert_eq!(decode(0x7cfc), load(15, true, 8, 9, 248));
+            // c.sd s0, 8(a0)
+            assert_eq!(decode(0xe500), store(8, 8, 10, 8));
+            // c.ldsp a0, 504(sp)
+            assert_eq!(decode(0x757e), load(10, true, 8, 2, 504));
+            // c.sdsp ra, 256(sp)
+            assert_eq!(decode(0xe206), store(1, 8, 2, 256));
+        }
+    }
+
+    #[test]
+    fn test_registers() {
+        // SAFETY: all the fields of the trap frame are integers
+        let mut trap_frame: TrapFrame = unsafe { core::mem::zeroed() };
+        trap_frame.ra = 1;
+        trap_frame.t0 = 5;
+        trap_frame.a0 = 10;
+        trap_frame.a5 = 15;
+
+        assert_eq!(read_reg(&mut trap_frame, "rs1", 0), Ok(0));
+        assert_eq!(read_reg(&mut trap_frame, "rs1", 1), Ok(1));
+        assert_eq!(read_reg(&mut trap_frame, "rs1", 5), Ok(5));
+        assert_eq!(read_reg(&mut trap_frame, "rs1", 10), Ok(10));
+        assert_eq!(read_reg(&mut trap_frame, "rs2", 15), Ok(15));
+
+        *reg_mut(&mut trap_frame, "rd", 11).unwrap() = 11;
+        assert_eq!(trap_frame.a1, 11);
+
+        // Callee-saved registers are not stored in the trap frame
+        for x in [8, 9, 18, 27] {
+            assert_eq!(
+                read_reg(&mut trap_frame, "rs1", x),
+                Err(Error::InvalidFieldVariant {
+                    field: "rs1",
+                    value: x
+                })
+            );
+        }
+        #[cfg(not(feature = "full-regs"))]
+        assert_eq!(
+            read_reg(&mut trap_frame, "rs1", 2),
+            Err(Error::InvalidFieldVariant {
+                field: "rs1",
+                value: 2
+            })
+        );
+        #[cfg(feature = "full-regs")]
+        {
+            trap_frame.sp = 2;
+            assert_eq!(read_reg(&mut trap_frame, "rs1", 2), Ok(2));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Detection of panics occurring while handling a trap.
+//!
+//! Panic handlers usually rely on interrupts, semihosting, or locks that may already be taken by
+//! the interrupted code. Thus, a panic in a trap handler may deadlock the regular panic handler.
+//! When the `panic-in-trap` feature is enabled, [`crate::start_trap_rust`] records the trap frame
+//! of the trap being handled, and panic handlers can call [`check`] to route panics occurring
+//! while handling a trap to the `PanicInTrap` handler:
+//!
+//! ``` ignore,no_run
+//! #[panic_handler]
+//! fn panic(info: &core::panic::PanicInfo) -> ! {
+//!     riscv_rt::panic_in_trap::check(info);
+//!     // the panic did not occur while handling a trap
+//!     loop {}
+//! }
+//! ```
+//!
+//! `PanicInTrap` receives the location of the panic (if available) and the trap frame of the
+//! trap being handled. By default, it disables interrupts, so no other trap can preempt it,
+//! and aborts with [`crate::AbortReason::PanicInTrap`]. It can be redefined in the following way:
+//!
+//! ``` no_run
+//! #[export_name = "PanicInTrap"]
+//! fn panic_in_trap(
+//!     location: Option<&core::panic::Location>,
+//!     trap_frame: &riscv_rt::TrapFrame,
+//! ) -> ! {
+//!     // ...
+//!     loop {}
+//! }
+//! ```
+//!
+//! # Note
+//!
+//! Only traps dispatched by [`crate::start_trap_rust`] are detected. In vectored mode (i.e.,
+//! `v-trap` feature enabled), core interrupt handlers are called directly by their trap entry
+//! points, and thus panics in these handlers are not detected.
+//!
+//! The trap frame is recorded in a global variable shared by all the harts. In multi-hart targets,
+//! a panic may be considered to occur while handling a trap if another hart is handling a trap.
+
+use crate::TrapFrame;
+use core::{
+    panic::{Location, PanicInfo},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Address of the trap frame of the trap being handled. It is 0 if no trap is being handled.
+static TRAP_FRAME: AtomicUsize = AtomicUsize::new(0);
+
+/// Records `trap_frame` as the trap frame of the trap being handled.
+///
+/// It returns the previously recorded trap frame, which must be restored with [`exit`]
+/// once the trap is handled (i.e., nested traps are supported).
+///
+/// In targets without atomic read-modify-write instructions (i.e., without the `A` extension),
+/// the trap frame is swapped with a load and a store. This is fine, as trap handlers run with
+/// interrupts disabled until they re-enable them.
+#[inline]
+pub(crate) fn enter(trap_frame: *const TrapFrame) -> usize {
+    match () {
+        #[cfg(target_has_atomic = "ptr")]
+        () => TRAP_FRAME.swap(trap_frame as usize, Ordering::Relaxed),
+        #[cfg(not(target_has_atomic = "ptr"))]
+        () => {
+            let previous = TRAP_FRAME.load(Ordering::Relaxed);
+            TRAP_FRAME.store(trap_frame as usize, Ordering::Relaxed);
+            previous
+        }
+    }
+}
+
+/// Restores the trap frame recorded before handling the current trap.
+#[inline]
+pub(crate) fn exit(previous: usize) {
+    TRAP_FRAME.store(previous, Ordering::Relaxed);
+}
+
+/// Returns `true` if a trap is being handled.
+#[inline]
+pub fn in_trap() -> bool {
+    TRAP_FRAME.load(Ordering::Relaxed) != 0
+}
+
+/// Calls the `PanicInTrap` handler if the panic occurred while handling a trap.
+/// Otherwise, it returns so the panic handler can proceed as usual.
+#[inline]
+pub fn check(info: &PanicInfo) {
+    extern "Rust" {
+        fn PanicInTrap(location: Option<&Location>, trap_frame: &TrapFrame) -> !;
+    }
+    let trap_frame = TRAP_FRAME.load(Ordering::Relaxed);
+    if trap_frame != 0 {
+        // SAFETY: the trap frame is valid until the trap handler returns
+        unsafe { PanicInTrap(info.location(), &*(trap_frame as *const TrapFrame)) }
+    }
+}
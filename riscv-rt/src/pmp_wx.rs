@@ -0,0 +1,119 @@
+//! W^X hardening of the runtime sections via PMP.
+//!
+//! When the `pmp-wx` feature is enabled, `_start` programs the following locked PMP entries
+//! right after initializing RAM and before calling `__post_init`:
+//!
+//! | Entries | Address range                   | Permissions |
+//! |---------|---------------------------------|-------------|
+//! | 0, 1    | `.text`                         | `RX`        |
+//! | 2, 3    | `.rodata`                       | `R`         |
+//! | 4, 5    | `.data`, `.bss`, heap, stack    | `RW`        |
+//! | 6, 7    | vector tables (`v-trap` only)   | `RX`        |
+//!
+//! Each address range uses two entries: an `OFF` entry with the start address, and a `TOR` entry
+//! with the end address and the permissions. Entries are locked, so they also apply to M-mode and
+//! cannot be modified until the next reset. The first [`ENTRIES`] PMP entries are used by the
+//! runtime, and the remaining ones are available to the application (e.g., in `#[post_init]`).
+//!
+//! Memory not covered by these entries (e.g., peripherals) remains accessible from M-mode.
+//! The RAM address range spans from `__sdata` to `__sstack`, so `REGION_DATA`, `REGION_BSS`,
+//! `REGION_HEAP`, and `REGION_STACK` must be placed in this order, and must not overlap
+//! `.text` nor `.rodata`. These constraints are checked at link time.
+//!
+//! # Smepmp
+//!
+//! If the `_pmp_mml` linker symbol is set to 1, `_start` also sets the `mseccfg.MML` bit
+//! (Smepmp) once the entries are programmed. With `mseccfg.MML` set, M-mode can only execute
+//! code from `.text` (and the vector tables), and S-mode and U-mode cannot access any of the
+//! runtime sections. Only set this symbol in targets that implement Smepmp, as accessing
+//! `mseccfg` raises an illegal instruction exception otherwise:
+//!
+//! ``` text
+//! PROVIDE(_pmp_mml = 1);
+//! ```
+//!
+//! # Note
+//!
+//! PMP entries have a platform-specific granularity. The boundaries of all the sections above
+//! must be aligned to this granularity, or the corresponding entries will be misconfigured.
+
+/// Number of PMP entries used by the runtime.
+#[cfg(not(feature = "v-trap"))]
+pub const ENTRIES: usize = 6;
+
+/// Number of PMP entries used by the runtime.
+#[cfg(feature = "v-trap")]
+pub const ENTRIES: usize = 8;
+
+/// Programs the PMP entries of the runtime sections.
+///
+/// # Safety
+///
+/// This function must be called only from `_start`, once per hart. Do **NOT** call this function directly.
+#[doc(hidden)]
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+#[no_mangle]
+pub unsafe extern "C" fn _setup_pmp() {
+    use core::ptr::addr_of;
+    use riscv::register::{pmpaddr0, pmpaddr1, pmpaddr2, pmpaddr3, pmpaddr4, pmpaddr5};
+    use riscv::register::{Permission, Range};
+
+    extern "C" {
+        static __stext: u8;
+        static __etext: u8;
+        static __srodata: u8;
+        static __erodata: u8;
+        static __sdata: u8;
+        static __sstack: u8;
+        static _pmp_mml: u8;
+    }
+
+    // PMP address registers hold bits 2.. of the address
+    let addr = |symbol: *const u8| symbol as usize >> 2;
+
+    // Address registers must be written before locking the configuration
+    pmpaddr0::write(addr(addr_of!(__stext)));
+    pmpaddr1::write(addr(addr_of!(__etext)));
+    pmpaddr2::write(addr(addr_of!(__srodata)));
+    pmpaddr3::write(addr(addr_of!(__erodata)));
+    pmpaddr4::write(addr(addr_of!(__sdata)));
+    pmpaddr5::write(addr(addr_of!(__sstack)));
+
+    #[cfg(feature = "v-trap")]
+    {
+        use riscv::register::{pmpaddr6, pmpaddr7};
+
+        extern "C" {
+            static __svector_table: u8;
+            static __evector_table: u8;
+        }
+
+        pmpaddr6::write(addr(addr_of!(__svector_table)));
+        pmpaddr7::write(addr(addr_of!(__evector_table)));
+    }
+
+    let config = [
+        (Range::OFF, Permission::NONE, false),
+        (Range::TOR, Permission::RX, true),
+        (Range::OFF, Permission::NONE, false),
+        (Range::TOR, Permission::R, true),
+        (Range::OFF, Permission::NONE, false),
+        (Range::TOR, Permission::RW, true),
+        (Range::OFF, Permission::NONE, false),
+        (Range::TOR, Permission::RX, true),
+    ];
+    for (index, &(range, permission, locked)) in config.iter().enumerate().take(ENTRIES) {
+        #[cfg(target_arch = "riscv32")]
+        match index {
+            0..=3 => riscv::register::pmpcfg0::set_pmp(index, range, permission, locked),
+            _ => riscv::register::pmpcfg1::set_pmp(index - 4, range, permission, locked),
+        }
+        #[cfg(target_arch = "riscv64")]
+        riscv::register::pmpcfg0::set_pmp(index, range, permission, locked);
+    }
+
+    if addr_of!(_pmp_mml) as usize != 0 {
+        // Set mseccfg.MML
+        core::arch::asm!("csrsi 0x747, 1");
+    }
+}
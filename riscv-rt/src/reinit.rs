@@ -0,0 +1,103 @@
+//! Re-initialization of the trap vector after boot.
+//!
+//! `_setup_interrupts` is called once by the runtime, right before the main function. When the
+//! `reinit-interrupts` feature is enabled, [`reinit_interrupts`] calls it again, so programs can
+//! switch to a new trap layout at runtime (e.g., after a firmware update places new trap handlers
+//! in memory, or in A/B update flows where a custom `_setup_interrupts` selects the active slot).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use riscv::register::mtvec::TrapMode;
+//!
+//! # fn main() {
+//! // the new trap handlers have been copied to memory
+//! riscv_rt::reinit_interrupts(TrapMode::Direct).expect("invalid trap vector");
+//! # }
+//! ```
+//!
+//! # Note
+//!
+//! Runtime [exception redirections](crate::exceptions) are not modified, so they must be updated
+//! separately if they point to handlers of the previous trap layout.
+
+pub use riscv::register::mtvec::TrapMode;
+use riscv_pac::result::{Error, Result};
+
+#[cfg(feature = "s-mode")]
+use riscv::{interrupt::supervisor as xinterrupt, register::stvec as xtvec};
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::{interrupt::machine as xinterrupt, register::mtvec as xtvec};
+
+/// Required alignment of the vector table in vectored mode.
+const VECTORED_ALIGN: usize = 4;
+
+/// Calls `_setup_interrupts` again and checks the resulting trap vector (`mtvec`, or `stvec` in S-mode).
+///
+/// This function runs with interrupts disabled in the current hart, so no trap is taken while the
+/// trap vector is updated. Before calling `_setup_interrupts`, it synchronizes the data and instruction
+/// streams with `fence` and `fence.i`, as the new trap handlers may have been written to memory by
+/// the current program.
+///
+/// Then, it checks that the trap vector uses the expected trap `mode`, and that its base address is
+/// aligned to `_trap_align` bytes (direct mode) or 4 bytes (vectored mode). If any of these checks
+/// fails, the previous trap vector is restored.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFieldVariant`] if the trap mode of the new trap vector is not `mode`.
+/// - [`Error::InvalidAlignment`] if the base address of the new trap vector is not properly aligned.
+pub fn reinit_interrupts(mode: TrapMode) -> Result<()> {
+    extern "C" {
+        fn _setup_interrupts();
+        static _trap_align: u8;
+    }
+
+    // `_trap_align` is an absolute symbol, so its address is its value
+    #[allow(unused_unsafe)] // no longer unsafe since rust 1.82.0
+    let trap_align = unsafe { core::ptr::addr_of!(_trap_align) } as usize;
+
+    xinterrupt::free(|| {
+        let previous = xtvec::read();
+
+        riscv::asm::fence();
+        riscv::asm::fence_i();
+        // SAFETY: `_setup_interrupts` is provided by the runtime or overridden by the user
+        unsafe { _setup_interrupts() };
+
+        let result = check(xtvec::read().bits(), mode, trap_align);
+        if result.is_err() {
+            // SAFETY: the previous trap vector was valid before calling `_setup_interrupts`
+            unsafe {
+                xtvec::write(
+                    previous.address(),
+                    previous.trap_mode().unwrap_or(TrapMode::Direct),
+                )
+            };
+        }
+        result
+    })
+}
+
+/// Checks the trap mode and the alignment of the base address of the raw trap vector `bits`.
+fn check(bits: usize, mode: TrapMode, trap_align: usize) -> Result<()> {
+    if bits & 0b11 != mode as usize {
+        return Err(Error::InvalidFieldVariant {
+            field: "mode",
+            value: bits & 0b11,
+        });
+    }
+    let address = bits & !0b11;
+    let alignment = match mode {
+        TrapMode::Direct => trap_align,
+        TrapMode::Vectored => VECTORED_ALIGN,
+    };
+    if address % alignment != 0 {
+        return Err(Error::InvalidAlignment {
+            value: address,
+            alignment,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Stack painting and high-water-mark measurement.
+//!
+//! With the `stack-paint` feature, every hart fills its stack area with `_stack_paint_pattern`
+//! at startup, before using the stack. Later, [`stack_usage`] scans the area from its bottom
+//! for the first word that does not match the pattern, which gives the maximum stack depth
+//! reached so far (i.e., the high-water mark).
+//!
+//! The stack area of hart `N` is the `_hart_stack_size` bytes below `_stack_start - N * _hart_stack_size`.
+//! In single-hart targets, the stack may grow beyond this area (e.g., into the heap), but only the
+//! first `_hart_stack_size` bytes are painted and measured.
+//!
+//! # Note
+//!
+//! The measurement underestimates the stack usage if the stack contains words equal to the pattern
+//! (e.g., uninitialized local variables). Choose a pattern that is unlikely to appear in the stack.
+//! With the `rnmi` feature, the RNMI stack at the top of the hart stack area is also measured.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! # fn main() {
+//! let used = riscv_rt::stack_usage(0);
+//! # let _ = used;
+//! # }
+//! ```
+
+use core::ptr::{addr_of, read_volatile};
+
+/// Returns the maximum number of bytes of the stack area of `hart_id` that have been used since startup.
+///
+/// # Panics
+///
+/// Panics if `hart_id` is greater than `_max_hart_id`.
+pub fn stack_usage(hart_id: usize) -> usize {
+    extern "C" {
+        static _stack_start: u8;
+        static _hart_stack_size: u8;
+        static _max_hart_id: u8;
+        /// Word with the value of `_stack_paint_pattern`, defined in the startup code.
+        static _stack_paint_word: u32;
+    }
+
+    // SAFETY: only the addresses of the linker symbols are used, and `_stack_paint_word` is read-only
+    #[allow(unused_unsafe)] // no longer unsafe since rust 1.82.0
+    let (stack_start, stack_size, max_hart_id, pattern) = unsafe {
+        (
+            addr_of!(_stack_start) as usize,
+            addr_of!(_hart_stack_size) as usize,
+            addr_of!(_max_hart_id) as usize,
+            read_volatile(addr_of!(_stack_paint_word)),
+        )
+    };
+    assert!(hart_id <= max_hart_id, "invalid hart ID {hart_id}");
+
+    let top = stack_start - hart_id * stack_size;
+    // The bottom is aligned to 4 bytes, as in the startup code
+    let bottom = (top - stack_size + 3) & !3;
+    let unused = (bottom..top)
+        .step_by(4)
+        // SAFETY: the stack area of the hart is valid and aligned to 4 bytes
+        .take_while(|&addr| unsafe { read_volatile(addr as *const u32) } == pattern)
+        .count();
+    (top - bottom).saturating_sub(4 * unused)
+}
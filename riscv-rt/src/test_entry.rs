@@ -0,0 +1,74 @@
+//! Returning entry point for test programs.
+//!
+//! With the `test-entry` feature, the function marked with [`#[entry]`](crate::entry) may return.
+//! Its return value must implement [`Termination`], which converts it into an exit code.
+//! Then, the runtime calls [`exit`] with that code.
+//!
+//! The default `_exit` implementation aborts with [`crate::AbortReason::Exit`], which loops forever by default. Test programs usually
+//! override it to report the exit code to the host (e.g., via semihosting):
+//!
+//! ```ignore
+//! use riscv_semihosting::debug::{self, EXIT_FAILURE, EXIT_SUCCESS};
+//!
+//! #[export_name = "_exit"]
+//! fn exit(code: i32) -> ! {
+//!     debug::exit(if code == 0 { EXIT_SUCCESS } else { EXIT_FAILURE });
+//!     loop {}
+//! }
+//!
+//! #[riscv_rt::entry]
+//! fn main() -> Result<(), MyError> {
+//!     run_tests()?;
+//!     Ok(())
+//! }
+//! ```
+
+/// Return types of the `main` function in `test-entry` mode.
+pub trait Termination {
+    /// Converts `self` into an exit code. By convention, 0 means success.
+    fn report(self) -> i32;
+}
+
+impl Termination for () {
+    #[inline]
+    fn report(self) -> i32 {
+        0
+    }
+}
+
+impl Termination for i32 {
+    #[inline]
+    fn report(self) -> i32 {
+        self
+    }
+}
+
+impl Termination for core::convert::Infallible {
+    #[inline]
+    fn report(self) -> i32 {
+        match self {}
+    }
+}
+
+impl<T: Termination, E> Termination for Result<T, E> {
+    #[inline]
+    fn report(self) -> i32 {
+        match self {
+            Ok(val) => val.report(),
+            Err(_) => 1,
+        }
+    }
+}
+
+/// Terminates the program with the given exit code.
+///
+/// It calls the `_exit` function, which users can override with `#[export_name = "_exit"]`.
+/// By default, `_exit` aborts with [`crate::AbortReason::Exit`].
+#[inline]
+pub fn exit(code: i32) -> ! {
+    extern "Rust" {
+        fn _exit(code: i32) -> !;
+    }
+    // SAFETY: `_exit` is provided by the runtime or overridden by the user
+    unsafe { _exit(code) }
+}
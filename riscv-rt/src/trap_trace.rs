@@ -0,0 +1,173 @@
+//! Trap tracing for debugging purposes.
+//!
+//! When the `trap-trace` feature is enabled, every trap entry is recorded in a per-hart ring buffer
+//! with the last [`TRACE_LEN`] traps. Each entry contains the value of `mcause`/`scause`, the value
+//! of `mepc`/`sepc`, and the cycle counter at the time of the trap. The [`snapshot`] function returns
+//! a copy of the ring buffer of a hart, which is useful for debugging spurious interrupt storms.
+//!
+//! Traps are recorded by [`crate::start_trap_rust`]. In vectored mode (i.e., `v-trap` feature enabled),
+//! core interrupts are also recorded by the `_continue_interrupt_trap` assembly routine.
+//!
+//! # Harts
+//!
+//! In M-mode, traps are recorded in the ring buffer of the hart which ID is `mhartid`. By default,
+//! only the traps of hart 0 are recorded. Set the `RISCV_RT_TRAP_TRACE_HARTS` environment variable
+//! to the number of harts to trace (i.e., [`TRACE_HARTS`]) when building the crate.
+//!
+//! In S-mode, the hart ID is not available to the runtime. Thus, the `trap-trace` feature
+//! requires the `single-hart` feature in S-mode. With the `single-hart` feature enabled,
+//! all the traps are recorded in the ring buffer of hart 0 regardless of the hart ID.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! riscv::interrupt::free(|| {
+//!     let trace = riscv_rt::trap_trace::snapshot(0).unwrap();
+//!     for entry in trace.entries() {
+//!         // print the entry...
+//!     }
+//! });
+//! ```
+
+use crate::xcause;
+use core::cell::UnsafeCell;
+use riscv_pac::result::{Error, Result};
+
+#[cfg(feature = "s-mode")]
+use riscv::register::{cycle as xcycle, sepc as xepc};
+
+#[cfg(not(feature = "s-mode"))]
+use riscv::register::{mcycle as xcycle, mepc as xepc};
+
+include!(concat!(env!("OUT_DIR"), "/trap_trace.rs"));
+
+/// Number of entries of the ring buffer of each hart.
+pub const TRACE_LEN: usize = 16;
+
+/// Trap trace entry.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TraceEntry {
+    /// Value of `mcause`/`scause` at trap entry.
+    pub cause: usize,
+    /// Value of `mepc`/`sepc` at trap entry.
+    pub epc: usize,
+    /// Value of the cycle counter at trap entry (`mcycle` in M-mode, `cycle` in S-mode).
+    pub cycle: u64,
+}
+
+/// Ring buffer with the last traps of a hart.
+struct TraceBuffer {
+    entries: UnsafeCell<[TraceEntry; TRACE_LEN]>,
+    /// Number of traps recorded so far (wrapping).
+    count: UnsafeCell<usize>,
+}
+
+// SAFETY: each ring buffer is only written by its own hart, with interrupts disabled
+unsafe impl Sync for TraceBuffer {}
+
+/// Empty ring buffer.
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_BUFFER: TraceBuffer = TraceBuffer {
+    entries: UnsafeCell::new(
+        [TraceEntry {
+            cause: 0,
+            epc: 0,
+            cycle: 0,
+        }; TRACE_LEN],
+    ),
+    count: UnsafeCell::new(0),
+};
+
+/// Ring buffers of all the traced harts.
+static BUFFERS: [TraceBuffer; TRACE_HARTS] = [EMPTY_BUFFER; TRACE_HARTS];
+
+/// Copy of the ring buffer of a hart.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TraceSnapshot {
+    entries: [TraceEntry; TRACE_LEN],
+    len: usize,
+    count: usize,
+}
+
+impl TraceSnapshot {
+    /// Returns the recorded entries, sorted from the oldest to the most recent trap.
+    #[inline]
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries[..self.len]
+    }
+
+    /// Returns the total number of traps recorded (wrapping), including overwritten entries.
+    #[inline]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Returns a copy of the ring buffer of the hart which ID is `hart_id`.
+///
+/// # Errors
+///
+/// If `hart_id` is not lower than [`TRACE_HARTS`], it returns [`Error::IndexOutOfBounds`].
+///
+/// # Note
+///
+/// If the hart records a trap while copying its ring buffer, the snapshot may be inconsistent.
+/// To get a consistent snapshot of the current hart, call this function with interrupts disabled.
+#[inline]
+pub fn snapshot(hart_id: usize) -> Result<TraceSnapshot> {
+    let buffer = BUFFERS.get(hart_id).ok_or(Error::IndexOutOfBounds {
+        index: hart_id,
+        min: 0,
+        max: TRACE_HARTS - 1,
+    })?;
+    // SAFETY: volatile reads of valid memory
+    let (raw, count) = unsafe {
+        (
+            buffer.entries.get().read_volatile(),
+            buffer.count.get().read_volatile(),
+        )
+    };
+    let len = count.min(TRACE_LEN);
+    let mut entries = [TraceEntry::default(); TRACE_LEN];
+    // the oldest entry is the next one to be overwritten
+    let oldest = count.wrapping_sub(len);
+    for (i, entry) in entries.iter_mut().take(len).enumerate() {
+        *entry = raw[oldest.wrapping_add(i) % TRACE_LEN];
+    }
+    Ok(TraceSnapshot {
+        entries,
+        len,
+        count,
+    })
+}
+
+/// Records the current trap in the ring buffer of the current hart.
+///
+/// # Safety
+///
+/// This function must be called only from trap entry routines, with interrupts disabled.
+/// Do **NOT** call this function directly.
+#[doc(hidden)]
+#[cfg_attr(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    link_section = ".trap.rust"
+)]
+#[no_mangle]
+pub unsafe extern "C" fn _trap_trace_record() {
+    #[cfg(any(feature = "single-hart", feature = "s-mode"))]
+    let hart_id = 0;
+    #[cfg(not(any(feature = "single-hart", feature = "s-mode")))]
+    let hart_id = riscv::register::mhartid::read();
+
+    if let Some(buffer) = BUFFERS.get(hart_id) {
+        let entry = TraceEntry {
+            cause: xcause::read().bits(),
+            epc: xepc::read(),
+            cycle: xcycle::read64(),
+        };
+        let count = buffer.count.get().read_volatile();
+        let entries = buffer.entries.get().cast::<TraceEntry>();
+        entries.add(count % TRACE_LEN).write_volatile(entry);
+        buffer.count.get().write_volatile(count.wrapping_add(1));
+    }
+}
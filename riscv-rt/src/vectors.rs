@@ -0,0 +1,106 @@
+//! Integrity verification of the trap dispatch tables.
+//!
+//! [`vectors_crc`] computes a CRC-32 (IEEE 802.3) over the following tables, in this order:
+//!
+//! - The vector tables in the `.vector_table` output section (`v-trap` feature only).
+//! - The `__CORE_INTERRUPTS` array (unless the `v-trap` or `no-interrupts` features are enabled).
+//! - The `__EXCEPTIONS` array (unless the `no-exceptions` feature is enabled).
+//!
+//! [`verify_vectors`] compares this CRC with the value of the `_vector_table_crc` word,
+//! which allows detecting bit flips in the memory holding the tables or accidental overwrites at runtime.
+//!
+//! # Expected CRC
+//!
+//! The linker cannot compute checksums of the contents of output sections. Thus, the expected CRC
+//! is written to `_vector_table_crc` after linking, with the `vectors-crc` task of the `xtask`
+//! crate of the `riscv` repository:
+//!
+//! ``` text
+//! cargo xtask vectors-crc target/riscv32imac-unknown-none-elf/release/app
+//! ```
+//!
+//! The task computes the CRC of the tables from the contents of the ELF file, and patches the
+//! `_vector_table_crc` word in place. Thus, it must be run on the final ELF file, before
+//! converting it to other formats (e.g., with `objcopy`). The word is only present in binaries
+//! that call [`verify_vectors`]. If it is not patched, it is 0 and [`verify_vectors`] returns `false`.
+//!
+//! # Note
+//!
+//! The tables contain the addresses of the trap handlers, so the expected CRC must be
+//! computed again whenever the binary changes (e.g., for debug and release builds).
+
+use core::ptr::addr_of;
+
+/// Updates the CRC-32 `crc` with `len` bytes starting at `ptr`.
+///
+/// Bytes are read with volatile accesses, so the compiler cannot assume the contents of the tables.
+///
+/// # Safety
+///
+/// `len` bytes starting at `ptr` must be readable.
+#[cfg_attr(
+    all(
+        not(feature = "v-trap"),
+        feature = "no-interrupts",
+        feature = "no-exceptions"
+    ),
+    allow(dead_code)
+)]
+unsafe fn update(mut crc: u32, ptr: *const u8, len: usize) -> u32 {
+    /// CRC-32 (IEEE 802.3) reversed polynomial.
+    const POLY: u32 = 0xEDB8_8320;
+
+    for i in 0..len {
+        crc ^= ptr.add(i).read_volatile() as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (POLY & (crc & 1).wrapping_neg());
+        }
+    }
+    crc
+}
+
+/// Returns the CRC-32 of the trap dispatch tables of the binary.
+///
+/// Check the [module documentation](self) for more details about the tables covered by the CRC.
+pub fn vectors_crc() -> u32 {
+    let crc = !0;
+
+    #[cfg(feature = "v-trap")]
+    let crc = unsafe {
+        extern "C" {
+            static __svector_table: u8;
+            static __evector_table: u8;
+        }
+        let start = addr_of!(__svector_table);
+        let len = addr_of!(__evector_table) as usize - start as usize;
+        update(crc, start, len)
+    };
+
+    #[cfg(not(any(feature = "v-trap", feature = "no-interrupts")))]
+    let crc = unsafe {
+        let table = addr_of!(crate::interrupts::__CORE_INTERRUPTS);
+        update(crc, table.cast(), core::mem::size_of_val(&*table))
+    };
+
+    #[cfg(not(feature = "no-exceptions"))]
+    let crc = unsafe {
+        let table = addr_of!(crate::exceptions::__EXCEPTIONS);
+        update(crc, table.cast(), core::mem::size_of_val(&*table))
+    };
+
+    !crc
+}
+
+/// Expected CRC-32 of the trap dispatch tables, patched in the ELF file after linking.
+#[export_name = "_vector_table_crc"]
+static VECTOR_TABLE_CRC: u32 = 0;
+
+/// Returns `true` if the CRC-32 of the trap dispatch tables matches the `_vector_table_crc` word.
+///
+/// It returns `false` if the tables were modified, or if `_vector_table_crc` was not patched.
+/// Check the [module documentation](self) for more details about how to patch the expected CRC.
+pub fn verify_vectors() -> bool {
+    // The word is patched after linking, so the compiler must not assume its value
+    let expected = unsafe { addr_of!(VECTOR_TABLE_CRC).read_volatile() };
+    expected != 0 && vectors_crc() == expected
+}
@@ -7,5 +7,8 @@ fn main() {
 
     if target.starts_with("riscv") {
         println!("cargo:rustc-cfg=riscv");
+    } else if target.contains("-none") {
+        // Host builds (e.g., unit tests) are expected to use the stub, but bare-metal builds are likely a mistake
+        println!("cargo:warning=riscv-semihosting: `{target}` is not a RISC-V target, so semihosting operations are replaced by a stub that only captures the output in memory");
     }
 }
@@ -0,0 +1,67 @@
+//! Command line of the program
+//!
+//! On-target test suites can be parameterized from the host (e.g., which peripheral to exercise)
+//! without rebuilding them. The command line is retrieved via the `GET_CMDLINE` semihosting
+//! operation, and split into whitespace-separated arguments. Arguments of the form `key=value`
+//! can be looked up with [`var`].
+//!
+//! In QEMU, the command line of the program is set with the `-append` option:
+//!
+//! ``` text
+//! $ qemu-system-riscv64 -machine virt -semihosting-config enable=on,target=native -kernel app -append "uart=1 verbose"
+//! ```
+//!
+//! `cargo xtask qemu` forwards the arguments given after `--` to the QEMU examples.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use riscv_semihosting::env;
+//!
+//! let mut buf = [0; env::MAX_CMDLINE_LEN];
+//! let cmdline = env::cmdline(&mut buf).unwrap_or("");
+//! let uart = env::var(cmdline, "uart").unwrap_or("0");
+//! let verbose = env::args(cmdline).any(|arg| arg == "verbose");
+//! ```
+
+// Errors are reported as in the `hio` module
+#![allow(clippy::result_unit_err)]
+
+use crate::nr;
+
+/// Recommended size (in bytes) of the buffer passed to [`cmdline`].
+pub const MAX_CMDLINE_LEN: usize = 256;
+
+/// Retrieves the command line of the program into `buf`.
+///
+/// The command line is truncated to the length of `buf`. It returns an error if the
+/// host does not provide a command line or if it is not valid UTF-8.
+pub fn cmdline(buf: &mut [u8]) -> Result<&str, ()> {
+    // The host writes the length of the command line in the second word of the parameter block
+    let mut block = [buf.as_mut_ptr() as usize, buf.len()];
+    match unsafe { crate::syscall1(nr::GET_CMDLINE, block.as_mut_ptr() as usize) } {
+        0 => {}
+        _ => return Err(()),
+    }
+    let len = block[1].min(buf.len());
+    core::str::from_utf8(&buf[..len]).map_err(drop)
+}
+
+/// Returns an iterator over the whitespace-separated arguments of `cmdline`.
+///
+/// By convention, the first argument is the name of the program. Quotes are not interpreted.
+pub fn args(cmdline: &str) -> core::str::SplitWhitespace<'_> {
+    cmdline.split_whitespace()
+}
+
+/// Returns an iterator over the `key=value` arguments of `cmdline` as `(key, value)` pairs.
+///
+/// Arguments without `=` are skipped. Values may be empty (e.g., `key=`).
+pub fn vars(cmdline: &str) -> impl Iterator<Item = (&str, &str)> {
+    args(cmdline).filter_map(|arg| arg.split_once('='))
+}
+
+/// Returns the value of the first `key=value` argument of `cmdline`, if any.
+pub fn var<'a>(cmdline: &'a str, key: &str) -> Option<&'a str> {
+    vars(cmdline).find(|(k, _)| *k == key).map(|(_, v)| v)
+}
@@ -17,6 +17,14 @@ impl HostStream {
     pub fn write_all(&mut self, buffer: &[u8]) -> Result<(), ()> {
         write_all(self.fd, buffer)
     }
+
+    /// Closes this stream.
+    pub(crate) fn close(self) -> Result<(), ()> {
+        match unsafe { syscall!(CLOSE, self.fd) } {
+            0 => Ok(()),
+            _ => Err(()),
+        }
+    }
 }
 
 impl fmt::Write for HostStream {
@@ -39,7 +47,8 @@ pub fn hstdout() -> Result<HostStream, ()> {
     open(":tt\0", nr::open::W_TRUNC)
 }
 
-fn open(name: &str, mode: usize) -> Result<HostStream, ()> {
+/// Opens the host file `name`, which must be null-terminated.
+pub(crate) fn open(name: &str, mode: usize) -> Result<HostStream, ()> {
     let name = name.as_bytes();
     match unsafe { syscall!(OPEN, name.as_ptr(), mode, name.len() - 1) } as isize {
         -1 => Err(()),
@@ -0,0 +1,159 @@
+//! Host stub for non-RISC-V targets
+//!
+//! Semihosting operations are only available in RISC-V targets. When this crate is built for other
+//! targets (e.g., to run unit tests or build the documentation on the host), [`syscall1`](crate::syscall1)
+//! emulates the operations used for printing, so crates that target both RISC-V and the host can run
+//! unit tests that exercise their logging paths:
+//!
+//! - `OPEN` of `:tt` returns a handle to the host standard output (`w` mode) or error (`a` mode).
+//!   Opening any other file fails.
+//! - `WRITE`, `WRITEC`, and `WRITE0` append the output to a capture buffer of [`CAPACITY`] bytes
+//!   per stream. Output that does not fit in the buffer is discarded.
+//! - `CLOSE` always succeeds.
+//! - Any other operation fails (i.e., it returns -1). Thus, `debug::exit` returns normally.
+//!
+//! Tests can inspect the captured output with [`with_stdout`] and [`with_stderr`], and discard it with [`clear`].
+//!
+//! # Example
+//!
+//! ```
+//! use core::fmt::Write;
+//! use riscv_semihosting::{hio, host};
+//!
+//! host::clear();
+//! write!(hio::hstdout().unwrap(), "answer: {}", 42).unwrap();
+//! host::with_stdout(|output| assert_eq!(output, b"answer: 42"));
+//! ```
+//!
+//! # Note
+//!
+//! The capture buffers are shared by all the threads of the program. As the test harness runs tests
+//! in parallel, tests that check the captured output must not run concurrently with other tests that
+//! print (e.g., group them in a single test, or run the tests with `--test-threads=1`).
+//!
+//! The [`hprintln!`](crate::hprintln) family of macros enters a critical section before printing.
+//! Thus, the program must provide a `critical-section` implementation for the host (e.g., by enabling
+//! the `std` feature of the `critical-section` crate in `dev-dependencies`).
+
+use crate::nr;
+use core::{
+    ptr::addr_of_mut,
+    slice,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Capacity (in bytes) of the capture buffer of each stream.
+pub const CAPACITY: usize = 4096;
+
+/// Handle of the host standard output.
+const STDOUT: usize = 1;
+/// Handle of the host standard error.
+const STDERR: usize = 2;
+
+/// Captured output of a stream.
+struct Buffer {
+    data: [u8; CAPACITY],
+    len: usize,
+}
+
+impl Buffer {
+    const fn new() -> Self {
+        Self {
+            data: [0; CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Appends as many bytes of `bytes` as possible to the buffer.
+    fn push(&mut self, bytes: &[u8]) {
+        let n = bytes.len().min(CAPACITY - self.len);
+        self.data[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+    }
+
+    /// Returns the captured bytes.
+    fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+static LOCK: AtomicBool = AtomicBool::new(false);
+static mut BUFFERS: [Buffer; 2] = [Buffer::new(), Buffer::new()];
+
+/// Executes `f` with exclusive access to the capture buffers of the standard output and error.
+fn with_buffers<R>(f: impl FnOnce(&mut [Buffer; 2]) -> R) -> R {
+    while LOCK
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+    // SAFETY: the lock ensures exclusive access to the buffers
+    let r = f(unsafe { &mut *addr_of_mut!(BUFFERS) });
+    LOCK.store(false, Ordering::Release);
+    r
+}
+
+/// Appends `bytes` to the capture buffer of the stream with handle `fd`.
+fn write(fd: usize, bytes: &[u8]) {
+    with_buffers(|buffers| buffers[fd - STDOUT].push(bytes));
+}
+
+/// Executes `f` with the output captured from the host standard output.
+pub fn with_stdout<R>(f: impl FnOnce(&[u8]) -> R) -> R {
+    with_buffers(|buffers| f(buffers[0].as_bytes()))
+}
+
+/// Executes `f` with the output captured from the host standard error.
+pub fn with_stderr<R>(f: impl FnOnce(&[u8]) -> R) -> R {
+    with_buffers(|buffers| f(buffers[1].as_bytes()))
+}
+
+/// Discards the output captured from the host standard output and error.
+pub fn clear() {
+    with_buffers(|buffers| buffers.iter_mut().for_each(|buffer| buffer.len = 0));
+}
+
+/// Emulates the semihosting operation `nr` with argument `arg`.
+///
+/// # Safety
+///
+/// The arguments must be valid for the associated operation, as in [`syscall1`](crate::syscall1).
+pub(crate) unsafe fn syscall(nr: usize, arg: usize) -> usize {
+    let args = arg as *const usize;
+    match nr {
+        nr::OPEN => {
+            let name = slice::from_raw_parts(*args as *const u8, *args.add(2));
+            match (name, *args.add(1)) {
+                (b":tt", nr::open::W_TRUNC) => STDOUT,
+                (b":tt", nr::open::W_APPEND) => STDERR,
+                _ => usize::MAX,
+            }
+        }
+        nr::CLOSE => 0,
+        nr::WRITE => match *args {
+            fd @ (STDOUT | STDERR) => {
+                write(
+                    fd,
+                    slice::from_raw_parts(*args.add(1) as *const u8, *args.add(2)),
+                );
+                0
+            }
+            _ => usize::MAX,
+        },
+        nr::WRITEC => {
+            write(STDOUT, slice::from_raw_parts(arg as *const u8, 1));
+            0
+        }
+        nr::WRITE0 => {
+            let ptr = arg as *const u8;
+            let mut len = 0;
+            while *ptr.add(len) != 0 {
+                len += 1;
+            }
+            write(STDOUT, slice::from_raw_parts(ptr, len));
+            0
+        }
+        _ => usize::MAX,
+    }
+}
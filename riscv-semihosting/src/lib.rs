@@ -169,6 +169,26 @@
 //!
 //! When this feature is enabled, the underlying system calls are patched out.
 //!
+//! ## `panic-handler`
+//!
+//! When this feature is enabled, this crate provides a `#[panic_handler]` for RISC-V targets
+//! that disables interrupts, prints the panic message to the host stderr, and loops forever.
+//! With the `no-semihosting` feature, the panic message is not printed.
+//! Check the `panic` module documentation for more details.
+//!
+//! ## `panic-exit`
+//!
+//! When this feature is enabled, the panic handler of the `panic-handler` feature (implied by this feature)
+//! exits with `EXIT_FAILURE` after printing the panic message (e.g., to terminate QEMU sessions with exit code 1).
+//!
+//! # Host builds
+//!
+//! When this crate is built for targets other than RISC-V (e.g., to run unit tests on the host),
+//! semihosting operations are replaced by a stub that captures the output printed to the host
+//! standard output and error in memory. Tests can inspect the captured output with the `host` module.
+//! Building for non-RISC-V bare-metal targets emits a compile-time warning, as the output is not
+//! printed anywhere.
+//!
 //! # Reference
 //!
 //! For documentation about the semihosting operations, check
@@ -187,10 +207,16 @@ use core::arch::asm;
 mod macros;
 
 pub mod debug;
+pub mod env;
 #[doc(hidden)]
 pub mod export;
 pub mod hio;
+#[cfg(not(riscv))]
+pub mod host;
 pub mod nr;
+#[cfg(all(riscv, feature = "panic-handler"))]
+pub mod panic;
+pub mod report;
 
 /// Performs a semihosting operation, takes a pointer to an argument block
 ///
@@ -239,6 +265,6 @@ pub unsafe fn syscall1(_nr: usize, _arg: usize) -> usize {
         #[cfg(all(riscv, feature = "no-semihosting"))]
         () => 0,
         #[cfg(not(riscv))]
-        () => unimplemented!(),
+        () => host::syscall(_nr, _arg),
     }
 }
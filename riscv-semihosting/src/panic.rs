@@ -0,0 +1,42 @@
+//! Panic handler that prints the panic message through semihosting.
+//!
+//! When the `panic-handler` feature is enabled, this crate provides a `#[panic_handler]` that:
+//!
+//! 1. Disables interrupts (unless the `u-mode` feature is enabled), so the panic is not preempted.
+//! 2. Prints the panic message to the host stderr (unless the `no-semihosting` feature is enabled).
+//! 3. If the `panic-exit` feature is enabled, exits with [`EXIT_FAILURE`](crate::debug::EXIT_FAILURE).
+//!    This terminates QEMU sessions with exit code 1, which is useful for automated tests.
+//! 4. Otherwise (or if the debugger requests the application to continue), loops forever.
+//!
+//! Thus, applications only need to link this crate to get a panic handler:
+//!
+//! ``` ignore
+//! use riscv_semihosting as _;
+//! ```
+//!
+//! # Note
+//!
+//! The panic handler is only defined for RISC-V targets, so host tests still use the standard library handler.
+
+use core::{
+    panic::PanicInfo,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    #[cfg(not(feature = "u-mode"))]
+    riscv::interrupt::disable();
+
+    #[cfg(not(feature = "no-semihosting"))]
+    heprintln!("{}", _info);
+
+    #[cfg(feature = "panic-exit")]
+    crate::debug::exit(crate::debug::EXIT_FAILURE);
+
+    loop {
+        // add some side effect to prevent this from turning into a UDF instruction
+        // see rust-lang/rust#28728 for details
+        compiler_fence(Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,244 @@
+//! Structured test reports written to a host file
+//!
+//! This module writes the results of on-target tests to a file of the host, so CI pipelines can
+//! collect them as artifacts (e.g., from QEMU runs). The path of the report is taken from the
+//! `report=<path>` argument of the command line of the program, which is retrieved via the
+//! `GET_CMDLINE` semihosting operation. The format of the report depends on the extension of the
+//! path: [JUnit XML](Format::JUnit) for `.xml` files, and [JSON](Format::Json) otherwise.
+//!
+//! In QEMU, the command line of the program is set with the `-append` option:
+//!
+//! ``` text
+//! $ qemu-system-riscv64 -machine virt -semihosting-config enable=on,target=native -kernel app -append "report=report.xml"
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use riscv_semihosting::report::{Outcome, Report};
+//!
+//! let mut report = Report::open("arith").unwrap();
+//! report.test("addition", Outcome::Passed).unwrap();
+//! report.test("division", Outcome::Failed("division by zero")).unwrap();
+//! report.test("overflow", Outcome::Skipped).unwrap();
+//! report.finish().unwrap();
+//! ```
+//!
+//! The JSON report of this example is:
+//!
+//! ``` text
+//! {"suite":"arith","tests":[{"name":"addition","outcome":"passed"},{"name":"division","outcome":"failed","message":"division by zero"},{"name":"overflow","outcome":"skipped"}],"passed":1,"failed":1,"skipped":1}
+//! ```
+//!
+//! And the JUnit XML report is:
+//!
+//! ``` text
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <testsuite name="arith">
+//!   <testcase name="addition"/>
+//!   <testcase name="division"><failure message="division by zero"/></testcase>
+//!   <testcase name="overflow"><skipped/></testcase>
+//! </testsuite>
+//! ```
+//!
+//! Test results are written as soon as they are reported, so the `testsuite` element of JUnit XML
+//! reports does not contain the number of tests. JUnit consumers compute it from the `testcase` elements.
+
+// Errors are reported as in the `hio` module
+#![allow(clippy::result_unit_err)]
+
+use crate::{
+    env,
+    hio::{self, HostStream},
+    nr,
+};
+use core::fmt::{self, Write};
+
+/// Maximum length (in bytes) of the command line of the program and the path of the report.
+pub const MAX_PATH_LEN: usize = 256;
+
+/// Key of the command line argument with the path of the report.
+const REPORT_KEY: &str = "report";
+
+/// Format of a test report.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// A single JSON object with the suite name, the test results, and the number of tests per outcome.
+    Json,
+    /// A JUnit XML `testsuite` element.
+    JUnit,
+}
+
+impl Format {
+    /// Returns the format of a report depending on the extension of its `path`.
+    pub fn from_path(path: &str) -> Self {
+        match path.ends_with(".xml") {
+            true => Self::JUnit,
+            false => Self::Json,
+        }
+    }
+}
+
+/// Outcome of a test.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome<'a> {
+    /// The test passed.
+    Passed,
+    /// The test failed with a message.
+    Failed(&'a str),
+    /// The test was not run.
+    Skipped,
+}
+
+/// A test report written to a host file.
+///
+/// The report is only complete once [`Report::finish`] is called.
+pub struct Report {
+    stream: HostStream,
+    format: Format,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl Report {
+    /// Creates the report of the test suite `suite` in the path given by the
+    /// `report=<path>` argument of the command line of the program.
+    ///
+    /// It returns an error if the command line is not available or does not contain the argument.
+    pub fn open(suite: &str) -> Result<Self, ()> {
+        let mut cmdline = [0; MAX_PATH_LEN];
+        let path = report_path(&mut cmdline)?;
+        Self::create(path, suite, Format::from_path(path))
+    }
+
+    /// Creates the report of the test suite `suite` in the host file `path` with the given `format`.
+    ///
+    /// If the file already exists, it is truncated.
+    pub fn create(path: &str, suite: &str, format: Format) -> Result<Self, ()> {
+        // The host expects a null-terminated path
+        let mut name = [0; MAX_PATH_LEN];
+        let len = path.len();
+        if len >= MAX_PATH_LEN {
+            return Err(());
+        }
+        name[..len].copy_from_slice(path.as_bytes());
+        let name = core::str::from_utf8(&name[..=len]).map_err(drop)?;
+
+        let mut report = Self {
+            stream: hio::open(name, nr::open::W_TRUNC)?,
+            format,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+        };
+        let suite = Escaped(suite, format);
+        match format {
+            Format::Json => report.write(format_args!("{{\"suite\":\"{suite}\",\"tests\":["))?,
+            Format::JUnit => report.write(format_args!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuite name=\"{suite}\">\n"
+            ))?,
+        }
+        Ok(report)
+    }
+
+    /// Returns the number of tests reported so far.
+    pub fn len(&self) -> usize {
+        self.passed + self.failed + self.skipped
+    }
+
+    /// Returns `true` if no tests have been reported yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reports the outcome of the test `name`.
+    pub fn test(&mut self, name: &str, outcome: Outcome) -> Result<(), ()> {
+        let format = self.format;
+        let name = Escaped(name, format);
+        match format {
+            Format::Json => {
+                if !self.is_empty() {
+                    self.write(format_args!(","))?;
+                }
+                match outcome {
+                    Outcome::Passed => self.write(format_args!(
+                        "{{\"name\":\"{name}\",\"outcome\":\"passed\"}}"
+                    )),
+                    Outcome::Failed(msg) => self.write(format_args!(
+                        "{{\"name\":\"{name}\",\"outcome\":\"failed\",\"message\":\"{}\"}}",
+                        Escaped(msg, format)
+                    )),
+                    Outcome::Skipped => self.write(format_args!(
+                        "{{\"name\":\"{name}\",\"outcome\":\"skipped\"}}"
+                    )),
+                }?
+            }
+            Format::JUnit => match outcome {
+                Outcome::Passed => self.write(format_args!("  <testcase name=\"{name}\"/>\n")),
+                Outcome::Failed(msg) => self.write(format_args!(
+                    "  <testcase name=\"{name}\"><failure message=\"{}\"/></testcase>\n",
+                    Escaped(msg, format)
+                )),
+                Outcome::Skipped => self.write(format_args!(
+                    "  <testcase name=\"{name}\"><skipped/></testcase>\n"
+                )),
+            }?,
+        }
+        match outcome {
+            Outcome::Passed => self.passed += 1,
+            Outcome::Failed(_) => self.failed += 1,
+            Outcome::Skipped => self.skipped += 1,
+        }
+        Ok(())
+    }
+
+    /// Completes the report and closes the host file.
+    pub fn finish(mut self) -> Result<(), ()> {
+        match self.format {
+            Format::Json => {
+                let (passed, failed, skipped) = (self.passed, self.failed, self.skipped);
+                self.write(format_args!(
+                    "],\"passed\":{passed},\"failed\":{failed},\"skipped\":{skipped}}}\n"
+                ))?
+            }
+            Format::JUnit => self.write(format_args!("</testsuite>\n"))?,
+        }
+        self.stream.close()
+    }
+
+    fn write(&mut self, args: fmt::Arguments) -> Result<(), ()> {
+        self.stream.write_fmt(args).map_err(drop)
+    }
+}
+
+/// Returns the value of the `report=<path>` argument of the command line of the program.
+fn report_path(cmdline: &mut [u8]) -> Result<&str, ()> {
+    env::var(env::cmdline(cmdline)?, REPORT_KEY).ok_or(())
+}
+
+/// String escaped according to the format of the report.
+struct Escaped<'a>(&'a str, Format);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match (self.1, c) {
+                (Format::Json, '"') => f.write_str("\\\"")?,
+                (Format::Json, '\\') => f.write_str("\\\\")?,
+                (Format::Json, c) if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                (Format::JUnit, '&') => f.write_str("&amp;")?,
+                (Format::JUnit, '<') => f.write_str("&lt;")?,
+                (Format::JUnit, '>') => f.write_str("&gt;")?,
+                (Format::JUnit, '"') => f.write_str("&quot;")?,
+                (Format::JUnit, '\'') => f.write_str("&apos;")?,
+                // XML 1.0 does not allow most ASCII control characters, not even escaped
+                (Format::JUnit, c) if c < ' ' && !matches!(c, '\t' | '\n' | '\r') => {
+                    f.write_char(char::REPLACEMENT_CHARACTER)?
+                }
+                (_, c) => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -36,11 +36,214 @@ pub enum Extension {
     X(String),
 }
 
+/// Canonical order of single-letter extensions, as defined in the RISC-V ISA specification.
+/// It is also used to sort Z-type extensions by category (i.e., their second letter).
+const CANONICAL_ORDER: &str = "iemafdqlcbkjtpvh";
+
+/// Ratified versions (major, minor) of some multi-letter extensions.
+const MULTI_LETTER_VERSIONS: &[(&str, u32, u32)] = &[
+    ("zicsr", 2, 0),
+    ("zifencei", 2, 0),
+    ("zicntr", 2, 0),
+    ("zihpm", 2, 0),
+    ("zmmul", 1, 0),
+    ("zaamo", 1, 0),
+    ("zalrsc", 1, 0),
+    ("zba", 1, 0),
+    ("zbb", 1, 0),
+    ("zbc", 1, 0),
+    ("zbs", 1, 0),
+    ("zca", 1, 0),
+    ("zcb", 1, 0),
+    ("zcd", 1, 0),
+    ("zcf", 1, 0),
+    ("zcmp", 1, 0),
+    ("zcmt", 1, 0),
+    ("zkr", 1, 0),
+    ("zkt", 1, 0),
+    ("smrnmi", 1, 0),
+    ("smstateen", 1, 0),
+    ("sstc", 1, 0),
+    ("svpbmt", 1, 0),
+];
+
+/// Linux `hwprobe` keys of the `RISCV_HWPROBE_KEY_IMA_EXT_0` bitmask and their bit position.
+/// `RISCV_HWPROBE_IMA_FD` is not included, as it requires both the F and D extensions.
+const HWPROBE_IMA_EXT_0: &[(&str, &str, u32)] = &[
+    ("c", "RISCV_HWPROBE_IMA_C", 1),
+    ("v", "RISCV_HWPROBE_IMA_V", 2),
+    ("zba", "RISCV_HWPROBE_EXT_ZBA", 3),
+    ("zbb", "RISCV_HWPROBE_EXT_ZBB", 4),
+    ("zbs", "RISCV_HWPROBE_EXT_ZBS", 5),
+    ("zicboz", "RISCV_HWPROBE_EXT_ZICBOZ", 6),
+    ("zbc", "RISCV_HWPROBE_EXT_ZBC", 7),
+    ("zbkb", "RISCV_HWPROBE_EXT_ZBKB", 8),
+    ("zbkc", "RISCV_HWPROBE_EXT_ZBKC", 9),
+    ("zbkx", "RISCV_HWPROBE_EXT_ZBKX", 10),
+    ("zknd", "RISCV_HWPROBE_EXT_ZKND", 11),
+    ("zkne", "RISCV_HWPROBE_EXT_ZKNE", 12),
+    ("zknh", "RISCV_HWPROBE_EXT_ZKNH", 13),
+    ("zksed", "RISCV_HWPROBE_EXT_ZKSED", 14),
+    ("zksh", "RISCV_HWPROBE_EXT_ZKSH", 15),
+    ("zkt", "RISCV_HWPROBE_EXT_ZKT", 16),
+    ("zvbb", "RISCV_HWPROBE_EXT_ZVBB", 17),
+    ("zvbc", "RISCV_HWPROBE_EXT_ZVBC", 18),
+    ("zvkb", "RISCV_HWPROBE_EXT_ZVKB", 19),
+    ("zvkg", "RISCV_HWPROBE_EXT_ZVKG", 20),
+    ("zvkned", "RISCV_HWPROBE_EXT_ZVKNED", 21),
+    ("zvknha", "RISCV_HWPROBE_EXT_ZVKNHA", 22),
+    ("zvknhb", "RISCV_HWPROBE_EXT_ZVKNHB", 23),
+    ("zvksed", "RISCV_HWPROBE_EXT_ZVKSED", 24),
+    ("zvksh", "RISCV_HWPROBE_EXT_ZVKSH", 25),
+    ("zvkt", "RISCV_HWPROBE_EXT_ZVKT", 26),
+    ("zfh", "RISCV_HWPROBE_EXT_ZFH", 27),
+    ("zfhmin", "RISCV_HWPROBE_EXT_ZFHMIN", 28),
+    ("zihintntl", "RISCV_HWPROBE_EXT_ZIHINTNTL", 29),
+    ("zvfh", "RISCV_HWPROBE_EXT_ZVFH", 30),
+    ("zvfhmin", "RISCV_HWPROBE_EXT_ZVFHMIN", 31),
+    ("zfa", "RISCV_HWPROBE_EXT_ZFA", 32),
+    ("ztso", "RISCV_HWPROBE_EXT_ZTSO", 33),
+    ("zacas", "RISCV_HWPROBE_EXT_ZACAS", 34),
+    ("zicond", "RISCV_HWPROBE_EXT_ZICOND", 35),
+    ("zihintpause", "RISCV_HWPROBE_EXT_ZIHINTPAUSE", 36),
+];
+
+/// Implication rules between extensions: each extension implies the extensions of its list.
+///
+/// Implications are applied transitively by [`Extensions::expand_implications`]. `Zvl<N>b`
+/// extensions are handled separately, as each of them implies all the smaller ones.
+const IMPLICATIONS: &[(&str, &[&str])] = &[
+    ("m", &["zmmul"]),
+    ("a", &["zaamo", "zalrsc"]),
+    ("f", &["zicsr"]),
+    ("d", &["f"]),
+    ("q", &["d"]),
+    ("c", &["zca"]),
+    ("b", &["zba", "zbb", "zbs"]),
+    ("v", &["zve64d", "zvl128b"]),
+    ("h", &["zicsr"]),
+    ("zicntr", &["zicsr"]),
+    ("zihpm", &["zicsr"]),
+    ("zcb", &["zca"]),
+    ("zcd", &["zca", "d"]),
+    ("zcf", &["zca", "f"]),
+    ("zcmp", &["zca"]),
+    ("zcmt", &["zca", "zicsr"]),
+    ("zfa", &["f"]),
+    ("zfh", &["zfhmin"]),
+    ("zfhmin", &["f"]),
+    ("zfinx", &["zicsr"]),
+    ("zdinx", &["zfinx"]),
+    ("zk", &["zkn", "zkr", "zkt"]),
+    ("zkn", &["zbkb", "zbkc", "zbkx", "zkne", "zknd", "zknh"]),
+    ("zks", &["zbkb", "zbkc", "zbkx", "zksed", "zksh"]),
+    ("zve32x", &["zicsr", "zvl32b"]),
+    ("zve32f", &["zve32x", "f"]),
+    ("zve64x", &["zve32x", "zvl64b"]),
+    ("zve64f", &["zve64x", "zve32f"]),
+    ("zve64d", &["zve64f", "d"]),
+    ("zvbb", &["zvkb"]),
+    ("zvfh", &["zvfhmin", "zfhmin"]),
+    ("zvfhmin", &["zve32f"]),
+];
+
+/// Linux `hwprobe` key for targets with both the F and D extensions.
+pub const HWPROBE_IMA_FD: (&str, u64) = ("RISCV_HWPROBE_IMA_FD", 1 << 0);
+
 impl Extension {
     /// Determines if the extension is a base extension.
     pub const fn is_base(&self) -> bool {
         matches!(self, Self::I | Self::E)
     }
+
+    /// Returns the lowercase name of the extension (e.g., `zicsr`).
+    ///
+    /// This is the identifier used in canonical ISA strings and in the
+    /// `riscv,isa-extensions` device tree property.
+    pub fn name(&self) -> String {
+        self.to_string().to_lowercase()
+    }
+
+    /// Returns the ratified version (major, minor) of the extension, if known.
+    pub fn version(&self) -> Option<(u32, u32)> {
+        match self {
+            Self::I => Some((2, 1)),
+            Self::E => Some((2, 0)),
+            Self::M => Some((2, 0)),
+            Self::A => Some((2, 1)),
+            Self::F | Self::D | Self::Q => Some((2, 2)),
+            Self::C => Some((2, 0)),
+            Self::B | Self::V | Self::H => Some((1, 0)),
+            Self::P => None, // not ratified yet
+            Self::Z(_) | Self::S(_) | Self::X(_) => {
+                let name = self.name();
+                MULTI_LETTER_VERSIONS
+                    .iter()
+                    .find(|(ext, _, _)| *ext == name)
+                    .map(|&(_, major, minor)| (major, minor))
+            }
+        }
+    }
+
+    /// Returns the Linux `hwprobe` key and bitmask of the extension in the
+    /// `RISCV_HWPROBE_KEY_IMA_EXT_0` key, if any.
+    ///
+    /// The F and D extensions are represented together by [`HWPROBE_IMA_FD`].
+    pub fn hwprobe_key(&self) -> Option<(&'static str, u64)> {
+        let name = self.name();
+        HWPROBE_IMA_EXT_0
+            .iter()
+            .find(|(ext, _, _)| *ext == name)
+            .map(|&(_, key, bit)| (key, 1 << bit))
+    }
+
+    /// Returns the extension with the given lowercase name (e.g., `zicsr`).
+    ///
+    /// Multi-letter names are capitalized, as in target triples (e.g., `Zicsr`).
+    fn from_name(name: &str) -> Self {
+        match Self::try_from(name) {
+            Ok(extension) => extension, // single-letter extension
+            Err(_) => {
+                let capitalized = name[..1].to_uppercase() + &name[1..];
+                Self::try_from(capitalized.as_str()).unwrap()
+            }
+        }
+    }
+
+    /// Returns the extensions directly implied by the extension.
+    fn implications(&self) -> Vec<Extension> {
+        let name = self.name();
+        let mut implied = IMPLICATIONS
+            .iter()
+            .find(|(ext, _)| *ext == name)
+            .map(|(_, implied)| implied.iter().map(|ext| Self::from_name(ext)).collect())
+            .unwrap_or_else(Vec::new);
+        // Zvl<N>b implies Zvl<N/2>b, down to Zvl32b
+        let zvl = name
+            .strip_prefix("zvl")
+            .and_then(|n| n.strip_suffix('b'))
+            .and_then(|n| n.parse::<u32>().ok());
+        if let Some(n) = zvl.filter(|&n| n > 32) {
+            implied.push(Self::from_name(&format!("zvl{}b", n / 2)));
+        }
+        implied
+    }
+
+    /// Returns the sort key of the extension in canonical ISA strings.
+    ///
+    /// Single-letter extensions follow the canonical order. Z-type extensions are sorted by
+    /// category (i.e., the canonical order of their second letter) and then alphabetically.
+    /// S-type and X-type extensions are sorted alphabetically.
+    fn canonical_key(&self) -> (usize, usize, String) {
+        let order = |c: char| CANONICAL_ORDER.find(c).unwrap_or(CANONICAL_ORDER.len());
+        let name = self.name();
+        match self {
+            Self::Z(_) => (1, order(name.chars().nth(1).unwrap_or('z')), name),
+            Self::S(_) => (2, 0, name),
+            Self::X(_) => (3, 0, name),
+            _ => (0, order(name.chars().next().unwrap()), name),
+        }
+    }
 }
 
 impl std::fmt::Display for Extension {
@@ -119,6 +322,16 @@ impl Extensions {
         res
     }
 
+    /// Returns a vector with the list of extensions sorted as in canonical ISA strings.
+    ///
+    /// Unlike [`Extensions::extensions`], Z-type extensions are sorted by category
+    /// (i.e., the canonical order of their second letter) and then alphabetically.
+    pub fn canonical_extensions(&self) -> Vec<Extension> {
+        let mut res = self.extensions();
+        res.sort_by_key(Extension::canonical_key);
+        res
+    }
+
     /// Returns the base extension (I or E) if present.
     pub fn base_extension(&self) -> Option<Extension> {
         if self.extensions.contains(&Extension::I) {
@@ -152,6 +365,29 @@ impl Extensions {
     pub fn remove(&mut self, extension: &Extension) -> bool {
         self.extensions.remove(extension)
     }
+
+    /// Adds all the extensions implied by the extensions of the collection, transitively
+    /// (e.g., D implies F, which implies Zicsr). Returns `true` if any extension was added.
+    ///
+    /// Only implications that do not depend on the XLEN are applied (e.g., C does not imply Zcf).
+    /// Note that G is already expanded to IMAFD when parsing a collection of extensions.
+    pub fn expand_implications(&mut self) -> bool {
+        let mut pending = self.extensions();
+        let mut added = false;
+        while let Some(extension) = pending.pop() {
+            for implied in extension.implications() {
+                if self.extensions.insert(implied.clone()) {
+                    pending.push(implied);
+                    added = true;
+                }
+            }
+        }
+        // C and D together imply Zcd
+        if self.contains(&Extension::C) && self.contains(&Extension::D) {
+            added |= self.extensions.insert(Extension::from_name("zcd"));
+        }
+        added
+    }
 }
 
 impl<'a> TryFrom<&'a str> for Extensions {
@@ -447,6 +683,36 @@ mod test {
         assert_eq!(extensions.base_extension(), None);
     }
 
+    #[test]
+    fn test_extensions_expand_implications() {
+        let mut extensions = Extensions::try_from("imc").unwrap();
+        assert!(extensions.expand_implications());
+        assert_eq!(extensions.to_string(), "imcZca_Zmmul");
+        assert!(!extensions.expand_implications());
+
+        let mut extensions = Extensions::try_from("gc").unwrap();
+        assert!(extensions.expand_implications());
+        assert_eq!(extensions.to_string(), "gcZaamo_Zalrsc_Zca_Zcd_Zicsr_Zmmul");
+
+        let mut extensions = Extensions::try_from("iv").unwrap();
+        assert!(extensions.expand_implications());
+        for name in [
+            "f", "d", "zicsr", "zve32x", "zve32f", "zve64x", "zve64f", "zve64d", "zvl32b",
+            "zvl64b", "zvl128b",
+        ] {
+            assert!(extensions.contains(&Extension::from_name(name)), "{name}");
+        }
+        assert!(!extensions.contains(&Extension::from_name("zvl256b")));
+
+        let mut extensions = Extensions::try_from("iZcmp_Zcmt").unwrap();
+        assert!(extensions.expand_implications());
+        assert_eq!(extensions.to_string(), "iZca_Zcmp_Zcmt_Zicsr");
+
+        let mut extensions = Extensions::try_from("iZvl256b").unwrap();
+        assert!(extensions.expand_implications());
+        assert_eq!(extensions.to_string(), "iZvl128b_Zvl256b_Zvl32b_Zvl64b");
+    }
+
     #[test]
     fn test_extensions_to_string() {
         let mut extensions = Extensions::try_from("imafdc").unwrap();
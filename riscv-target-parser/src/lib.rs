@@ -1,5 +1,14 @@
 pub mod extension;
-pub use extension::{Extension, Extensions};
+pub use extension::{Extension, Extensions, HWPROBE_IMA_FD};
+
+/// Cfgs of the single-letter extensions that [`RiscvTarget::rustc_flags`] may return.
+///
+/// Build scripts can declare them with `cargo:rustc-check-cfg`, so `rustc` warns about misspelled cfgs
+/// (e.g., `cfg(riscv_m)`).
+pub const RUSTC_CFGS: [&str; 12] = [
+    "riscvi", "riscve", "riscvm", "riscva", "riscvf", "riscvd", "riscvq", "riscvc", "riscvb",
+    "riscvp", "riscvv", "riscvh",
+];
 
 /// Error variants for the RISC-V target parser.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -99,11 +108,57 @@ macro_rules! impl_try_from_width {
 }
 impl_try_from_width!(u8, u16, u32, u64, u128, usize, i16, i32, i64, i128, isize);
 
+/// The code model of the RISC-V target.
+///
+/// The code model determines the range of addresses that the generated code can reach.
+/// It only restricts the memory layout of 64-bit targets, as 32-bit targets can reach the whole address space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeModel {
+    /// `medlow` code model (`-C code-model=small`).
+    /// Symbols must be placed in the lowest (or highest) 2 GiB of the address space.
+    Medlow,
+    /// `medany` code model (`-C code-model=medium`).
+    /// Symbols must be placed within 2 GiB of the code that references them.
+    Medany,
+}
+
+impl CodeModel {
+    /// Returns the default code model of the Rust RISC-V targets of the given width.
+    pub const fn default_for(width: Width) -> Self {
+        match width {
+            Width::W32 => Self::Medlow,
+            _ => Self::Medany,
+        }
+    }
+
+    /// Returns the code model of the given `-C code-model` value.
+    ///
+    /// It returns `None` for code models that do not restrict the memory layout as
+    /// [`CodeModel::Medlow`] and [`CodeModel::Medany`] do (e.g., `large`).
+    pub fn from_flag(value: &str) -> Option<Self> {
+        match value {
+            "small" | "medlow" => Some(Self::Medlow),
+            "medium" | "medany" => Some(Self::Medany),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CodeModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Medlow => write!(f, "medlow"),
+            Self::Medany => write!(f, "medany"),
+        }
+    }
+}
+
 /// Struct that represents a RISC-V target.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RiscvTarget {
     width: Width,
     extensions: Extensions,
+    code_model: Option<CodeModel>,
 }
 
 impl RiscvTarget {
@@ -144,6 +199,13 @@ impl RiscvTarget {
                 return Err(Error::UnknownTargetFeature(target_feature));
             }
         }
+        if let Some(code_model) = cargo_flags
+            .split(0x1fu8 as char)
+            .filter_map(|arg| arg.trim_start_matches("-C").strip_prefix("code-model="))
+            .next_back()
+        {
+            target.code_model = CodeModel::from_flag(code_model);
+        }
         Ok(target)
     }
 
@@ -160,7 +222,21 @@ impl RiscvTarget {
     ///    println!("cargo:rustc-check-cfg=cfg({})", flag);
     ///    println!("cargo:rustc-cfg={}", flag);
     /// }
+    /// ```
+    ///
+    /// Multi-letter extensions (e.g., `riscvzicsr`) are also returned. Build scripts that only
+    /// use single-letter extensions can filter the flags with [`RUSTC_CFGS`]:
     ///
+    /// ```no_run
+    /// # let target = riscv_target_parser::RiscvTarget::build("riscv32imac-unknown-none-elf", "").unwrap();
+    /// for cfg in riscv_target_parser::RUSTC_CFGS {
+    ///    println!("cargo:rustc-check-cfg=cfg({})", cfg);
+    /// }
+    /// for flag in target.rustc_flags() {
+    ///    if riscv_target_parser::RUSTC_CFGS.contains(&flag.as_str()) {
+    ///        println!("cargo:rustc-cfg={}", flag);
+    ///    }
+    /// }
     pub fn rustc_flags(&self) -> Vec<String> {
         self.extensions
             .extensions()
@@ -206,15 +282,146 @@ impl RiscvTarget {
         patch
     }
 
+    /// Returns the base extension and the other extensions of the target in canonical order.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the target does not have a base extension.
+    fn canonical_extensions(&self) -> (Extension, Vec<Extension>) {
+        let base = self
+            .extensions
+            .base_extension()
+            .expect("RISC-V target must have a base extension");
+        let others = self
+            .extensions
+            .canonical_extensions()
+            .into_iter()
+            .filter(|e| !e.is_base())
+            .collect();
+        (base, others)
+    }
+
+    /// Returns the canonical ISA string of the target, as defined in the RISC-V ISA specification.
+    ///
+    /// The ISA string is lowercase, it includes the version of every known extension (e.g., `2p1`),
+    /// and extensions are separated by underscores. G is expanded to IMAFD.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let target = riscv_target_parser::RiscvTarget::build("riscv32imac-unknown-none-elf", "").unwrap();
+    /// assert_eq!(target.canonical_isa_string(), "rv32i2p1_m2p0_a2p1_c2p0");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// It panics if the target does not have a base extension.
+    pub fn canonical_isa_string(&self) -> String {
+        let versioned = |ext: &Extension| match ext.version() {
+            Some((major, minor)) => format!("{}{major}p{minor}", ext.name()),
+            None => ext.name(),
+        };
+        let (base, others) = self.canonical_extensions();
+        let mut isa = format!("rv{}{}", self.width, versioned(&base));
+        for ext in others {
+            isa.push('_');
+            isa.push_str(&versioned(&ext));
+        }
+        isa
+    }
+
+    /// Returns the ISA string of the target for the `riscv,isa` device tree property.
+    ///
+    /// Single-letter extensions are concatenated, while multi-letter extensions are
+    /// separated by underscores (e.g., `rv64imafdc_zicsr_zifencei`).
+    ///
+    /// # Panics
+    ///
+    /// It panics if the target does not have a base extension.
+    pub fn dt_isa_string(&self) -> String {
+        let (base, others) = self.canonical_extensions();
+        let mut isa = format!("rv{}{}", self.width, base.name());
+        for ext in others {
+            if matches!(ext, Extension::Z(_) | Extension::S(_) | Extension::X(_)) {
+                isa.push('_');
+            }
+            isa.push_str(&ext.name());
+        }
+        isa
+    }
+
+    /// Returns the extensions of the target for the `riscv,isa-extensions` device tree property.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the target does not have a base extension.
+    pub fn dt_isa_extensions(&self) -> Vec<String> {
+        let (base, others) = self.canonical_extensions();
+        std::iter::once(base)
+            .chain(others)
+            .map(|e| e.name())
+            .collect()
+    }
+
+    /// Returns the Linux `hwprobe` keys of the `RISCV_HWPROBE_KEY_IMA_EXT_0` bitmask
+    /// that correspond to the extensions of the target.
+    pub fn hwprobe_keys(&self) -> Vec<&'static str> {
+        self.hwprobe_ima_ext_0_pairs()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Returns the value of the Linux `RISCV_HWPROBE_KEY_IMA_EXT_0` bitmask
+    /// that corresponds to the extensions of the target.
+    pub fn hwprobe_ima_ext_0(&self) -> u64 {
+        self.hwprobe_ima_ext_0_pairs()
+            .into_iter()
+            .fold(0, |mask, (_, bit)| mask | bit)
+    }
+
+    /// Returns the `RISCV_HWPROBE_KEY_IMA_EXT_0` keys and bitmasks of the target, sorted by bit.
+    fn hwprobe_ima_ext_0_pairs(&self) -> Vec<(&'static str, u64)> {
+        let mut pairs = Vec::new();
+        if self.extensions.contains(&Extension::F) && self.extensions.contains(&Extension::D) {
+            pairs.push(HWPROBE_IMA_FD);
+        }
+        pairs.extend(
+            self.extensions
+                .extensions()
+                .iter()
+                .filter_map(Extension::hwprobe_key),
+        );
+        pairs.sort_by_key(|&(_, bit)| bit);
+        pairs
+    }
+
     /// Returns the width of the RISC-V architecture.
     pub fn width(&self) -> Width {
         self.width
     }
 
+    /// Returns the code model of the RISC-V target.
+    ///
+    /// It is taken from the `-C code-model` flag. If the flag is not set,
+    /// it returns the default code model of the Rust targets (see [`CodeModel::default_for`]).
+    /// If the flag sets an unsupported code model (e.g., `large`), it returns `None`.
+    pub fn code_model(&self) -> Option<CodeModel> {
+        self.code_model
+    }
+
     /// Returns the base extension of the RISC-V architecture (if any).
     pub fn base_extension(&self) -> Option<Extension> {
         self.extensions.base_extension()
     }
+
+    /// Adds all the extensions implied by the extensions of the target (e.g., D implies F).
+    /// Returns `true` if any extension was added.
+    ///
+    /// See [`Extensions::expand_implications`] for more details.
+    pub fn expand_implications(&mut self) -> bool {
+        self.extensions.expand_implications()
+    }
 }
 
 impl<'a> TryFrom<TargetTriple<'a>> for RiscvTarget {
@@ -232,7 +439,12 @@ impl<'a> TryFrom<TargetTriple<'a>> for RiscvTarget {
                         let (width_str, extensions_str) = arch.split_at(digit_end);
                         let width = width_str.parse::<u32>().unwrap().try_into()?;
                         let extensions = extensions_str.try_into()?;
-                        Ok(Self { width, extensions })
+                        let code_model = CodeModel::default_for(width);
+                        Ok(Self {
+                            width,
+                            extensions,
+                            code_model: Some(code_model),
+                        })
                     }
                 }
             }
@@ -251,4 +463,98 @@ mod test {
         let rustc_flags = target.rustc_flags();
         assert_eq!(rustc_flags, vec!["riscvi", "riscvm", "riscvf", "riscvc"]);
     }
+
+    #[test]
+    fn test_rustc_cfgs() {
+        let target = "riscv64gc-unknown-none-elf";
+        let cargo_flags = "target-feature=+v,+h";
+        let target = super::RiscvTarget::build(target, cargo_flags).unwrap();
+        for flag in target.rustc_flags() {
+            let single_letter = flag.len() == "riscvx".len();
+            assert_eq!(super::RUSTC_CFGS.contains(&flag.as_str()), single_letter);
+        }
+    }
+
+    #[test]
+    fn test_expand_implications() {
+        let target = "riscv32imafc-unknown-none-elf";
+        let cargo_flags = "target-feature=+d";
+        let mut target = super::RiscvTarget::build(target, cargo_flags).unwrap();
+        assert!(!target.rustc_flags().contains(&"riscvZicsr".to_string()));
+        assert!(target.expand_implications());
+        assert!(target.rustc_flags().contains(&"riscvZicsr".to_string()));
+        assert!(target.rustc_flags().contains(&"riscvZcd".to_string()));
+        assert!(!target.expand_implications());
+    }
+
+    #[test]
+    fn test_code_model() {
+        use super::{CodeModel, RiscvTarget};
+
+        let target = RiscvTarget::build("riscv32imac-unknown-none-elf", "").unwrap();
+        assert_eq!(target.code_model(), Some(CodeModel::Medlow));
+        let target = RiscvTarget::build("riscv64gc-unknown-none-elf", "").unwrap();
+        assert_eq!(target.code_model(), Some(CodeModel::Medany));
+
+        let cargo_flags = "-C\x1fcode-model=small\x1f-C\x1ftarget-feature=+m";
+        let target = RiscvTarget::build("riscv64imac-unknown-none-elf", cargo_flags).unwrap();
+        assert_eq!(target.code_model(), Some(CodeModel::Medlow));
+        assert_eq!(target.code_model().unwrap().to_string(), "medlow");
+
+        let cargo_flags = "-Ccode-model=small\x1f-Ccode-model=medium";
+        let target = RiscvTarget::build("riscv64imac-unknown-none-elf", cargo_flags).unwrap();
+        assert_eq!(target.code_model(), Some(CodeModel::Medany));
+
+        let cargo_flags = "-Ccode-model=large";
+        let target = RiscvTarget::build("riscv64imac-unknown-none-elf", cargo_flags).unwrap();
+        assert_eq!(target.code_model(), None);
+    }
+
+    #[test]
+    fn test_canonical_isa_string() {
+        let target = super::RiscvTarget::build("riscv32imac-unknown-none-elf", "").unwrap();
+        assert_eq!(target.canonical_isa_string(), "rv32i2p1_m2p0_a2p1_c2p0");
+        assert_eq!(target.dt_isa_string(), "rv32imac");
+        assert_eq!(target.dt_isa_extensions(), vec!["i", "m", "a", "c"]);
+
+        let target = super::RiscvTarget::build(
+            "riscv64gcXSifivecdiscarddlone_Ssccfg_Zicsr_Zba_Zaamo-unknown-none-elf",
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            target.canonical_isa_string(),
+            "rv64i2p1_m2p0_a2p1_f2p2_d2p2_c2p0_zicsr2p0_zaamo1p0_zba1p0_ssccfg_xsifivecdiscarddlone"
+        );
+        assert_eq!(
+            target.dt_isa_string(),
+            "rv64imafdc_zicsr_zaamo_zba_ssccfg_xsifivecdiscarddlone"
+        );
+
+        let target = super::RiscvTarget::build("riscv32emc-unknown-none-elf", "").unwrap();
+        assert_eq!(target.canonical_isa_string(), "rv32e2p0_m2p0_c2p0");
+    }
+
+    #[test]
+    fn test_hwprobe() {
+        let target = super::RiscvTarget::build("riscv64gc-unknown-linux-gnu", "").unwrap();
+        assert_eq!(
+            target.hwprobe_keys(),
+            vec!["RISCV_HWPROBE_IMA_FD", "RISCV_HWPROBE_IMA_C"]
+        );
+        assert_eq!(target.hwprobe_ima_ext_0(), 0b11);
+
+        let target =
+            super::RiscvTarget::build("riscv64imafcv_Zba_Zbb-unknown-linux-gnu", "").unwrap();
+        assert_eq!(
+            target.hwprobe_keys(),
+            vec![
+                "RISCV_HWPROBE_IMA_C",
+                "RISCV_HWPROBE_IMA_V",
+                "RISCV_HWPROBE_EXT_ZBA",
+                "RISCV_HWPROBE_EXT_ZBB"
+            ]
+        );
+        assert_eq!(target.hwprobe_ima_ext_0(), 0b11110);
+    }
 }
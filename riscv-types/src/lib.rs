@@ -0,0 +1,8 @@
+//! Types shared by the RISC-V crates.
+//!
+//! The `riscv`, `riscv-pac`, `riscv-peripheral`, and `riscv-rt` crates re-export these types,
+//! so downstream code can use a single error path with `?` regardless of the crate that failed.
+
+#![no_std]
+
+pub mod result;
@@ -0,0 +1,136 @@
+use core::fmt;
+
+/// Convenience alias for the [Result](core::result::Result) type for the library.
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Represents error variants for the library.
+///
+/// This error type is shared by the `riscv`, `riscv-pac`, `riscv-peripheral`, and `riscv-rt` crates,
+/// so downstream code can propagate errors from any of them with `?`.
+/// New variants may be added in the future, so matches must include a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Attempted out-of-bounds access.
+    IndexOutOfBounds {
+        index: usize,
+        min: usize,
+        max: usize,
+    },
+    /// Invalid field value.
+    InvalidFieldValue {
+        field: &'static str,
+        value: usize,
+        bitmask: usize,
+    },
+    /// Invalid value of a register field that does not match any known variants.
+    InvalidFieldVariant { field: &'static str, value: usize },
+    /// Invalid value.
+    InvalidValue { value: usize, bitmask: usize },
+    /// Invalid value that does not match any known variants.
+    InvalidVariant(usize),
+    /// Value (e.g., an address) that is not aligned to the required number of bytes.
+    InvalidAlignment { value: usize, alignment: usize },
+    /// Unimplemented function or type.
+    Unimplemented,
+    /// Resource (e.g., the heap region) that has already been initialized.
+    AlreadyInitialized,
+    /// Memory region without any bytes (e.g., a heap region of size 0).
+    EmptyRegion,
+    /// Error returned by the supervisor execution environment (SEE) in an SBI call.
+    Sbi(SbiError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfBounds { index, min, max } => write!(
+                f,
+                "out-of-bounds access, index: {index}, min: {min}, max: {max}"
+            ),
+            Self::InvalidFieldValue {
+                field,
+                value,
+                bitmask,
+            } => write!(
+                f,
+                "invalid {field} field value: {value:#x}, valid bitmask: {bitmask:#x}",
+            ),
+            Self::InvalidFieldVariant { field, value } => {
+                write!(f, "invalid {field} field variant: {value:#x}")
+            }
+            Self::InvalidValue { value, bitmask } => {
+                write!(f, "invalid value: {value:#x}, valid bitmask: {bitmask:#x}",)
+            }
+            Self::InvalidVariant(value) => {
+                write!(f, "invalid variant: {value:#x}")
+            }
+            Self::InvalidAlignment { value, alignment } => {
+                write!(
+                    f,
+                    "invalid alignment: {value:#x}, required alignment: {alignment}"
+                )
+            }
+            Self::Unimplemented => write!(f, "unimplemented"),
+            Self::AlreadyInitialized => write!(f, "already initialized"),
+            Self::EmptyRegion => write!(f, "empty memory region"),
+            Self::Sbi(error) => write!(f, "SBI error: {error:?}"),
+        }
+    }
+}
+
+/// Standard SBI error codes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SbiError {
+    /// Failed.
+    Failed,
+    /// Not supported.
+    NotSupported,
+    /// Invalid parameter(s).
+    InvalidParam,
+    /// Denied or not allowed.
+    Denied,
+    /// Invalid address(es).
+    InvalidAddress,
+    /// Already available.
+    AlreadyAvailable,
+    /// Already started.
+    AlreadyStarted,
+    /// Already stopped.
+    AlreadyStopped,
+    /// Shared memory not available.
+    NoShmem,
+    /// Invalid state.
+    InvalidState,
+    /// Bad (or invalid) range.
+    BadRange,
+    /// Failed due to timeout.
+    Timeout,
+    /// Input/Output error.
+    Io,
+    /// Error code not defined in the SBI specification.
+    Unknown(isize),
+}
+
+impl SbiError {
+    /// Converts a raw SBI error code into an [`SbiError`].
+    #[inline]
+    pub const fn from_code(code: isize) -> Self {
+        match code {
+            -1 => Self::Failed,
+            -2 => Self::NotSupported,
+            -3 => Self::InvalidParam,
+            -4 => Self::Denied,
+            -5 => Self::InvalidAddress,
+            -6 => Self::AlreadyAvailable,
+            -7 => Self::AlreadyStarted,
+            -8 => Self::AlreadyStopped,
+            -9 => Self::NoShmem,
+            -10 => Self::InvalidState,
+            -11 => Self::BadRange,
+            -12 => Self::Timeout,
+            -13 => Self::Io,
+            _ => Self::Unknown(code),
+        }
+    }
+}
@@ -1,13 +1,11 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use quote::{format_ident, quote};
 use std::collections::HashMap;
 use std::str::FromStr;
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input,
-    spanned::Spanned,
-    Data, DeriveInput, Ident, Token,
+    parse_macro_input, Data, DeriveInput, Ident, LitStr, Path, Token, Visibility,
 };
 
 /// Struct to represent a function parameter.
@@ -132,7 +130,7 @@ impl PacTrait {
 impl Parse for PacTrait {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         input.parse::<Token![unsafe]>()?;
-        let trait_name: TokenStream2 = input.parse()?;
+        let trait_name: Ident = input.parse()?;
         match trait_name.to_string().as_str() {
             "ExceptionNumber" => Ok(Self::Exception),
             "CoreInterruptNumber" => Ok(Self::Interrupt(InterruptType::Core)),
@@ -147,6 +145,73 @@ impl Parse for PacTrait {
     }
 }
 
+/// Memory where the dispatch table of trap handlers is placed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Table {
+    /// Read-only table (default). Handlers are resolved at link time.
+    Rom,
+    /// Mutable table in RAM. Handlers can be replaced at runtime.
+    Ram,
+}
+
+/// Arguments of the `pac_enum` macro: `unsafe <trait>[, table = "<rom|ram>"][, plic = <path>]`
+struct PacEnumArgs {
+    pac_trait: PacTrait,
+    table: Table,
+    /// PLIC peripheral of the `riscv-peripheral` crate (only for `ExternalInterruptNumber`)
+    plic: Option<Path>,
+}
+
+impl Parse for PacEnumArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pac_trait: PacTrait = input.parse()?;
+        let mut table = Table::Rom;
+        let mut plic = None;
+        while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key == "table" {
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                table = match value.value().as_str() {
+                    "rom" => Table::Rom,
+                    "ram" => Table::Ram,
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "Unknown table location. Expected: 'rom' or 'ram'",
+                        ))
+                    }
+                };
+                if table == Table::Ram && pac_trait.trap_config().is_none() {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "The 'table' argument is only valid for 'ExceptionNumber', 'CoreInterruptNumber', and 'ExternalInterruptNumber'",
+                    ));
+                }
+            } else if key == "plic" {
+                if !matches!(pac_trait, PacTrait::Interrupt(InterruptType::External)) {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "The 'plic' argument is only valid for 'ExternalInterruptNumber'",
+                    ));
+                }
+                input.parse::<Token![=]>()?;
+                plic = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "Unknown argument. Expected: 'table' or 'plic'",
+                ));
+            }
+        }
+        Ok(Self {
+            pac_trait,
+            table,
+            plic,
+        })
+    }
+}
+
 /// Marker traits for interrupts
 enum InterruptType {
     Core,
@@ -181,6 +246,8 @@ impl InterruptType {
 
 /// Struct containing the information needed to implement the `riscv-pac` traits for an enum
 struct PacEnumItem {
+    /// The visibility of the enum
+    vis: Visibility,
     /// The name of the enum
     name: Ident,
     /// The maximum discriminant value
@@ -191,6 +258,7 @@ struct PacEnumItem {
 
 impl PacEnumItem {
     fn new(input: &DeriveInput) -> Self {
+        let vis = input.vis.clone();
         let name = input.ident.clone();
         let (mut numbers, mut max_number) = (HashMap::new(), 0);
 
@@ -222,6 +290,7 @@ impl PacEnumItem {
         }
 
         Self {
+            vis,
             name,
             max_number,
             numbers,
@@ -301,11 +370,84 @@ core::arch::global_asm!("
         TokenStream2::from_str(&asm).unwrap()
     }
 
+    /// Returns a token stream representing the `<enum>Ext` trait to configure the sources in the PLIC,
+    /// and its implementation for the enum.
+    fn plic_ext(&self, plic: &Path) -> TokenStream2 {
+        let vis = &self.vis;
+        let name = &self.name;
+        let ext_trait = format_ident!("{}Ext", name);
+        let ext_doc = format!("Extension trait to configure the [`{name}`] sources in the PLIC.");
+
+        quote! {
+            #[doc = #ext_doc]
+            #vis trait #ext_trait {
+                /// Enables the interrupt source in a PLIC context.
+                ///
+                /// # Safety
+                ///
+                /// Enabling an interrupt source can break mask-based critical sections.
+                unsafe fn enable(self, ctx: riscv_peripheral::plic::CTX<#plic>);
+
+                /// Disables the interrupt source in a PLIC context.
+                fn disable(self, ctx: riscv_peripheral::plic::CTX<#plic>);
+
+                /// Returns `true` if the interrupt source is enabled in a PLIC context.
+                fn is_enabled(self, ctx: riscv_peripheral::plic::CTX<#plic>) -> bool;
+
+                /// Sets the priority level of the interrupt source.
+                ///
+                /// # Safety
+                ///
+                /// Changing the priority level can break priority-based critical sections.
+                unsafe fn set_priority<P: riscv_peripheral::plic::PriorityNumber>(self, priority: P);
+
+                /// Returns the priority level of the interrupt source.
+                fn priority<P: riscv_peripheral::plic::PriorityNumber>(self) -> P;
+
+                /// Returns `true` if the interrupt source is pending.
+                fn is_pending(self) -> bool;
+            }
+
+            impl #ext_trait for #name {
+                #[inline]
+                unsafe fn enable(self, ctx: riscv_peripheral::plic::CTX<#plic>) {
+                    ctx.enables().enable(self);
+                }
+
+                #[inline]
+                fn disable(self, ctx: riscv_peripheral::plic::CTX<#plic>) {
+                    ctx.enables().disable(self);
+                }
+
+                #[inline]
+                fn is_enabled(self, ctx: riscv_peripheral::plic::CTX<#plic>) -> bool {
+                    ctx.enables().is_enabled(self)
+                }
+
+                #[inline]
+                unsafe fn set_priority<P: riscv_peripheral::plic::PriorityNumber>(self, priority: P) {
+                    riscv_peripheral::plic::PLIC::<#plic>::priorities().set_priority(self, priority);
+                }
+
+                #[inline]
+                fn priority<P: riscv_peripheral::plic::PriorityNumber>(self) -> P {
+                    riscv_peripheral::plic::PLIC::<#plic>::priorities().get_priority(self)
+                }
+
+                #[inline]
+                fn is_pending(self) -> bool {
+                    riscv_peripheral::plic::PLIC::<#plic>::pendings().is_pending(self)
+                }
+            }
+        }
+    }
+
     /// Returns a vector of token streams representing the trait implementations for
     /// the enum. If the trait is an interrupt trait, the implementation also includes
     /// the interrupt handler functions and the interrupt array.
-    fn impl_trait(&self, attr: &PacTrait) -> Vec<TokenStream2> {
+    fn impl_trait(&self, args: &PacEnumArgs) -> Vec<TokenStream2> {
         let mut res = vec![];
+        let attr = &args.pac_trait;
 
         let name = &self.name;
 
@@ -351,37 +493,100 @@ core::arch::global_asm!("
             let handlers = self.handlers(&trap_config);
             let interrupt_array = self.handlers_array();
 
-            // Push the interrupt handler functions and the interrupt array
+            // Push the interrupt handler functions
             res.push(quote! {
                 extern "C" {
                     #(#handlers;)*
                 }
+            });
 
-                #[doc(hidden)]
-                #[no_mangle]
-                pub static #vector_table: [Option<unsafe extern "C" fn(#(#array_signature),*)>; #max_discriminant + 1] = [
-                    #(#interrupt_array),*
-                ];
-
-                #[inline]
-                #[no_mangle]
-                unsafe extern "C" fn #dispatch_fn_name(#(#dispatch_fn_args),*) {
-                    extern "C" {
-                        fn #default_handler(#(#extern_signature),*);
+            match args.table {
+                Table::Rom => res.push(quote! {
+                    #[doc(hidden)]
+                    #[no_mangle]
+                    pub static #vector_table: [Option<unsafe extern "C" fn(#(#array_signature),*)>; #max_discriminant + 1] = [
+                        #(#interrupt_array),*
+                    ];
+
+                    #[inline]
+                    #[no_mangle]
+                    unsafe extern "C" fn #dispatch_fn_name(#(#dispatch_fn_args),*) {
+                        extern "C" {
+                            fn #default_handler(#(#extern_signature),*);
+                        }
+
+                        match #vector_table.get(code) {
+                            Some(Some(handler)) => handler(#(#handler_input),*),
+                            _ => #default_handler(#(#handler_input),*),
+                        }
+                    }
+                }),
+                Table::Ram => res.push(quote! {
+                    #[doc(hidden)]
+                    #[no_mangle]
+                    pub static mut #vector_table: [Option<unsafe extern "C" fn(#(#array_signature),*)>; #max_discriminant + 1] = [
+                        #(#interrupt_array),*
+                    ];
+
+                    #[inline]
+                    #[no_mangle]
+                    unsafe extern "C" fn #dispatch_fn_name(#(#dispatch_fn_args),*) {
+                        extern "C" {
+                            fn #default_handler(#(#extern_signature),*);
+                        }
+
+                        let handler = match code {
+                            0..=#max_discriminant => core::ptr::addr_of!(#vector_table[code]).read_volatile(),
+                            _ => None,
+                        };
+                        match handler {
+                            Some(handler) => handler(#(#handler_input),*),
+                            None => #default_handler(#(#handler_input),*),
+                        }
                     }
 
-                    match #vector_table.get(code) {
-                        Some(Some(handler)) => handler(#(#handler_input),*),
-                        _ => #default_handler(#(#handler_input),*),
+                    impl #name {
+                        /// Returns the handler of this source in the dispatch table.
+                        ///
+                        /// If it is `None`, the default handler is called instead.
+                        #[inline]
+                        pub fn handler(self) -> Option<unsafe extern "C" fn(#(#array_signature),*)> {
+                            // SAFETY: volatile read of a valid element of the dispatch table
+                            unsafe { core::ptr::addr_of!(#vector_table[self as usize]).read_volatile() }
+                        }
+
+                        /// Replaces the handler of this source in the dispatch table, and returns the previous one.
+                        ///
+                        /// If `handler` is `None`, the default handler is called instead.
+                        /// The table is updated with interrupts disabled in the current HART.
+                        #[inline]
+                        pub fn set_handler(
+                            self,
+                            handler: Option<unsafe extern "C" fn(#(#array_signature),*)>,
+                        ) -> Option<unsafe extern "C" fn(#(#array_signature),*)> {
+                            riscv::interrupt::free(|| {
+                                // SAFETY: the element is only modified with interrupts disabled
+                                unsafe {
+                                    let slot = core::ptr::addr_of_mut!(#vector_table[self as usize]);
+                                    let previous = slot.read_volatile();
+                                    slot.write_volatile(handler);
+                                    previous
+                                }
+                            })
+                        }
                     }
-                }
-            });
+                }),
+            }
         }
 
         if let PacTrait::Interrupt(InterruptType::Core) = attr {
             res.push(self.vector_table());
         }
 
+        if let Some(plic) = &args.plic {
+            res.push(self.plic_ext(plic));
+        }
+
         res
     }
 }
@@ -394,6 +599,45 @@ core::arch::global_asm!("
 /// The trait name must be one of `ExceptionNumber`, `InterruptNumber`, `PriorityNumber`, or `HartIdNumber`.
 /// Marker traits `CoreInterruptNumber` and `ExternalInterruptNumber` cannot be implemented using this macro.
 ///
+/// For `ExceptionNumber`, `CoreInterruptNumber`, and `ExternalInterruptNumber`, the macro also generates
+/// the dispatch table of trap handlers. By default, the table is read-only, and handlers are resolved at link time.
+/// With the `table = "ram"` argument, the table is placed in RAM (`static mut`), and the enum gets the
+/// `handler` and `set_handler` methods to replace handlers at runtime:
+///
+/// ```ignore
+/// #[pac_enum(unsafe CoreInterruptNumber, table = "ram")]
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// enum Interrupt {
+///     MachineSoft = 3,
+///     MachineTimer = 7,
+/// }
+///
+/// unsafe extern "C" fn my_timer_handler() {}
+///
+/// let previous = Interrupt::MachineTimer.set_handler(Some(my_timer_handler));
+/// ```
+///
+/// Note that, in vectored mode (i.e., `v-trap` feature of `riscv-rt`), core interrupts jump directly to
+/// their trap handlers without using the dispatch table.
+///
+/// For `ExternalInterruptNumber`, the `plic = <path>` argument generates the `<enum>Ext` trait (e.g., `InterruptExt`)
+/// to configure the sources in the PLIC of the `riscv-peripheral` crate. `<path>` must be a type that implements
+/// `riscv_peripheral::plic::Plic` (e.g., the `PLIC` struct of `riscv_peripheral::plic_codegen!`), and the crate
+/// calling the macro must depend on `riscv-peripheral`:
+///
+/// ```ignore
+/// #[pac_enum(unsafe ExternalInterruptNumber, plic = PLIC)]
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// pub enum Interrupt {
+///     UART0 = 1,
+///     GPIO0 = 2,
+/// }
+///
+/// unsafe { Interrupt::UART0.set_priority(Priority::P1) };
+/// unsafe { Interrupt::UART0.enable(PLIC::ctx0()) };
+/// assert!(!Interrupt::GPIO0.is_pending());
+/// ```
+///
 /// # Safety
 ///
 /// The struct to be implemented must comply with the requirements of the specified trait.
@@ -427,7 +671,7 @@ pub fn pac_enum(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as DeriveInput);
     let pac_enum = PacEnumItem::new(&input);
 
-    let attr = parse_macro_input!(attr as PacTrait);
+    let attr = parse_macro_input!(attr as PacEnumArgs);
 
     let trait_impl = pac_enum.impl_trait(&attr);
     quote! {
@@ -46,6 +46,37 @@ instruction!(
     /// Generates a breakpoint exception.
     , unsafe ebreak, "ebreak", options(nomem, nostack));
 
+instruction!(
+    /// Software breakpoint (`EBREAK` instruction)
+    ///
+    /// Halts the hart if a debugger is attached and configured to enter debug mode on `EBREAK`
+    /// (i.e., `dcsr.ebreakm`/`dcsr.ebreaks` set). Otherwise, it generates a breakpoint exception.
+    ///
+    /// The `EBREAK` instruction is never compressed, and it is not surrounded by the `slli`/`srai`
+    /// sequence of semihosting calls. Thus, debuggers never mistake it for a semihosting request,
+    /// and exception handlers can always step over it by advancing `mepc`/`sepc` by 4 bytes.
+    ///
+    /// **WARNING**: panics on non-`riscv` targets.
+    , breakpoint, ".option push\n.option norvc\nebreak\n.option pop", options(nomem, nostack));
+
+/// Undefined instruction (`UNIMP` instruction)
+///
+/// Generates an illegal instruction exception. It is useful to abort the program
+/// in a way that is reported by the exception handler (e.g., in panic handlers).
+///
+/// Exception handlers must not resume execution after this instruction.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline(always)]
+pub fn undefined_instruction() -> ! {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => unsafe { core::arch::asm!("unimp", options(noreturn, nomem, nostack)) },
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => unimplemented!(),
+    }
+}
+
 instruction!(
     /// `ECALL` instruction wrapper
     ///
@@ -157,3 +188,364 @@ pub fn delay(cycles: u32) {
         () => unimplemented!(),
     }
 }
+
+/// Emits exactly `N` `nop` instructions, without any loop.
+///
+/// Unlike [`delay`], the padding is unrolled at compile time, so timing-sensitive code (e.g.,
+/// bit-banging drivers) can rely on a fixed number of instructions between two operations.
+/// As the instructions are emitted with inline assembly, the optimizer never removes them, nor
+/// moves memory accesses (e.g., volatile MMIO writes) across them.
+///
+/// `N` must be lower than 65536. Otherwise, the build fails.
+///
+/// # Note
+///
+/// The padding is split in up to 16 blocks, one per bit set in `N`. The blocks are
+/// selected at compile time, but builds without optimizations may still branch between them.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+///
+/// # Example
+///
+/// ``` no_run
+/// // 3 cycles between two writes on cores with single-cycle `nop`s
+/// riscv::asm::nops::<3>();
+/// ```
+#[inline(always)]
+pub fn nops<const N: usize>() {
+    #[allow(clippy::let_unit_value)]
+    let () = Nops::<N>::CHECK;
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            macro_rules! nops_block {
+                ($($bit:literal),*) => {
+                    $(if N & (1 << $bit) != 0 {
+                        unsafe {
+                            core::arch::asm!(
+                                concat!(".rept 1 << ", stringify!($bit)),
+                                "nop",
+                                ".endr",
+                                // Not `nomem`: the padding must stay between the memory accesses around it
+                                options(nostack),
+                            )
+                        };
+                    })*
+                };
+            }
+            nops_block!(15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0);
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => unimplemented!(),
+    }
+}
+
+/// Compile-time check of the number of instructions emitted by [`nops`].
+struct Nops<const N: usize>;
+
+impl<const N: usize> Nops<N> {
+    const CHECK: () = assert!(N < 1 << 16, "nops: N must be lower than 65536");
+}
+
+/// Convenience macro to generate functions that read a general purpose register.
+macro_rules! read_register {
+    ($(#[$attr:meta])*, $fnname:ident, $reg:literal) => {
+        $(#[$attr])*
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        #[inline(always)]
+        pub fn $fnname() -> usize {
+            match () {
+                #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+                () => {
+                    let r: usize;
+                    unsafe {
+                        core::arch::asm!(concat!("mv {0}, ", $reg), out(reg) r, options(nomem, nostack, preserves_flags));
+                    }
+                    r
+                }
+                #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+                () => unimplemented!(),
+            }
+        }
+    };
+}
+
+/// Reads the program counter using the `AUIPC` instruction.
+///
+/// The function is always inlined, so it returns the address of the `AUIPC` instruction
+/// in the caller. This is useful for diagnostics and for computing relocation offsets
+/// (i.e., the difference between the run-time and link-time addresses of the code).
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline(always)]
+pub fn read_pc() -> usize {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let r: usize;
+            unsafe {
+                core::arch::asm!("auipc {0}, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+            }
+            r
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => unimplemented!(),
+    }
+}
+
+read_register!(
+    /// Reads the stack pointer (`sp`).
+    ///
+    /// This is useful for stack usage diagnostics and stack overflow checks.
+    , read_sp, "sp");
+
+read_register!(
+    /// Reads the global pointer (`gp`).
+    ///
+    /// The global pointer is usually set to `__global_pointer$` by the startup code.
+    , read_gp, "gp");
+
+read_register!(
+    /// Reads the thread pointer (`tp`).
+    , read_tp, "tp");
+
+read_register!(
+    /// Reads the frame pointer (`fp`, alias of `s0`).
+    ///
+    /// The frame pointer only points to the current stack frame if the code is compiled
+    /// with frame pointers enabled (e.g., `-C force-frame-pointers=yes`).
+    , read_fp, "s0");
+
+/// Emits an `lr.<width>` instruction with the given `aq`/`rl` suffix.
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_feature = "a"
+))]
+macro_rules! lr_asm {
+    ($width:literal, $suffix:literal, $ptr:expr) => {{
+        let r: usize;
+        core::arch::asm!(
+            concat!("lr.", $width, $suffix, " {0}, ({1})"),
+            out(reg) r,
+            in(reg) $ptr,
+            options(nostack),
+        );
+        r
+    }};
+}
+
+/// Emits an `sc.<width>` instruction with the given `aq`/`rl` suffix.
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_feature = "a"
+))]
+macro_rules! sc_asm {
+    ($width:literal, $suffix:literal, $ptr:expr, $val:expr) => {{
+        let r: usize;
+        core::arch::asm!(
+            concat!("sc.", $width, $suffix, " {0}, {1}, ({2})"),
+            out(reg) r,
+            in(reg) $val,
+            in(reg) $ptr,
+            options(nostack),
+        );
+        r
+    }};
+}
+
+/// Emits a bounded LR/SC compare-and-swap loop with the given `aq`/`rl` suffixes.
+///
+/// The loop is a constrained LR/SC loop: only forward branches are executed between
+/// the `lr` and `sc` instructions, so implementations guarantee its forward progress.
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_feature = "a"
+))]
+macro_rules! cas_asm {
+    ($width:literal, $aq:literal, $rl:literal, $ptr:expr, $current:expr, $new:expr, $tries:expr) => {{
+        let (prev, fail): (usize, usize);
+        core::arch::asm!(
+            concat!("1: lr.", $width, $aq, " {prev}, ({ptr})"),
+            "bne {prev}, {current}, 2f",
+            concat!("sc.", $width, $rl, " {fail}, {new}, ({ptr})"),
+            "beqz {fail}, 2f",
+            "addi {tries}, {tries}, -1",
+            "bnez {tries}, 1b",
+            "2:",
+            prev = out(reg) prev,
+            fail = out(reg) fail,
+            ptr = in(reg) $ptr,
+            current = in(reg) $current,
+            new = in(reg) $new,
+            tries = inout(reg) $tries => _,
+            options(nostack),
+        );
+        (prev, fail)
+    }};
+}
+
+/// Convenience macro to generate the LR/SC wrappers and the compare-and-swap loop of an access width.
+macro_rules! lr_sc {
+    ($(#[$cfg:meta])*, $width:literal, $ty:ty, $sty:ty, $lr:ident, $sc:ident, $cas:ident) => {
+        #[doc = concat!("Loads a reserved value from `ptr` with the `lr.", $width, "` instruction.")]
+        ///
+        /// The `aq`/`rl` bits follow the RVWMO mapping of `order`: `Relaxed` (none),
+        /// `Acquire` (`aq`), and `SeqCst` (`aqrl`).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `order` is `Release` or `AcqRel`.
+        ///
+        /// # Safety
+        ///
+        /// - `ptr` must be valid for reads and naturally aligned.
+        /// - The compiler may place other instructions between separate calls to this function and
+        #[doc = concat!("   [`", stringify!($sc), "`]. Thus, LR/SC loops built with them are not guaranteed")]
+        #[doc = concat!("   to make forward progress. Use [`", stringify!($cas), "`] when possible.")]
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        $(#[$cfg])*
+        #[inline(always)]
+        #[cfg_attr(
+            not(any(target_arch = "riscv32", target_arch = "riscv64")),
+            allow(unused_variables)
+        )]
+        pub unsafe fn $lr(ptr: *const $ty, order: core::sync::atomic::Ordering) -> $ty {
+            match () {
+                #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+                () => {
+                    use core::sync::atomic::Ordering;
+                    let r = match order {
+                        Ordering::Relaxed => lr_asm!($width, "", ptr),
+                        Ordering::Acquire => lr_asm!($width, ".aq", ptr),
+                        Ordering::SeqCst => lr_asm!($width, ".aqrl", ptr),
+                        _ => panic!("there is no such thing as a release reserved load"),
+                    };
+                    r as $ty
+                }
+                #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+                () => unimplemented!(),
+            }
+        }
+
+        #[doc = concat!("Stores `val` in `ptr` with the `sc.", $width, "` instruction if the reservation")]
+        #[doc = concat!("of the previous [`", stringify!($lr), "`] is still valid.")]
+        ///
+        /// It returns `true` if the value was stored. The `aq`/`rl` bits follow the RVWMO mapping
+        /// of `order`: `Relaxed` (none), `Release` (`rl`), and `SeqCst` (`rl`).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `order` is `Acquire` or `AcqRel`.
+        ///
+        /// # Safety
+        ///
+        /// - `ptr` must be valid for writes and naturally aligned.
+        #[doc = concat!("- See the forward progress notes of [`", stringify!($lr), "`].")]
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        $(#[$cfg])*
+        #[inline(always)]
+        #[cfg_attr(
+            not(any(target_arch = "riscv32", target_arch = "riscv64")),
+            allow(unused_variables)
+        )]
+        pub unsafe fn $sc(ptr: *mut $ty, val: $ty, order: core::sync::atomic::Ordering) -> bool {
+            match () {
+                #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+                () => {
+                    use core::sync::atomic::Ordering;
+                    let val = val as usize;
+                    let r = match order {
+                        Ordering::Relaxed => sc_asm!($width, "", ptr, val),
+                        Ordering::Release | Ordering::SeqCst => sc_asm!($width, ".rl", ptr, val),
+                        _ => panic!("there is no such thing as an acquire conditional store"),
+                    };
+                    r == 0
+                }
+                #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+                () => unimplemented!(),
+            }
+        }
+
+        #[doc = concat!("Stores `new` in `ptr` if its value is `current`, with an `lr.", $width, "`/`sc.", $width, "` loop.")]
+        ///
+        /// It returns `Ok` with the previous value if `new` was stored. Otherwise, it returns `Err`
+        /// with the value found in `ptr`. Like `compare_exchange_weak`, it may fail spuriously:
+        /// if the store conditional fails `max_retries + 1` times in a row (e.g., due to interrupts
+        /// or other harts accessing the same reservation set), it returns `Err(current)`.
+        ///
+        /// The `aq`/`rl` bits follow the RVWMO mapping of `order`: `Relaxed` (none), `Acquire` (`lr.aq`),
+        /// `Release` (`sc.rl`), `AcqRel` (`lr.aq` and `sc.rl`), and `SeqCst` (`lr.aqrl` and `sc.rl`).
+        ///
+        /// # Safety
+        ///
+        /// `ptr` must be valid for reads and writes and naturally aligned.
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        ///
+        /// # Example
+        ///
+        /// ``` no_run
+        /// use core::sync::atomic::Ordering;
+        ///
+        #[doc = concat!("static mut LOCK: ", stringify!($ty), " = 0;")]
+        ///
+        /// // SAFETY: `LOCK` is valid and naturally aligned
+        #[doc = concat!("let locked = unsafe { riscv::asm::", stringify!($cas), "(core::ptr::addr_of_mut!(LOCK), 0, 1, Ordering::Acquire, 16) };")]
+        /// assert!(locked.is_ok());
+        /// ```
+        $(#[$cfg])*
+        #[inline(always)]
+        #[cfg_attr(
+            not(any(target_arch = "riscv32", target_arch = "riscv64")),
+            allow(unused_variables)
+        )]
+        pub unsafe fn $cas(
+            ptr: *mut $ty,
+            current: $ty,
+            new: $ty,
+            order: core::sync::atomic::Ordering,
+            max_retries: usize,
+        ) -> Result<$ty, $ty> {
+            match () {
+                #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+                () => {
+                    use core::sync::atomic::Ordering;
+                    // `lr.w` sign-extends the loaded value in RV64
+                    let (expected, new) = (current as $sty as isize, new as usize);
+                    let tries = max_retries.saturating_add(1);
+                    let (prev, fail) = match order {
+                        Ordering::Relaxed => cas_asm!($width, "", "", ptr, expected, new, tries),
+                        Ordering::Acquire => cas_asm!($width, ".aq", "", ptr, expected, new, tries),
+                        Ordering::Release => cas_asm!($width, "", ".rl", ptr, expected, new, tries),
+                        Ordering::AcqRel => cas_asm!($width, ".aq", ".rl", ptr, expected, new, tries),
+                        _ => cas_asm!($width, ".aqrl", ".rl", ptr, expected, new, tries),
+                    };
+                    let prev = prev as $ty;
+                    match prev == current && fail == 0 {
+                        true => Ok(prev),
+                        false => Err(prev),
+                    }
+                }
+                #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+                () => unimplemented!(),
+            }
+        }
+    };
+}
+
+lr_sc!(
+    #[cfg(any(
+        target_feature = "a",
+        not(any(target_arch = "riscv32", target_arch = "riscv64"))
+    ))]
+    , "w", u32, i32, lr_w, sc_w, compare_and_swap_w);
+
+lr_sc!(
+    #[cfg(any(
+        all(target_arch = "riscv64", target_feature = "a"),
+        not(any(target_arch = "riscv32", target_arch = "riscv64"))
+    ))]
+    , "d", u64, i64, lr_d, sc_d, compare_and_swap_d);
@@ -0,0 +1,190 @@
+//! Bit manipulation instructions (Zbb and Zbs extensions)
+//!
+//! The functions of this module use the corresponding instruction of the B extension when the target
+//! supports it (i.e., `target_feature = "zbb"` or `target_feature = "zbs"` is set), and a portable
+//! implementation with the same semantics otherwise. Thus, drivers can use them unconditionally.
+//!
+//! Values are `XLEN` bits wide (i.e., `usize`). Single-bit functions only use the lower
+//! `log2(XLEN)` bits of the bit index, as the Zbs instructions do.
+//!
+//! # Example
+//!
+//! ```
+//! use riscv::bitmanip;
+//!
+//! assert_eq!(bitmanip::cpop(0b1011), 3);
+//! assert_eq!(bitmanip::bset(0, 4), 0b1_0000);
+//! assert_eq!(bitmanip::bext(0b1_0000, 4), 1);
+//! ```
+
+/// Mask of the bit index of single-bit instructions.
+#[allow(dead_code)]
+const SHAMT_MASK: usize = usize::BITS as usize - 1;
+
+/// Portable implementation of [`orc_b`].
+#[allow(dead_code)]
+#[inline]
+fn orc_b_fallback(rs1: usize) -> usize {
+    (0..usize::BITS as usize)
+        .step_by(8)
+        .filter(|shift| (rs1 >> shift) & 0xff != 0)
+        .fold(0, |rd, shift| rd | (0xff << shift))
+}
+
+macro_rules! zbb_unary {
+    ($(#[$attr:meta])*, $fnname:ident, $asm:literal, $fallback:expr) => {
+        $(#[$attr])*
+        #[inline]
+        pub fn $fnname(rs1: usize) -> usize {
+            match () {
+                #[cfg(all(
+                    any(target_arch = "riscv32", target_arch = "riscv64"),
+                    target_feature = "zbb"
+                ))]
+                () => {
+                    let rd: usize;
+                    unsafe {
+                        core::arch::asm!(
+                            concat!($asm, " {0}, {1}"),
+                            out(reg) rd,
+                            in(reg) rs1,
+                            options(pure, nomem, nostack),
+                        )
+                    };
+                    rd
+                }
+                #[cfg(not(all(
+                    any(target_arch = "riscv32", target_arch = "riscv64"),
+                    target_feature = "zbb"
+                )))]
+                () => $fallback(rs1),
+            }
+        }
+    };
+}
+
+macro_rules! zbs_binary {
+    ($(#[$attr:meta])*, $fnname:ident, $asm:literal, $fallback:expr) => {
+        $(#[$attr])*
+        #[inline]
+        pub fn $fnname(rs1: usize, index: usize) -> usize {
+            match () {
+                #[cfg(all(
+                    any(target_arch = "riscv32", target_arch = "riscv64"),
+                    target_feature = "zbs"
+                ))]
+                () => {
+                    let rd: usize;
+                    unsafe {
+                        core::arch::asm!(
+                            concat!($asm, " {0}, {1}, {2}"),
+                            out(reg) rd,
+                            in(reg) rs1,
+                            in(reg) index,
+                            options(pure, nomem, nostack),
+                        )
+                    };
+                    rd
+                }
+                #[cfg(not(all(
+                    any(target_arch = "riscv32", target_arch = "riscv64"),
+                    target_feature = "zbs"
+                )))]
+                () => $fallback(rs1, index & SHAMT_MASK),
+            }
+        }
+    };
+}
+
+zbb_unary!(
+    /// Count leading zero bits (`clz` instruction).
+    ///
+    /// It returns `XLEN` if `rs1` is 0.
+    , clz, "clz", |rs1: usize| rs1.leading_zeros() as usize);
+
+zbb_unary!(
+    /// Count trailing zero bits (`ctz` instruction).
+    ///
+    /// It returns `XLEN` if `rs1` is 0.
+    , ctz, "ctz", |rs1: usize| rs1.trailing_zeros() as usize);
+
+zbb_unary!(
+    /// Count set bits (`cpop` instruction).
+    , cpop, "cpop", |rs1: usize| rs1.count_ones() as usize);
+
+zbb_unary!(
+    /// Bitwise OR-combine of bytes (`orc.b` instruction).
+    ///
+    /// Each byte of the result is `0xff` if the corresponding byte of `rs1` is not zero, and `0x00` otherwise.
+    /// It is useful to find null bytes in a word (e.g., in `strlen`).
+    , orc_b, "orc.b", orc_b_fallback);
+
+zbb_unary!(
+    /// Byte-reverse register (`rev8` instruction).
+    , rev8, "rev8", |rs1: usize| rs1.swap_bytes());
+
+zbs_binary!(
+    /// Single-bit set (`bset` instruction).
+    ///
+    /// It returns `rs1` with the bit `index` set.
+    , bset, "bset", |rs1: usize, index: usize| rs1 | (1 << index));
+
+zbs_binary!(
+    /// Single-bit clear (`bclr` instruction).
+    ///
+    /// It returns `rs1` with the bit `index` cleared.
+    , bclr, "bclr", |rs1: usize, index: usize| rs1 & !(1 << index));
+
+zbs_binary!(
+    /// Single-bit invert (`binv` instruction).
+    ///
+    /// It returns `rs1` with the bit `index` inverted.
+    , binv, "binv", |rs1: usize, index: usize| rs1 ^ (1 << index));
+
+zbs_binary!(
+    /// Single-bit extract (`bext` instruction).
+    ///
+    /// It returns the bit `index` of `rs1` (i.e., 0 or 1).
+    , bext, "bext", |rs1: usize, index: usize| (rs1 >> index) & 1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XLEN: usize = usize::BITS as usize;
+
+    #[test]
+    fn test_zbb() {
+        assert_eq!(clz(0), XLEN);
+        assert_eq!(clz(1), XLEN - 1);
+        assert_eq!(clz(usize::MAX), 0);
+        assert_eq!(ctz(0), XLEN);
+        assert_eq!(ctz(0b1000), 3);
+        assert_eq!(ctz(1 << (XLEN - 1)), XLEN - 1);
+        assert_eq!(cpop(0), 0);
+        assert_eq!(cpop(0b1011), 3);
+        assert_eq!(cpop(usize::MAX), XLEN);
+
+        assert_eq!(orc_b(0), 0);
+        assert_eq!(orc_b(usize::MAX), usize::MAX);
+        assert_eq!(orc_b(0x0100_8000), 0xff00_ff00);
+        assert_eq!(orc_b(1 << (XLEN - 1)), 0xff << (XLEN - 8));
+
+        assert_eq!(rev8(0x0102_0304), 0x0403_0201 << (XLEN - 32));
+        assert_eq!(rev8(rev8(0x1234_5678)), 0x1234_5678);
+    }
+
+    #[test]
+    fn test_zbs() {
+        assert_eq!(bset(0, 0), 1);
+        assert_eq!(bset(0, XLEN - 1), 1 << (XLEN - 1));
+        assert_eq!(bset(0, XLEN + 2), 0b100); // only the lower bits of the index are used
+        assert_eq!(bclr(usize::MAX, 3), !0b1000);
+        assert_eq!(bclr(0, 3), 0);
+        assert_eq!(binv(0b1010, 1), 0b1000);
+        assert_eq!(binv(0b1000, 1), 0b1010);
+        assert_eq!(bext(0b1010, 1), 1);
+        assert_eq!(bext(0b1010, 2), 0);
+        assert_eq!(bext(1 << (XLEN - 1), XLEN - 1), 1);
+    }
+}
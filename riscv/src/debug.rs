@@ -0,0 +1,341 @@
+//! Single-stepping and watchpoints with the trigger module (Sdtrig)
+//!
+//! Instruction count (`icount`) triggers fire after a number of instructions retire in the selected
+//! privilege modes. An `icount` trigger with a count of 1 raises a breakpoint exception right after
+//! the next instruction, so firmware can execute code one instruction at a time (e.g., in instruction
+//! tracers or emulator validation tools).
+//!
+//! Address match (`mcontrol6`) triggers fire when a memory access matches the address in `tdata2`.
+//! [`watch_store`] arms a trigger that raises a breakpoint exception when a store writes to a memory
+//! region (i.e., a watchpoint), which helps to find the code that corrupts a variable.
+//!
+//! # Example
+//!
+//! A M-mode monitor single-steps U-mode code as follows:
+//!
+//! 1. Call [`arm_single_step`] with [`Modes::USER`] to arm a trigger.
+//! 2. Return to U-mode (e.g., with `mret`).
+//! 3. After executing one instruction, the hart takes a `Breakpoint` exception, with `mepc` pointing
+//!    to the next instruction to be executed. The exception handler can inspect the state of the
+//!    hart, and call [`arm_single_step`] again before returning to step the next instruction.
+//! 4. Call [`disarm`] to stop stepping.
+//!
+//! # Note
+//!
+//! This module only provides the primitives to arm triggers. Running a closure one instruction at a
+//! time requires a trap handler that takes the breakpoint exceptions and resumes the stepped code,
+//! which is out of the scope of this crate (e.g., runtimes such as `riscv-rt` can build it on top of
+//! [`arm_single_step`]).
+//!
+//! In M-mode, triggers that raise breakpoint exceptions only fire if `tcontrol.mte` is set
+//! (if `tcontrol` is implemented). Thus, single-stepping M-mode code also requires setting this bit.
+//! The `dcsr.step` bit of the Sdext extension is only accessible in Debug Mode, so it is not
+//! available to firmware.
+//!
+//! **WARNING**: these functions access M-mode CSRs, and return [`Error::Unimplemented`] on non-`riscv` targets.
+
+use crate::register::{tdata1, tdata2, tselect};
+use crate::result::{Error, Result};
+
+/// Position of the `type` field of `tdata1` (most significant 4 bits).
+const TYPE_SHIFT: u32 = usize::BITS - 4;
+/// Value of the `type` field of `tdata1` for instruction count triggers.
+const TYPE_ICOUNT: usize = 3;
+/// `hit` bit of `icount` triggers.
+const ICOUNT_HIT: usize = 1 << 24;
+/// Position of the `count` field of `icount` triggers.
+const ICOUNT_COUNT_SHIFT: u32 = 10;
+/// Maximum value of the `count` field of `icount` triggers (14 bits).
+pub const ICOUNT_MAX: u16 = (1 << 14) - 1;
+/// Value of the `type` field of `tdata1` for address match triggers.
+const TYPE_MCONTROL6: usize = 6;
+/// `hit0` and `hit1` bits of `mcontrol6` triggers.
+const MCONTROL6_HIT: usize = (1 << 25) | (1 << 22);
+/// Position of the `match` field of `mcontrol6` triggers.
+const MCONTROL6_MATCH_SHIFT: u32 = 7;
+/// Mask of the `match` field of `mcontrol6` triggers.
+const MCONTROL6_MATCH_MASK: usize = 0xf << MCONTROL6_MATCH_SHIFT;
+/// `match` value of `mcontrol6` triggers for addresses equal to `tdata2`.
+const MATCH_EQUAL: usize = 0;
+/// `match` value of `mcontrol6` triggers for addresses in the NAPOT region encoded in `tdata2`.
+const MATCH_NAPOT: usize = 1;
+/// `store` bit of `mcontrol6` triggers.
+const MCONTROL6_STORE: usize = 1 << 1;
+/// The privilege mode bits of `mcontrol6` triggers are 3 bits below those of `icount` triggers.
+const MCONTROL6_MODES_SHIFT: u32 = 3;
+
+/// Set of privilege modes in which a trigger fires.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Modes(usize);
+
+impl Modes {
+    /// M-mode.
+    pub const MACHINE: Self = Self(1 << 9);
+    /// S-mode.
+    pub const SUPERVISOR: Self = Self(1 << 7);
+    /// U-mode.
+    pub const USER: Self = Self(1 << 6);
+
+    /// Returns the union of two sets of privilege modes.
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns `true` if all the privilege modes of `other` are in the set.
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Returns the value of `tdata1` for an `icount` trigger that raises a breakpoint exception
+/// after `count` instructions retire in any of the privilege `modes`.
+///
+/// The `count` is truncated to [`ICOUNT_MAX`].
+#[inline]
+pub const fn icount_tdata1(count: u16, modes: Modes) -> usize {
+    let count = (count & ICOUNT_MAX) as usize;
+    // action = 0 (breakpoint exception), dmode = 0 (accessible from M-mode)
+    (TYPE_ICOUNT << TYPE_SHIFT) | (count << ICOUNT_COUNT_SHIFT) | modes.0
+}
+
+/// Returns the values of `tdata1` and `tdata2` for an `mcontrol6` trigger that raises a breakpoint
+/// exception when a store executed in any of the privilege `modes` writes to the `len` bytes starting at `addr`.
+///
+/// `len` is rounded up to the next power of two, and `addr` must be aligned to it. Regions larger
+/// than one byte are matched as a naturally aligned power-of-two (NAPOT) region.
+///
+/// # Errors
+///
+/// - [`Error::InvalidValue`] if `len` cannot be rounded up to a power of two.
+/// - [`Error::InvalidAlignment`] if `addr` is not aligned to the rounded `len`.
+#[inline]
+pub fn store_match_tdata(addr: usize, len: usize, modes: Modes) -> Result<(usize, usize)> {
+    let size = len
+        .max(1)
+        .checked_next_power_of_two()
+        .ok_or(Error::InvalidValue {
+            value: len,
+            bitmask: usize::MAX >> 1,
+        })?;
+    if addr & (size - 1) != 0 {
+        return Err(Error::InvalidAlignment {
+            value: addr,
+            alignment: size,
+        });
+    }
+    // The NAPOT region is encoded in the trailing ones of tdata2
+    let (matching, tdata2) = match size {
+        1 => (MATCH_EQUAL, addr),
+        _ => (MATCH_NAPOT, addr | (size / 2 - 1)),
+    };
+    // action = 0 (breakpoint exception), dmode = 0 (accessible from M-mode), size = 0 (any access size)
+    let tdata1 = (TYPE_MCONTROL6 << TYPE_SHIFT)
+        | (matching << MCONTROL6_MATCH_SHIFT)
+        | (modes.0 >> MCONTROL6_MODES_SHIFT)
+        | MCONTROL6_STORE;
+    Ok((tdata1, tdata2))
+}
+
+/// Selects the trigger `index`.
+///
+/// It returns [`Error::IndexOutOfBounds`] if the trigger is not implemented.
+#[inline]
+fn select(index: usize) -> Result<()> {
+    tselect::try_write(index)?;
+    match tselect::try_read()? {
+        selected if selected == index => Ok(()),
+        _ => Err(Error::IndexOutOfBounds {
+            index,
+            min: 0,
+            max: index.saturating_sub(1),
+        }),
+    }
+}
+
+/// Arms the trigger `index` to single-step the next instruction executed in any of the privilege `modes`.
+///
+/// Once the instruction retires, the hart takes a `Breakpoint` exception. The trigger is
+/// disabled after firing, so this function must be called again to step the next instruction.
+///
+/// # Errors
+///
+/// - [`Error::IndexOutOfBounds`] if the trigger `index` is not implemented.
+/// - [`Error::Unimplemented`] if the trigger does not support `icount` triggers.
+///
+/// # Safety
+///
+/// The breakpoint exception must be handled (e.g., in the `Breakpoint` exception handler).
+/// Arming a trigger can also break debuggers that use the same trigger.
+#[inline]
+pub unsafe fn arm_single_step(index: usize, modes: Modes) -> Result<()> {
+    select(index)?;
+    let config = icount_tdata1(1, modes);
+    // Disable the trigger while it is being configured
+    tdata1::try_write(0)?;
+    tdata1::try_write(config)?;
+    // The type field is WARL: unsupported trigger types are not written
+    match tdata1::try_read()? >> TYPE_SHIFT {
+        TYPE_ICOUNT => Ok(()),
+        _ => {
+            tdata1::try_write(0)?;
+            Err(Error::Unimplemented)
+        }
+    }
+}
+
+/// Arms the trigger `index` to watch the stores to the `len` bytes starting at `addr`
+/// executed in any of the privilege `modes`.
+///
+/// When a store writes to the region, the hart takes a `Breakpoint` exception. Depending on the
+/// implementation, the exception is raised before or after the store is performed. The trigger
+/// remains armed until [`disarm`] is called. Check [`store_match_tdata`] for the supported regions.
+///
+/// # Errors
+///
+/// - [`Error::InvalidValue`] or [`Error::InvalidAlignment`] if the region is not supported.
+/// - [`Error::IndexOutOfBounds`] if the trigger `index` is not implemented.
+/// - [`Error::Unimplemented`] if the trigger does not support `mcontrol6` triggers with the required match mode.
+///
+/// # Safety
+///
+/// The breakpoint exception must be handled (e.g., in the `Breakpoint` exception handler).
+/// If the exception is raised before the store is performed, the handler must disarm the trigger
+/// before returning, or the store raises the exception again. Arming a trigger can also break
+/// debuggers that use the same trigger.
+#[inline]
+pub unsafe fn watch_store(index: usize, addr: usize, len: usize, modes: Modes) -> Result<()> {
+    let (config, tdata2) = store_match_tdata(addr, len, modes)?;
+    select(index)?;
+    // Disable the trigger while it is being configured
+    tdata1::try_write(0)?;
+    tdata2::try_write(tdata2)?;
+    tdata1::try_write(config)?;
+    // The type and match fields are WARL: unsupported configurations are not written
+    match tdata1::try_read()? {
+        tdata1
+            if tdata1 >> TYPE_SHIFT == TYPE_MCONTROL6
+                && tdata1 & MCONTROL6_MATCH_MASK == config & MCONTROL6_MATCH_MASK =>
+        {
+            Ok(())
+        }
+        _ => {
+            tdata1::try_write(0)?;
+            Err(Error::Unimplemented)
+        }
+    }
+}
+
+/// Disarms the trigger `index`.
+///
+/// # Errors
+///
+/// It returns [`Error::IndexOutOfBounds`] if the trigger `index` is not implemented.
+#[inline]
+pub fn disarm(index: usize) -> Result<()> {
+    select(index)?;
+    tdata1::try_write(0)
+}
+
+/// Returns `true` if the `icount` or `mcontrol6` trigger `index` has fired since it was armed.
+///
+/// # Note
+///
+/// The hit bits of `icount` and `mcontrol6` triggers are optional, so this function
+/// may return `false` in platforms that do not implement them. It always returns `false`
+/// for other types of triggers.
+///
+/// # Errors
+///
+/// It returns [`Error::IndexOutOfBounds`] if the trigger `index` is not implemented.
+#[inline]
+pub fn hit(index: usize) -> Result<bool> {
+    select(index)?;
+    let tdata1 = tdata1::try_read()?;
+    let hit = match tdata1 >> TYPE_SHIFT {
+        TYPE_ICOUNT => ICOUNT_HIT,
+        TYPE_MCONTROL6 => MCONTROL6_HIT,
+        _ => 0,
+    };
+    Ok(tdata1 & hit != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes() {
+        let modes = Modes::USER.union(Modes::SUPERVISOR);
+        assert!(modes.contains(Modes::USER));
+        assert!(modes.contains(Modes::SUPERVISOR));
+        assert!(!modes.contains(Modes::MACHINE));
+        assert!(!Modes::default().contains(Modes::USER));
+    }
+
+    #[test]
+    fn test_icount_tdata1() {
+        let tdata1 = icount_tdata1(1, Modes::USER);
+        assert_eq!(tdata1 >> TYPE_SHIFT, TYPE_ICOUNT);
+        assert_eq!(tdata1 & !(0xf << TYPE_SHIFT), (1 << 10) | (1 << 6));
+
+        let tdata1 = icount_tdata1(u16::MAX, Modes::MACHINE.union(Modes::SUPERVISOR));
+        assert_eq!(tdata1 >> TYPE_SHIFT, TYPE_ICOUNT);
+        assert_eq!(
+            tdata1 & !(0xf << TYPE_SHIFT),
+            (0x3fff << 10) | (1 << 9) | (1 << 7)
+        );
+    }
+
+    #[test]
+    fn test_arm_single_step() {
+        assert_eq!(
+            unsafe { arm_single_step(0, Modes::USER) },
+            Err(Error::Unimplemented)
+        );
+        assert_eq!(disarm(0), Err(Error::Unimplemented));
+        assert_eq!(hit(0), Err(Error::Unimplemented));
+    }
+
+    #[test]
+    fn test_store_match_tdata() {
+        let mode_bits = !(0xf << TYPE_SHIFT) & !MCONTROL6_MATCH_MASK;
+
+        let (tdata1, tdata2) = store_match_tdata(0x8000_1003, 1, Modes::MACHINE).unwrap();
+        assert_eq!(tdata1 >> TYPE_SHIFT, TYPE_MCONTROL6);
+        assert_eq!(tdata1 & MCONTROL6_MATCH_MASK, MATCH_EQUAL);
+        assert_eq!(tdata1 & mode_bits, (1 << 6) | MCONTROL6_STORE);
+        assert_eq!(tdata2, 0x8000_1003);
+
+        let modes = Modes::SUPERVISOR.union(Modes::USER);
+        let (tdata1, tdata2) = store_match_tdata(0x8000_1000, 12, modes).unwrap();
+        assert_eq!(tdata1 >> TYPE_SHIFT, TYPE_MCONTROL6);
+        assert_eq!(
+            tdata1 & MCONTROL6_MATCH_MASK,
+            MATCH_NAPOT << MCONTROL6_MATCH_SHIFT
+        );
+        assert_eq!(tdata1 & mode_bits, (1 << 4) | (1 << 3) | MCONTROL6_STORE);
+        assert_eq!(tdata2, 0x8000_1007); // 16-byte region
+
+        assert_eq!(
+            store_match_tdata(0x8000_1000, 0, modes).unwrap().1,
+            0x8000_1000
+        );
+        assert_eq!(
+            store_match_tdata(0x8000_1004, 8, modes),
+            Err(Error::InvalidAlignment {
+                value: 0x8000_1004,
+                alignment: 8
+            })
+        );
+        assert!(matches!(
+            store_match_tdata(0, usize::MAX, modes),
+            Err(Error::InvalidValue { .. })
+        ));
+        assert_eq!(
+            unsafe { watch_store(0, 0x8000_1000, 4, modes) },
+            Err(Error::Unimplemented)
+        );
+    }
+}
@@ -3,6 +3,7 @@
 // NOTE: Adapted from cortex-m/src/interrupt.rs
 
 use crate::result::Result;
+use core::sync::atomic::{AtomicPtr, Ordering};
 
 // re-export useful riscv-pac traits
 pub use riscv_pac::{CoreInterruptNumber, ExceptionNumber, InterruptNumber};
@@ -15,6 +16,45 @@ pub use machine::*;
 #[cfg(feature = "s-mode")]
 pub use supervisor::*;
 
+/// Returns an iterator over the interrupt sources of `I` whose bits are set in `bits`
+/// (e.g., the raw value of `mie` or `mip`).
+#[inline]
+fn sources<I: CoreInterruptNumber>(bits: usize) -> impl Iterator<Item = I> {
+    let max = I::MAX_INTERRUPT_NUMBER.min(usize::BITS as usize - 1);
+    (0..=max)
+        .filter(move |n| bits & (1 << n) != 0)
+        .filter_map(|n| I::from_number(n).ok())
+}
+
+/// Hook called by `try_free` when the closure re-enables interrupts (null for the default hook).
+static POISON_HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Sets the hook called by [`machine::try_free`] and [`supervisor::try_free`] when the closure
+/// re-enables interrupts inside the critical section (i.e., the critical section is poisoned).
+///
+/// The hook is called with interrupts enabled, right after the closure returns.
+/// The default hook panics.
+///
+/// # Note
+///
+/// Poisoning is only detected in debug builds.
+#[inline]
+pub fn set_poison_hook(hook: fn()) {
+    POISON_HOOK.store(hook as *mut (), Ordering::Relaxed);
+}
+
+/// Calls the hook set with [`set_poison_hook`], or panics if no hook is set.
+#[cfg_attr(not(debug_assertions), allow(dead_code))]
+fn poisoned() {
+    let hook = POISON_HOOK.load(Ordering::Relaxed);
+    if hook.is_null() {
+        panic!("interrupts were re-enabled inside a critical section");
+    }
+    // SAFETY: non-null values of `POISON_HOOK` are `fn()` pointers stored by `set_poison_hook`
+    let hook: fn() = unsafe { core::mem::transmute(hook) };
+    hook();
+}
+
 /// Trap Cause.
 ///
 /// This enum represents the cause of a trap. It can be either an interrupt or an exception.
@@ -53,6 +93,9 @@ pub enum Trap<I, E> {
 }
 
 /// Trap Error
+#[deprecated(
+    note = "use `riscv::result::Error` instead, as returned by `InterruptNumber::from_number`"
+)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TrapError {
     InvalidInterrupt(usize),
@@ -60,6 +103,35 @@ pub enum TrapError {
 }
 
 impl Trap<usize, usize> {
+    /// Interrupt bit of raw trap cause codes (i.e., the most significant bit of `mcause`/`scause`).
+    ///
+    /// It is bit 31 in RV32 targets and bit 63 in RV64 targets.
+    pub const INTERRUPT_BIT: usize = 1 << (usize::BITS - 1);
+
+    /// Creates a generic trap cause from the raw bits of a cause register (e.g., `mcause` or `scause`).
+    ///
+    /// The most significant bit (i.e., [`INTERRUPT_BIT`](Self::INTERRUPT_BIT)) indicates whether
+    /// the trap is an interrupt, and the remaining bits contain the interrupt or exception code.
+    #[inline]
+    pub const fn from_cause_bits(bits: usize) -> Self {
+        let code = bits & !Self::INTERRUPT_BIT;
+        match bits & Self::INTERRUPT_BIT {
+            0 => Trap::Exception(code),
+            _ => Trap::Interrupt(code),
+        }
+    }
+
+    /// Returns the raw bits of a cause register (e.g., `mcause` or `scause`) for this trap cause.
+    ///
+    /// The most significant bit of the code is ignored, as it is reserved for the interrupt bit.
+    #[inline]
+    pub const fn to_cause_bits(self) -> usize {
+        match self {
+            Trap::Interrupt(code) => (code & !Self::INTERRUPT_BIT) | Self::INTERRUPT_BIT,
+            Trap::Exception(code) => code & !Self::INTERRUPT_BIT,
+        }
+    }
+
     /// Converts a target-specific trap cause to a generic trap cause
     #[inline]
     pub fn from<I: CoreInterruptNumber, E: ExceptionNumber>(trap: Trap<I, E>) -> Self {
@@ -96,3 +168,71 @@ impl<I: CoreInterruptNumber, E: ExceptionNumber> Trap<I, E> {
         trap.try_into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trap_cause_bits() {
+        let bit = Trap::INTERRUPT_BIT;
+        assert_eq!(bit, 1 << (usize::BITS - 1));
+
+        for code in [0, 1, 7, 11, 0x42, !bit] {
+            let interrupt = Trap::Interrupt(code);
+            let exception = Trap::Exception(code);
+
+            assert_eq!(interrupt.to_cause_bits(), code | bit);
+            assert_eq!(exception.to_cause_bits(), code);
+            assert_eq!(Trap::from_cause_bits(code | bit), interrupt);
+            assert_eq!(Trap::from_cause_bits(code), exception);
+        }
+
+        // the interrupt bit of the code is ignored
+        assert_eq!(Trap::Interrupt(usize::MAX).to_cause_bits(), usize::MAX);
+        assert_eq!(Trap::Exception(usize::MAX).to_cause_bits(), !bit);
+
+        let trap: Trap<machine::Interrupt, machine::Exception> =
+            Trap::from_cause_bits(bit | 7).try_into().unwrap();
+        assert_eq!(trap, Trap::Interrupt(machine::Interrupt::MachineTimer));
+    }
+
+    #[test]
+    fn test_sources() {
+        use machine::Interrupt::*;
+
+        let none: [machine::Interrupt; 0] = [];
+        assert!(sources::<machine::Interrupt>(0).eq(none));
+        assert!(sources::<machine::Interrupt>(1 << 3 | 1 << 7).eq([MachineSoft, MachineTimer]));
+        // bits of invalid sources are skipped
+        assert!(sources::<machine::Interrupt>(usize::MAX).eq([
+            SupervisorSoft,
+            MachineSoft,
+            SupervisorTimer,
+            MachineTimer,
+            SupervisorExternal,
+            MachineExternal
+        ]));
+
+        let r = machine::try_enabled_sources::<machine::Interrupt>().map(|s| s.count());
+        assert_eq!(r, Err(crate::result::Error::Unimplemented));
+        let r = supervisor::try_pending_sources::<supervisor::Interrupt>().map(|s| s.count());
+        assert_eq!(r, Err(crate::result::Error::Unimplemented));
+    }
+
+    #[test]
+    fn test_poison_hook() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn hook() {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        }
+
+        set_poison_hook(hook);
+        poisoned();
+        poisoned();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    }
+}
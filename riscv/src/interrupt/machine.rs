@@ -1,7 +1,8 @@
 use crate::{
     interrupt::Trap,
-    register::{mcause, mepc, mstatus},
+    register::{mcause, mepc, mie, mintthresh, mip, mstatus},
 };
+use core::cmp::Ordering;
 use riscv_pac::{
     result::{Error, Result},
     CoreInterruptNumber, ExceptionNumber, InterruptNumber,
@@ -45,6 +46,36 @@ unsafe impl InterruptNumber for Interrupt {
 /// SAFETY: `Interrupt` represents the standard RISC-V core interrupts
 unsafe impl CoreInterruptNumber for Interrupt {}
 
+impl Interrupt {
+    /// Standard M-mode interrupts sorted by their architected priority, from highest to lowest.
+    ///
+    /// When several interrupts are pending and enabled at the same time, the hart traps to the
+    /// first one of this list: MEI > MSI > MTI > SEI > SSI > STI.
+    pub const PRIORITY_ORDER: [Self; 6] = [
+        Self::MachineExternal,
+        Self::MachineSoft,
+        Self::MachineTimer,
+        Self::SupervisorExternal,
+        Self::SupervisorSoft,
+        Self::SupervisorTimer,
+    ];
+
+    /// Returns the architected priority of the interrupt.
+    ///
+    /// Higher values mean higher priority, from 0 (`SupervisorTimer`) to 5 (`MachineExternal`).
+    #[inline]
+    pub const fn priority(self) -> usize {
+        match self {
+            Self::MachineExternal => 5,
+            Self::MachineSoft => 4,
+            Self::MachineTimer => 3,
+            Self::SupervisorExternal => 2,
+            Self::SupervisorSoft => 1,
+            Self::SupervisorTimer => 0,
+        }
+    }
+}
+
 /// Standard M-mode RISC-V exceptions
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(usize)]
@@ -131,6 +162,62 @@ pub fn cause<I: CoreInterruptNumber, E: ExceptionNumber>() -> Trap<I, E> {
     try_cause().unwrap()
 }
 
+/// Returns an iterator over the interrupt sources enabled in [`mie`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+/// If the register is not accessible, it returns an error.
+#[inline]
+pub fn try_enabled_sources<I: CoreInterruptNumber>() -> Result<impl Iterator<Item = I>> {
+    Ok(super::sources(mie::try_read()?.bits()))
+}
+
+/// Returns an iterator over the interrupt sources enabled in [`mie`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+#[inline]
+pub fn enabled_sources<I: CoreInterruptNumber>() -> impl Iterator<Item = I> {
+    try_enabled_sources().unwrap()
+}
+
+/// Returns an iterator over the interrupt sources pending in [`mip`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+/// If the register is not accessible, it returns an error.
+#[inline]
+pub fn try_pending_sources<I: CoreInterruptNumber>() -> Result<impl Iterator<Item = I>> {
+    Ok(super::sources(mip::try_read()?.bits()))
+}
+
+/// Returns an iterator over the interrupt sources pending in [`mip`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+#[inline]
+pub fn pending_sources<I: CoreInterruptNumber>() -> impl Iterator<Item = I> {
+    try_pending_sources().unwrap()
+}
+
+/// Compares two interrupts by their architected priority.
+///
+/// It returns [`Ordering::Greater`] if `a` has higher priority than `b`. Thus, software dispatchers
+/// can sort interrupts from highest to lowest priority with `sort_by(|a, b| cause_priority(*b, *a))`.
+#[inline]
+pub fn cause_priority(a: Interrupt, b: Interrupt) -> Ordering {
+    a.priority().cmp(&b.priority())
+}
+
+/// Returns the pending interrupt with the highest architected priority in a [`mip`] snapshot.
+///
+/// Software dispatchers that poll instead of trapping can use it to handle the pending sources
+/// in the same order as the hardware. To only consider enabled interrupts, mask the snapshot
+/// with [`mie`] (e.g., `Mip::from_bits(mip.bits() & mie.bits())`).
+#[inline]
+pub fn highest_pending(mip: mip::Mip) -> Option<Interrupt> {
+    Interrupt::PRIORITY_ORDER
+        .iter()
+        .copied()
+        .find(|&interrupt| mip.bits() & (1 << interrupt.number()) != 0)
+}
+
 /// Execute closure `f` with interrupts disabled in the current hart (machine mode).
 ///
 /// This method does not synchronise multiple harts, so it is not suitable for
@@ -160,6 +247,47 @@ where
     r
 }
 
+/// Execute closure `f` with interrupts disabled in the current hart (machine mode),
+/// checking that `f` does not re-enable them.
+///
+/// It behaves as [`free`], but in debug builds it reads back [`mstatus`] when `f` returns.
+/// If `f` re-enabled the interrupts, the critical section was broken, and the hook set with
+/// [`set_poison_hook`](crate::interrupt::set_poison_hook) is called before restoring
+/// the previous interrupt state.
+///
+/// # Errors
+///
+/// It returns [`Error::Unimplemented`] without calling `f` if [`mstatus`] is not accessible
+/// (e.g., in non-RISC-V targets).
+#[inline]
+pub fn try_free<F, R>(f: F) -> Result<R>
+where
+    F: FnOnce() -> R,
+{
+    let mstatus = mstatus::try_read()?;
+
+    // disable interrupts
+    disable();
+
+    let r = f();
+
+    // In debug builds, check that `f` did not re-enable interrupts
+    #[cfg(debug_assertions)]
+    if mstatus::read().mie() {
+        super::poisoned();
+    }
+
+    // If the interrupts were active before our `disable` call, then re-enable
+    // them. Otherwise, make sure they are disabled
+    if mstatus.mie() {
+        unsafe { enable() };
+    } else {
+        disable();
+    }
+
+    Ok(r)
+}
+
 /// Execute closure `f` with interrupts enabled in the current hart (machine mode).
 ///
 /// This method is assumed to be called within an interrupt handler, and allows
@@ -203,6 +331,35 @@ where
     r
 }
 
+/// Execute closure `f` with the interrupt level threshold raised to, at least, `level`
+/// in the current hart (machine mode).
+///
+/// This function is intended for targets with a CLIC. It temporarily blocks all the interrupts
+/// with a level lower than or equal to `level`, in the same way that the threshold of a PLIC context works.
+/// If the current threshold is already greater than or equal to `level`, it is left unchanged.
+/// After the closure `f` is executed, the [`mintthresh`] register is restored to its previous value.
+#[inline]
+pub fn with_priority_floor<F, R>(level: u8, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let mintthresh = mintthresh::read();
+
+    // raise the threshold only if it blocks more interrupts
+    if level as usize > mintthresh.th() {
+        let mut floor = mintthresh;
+        floor.set_th(level as usize);
+        mintthresh::write(floor);
+    }
+
+    let r = f();
+
+    // restore the previous threshold
+    mintthresh::write(mintthresh);
+
+    r
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -234,6 +391,64 @@ mod test {
         assert_eq!(MachineExternal.number(), Interrupt::MAX_INTERRUPT_NUMBER)
     }
 
+    #[test]
+    fn test_priority() {
+        for (i, &interrupt) in Interrupt::PRIORITY_ORDER.iter().enumerate() {
+            assert_eq!(interrupt.priority(), 5 - i);
+        }
+
+        assert_eq!(
+            cause_priority(MachineExternal, MachineSoft),
+            Ordering::Greater
+        );
+        assert_eq!(
+            cause_priority(MachineTimer, SupervisorExternal),
+            Ordering::Greater
+        );
+        assert_eq!(
+            cause_priority(SupervisorTimer, SupervisorSoft),
+            Ordering::Less
+        );
+        assert_eq!(cause_priority(MachineSoft, MachineSoft), Ordering::Equal);
+
+        let mut interrupts = [
+            SupervisorTimer,
+            MachineTimer,
+            SupervisorSoft,
+            MachineExternal,
+        ];
+        interrupts.sort_by(|a, b| cause_priority(*b, *a));
+        assert_eq!(
+            interrupts,
+            [
+                MachineExternal,
+                MachineTimer,
+                SupervisorSoft,
+                SupervisorTimer
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highest_pending() {
+        let mip = |bits| mip::Mip::from_bits(bits);
+
+        assert_eq!(highest_pending(mip(0)), None);
+        assert_eq!(highest_pending(mip(1 << 5)), Some(SupervisorTimer));
+        assert_eq!(
+            highest_pending(mip((1 << 5) | (1 << 1))),
+            Some(SupervisorSoft)
+        );
+        assert_eq!(
+            highest_pending(mip((1 << 9) | (1 << 7))),
+            Some(MachineTimer)
+        );
+        assert_eq!(highest_pending(mip((1 << 7) | (1 << 3))), Some(MachineSoft));
+        assert_eq!(highest_pending(mip(0xaaa)), Some(MachineExternal));
+        // non-standard bits are ignored
+        assert_eq!(highest_pending(mip(1 << 13)), None);
+    }
+
     #[test]
     fn test_exception() {
         assert_eq!(Exception::from_number(0), Ok(InstructionMisaligned));
@@ -271,4 +486,11 @@ mod test {
 
         assert_eq!(StorePageFault.number(), Exception::MAX_EXCEPTION_NUMBER)
     }
+
+    #[test]
+    fn test_try_free() {
+        // CSRs are not accessible in non-RISC-V targets
+        let r = try_free(|| unreachable!("the closure must not be called"));
+        assert_eq!(r, Err::<(), _>(Error::Unimplemented));
+    }
 }
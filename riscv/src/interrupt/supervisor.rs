@@ -1,6 +1,6 @@
 use crate::{
     interrupt::Trap,
-    register::{scause, sepc, sstatus},
+    register::{scause, sepc, sie, sintthresh, sip, sstatus},
 };
 use riscv_pac::{
     result::{Error, Result},
@@ -123,6 +123,40 @@ pub fn cause<I: CoreInterruptNumber, E: ExceptionNumber>() -> Trap<I, E> {
     try_cause().unwrap()
 }
 
+/// Returns an iterator over the interrupt sources enabled in [`sie`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+/// If the register is not accessible, it returns an error.
+#[inline]
+pub fn try_enabled_sources<I: CoreInterruptNumber>() -> Result<impl Iterator<Item = I>> {
+    Ok(super::sources(sie::try_read()?.bits()))
+}
+
+/// Returns an iterator over the interrupt sources enabled in [`sie`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+#[inline]
+pub fn enabled_sources<I: CoreInterruptNumber>() -> impl Iterator<Item = I> {
+    try_enabled_sources().unwrap()
+}
+
+/// Returns an iterator over the interrupt sources pending in [`sip`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+/// If the register is not accessible, it returns an error.
+#[inline]
+pub fn try_pending_sources<I: CoreInterruptNumber>() -> Result<impl Iterator<Item = I>> {
+    Ok(super::sources(sip::try_read()?.bits()))
+}
+
+/// Returns an iterator over the interrupt sources pending in [`sip`].
+///
+/// Set bits that do not correspond to a valid interrupt source of `I` are skipped.
+#[inline]
+pub fn pending_sources<I: CoreInterruptNumber>() -> impl Iterator<Item = I> {
+    try_pending_sources().unwrap()
+}
+
 /// Execute closure `f` with interrupts disabled in the current hart (supervisor mode).
 ///
 /// This method does not synchronise multiple harts, so it is not suitable for
@@ -152,6 +186,47 @@ where
     r
 }
 
+/// Execute closure `f` with interrupts disabled in the current hart (supervisor mode),
+/// checking that `f` does not re-enable them.
+///
+/// It behaves as [`free`], but in debug builds it reads back [`sstatus`] when `f` returns.
+/// If `f` re-enabled the interrupts, the critical section was broken, and the hook set with
+/// [`set_poison_hook`](crate::interrupt::set_poison_hook) is called before restoring
+/// the previous interrupt state.
+///
+/// # Errors
+///
+/// It returns [`Error::Unimplemented`] without calling `f` if [`sstatus`] is not accessible
+/// (e.g., in non-RISC-V targets).
+#[inline]
+pub fn try_free<F, R>(f: F) -> Result<R>
+where
+    F: FnOnce() -> R,
+{
+    let sstatus = sstatus::try_read()?;
+
+    // disable interrupts
+    disable();
+
+    let r = f();
+
+    // In debug builds, check that `f` did not re-enable interrupts
+    #[cfg(debug_assertions)]
+    if sstatus::read().sie() {
+        super::poisoned();
+    }
+
+    // If the interrupts were active before our `disable` call, then re-enable
+    // them. Otherwise, make sure they are disabled
+    if sstatus.sie() {
+        unsafe { enable() };
+    } else {
+        disable();
+    }
+
+    Ok(r)
+}
+
 /// Execute closure `f` with interrupts enabled in the current hart (supervisor mode).
 ///
 /// This method is assumed to be called within an interrupt handler, and allows
@@ -193,6 +268,35 @@ where
     r
 }
 
+/// Execute closure `f` with the interrupt level threshold raised to, at least, `level`
+/// in the current hart (supervisor mode).
+///
+/// This function is intended for targets with a CLIC. It temporarily blocks all the interrupts
+/// with a level lower than or equal to `level`, in the same way that the threshold of a PLIC context works.
+/// If the current threshold is already greater than or equal to `level`, it is left unchanged.
+/// After the closure `f` is executed, the [`sintthresh`] register is restored to its previous value.
+#[inline]
+pub fn with_priority_floor<F, R>(level: u8, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let sintthresh = sintthresh::read();
+
+    // raise the threshold only if it blocks more interrupts
+    if level as usize > sintthresh.th() {
+        let mut floor = sintthresh;
+        floor.set_th(level as usize);
+        sintthresh::write(floor);
+    }
+
+    let r = f();
+
+    // restore the previous threshold
+    sintthresh::write(sintthresh);
+
+    r
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -257,4 +361,11 @@ mod test {
 
         assert_eq!(StorePageFault.number(), Exception::MAX_EXCEPTION_NUMBER)
     }
+
+    #[test]
+    fn test_try_free() {
+        // CSRs are not accessible in non-RISC-V targets
+        let r = try_free(|| unreachable!("the closure must not be called"));
+        assert_eq!(r, Err::<(), _>(Error::Unimplemented));
+    }
 }
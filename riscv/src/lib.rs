@@ -21,6 +21,8 @@
 //! By default, the crate assumes that the target is running in M-mode.
 //! Thus, `interrupt` re-exports the M-mode functions defined in `interrupt::machine`.
 //!
+//! It also makes [`hartid`] get the hart ID from the runtime instead of reading `mhartid`.
+//!
 //! ## `critical-section-single-hart`
 //!
 //! This feature enables a [`critical-section`](https://github.com/rust-embedded/critical-section)
@@ -39,10 +41,21 @@
 pub use paste::paste;
 
 pub mod asm;
+pub mod bitmanip;
 pub mod bits;
+pub mod debug;
 pub mod delay;
 pub mod interrupt;
+pub mod paging;
+pub mod perf;
 pub mod register;
+pub mod sbi;
+pub mod sync;
+#[cfg(any(
+    target_feature = "v",
+    not(any(target_arch = "riscv32", target_arch = "riscv64"))
+))]
+pub mod vector;
 
 // Re-export crates of the RISC-V ecosystem
 #[cfg(feature = "riscv-macros")]
@@ -55,6 +68,30 @@ mod macros;
 #[cfg(all(riscv, feature = "critical-section-single-hart"))]
 mod critical_section;
 
+/// Returns the ID of the current hart.
+///
+/// In M-mode, it reads the [`mhartid`](register::mhartid) CSR. As `mhartid` is not accessible in S-mode,
+/// with the `s-mode` feature it calls the `_riscv_hartid` function provided by the runtime instead.
+/// The runtime gets the hart ID at boot (e.g., in `a0` from the SBI implementation) and must return
+/// it from any context of the hart. `riscv-rt` provides `_riscv_hartid` with its `s-mode` feature.
+///
+/// Libraries can use this function instead of threading the boot argument through every layer.
+#[inline]
+pub fn hartid() -> usize {
+    match () {
+        #[cfg(not(feature = "s-mode"))]
+        () => register::mhartid::read(),
+        #[cfg(feature = "s-mode")]
+        () => {
+            extern "C" {
+                fn _riscv_hartid() -> usize;
+            }
+            // SAFETY: `_riscv_hartid` is provided by the runtime and has no side effects
+            unsafe { _riscv_hartid() }
+        }
+    }
+}
+
 /// Used to reexport items for use in macros. Do not use directly.
 /// Not covered by semver guarantees.
 #[doc(hidden)]
@@ -1,3 +1,136 @@
+/// Asserts that a boolean expression is `true` in debug builds, stopping at a breakpoint otherwise.
+///
+/// If the assertion fails, this macro triggers a [breakpoint](crate::asm::breakpoint) before panicking
+/// as [`debug_assert!`] does. In this way, an attached debugger stops right at the failing assertion,
+/// with the state of the hart before unwinding to the panic handler. Without a debugger, the breakpoint
+/// exception must be handled (i.e., stepped over) for the panic to be reached.
+///
+/// As [`debug_assert!`], this macro is only enabled in builds with debug assertions.
+/// On non-`riscv` targets, it does not trigger the breakpoint.
+///
+/// # Example
+///
+/// ``` no_run
+/// let len = 4;
+/// riscv::debug_assert_break!(len % 4 == 0);
+/// riscv::debug_assert_break!(len < 8, "length overflow: {}", len);
+/// ```
+#[macro_export]
+macro_rules! debug_assert_break {
+    ($cond:expr $(,)?) => {
+        $crate::debug_assert_break!($cond, "{}", concat!("assertion failed: ", stringify!($cond)))
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if cfg!(debug_assertions) && !$cond {
+            #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+            $crate::asm::breakpoint();
+            panic!($($arg)+);
+        }
+    };
+}
+
+/// Aligns the next instruction of the current function to `bytes` bytes.
+///
+/// The assembler pads the code with `nop` instructions up to the next `bytes` boundary.
+/// The padding is executed, so timing-sensitive code must take it into account. This macro is
+/// useful to place loops that must not cross cache or fetch boundaries.
+///
+/// `bytes` must be a literal power of 2. Otherwise, the build fails.
+/// On non-`riscv` targets, this macro does nothing.
+///
+/// # Example
+///
+/// ``` no_run
+/// riscv::align_code!(16);
+/// riscv::asm::nops::<4>();
+/// ```
+#[macro_export]
+macro_rules! align_code {
+    ($bytes:literal) => {{
+        const _: () = assert!(
+            ($bytes as usize).is_power_of_two(),
+            concat!("align_code: ", stringify!($bytes), " is not a power of 2"),
+        );
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        unsafe {
+            ::core::arch::asm!(
+                concat!(".balign ", stringify!($bytes)),
+                // Not `nomem`: the padding must stay between the memory accesses around it
+                options(nostack)
+            )
+        };
+    }};
+}
+
+/// Selects items depending on the standard RISC-V extensions of the target.
+///
+/// `cfg_riscv!(if EXT { ... } else { ... })` expands the first block of items if the target supports the
+/// single-letter extension `EXT`, and the optional `else` block otherwise. Only the single-letter extensions
+/// `i`, `e`, `m`, `a`, `f`, `d`, `c`, `b`, and `v` are accepted. Thus, typos (e.g., `if md`) fail at
+/// compile time instead of silently disabling code.
+///
+/// # Note
+///
+/// The extensions are checked with the `target_feature` cfgs of `rustc` (e.g., `target_feature = "m"`),
+/// so crates using this macro do not need a build script. Extension `i` is supported by all the RISC-V
+/// targets except RV32E/RV64E. On non-`riscv` targets, no extension is supported.
+///
+/// # Example
+///
+/// ``` no_run
+/// riscv::cfg_riscv!(if m {
+///     fn mul(a: u32, b: u32) -> u32 { a * b }
+/// } else {
+///     fn mul(a: u32, b: u32) -> u32 { (0..b).fold(0, |acc, _| acc + a) }
+/// });
+/// ```
+#[macro_export]
+macro_rules! cfg_riscv {
+    (if i $($rest:tt)*) => {
+        $crate::cfg_riscv!(@cfg [all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            not(target_feature = "e")
+        )] $($rest)*);
+    };
+    (if e $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "e" $($rest)*);
+    };
+    (if m $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "m" $($rest)*);
+    };
+    (if a $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "a" $($rest)*);
+    };
+    (if f $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "f" $($rest)*);
+    };
+    (if d $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "d" $($rest)*);
+    };
+    (if c $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "c" $($rest)*);
+    };
+    (if b $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "b" $($rest)*);
+    };
+    (if v $($rest:tt)*) => {
+        $crate::cfg_riscv!(@feature "v" $($rest)*);
+    };
+    (if $ext:ident $($rest:tt)*) => {
+        compile_error!(concat!("cfg_riscv: unknown RISC-V extension `", stringify!($ext), "`"));
+    };
+    (@feature $feature:literal $($rest:tt)*) => {
+        $crate::cfg_riscv!(@cfg [all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_feature = $feature
+        )] $($rest)*);
+    };
+    (@cfg [$cfg:meta] { $($then:item)* } $(else { $($else:item)* })?) => {
+        $(#[cfg($cfg)] $then)*
+        $($(#[cfg(not($cfg))] $else)*)?
+    };
+}
+
 /// Macro to create a mutable reference to a statically allocated value
 ///
 /// This macro returns a value with type `Option<&'static mut $ty>`. `Some($expr)` will be returned
@@ -55,3 +188,34 @@ macro_rules! singleton {
         })
     };
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_debug_assert_break() {
+        let len = 4;
+        debug_assert_break!(len % 4 == 0);
+        debug_assert_break!(len < 8, "length overflow: {}", len);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: len % 8 == 0")]
+    fn test_debug_assert_break_fail() {
+        let len = 4;
+        debug_assert_break!(len % 8 == 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: [len].iter().all(|l| { l % 8 == 0 })")]
+    fn test_debug_assert_break_fail_braces() {
+        let len = 4;
+        debug_assert_break!([len].iter().all(|l| { l % 8 == 0 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "length overflow: 4")]
+    fn test_debug_assert_break_fail_msg() {
+        let len = 4;
+        debug_assert_break!(len < 4, "length overflow: {}", len);
+    }
+}
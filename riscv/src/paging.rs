@@ -0,0 +1,391 @@
+//! Page table encodings
+//!
+//! Constants and helpers to encode page table entries (PTEs) and to compute page sizes and
+//! virtual page numbers of the Sv32, Sv39, Sv48, and Sv57 page-based virtual memory schemes.
+//! This module does not allocate or walk page tables: it only provides the encodings defined by
+//! the privileged specification and the Svnapot and Svpbmt extensions, so kernels do not need to
+//! redefine them.
+//!
+//! PTEs are represented as `u64` values. Sv32 PTEs are 32 bits wide, and must be truncated to `u32`
+//! before writing them to a page table. Note that Sv32 PTEs do not support the [`pte::PBMT_MASK`] and
+//! [`pte::N`] bits.
+//!
+//! The helpers of [`Scheme`] compute sizes and indices as `usize` values. Thus, the Sv39, Sv48,
+//! and Sv57 schemes are only fully supported in 64-bit targets.
+//!
+//! # Example
+//!
+//! ```
+//! use riscv::paging::{pte, Pbmt, Scheme};
+//!
+//! // 2 MiB megapage for a device, readable and writable
+//! let scheme = Scheme::Sv39;
+//! assert_eq!(scheme.page_size(1), Ok(0x20_0000));
+//! let flags = pte::V | pte::R | pte::W | pte::A | pte::D | Pbmt::Io.bits();
+//! let entry = pte::new(0x1000_0000 >> 12, flags);
+//! assert_eq!(pte::ppn(entry), 0x1_0000);
+//! assert_eq!(Pbmt::from_pte(entry), Ok(Pbmt::Io));
+//! ```
+
+use crate::result::{Error, Result};
+
+/// Number of bits of the offset within a 4 KiB base page.
+pub const PAGE_SHIFT: usize = 12;
+
+/// Size (in bytes) of a base page.
+pub const PAGE_SIZE: usize = 1 << PAGE_SHIFT;
+
+/// Page table entry bits and encodings.
+pub mod pte {
+    use crate::result::{Error, Result};
+
+    /// Valid.
+    pub const V: u64 = 1 << 0;
+    /// Readable.
+    pub const R: u64 = 1 << 1;
+    /// Writable.
+    pub const W: u64 = 1 << 2;
+    /// Executable.
+    pub const X: u64 = 1 << 3;
+    /// Accessible to U-mode.
+    pub const U: u64 = 1 << 4;
+    /// Global mapping.
+    pub const G: u64 = 1 << 5;
+    /// Accessed.
+    pub const A: u64 = 1 << 6;
+    /// Dirty.
+    pub const D: u64 = 1 << 7;
+
+    /// Mask of the permission bits (`R`, `W`, and `X`). A PTE is a leaf if any of these bits is set.
+    pub const RWX_MASK: u64 = R | W | X;
+    /// Mask of the flag bits (`V` to `D`).
+    pub const FLAGS_MASK: u64 = 0xFF;
+
+    /// Offset of the bits reserved for supervisor software.
+    pub const RSW_SHIFT: usize = 8;
+    /// Mask of the bits reserved for supervisor software.
+    pub const RSW_MASK: u64 = 0b11 << RSW_SHIFT;
+
+    /// Offset of the physical page number.
+    pub const PPN_SHIFT: usize = 10;
+    /// Mask of the physical page number (44 bits in Sv39, Sv48, and Sv57; 22 bits in Sv32), once shifted.
+    pub const PPN_MASK: u64 = (1 << 44) - 1;
+
+    /// Offset of the page-based memory type bits (Svpbmt).
+    pub const PBMT_SHIFT: usize = 61;
+    /// Mask of the page-based memory type bits (Svpbmt).
+    pub const PBMT_MASK: u64 = 0b11 << PBMT_SHIFT;
+
+    /// NAPOT translation contiguity bit (Svnapot).
+    pub const N: u64 = 1 << 63;
+
+    /// Number of base pages of a 64 KiB NAPOT page (Svnapot).
+    pub const NAPOT_64K_PAGES: u64 = 16;
+    /// Size (in bytes) of a 64 KiB NAPOT page (Svnapot).
+    pub const NAPOT_64K_SIZE: usize = 64 * 1024;
+
+    /// Encodes a PTE with the physical page number `ppn` and the bits of `flags`.
+    ///
+    /// `flags` may contain any bit except those of the physical page number.
+    #[inline]
+    pub const fn new(ppn: u64, flags: u64) -> u64 {
+        ((ppn & PPN_MASK) << PPN_SHIFT) | (flags & !(PPN_MASK << PPN_SHIFT))
+    }
+
+    /// Returns the physical page number of a PTE.
+    ///
+    /// For NAPOT PTEs, the lower 4 bits contain the NAPOT encoding (see [`napot_64k`]).
+    #[inline]
+    pub const fn ppn(pte: u64) -> u64 {
+        (pte >> PPN_SHIFT) & PPN_MASK
+    }
+
+    /// Returns the flag bits (`V` to `D`) of a PTE.
+    #[inline]
+    pub const fn flags(pte: u64) -> u64 {
+        pte & FLAGS_MASK
+    }
+
+    /// Returns `true` if the PTE is valid.
+    #[inline]
+    pub const fn is_valid(pte: u64) -> bool {
+        pte & V != 0
+    }
+
+    /// Returns `true` if the PTE is a leaf (i.e., any of the `R`, `W`, or `X` bits is set).
+    /// Otherwise, valid PTEs point to the next level of the page table.
+    #[inline]
+    pub const fn is_leaf(pte: u64) -> bool {
+        pte & RWX_MASK != 0
+    }
+
+    /// Returns `true` if the PTE is a NAPOT PTE (Svnapot).
+    #[inline]
+    pub const fn is_napot(pte: u64) -> bool {
+        pte & N != 0
+    }
+
+    /// Encodes a 64 KiB NAPOT leaf PTE (Svnapot) for the base page with physical page number `ppn`.
+    ///
+    /// The PTE must be replicated in the 16 level-0 entries that map the 64 KiB region.
+    ///
+    /// # Errors
+    ///
+    /// If `ppn` is not aligned to [`NAPOT_64K_PAGES`], it returns [`Error::InvalidAlignment`].
+    #[inline]
+    pub const fn napot_64k(ppn: u64, flags: u64) -> Result<u64> {
+        if ppn % NAPOT_64K_PAGES != 0 {
+            return Err(Error::InvalidAlignment {
+                value: ppn as usize,
+                alignment: NAPOT_64K_PAGES as usize,
+            });
+        }
+        Ok(new(ppn | (NAPOT_64K_PAGES >> 1), flags | N))
+    }
+}
+
+/// Page-based memory types (Svpbmt).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u64)]
+pub enum Pbmt {
+    /// None: use the physical memory attributes of the region.
+    Pma = 0,
+    /// Non-cacheable, idempotent, weakly-ordered main memory.
+    Nc = 1,
+    /// Non-cacheable, non-idempotent, strongly-ordered I/O memory.
+    Io = 2,
+}
+
+impl Pbmt {
+    /// Returns the bits of the memory type, at their position in a PTE.
+    #[inline]
+    pub const fn bits(self) -> u64 {
+        (self as u64) << pte::PBMT_SHIFT
+    }
+
+    /// Returns the memory type of a PTE.
+    ///
+    /// # Errors
+    ///
+    /// If the memory type is reserved, it returns [`Error::InvalidVariant`].
+    #[inline]
+    pub const fn from_pte(pte: u64) -> Result<Self> {
+        match (pte & pte::PBMT_MASK) >> pte::PBMT_SHIFT {
+            0 => Ok(Self::Pma),
+            1 => Ok(Self::Nc),
+            2 => Ok(Self::Io),
+            value => Err(Error::InvalidVariant(value as usize)),
+        }
+    }
+}
+
+/// Page-based virtual memory schemes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Scheme {
+    /// Page-based 32-bit virtual addressing
+    Sv32,
+    /// Page-based 39-bit virtual addressing
+    Sv39,
+    /// Page-based 48-bit virtual addressing
+    Sv48,
+    /// Page-based 57-bit virtual addressing
+    Sv57,
+}
+
+impl Scheme {
+    /// Returns the value of the `MODE` field of `satp` for this scheme.
+    #[inline]
+    pub const fn satp_mode(self) -> usize {
+        match self {
+            Self::Sv32 => 1,
+            Self::Sv39 => 8,
+            Self::Sv48 => 9,
+            Self::Sv57 => 10,
+        }
+    }
+
+    /// Returns the number of levels of the page table.
+    #[inline]
+    pub const fn levels(self) -> usize {
+        match self {
+            Self::Sv32 => 2,
+            Self::Sv39 => 3,
+            Self::Sv48 => 4,
+            Self::Sv57 => 5,
+        }
+    }
+
+    /// Returns the number of bits of a virtual address.
+    #[inline]
+    pub const fn va_bits(self) -> usize {
+        PAGE_SHIFT + self.levels() * self.vpn_bits()
+    }
+
+    /// Returns the size (in bytes) of a PTE.
+    #[inline]
+    pub const fn pte_size(self) -> usize {
+        match self {
+            Self::Sv32 => 4,
+            _ => 8,
+        }
+    }
+
+    /// Returns the number of bits of each virtual page number field.
+    #[inline]
+    pub const fn vpn_bits(self) -> usize {
+        match self {
+            Self::Sv32 => 10,
+            _ => 9,
+        }
+    }
+
+    /// Returns the number of PTEs of a page table (i.e., a base page).
+    #[inline]
+    pub const fn entries(self) -> usize {
+        1 << self.vpn_bits()
+    }
+
+    /// Checks that `level` is a valid level of the page table.
+    #[inline]
+    const fn check_level(self, level: usize) -> Result<()> {
+        match level < self.levels() {
+            true => Ok(()),
+            false => Err(Error::IndexOutOfBounds {
+                index: level,
+                min: 0,
+                max: self.levels() - 1,
+            }),
+        }
+    }
+
+    /// Returns the size (in bytes) of the pages mapped by a leaf PTE at `level`.
+    ///
+    /// Level 0 maps base pages. For example, in Sv39, level 1 maps 2 MiB megapages
+    /// and level 2 maps 1 GiB gigapages.
+    ///
+    /// # Errors
+    ///
+    /// If `level` is not lower than [`Scheme::levels`], it returns [`Error::IndexOutOfBounds`].
+    #[inline]
+    pub const fn page_size(self, level: usize) -> Result<usize> {
+        if let Err(e) = self.check_level(level) {
+            return Err(e);
+        }
+        Ok(1 << (PAGE_SHIFT + level * self.vpn_bits()))
+    }
+
+    /// Returns the virtual page number field of `va` at `level`, i.e., the index of the PTE
+    /// of the page table at `level` that translates `va`.
+    ///
+    /// # Errors
+    ///
+    /// If `level` is not lower than [`Scheme::levels`], it returns [`Error::IndexOutOfBounds`].
+    #[inline]
+    pub const fn vpn(self, va: usize, level: usize) -> Result<usize> {
+        if let Err(e) = self.check_level(level) {
+            return Err(e);
+        }
+        Ok((va >> (PAGE_SHIFT + level * self.vpn_bits())) & (self.entries() - 1))
+    }
+
+    /// Returns the offset of `va` within the page mapped by a leaf PTE at `level`.
+    ///
+    /// # Errors
+    ///
+    /// If `level` is not lower than [`Scheme::levels`], it returns [`Error::IndexOutOfBounds`].
+    #[inline]
+    pub const fn page_offset(self, va: usize, level: usize) -> Result<usize> {
+        match self.page_size(level) {
+            Ok(size) => Ok(va & (size - 1)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pte() {
+        let entry = pte::new(0x8_0200, pte::V | pte::R | pte::X | pte::A);
+        assert_eq!(entry, 0x2008_004B);
+        assert_eq!(pte::ppn(entry), 0x8_0200);
+        assert_eq!(pte::flags(entry), 0x4B);
+        assert!(pte::is_valid(entry));
+        assert!(pte::is_leaf(entry));
+        assert!(!pte::is_napot(entry));
+        assert!(!pte::is_leaf(pte::new(0x8_0200, pte::V)));
+
+        // flags cannot overwrite the physical page number
+        assert_eq!(pte::ppn(pte::new(0x1, u64::MAX)), 0x1);
+    }
+
+    #[test]
+    fn test_napot() {
+        let entry = pte::napot_64k(0x8_0210, pte::V | pte::R | pte::W).unwrap();
+        assert!(pte::is_napot(entry));
+        assert_eq!(pte::ppn(entry), 0x8_0218);
+        assert_eq!(
+            pte::napot_64k(0x8_0211, pte::V),
+            Err(Error::InvalidAlignment {
+                value: 0x8_0211,
+                alignment: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_pbmt() {
+        for pbmt in [Pbmt::Pma, Pbmt::Nc, Pbmt::Io] {
+            let entry = pte::new(0x1234, pte::V | pbmt.bits());
+            assert_eq!(Pbmt::from_pte(entry), Ok(pbmt));
+            assert_eq!(pte::ppn(entry), 0x1234);
+        }
+        assert_eq!(
+            Pbmt::from_pte(pte::PBMT_MASK),
+            Err(Error::InvalidVariant(3))
+        );
+    }
+
+    #[test]
+    fn test_scheme() {
+        let schemes = [Scheme::Sv32, Scheme::Sv39, Scheme::Sv48, Scheme::Sv57];
+        let va_bits = [32, 39, 48, 57];
+        let satp_modes = [1, 8, 9, 10];
+        for ((scheme, va_bits), satp_mode) in schemes.into_iter().zip(va_bits).zip(satp_modes) {
+            assert_eq!(scheme.va_bits(), va_bits);
+            assert_eq!(scheme.satp_mode(), satp_mode);
+            assert_eq!(scheme.entries() * scheme.pte_size(), PAGE_SIZE);
+            assert_eq!(scheme.page_size(0), Ok(PAGE_SIZE));
+            assert_eq!(
+                scheme.page_size(scheme.levels()),
+                Err(Error::IndexOutOfBounds {
+                    index: scheme.levels(),
+                    min: 0,
+                    max: scheme.levels() - 1
+                })
+            );
+        }
+
+        assert_eq!(Scheme::Sv32.page_size(1), Ok(0x40_0000));
+        assert_eq!(Scheme::Sv39.page_size(2), Ok(0x4000_0000));
+
+        let va = 0x1234_5678;
+        assert_eq!(Scheme::Sv32.vpn(va, 0), Ok(0x345));
+        assert_eq!(Scheme::Sv32.vpn(va, 1), Ok(0x48));
+        assert_eq!(Scheme::Sv32.page_offset(va, 0), Ok(0x678));
+        assert_eq!(Scheme::Sv32.page_offset(va, 1), Ok(0x34_5678));
+        assert_eq!(Scheme::Sv39.vpn(va, 0), Ok(0x145));
+        assert_eq!(Scheme::Sv39.vpn(va, 1), Ok(0x91));
+        assert_eq!(Scheme::Sv39.vpn(va, 2), Ok(0x0));
+        assert_eq!(
+            Scheme::Sv39.vpn(va, 3),
+            Err(Error::IndexOutOfBounds {
+                index: 3,
+                min: 0,
+                max: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! Performance counters
+//!
+//! Benchmarking code can reset the machine counters before each run and read them afterwards,
+//! instead of computing deltas between two readings that are subject to wrap around.
+//!
+//! Code running in U-mode can read the counters (e.g., `rdtime` or `rdcycle`) only if they are
+//! enabled in `mcounteren` and, if S-mode is implemented, in `scounteren`. [`enable_user_counters`]
+//! sets the bits of both registers, and reports which counters are actually readable from U-mode.
+//!
+//! [`pause_counters`] stops counters with `mcountinhibit` until the returned guard is dropped
+//! (e.g., around cryptographic code, so it cannot be profiled, or in low-power states to save power).
+//!
+//! **WARNING**: unless stated otherwise, these functions access M-mode CSRs, and panic on non-`riscv` targets.
+
+use crate::register::{mcountinhibit, mcycle, minstret};
+use crate::result::Result;
+
+/// Resets the [`mcycle`] and [`minstret`] counters to zero.
+///
+/// # Note
+///
+/// Counters keep running while they are reset, so `mcycle` will account for
+/// a few cycles (and `minstret` for a few instructions) when this function returns.
+/// Use `mcountinhibit` to stop the counters if this is not acceptable.
+#[inline]
+pub fn reset_counters() {
+    mcycle::write64(0);
+    minstret::write64(0);
+}
+
+/// Set of counters, as in the `mcounteren` and `scounteren` registers.
+///
+/// Bit `i` corresponds to `cycle` (0), `time` (1), `instret` (2), or `hpmcounter<i>` (3-31).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Counters(u32);
+
+impl Counters {
+    /// No counters.
+    pub const NONE: Self = Self(0);
+    /// The `cycle` counter.
+    pub const CYCLE: Self = Self(1 << 0);
+    /// The `time` counter.
+    pub const TIME: Self = Self(1 << 1);
+    /// The `instret` counter.
+    pub const INSTRET: Self = Self(1 << 2);
+    /// All the counters, including the `hpmcounter3`-`hpmcounter31` counters.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Creates a set of counters from its raw bits.
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits of the set of counters.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the `hpmcounter<index>` counter.
+    ///
+    /// **WARNING**: panics if `index` is not in the range `3..=31`.
+    #[inline]
+    pub const fn hpm(index: usize) -> Self {
+        assert!(index >= 3 && index < 32);
+        Self(1 << index)
+    }
+
+    /// Returns the union of two sets of counters.
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns the intersection of two sets of counters.
+    #[inline]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Returns `true` if all the counters of `other` are in the set.
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Enables the `counters` for U-mode, and returns the counters that U-mode can actually read.
+///
+/// In M-mode, it sets the bits of `counters` in `mcounteren`. If the `misa` register reports
+/// that S-mode is implemented, it also sets the bits in `scounteren`. With the `s-mode` feature,
+/// it only sets the bits in `scounteren`, and U-mode access also depends on the configuration
+/// of `mcounteren` by the M-mode firmware (e.g., OpenSBI), which is not visible from S-mode.
+///
+/// Counter enable bits are WARL: bits of unimplemented counters may be hardwired to zero.
+/// Thus, the returned set is computed by reading back the registers after the writes.
+/// The returned set is a subset of `counters`, as other enabled counters are not reported.
+///
+/// # Errors
+///
+/// On non-`riscv` targets, it returns [`Error::Unimplemented`](crate::result::Error::Unimplemented).
+#[inline]
+pub fn enable_user_counters(counters: Counters) -> Result<Counters> {
+    #[cfg(not(feature = "s-mode"))]
+    {
+        use crate::register::{mcounteren, misa};
+
+        let mcounteren_bits = mcounteren::try_read()?.bits() | counters.bits() as usize;
+        mcounteren::try_write(mcounteren::Mcounteren::from_bits(mcounteren_bits))?;
+        let enabled = Counters::from_bits(mcounteren::try_read()?.bits() as u32);
+
+        // If S-mode is not implemented, scounteren does not exist and U-mode only depends on mcounteren
+        match misa::try_read() {
+            Ok(misa) if misa.has_extension('S') => {
+                Ok(enable_scounteren(counters)?.intersection(enabled))
+            }
+            _ => Ok(counters.intersection(enabled)),
+        }
+    }
+    #[cfg(feature = "s-mode")]
+    enable_scounteren(counters)
+}
+
+/// Sets the bits of `counters` in `scounteren`, and returns the counters enabled in `scounteren`.
+#[inline]
+fn enable_scounteren(counters: Counters) -> Result<Counters> {
+    use crate::register::scounteren;
+
+    // Check that scounteren is accessible before writing
+    scounteren::try_read()?;
+    for index in (0..32).filter(|&i| counters.bits() & (1 << i) != 0) {
+        // SAFETY: enabling counters for U-mode does not break memory safety
+        unsafe {
+            match index {
+                0 => scounteren::set_cy(),
+                1 => scounteren::set_tm(),
+                2 => scounteren::set_ir(),
+                _ => scounteren::try_set_hpm(index)?,
+            }
+        }
+    }
+    let enabled = Counters::from_bits(scounteren::try_read()?.bits() as u32);
+    Ok(counters.intersection(enabled))
+}
+
+/// Guard returned by [`pause_counters`]. The paused counters resume when it is dropped.
+#[derive(Debug)]
+#[must_use = "the counters resume when the guard is dropped"]
+pub struct PausedCounters {
+    paused: Counters,
+}
+
+impl PausedCounters {
+    /// Returns the counters paused by this guard.
+    ///
+    /// Counters already inhibited before calling [`pause_counters`] are not included,
+    /// as they are not resumed when the guard is dropped.
+    #[inline]
+    pub const fn paused(&self) -> Counters {
+        self.paused
+    }
+}
+
+impl Drop for PausedCounters {
+    #[inline]
+    fn drop(&mut self) {
+        if let Ok(mcountinhibit) = mcountinhibit::try_read() {
+            let bits = mcountinhibit.bits() & !(self.paused.bits() as usize);
+            mcountinhibit::write(mcountinhibit::Mcountinhibit::from_bits(bits));
+        }
+    }
+}
+
+/// Pauses the `counters` by setting their bits in `mcountinhibit`, and returns a guard that
+/// resumes them when dropped.
+///
+/// Inhibit bits are WARL: bits of unimplemented counters may be hardwired to zero, and the `time`
+/// counter cannot be inhibited. Thus, [`PausedCounters::paused`] reports the counters actually paused,
+/// computed by reading back the register after the write. Guards can be nested, as each guard only
+/// resumes the counters that it paused.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn pause_counters(counters: Counters) -> PausedCounters {
+    try_pause_counters(counters).unwrap()
+}
+
+/// Attempts to pause the `counters`, and returns a guard that resumes them when dropped.
+///
+/// See [`pause_counters`] for more details.
+///
+/// # Errors
+///
+/// On non-`riscv` targets, it returns [`Error::Unimplemented`](crate::result::Error::Unimplemented).
+#[inline]
+pub fn try_pause_counters(counters: Counters) -> Result<PausedCounters> {
+    let previous = mcountinhibit::try_read()?.bits();
+    mcountinhibit::try_write(mcountinhibit::Mcountinhibit::from_bits(
+        previous | counters.bits() as usize,
+    ))?;
+    let inhibited = mcountinhibit::try_read()?.bits() & !previous;
+    Ok(PausedCounters {
+        paused: counters.intersection(Counters::from_bits(inhibited as u32)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::Error;
+
+    #[test]
+    fn test_counters() {
+        let counters = Counters::CYCLE
+            .union(Counters::TIME)
+            .union(Counters::hpm(5));
+        assert_eq!(counters.bits(), 0b10_0011);
+        assert!(counters.contains(Counters::TIME));
+        assert!(!counters.contains(Counters::INSTRET));
+        assert!(Counters::ALL.contains(counters));
+        assert_eq!(counters.intersection(Counters::INSTRET), Counters::NONE);
+        assert_eq!(Counters::from_bits(0b100), Counters::INSTRET);
+        assert_eq!(Counters::default(), Counters::NONE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_counters_hpm_out_of_bounds() {
+        Counters::hpm(2);
+    }
+
+    #[test]
+    fn test_pause_counters() {
+        assert_eq!(
+            try_pause_counters(Counters::CYCLE).map(|guard| guard.paused()),
+            Err(Error::Unimplemented)
+        );
+    }
+
+    #[test]
+    fn test_enable_user_counters() {
+        assert_eq!(
+            enable_user_counters(Counters::TIME),
+            Err(Error::Unimplemented)
+        );
+    }
+}
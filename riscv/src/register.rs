@@ -10,6 +10,7 @@
 //! - minstreth
 //! - mhpmcounter<3-31>h
 //! - mstatush
+//! - stimecmph
 //!
 //! # On Floating-Point CSRs
 //!
@@ -39,6 +40,30 @@
 #[macro_use]
 mod macros;
 
+/// Field-level difference between two values of a CSR.
+///
+/// It is returned by the `diff` methods of CSR types (e.g., [`mstatus::Mstatus::diff`]).
+/// Its [`Display`](core::fmt::Display) implementation prints the uppercase name of the field
+/// followed by the old and new values (e.g., `MPP 3→1`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FieldChange {
+    /// Name of the field.
+    pub field: &'static str,
+    /// Value of the field in the old CSR value.
+    pub old: usize,
+    /// Value of the field in the new CSR value.
+    pub new: usize,
+}
+
+impl core::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for c in self.field.chars() {
+            write!(f, "{}", c.to_ascii_uppercase())?;
+        }
+        write!(f, " {}\u{2192}{}", self.old, self.new)
+    }
+}
+
 // User Counter/Timers
 pub mod cycle;
 pub mod cycleh;
@@ -49,6 +74,9 @@ pub mod instreth;
 pub mod time;
 pub mod timeh;
 
+// Entropy Source (Zkr)
+pub mod seed;
+
 // Supervisor Trap Setup
 pub mod scounteren;
 pub mod sie;
@@ -61,10 +89,31 @@ pub mod sepc;
 pub mod sip;
 pub mod sscratch;
 pub mod stval;
+pub mod xcause;
+
+// Supervisor Timer Compare (Sstc)
+pub mod stimecmp;
+pub mod stimecmph;
+
+// Supervisor CLIC
+pub mod sintthresh;
+
+// Supervisor Advanced Interrupt Architecture (Ssaia) and Indirect CSR Access (Sscsrind)
+pub mod sireg;
+mod siregx;
+pub use self::siregx::*;
+pub mod siselect;
+pub mod stopei;
+pub mod stopi;
 
 // Supervisor Protection and Translation
 pub mod satp;
 
+// Supervisor State Enable
+pub mod sstateen0;
+mod sstateenx;
+pub use self::sstateenx::*;
+
 // Machine Information Registers
 pub mod marchid;
 pub mod mhartid;
@@ -86,7 +135,26 @@ pub mod mcause;
 pub mod mepc;
 pub mod mip;
 pub mod mscratch;
+pub mod mtinst;
 pub mod mtval;
+pub mod mtval2;
+
+// Machine CLIC
+pub mod mintthresh;
+
+// Machine Advanced Interrupt Architecture (Smaia) and Indirect CSR Access (Smcsrind)
+pub mod mireg;
+mod miregx;
+pub use self::miregx::*;
+pub mod miselect;
+pub mod mtopei;
+pub mod mtopi;
+
+// Machine Resumable NMI
+pub mod mncause;
+pub mod mnepc;
+pub mod mnscratch;
+pub mod mnstatus;
 
 // Machine Protection and Translation
 mod pmpcfgx;
@@ -107,9 +175,23 @@ pub mod minstreth;
 mod mhpmeventx;
 pub use self::mhpmeventx::*;
 
+// Machine State Enable
+pub mod mstateen0;
+pub mod mstateen0h;
+mod mstateenx;
+pub use self::mstateenx::*;
+
+// Indirect CSR Access (Smcsrind/Sscsrind)
+pub mod indirect;
+
 #[cfg(test)]
 mod tests;
 
-// TODO: Debug/Trace Registers (shared with Debug Mode)
+// Debug/Trace Registers (shared with Debug Mode)
+pub mod mcontext;
+pub mod scontext;
+pub mod tdata1;
+pub mod tdata2;
+pub mod tselect;
 
 // TODO: Debug Mode Registers
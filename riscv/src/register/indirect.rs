@@ -0,0 +1,189 @@
+//! Indirect CSR access (Smcsrind/Sscsrind)
+//!
+//! Indirect CSR access multiplexes many registers behind a select/alias pair of CSRs: software writes
+//! the number of the register to `miselect` (`siselect` in S-mode), and then accesses it through one
+//! of the alias CSRs `mireg`, `mireg2`, ..., `mireg6` (`sireg`, ..., `sireg6` in S-mode).
+//! Each alias gives access to a different register of the same select value (see [`Ireg`]).
+//! Smaia/Ssaia only define `mireg` and `sireg`, which is the alias used by [`machine::read`],
+//! [`machine::write`], [`supervisor::read`], and [`supervisor::write`].
+//!
+//! # Race conditions
+//!
+//! Accessing an indirect register is not atomic: if a trap handler modifies the select CSR between
+//! the write to the select CSR and the access to the alias CSR, the access targets the wrong register.
+//! The functions of this module disable interrupts of the current privilege level while accessing an
+//! indirect register, so trap handlers of the same level can safely use them too. However, traps taken
+//! by a higher privilege level (e.g., an SBI implementation in M-mode interrupting S-mode) are not
+//! masked, so their handlers must save and restore the select CSR of the lower privilege level if
+//! they modify it. Accessing the select and alias CSRs directly (e.g., with [`super::miselect`] and
+//! [`super::mireg`]) bypasses these guarantees.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use riscv::register::indirect::{machine, Ireg};
+//!
+//! // IMSIC interrupt file: read `eithreshold` (0x72) and the `eip0` (0x80) pending bits
+//! let threshold = machine::read(0x72);
+//! let pending = machine::read_ireg(0x80, Ireg::Ireg);
+//! ```
+
+/// Alias CSRs of an indirect register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ireg {
+    /// `mireg`/`sireg`
+    Ireg = 1,
+    /// `mireg2`/`sireg2`
+    Ireg2 = 2,
+    /// `mireg3`/`sireg3`
+    Ireg3 = 3,
+    /// `mireg4`/`sireg4`
+    Ireg4 = 4,
+    /// `mireg5`/`sireg5`
+    Ireg5 = 5,
+    /// `mireg6`/`sireg6`
+    Ireg6 = 6,
+}
+
+macro_rules! indirect {
+    (
+        $(#[$doc:meta])*
+        $mod:ident, $free:path, $select:ident,
+        [$ireg:ident, $ireg2:ident, $ireg3:ident, $ireg4:ident, $ireg5:ident, $ireg6:ident]
+    ) => {
+        $(#[$doc])*
+        pub mod $mod {
+            use super::Ireg;
+            use crate::register::{$ireg, $ireg2, $ireg3, $ireg4, $ireg5, $ireg6, $select};
+            use crate::result::Result;
+
+            #[doc = concat!("Reads the indirect register `select` through `", stringify!($ireg), "`.")]
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub fn read(select: usize) -> usize {
+                try_read(select).unwrap()
+            }
+
+            #[doc = concat!("Attempts to read the indirect register `select` through `", stringify!($ireg), "`.")]
+            #[inline]
+            pub fn try_read(select: usize) -> Result<usize> {
+                try_read_ireg(select, Ireg::Ireg)
+            }
+
+            #[doc = concat!("Writes `bits` to the indirect register `select` through `", stringify!($ireg), "`.")]
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub fn write(select: usize, bits: usize) {
+                try_write(select, bits).unwrap();
+            }
+
+            #[doc = concat!("Attempts to write `bits` to the indirect register `select` through `", stringify!($ireg), "`.")]
+            #[inline]
+            pub fn try_write(select: usize, bits: usize) -> Result<()> {
+                try_write_ireg(select, Ireg::Ireg, bits)
+            }
+
+            /// Reads the indirect register `select` through the alias CSR `ireg`.
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub fn read_ireg(select: usize, ireg: Ireg) -> usize {
+                try_read_ireg(select, ireg).unwrap()
+            }
+
+            /// Attempts to read the indirect register `select` through the alias CSR `ireg`.
+            #[inline]
+            pub fn try_read_ireg(select: usize, ireg: Ireg) -> Result<usize> {
+                $free(|| {
+                    $select::try_write(select)?;
+                    match ireg {
+                        Ireg::Ireg => $ireg::try_read(),
+                        Ireg::Ireg2 => $ireg2::try_read(),
+                        Ireg::Ireg3 => $ireg3::try_read(),
+                        Ireg::Ireg4 => $ireg4::try_read(),
+                        Ireg::Ireg5 => $ireg5::try_read(),
+                        Ireg::Ireg6 => $ireg6::try_read(),
+                    }
+                })?
+            }
+
+            /// Writes `bits` to the indirect register `select` through the alias CSR `ireg`.
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub fn write_ireg(select: usize, ireg: Ireg, bits: usize) {
+                try_write_ireg(select, ireg, bits).unwrap();
+            }
+
+            /// Attempts to write `bits` to the indirect register `select` through the alias CSR `ireg`.
+            #[inline]
+            pub fn try_write_ireg(select: usize, ireg: Ireg, bits: usize) -> Result<()> {
+                $free(|| {
+                    $select::try_write(select)?;
+                    match ireg {
+                        Ireg::Ireg => $ireg::try_write(bits),
+                        Ireg::Ireg2 => $ireg2::try_write(bits),
+                        Ireg::Ireg3 => $ireg3::try_write(bits),
+                        Ireg::Ireg4 => $ireg4::try_write(bits),
+                        Ireg::Ireg5 => $ireg5::try_write(bits),
+                        Ireg::Ireg6 => $ireg6::try_write(bits),
+                    }
+                })?
+            }
+        }
+    };
+}
+
+indirect!(
+    /// Machine-level indirect CSR access (`miselect` and `mireg*`).
+    ///
+    /// Machine interrupts are disabled while accessing an indirect register.
+    machine,
+    crate::interrupt::machine::try_free,
+    miselect,
+    [mireg, mireg2, mireg3, mireg4, mireg5, mireg6]
+);
+
+indirect!(
+    /// Supervisor-level indirect CSR access (`siselect` and `sireg*`).
+    ///
+    /// Supervisor interrupts are disabled while accessing an indirect register.
+    supervisor,
+    crate::interrupt::supervisor::try_free,
+    siselect,
+    [sireg, sireg2, sireg3, sireg4, sireg5, sireg6]
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::Error;
+
+    #[test]
+    fn test_indirect() {
+        for ireg in [Ireg::Ireg, Ireg::Ireg2, Ireg::Ireg6] {
+            assert_eq!(
+                machine::try_read_ireg(0x72, ireg),
+                Err(Error::Unimplemented)
+            );
+            assert_eq!(
+                machine::try_write_ireg(0x72, ireg, 1),
+                Err(Error::Unimplemented)
+            );
+            assert_eq!(
+                supervisor::try_read_ireg(0x72, ireg),
+                Err(Error::Unimplemented)
+            );
+            assert_eq!(
+                supervisor::try_write_ireg(0x72, ireg, 1),
+                Err(Error::Unimplemented)
+            );
+        }
+        assert_eq!(machine::try_read(0x72), Err(Error::Unimplemented));
+        assert_eq!(machine::try_write(0x72, 1), Err(Error::Unimplemented));
+        assert_eq!(supervisor::try_read(0x72), Err(Error::Unimplemented));
+        assert_eq!(supervisor::try_write(0x72, 1), Err(Error::Unimplemented));
+    }
+}
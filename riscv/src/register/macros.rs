@@ -343,6 +343,138 @@ macro_rules! clear_rv32 {
     };
 }
 
+/// Convenience macro around the `csrrw`, `csrrs`, and `csrrc` assembly instructions to modify
+/// the CSR register and return its previous value with a single instruction.
+///
+/// Unlike the [set](crate::set) and [clear](crate::clear) macros, the previous value is not discarded.
+/// Thus, saving and modifying the CSR is atomic, and there is no race window between reading and writing.
+#[macro_export]
+macro_rules! swap_csr {
+    ($csr_number:literal) => {
+        $crate::swap_csr!($csr_number, any(target_arch = "riscv32", target_arch = "riscv64"));
+    };
+    ($csr_number:literal, $($cfg:meta),*) => {
+        /// Writes `bits` to the CSR, and returns its previous value.
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        #[inline]
+        pub unsafe fn replace(bits: usize) -> usize {
+            try_replace(bits).unwrap()
+        }
+
+        /// Attempts to write `bits` to the CSR, and returns its previous value.
+        #[inline]
+        #[cfg_attr(not($($cfg),*), allow(unused_variables))]
+        pub unsafe fn try_replace(bits: usize) -> $crate::result::Result<usize> {
+            $crate::swap_csr!(@asm "csrrw", $csr_number, bits, $($cfg),*)
+        }
+
+        /// Sets the `bits` of the CSR, and returns its previous value.
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        #[inline]
+        pub unsafe fn set_and_get(bits: usize) -> usize {
+            try_set_and_get(bits).unwrap()
+        }
+
+        /// Attempts to set the `bits` of the CSR, and returns its previous value.
+        #[inline]
+        #[cfg_attr(not($($cfg),*), allow(unused_variables))]
+        pub unsafe fn try_set_and_get(bits: usize) -> $crate::result::Result<usize> {
+            $crate::swap_csr!(@asm "csrrs", $csr_number, bits, $($cfg),*)
+        }
+
+        /// Clears the `bits` of the CSR, and returns its previous value.
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        #[inline]
+        pub unsafe fn clear_and_get(bits: usize) -> usize {
+            try_clear_and_get(bits).unwrap()
+        }
+
+        /// Attempts to clear the `bits` of the CSR, and returns its previous value.
+        #[inline]
+        #[cfg_attr(not($($cfg),*), allow(unused_variables))]
+        pub unsafe fn try_clear_and_get(bits: usize) -> $crate::result::Result<usize> {
+            $crate::swap_csr!(@asm "csrrc", $csr_number, bits, $($cfg),*)
+        }
+    };
+    (@asm $instruction:literal, $csr_number:literal, $bits:ident, $($cfg:meta),*) => {
+        match () {
+            #[cfg($($cfg),*)]
+            () => {
+                let r: usize;
+                core::arch::asm!(concat!($instruction, " {0}, ", stringify!($csr_number), ", {1}"), out(reg) r, in(reg) $bits);
+                Ok(r)
+            }
+            #[cfg(not($($cfg),*))]
+            () => Err($crate::result::Error::Unimplemented),
+        }
+    };
+}
+
+/// Convenience macro to define `take_<field>` functions for CSR fields with read-clear semantics
+/// (e.g., pending or status flags that must be acknowledged by software).
+///
+/// `take_<field>` clears the bits of the field with a single `csrrc` instruction and returns their previous value.
+/// Unlike reading the CSR and writing it back, the other bits of the CSR are not modified, so flags
+/// raised by the hardware between the read and the write are not cleared accidentally.
+///
+/// This macro is intended for use with the [swap_csr](crate::swap_csr) macro.
+#[macro_export]
+macro_rules! read_clear_csr_field {
+    ($(#[$field_doc:meta])+
+     $field:ident: $bit:literal$(,)?
+    ) => {
+        $crate::paste! {
+            $(#[$field_doc])+
+            ///
+            /// Clears the bit and returns its previous value.
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub unsafe fn [<take_ $field>]() -> bool {
+                [<try_take_ $field>]().unwrap()
+            }
+
+            $(#[$field_doc])+
+            ///
+            /// Attempts to clear the bit and return its previous value.
+            #[inline]
+            pub unsafe fn [<try_take_ $field>]() -> $crate::result::Result<bool> {
+                const MASK: usize = 1 << $bit;
+                try_clear_and_get(MASK).map(|bits| bits & MASK != 0)
+            }
+        }
+    };
+
+    ($(#[$field_doc:meta])+
+     $field:ident: [$bit_start:literal : $bit_end:literal]$(,)?
+    ) => {
+        $crate::paste! {
+            $(#[$field_doc])+
+            ///
+            /// Clears the bits and returns their previous value.
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub unsafe fn [<take_ $field>]() -> usize {
+                [<try_take_ $field>]().unwrap()
+            }
+
+            $(#[$field_doc])+
+            ///
+            /// Attempts to clear the bits and return their previous value.
+            #[inline]
+            pub unsafe fn [<try_take_ $field>]() -> $crate::result::Result<usize> {
+                const WIDTH: usize = $bit_end - $bit_start + 1;
+                const MASK: usize = (usize::MAX >> (usize::BITS as usize - WIDTH)) << $bit_start;
+                try_clear_and_get(MASK).map(|bits| $crate::bits::bf_extract(bits, $bit_start, WIDTH))
+            }
+        }
+    };
+}
+
 /// Convenience macro to define field setter functions for a CSR type.
 #[macro_export]
 macro_rules! set_csr {
@@ -403,6 +535,34 @@ macro_rules! read_composite_csr {
     };
 }
 
+/// Convenience macro to write a composite value to a CSR register.
+///
+/// - `RV32`: writes the low 32-bits to zero, then the high 32-bits, and finally the low 32-bits.
+///   Clearing the low half first prevents it from carrying into the high half between both writes.
+/// - `RV64`: writes the full 64-bits of the CSR.
+#[macro_export]
+macro_rules! write_composite_csr {
+    ($hi:expr, $lo:expr) => {
+        /// Writes the CSR as a 64-bit value
+        ///
+        /// **WARNING**: panics on non-`riscv` targets.
+        #[inline]
+        pub fn write64(bits: u64) {
+            match () {
+                #[cfg(target_arch = "riscv32")]
+                () => {
+                    $lo(0);
+                    $hi((bits >> 32) as usize);
+                    $lo(bits as usize);
+                }
+
+                #[cfg(not(target_arch = "riscv32"))]
+                () => $lo(bits as usize),
+            }
+        }
+    };
+}
+
 macro_rules! set_pmp {
     () => {
         /// Set the pmp configuration corresponding to the index.
@@ -530,6 +690,249 @@ macro_rules! csr {
     };
 }
 
+/// Helper macro to generate a `diff` method for a CSR type.
+///
+/// The `diff` method compares two values of the CSR type field by field, and returns an iterator
+/// over the [`FieldChange`](crate::register::FieldChange)s of the listed fields.
+/// Fields are listed either as `field: bit` (single-bit fields) or `field: [start:end]` (bit ranges).
+/// Fields that only exist in some targets (e.g., RV64-only fields) are preceded by a `#[cfg(...)]`
+/// attribute, so a single table describes the CSR for all the XLEN values.
+#[macro_export]
+macro_rules! csr_diff {
+    (@range [$start:literal : $end:literal]) => {
+        ($start, $end - $start + 1)
+    };
+    (@range $bit:literal) => {
+        ($bit, 1)
+    };
+    ($ty:ident, [$($(#[cfg($cfg:meta)])? $field:ident: $bits:tt),+ $(,)?]) => {
+        impl $ty {
+            /// Returns an iterator over the fields that changed from `self` to `other`.
+            ///
+            /// This is useful for logging register changes in bring-up debugging.
+            #[inline]
+            pub fn diff(&self, other: &Self) -> impl Iterator<Item = $crate::register::FieldChange> {
+                let (old, new) = (self.bits(), other.bits());
+                IntoIterator::into_iter([$(
+                    match () {
+                        #[cfg(all($($cfg)?))]
+                        () => Some((stringify!($field), $crate::csr_diff!(@range $bits))),
+                        #[cfg(not(all($($cfg)?)))]
+                        () => None,
+                    }
+                ),+])
+                    .flatten()
+                    .filter_map(move |(field, (bit, width))| {
+                        let old = $crate::bits::bf_extract(old, bit, width);
+                        let new = $crate::bits::bf_extract(new, bit, width);
+                        match old == new {
+                            true => None,
+                            false => Some($crate::register::FieldChange { field, old, new }),
+                        }
+                    })
+            }
+        }
+    };
+}
+
+/// Helper macro to define the typed wrapper of an exception program counter CSR (e.g., `mepc`).
+///
+/// The CSR must be readable and writable as `usize` with `read`, `try_read`, `write`, and `try_write`
+/// functions in the calling module (e.g., with `read_csr_as_usize!` and `write_csr_as_usize!`).
+/// It also generates the unit tests of the wrapper.
+#[macro_export]
+macro_rules! exception_pc_csr {
+    ($ty:ident, $csr:ident) => {
+        #[doc = concat!("Typed wrapper of the ", stringify!($csr), " register")]
+        ///
+        /// It eases resuming execution after the instruction that caused an exception:
+        ///
+        /// ```no_run
+        #[doc = concat!("use riscv::register::", stringify!($csr), "::", stringify!($ty), ";")]
+        ///
+        #[doc = concat!("let mut ", stringify!($csr), " = ", stringify!($ty), "::read();")]
+        #[doc = concat!("// SAFETY: ", stringify!($csr), " points to the instruction that caused the exception")]
+        #[doc = concat!(stringify!($csr), ".step_over_instruction(|pc| unsafe { (pc as *const u16).read_volatile() });")]
+        #[doc = concat!(stringify!($csr), ".write();")]
+        /// ```
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub struct $ty {
+            bits: usize,
+        }
+
+        impl $ty {
+            /// Instruction address alignment (IALIGN) of the target in bytes.
+            ///
+            /// It is 2 bytes for targets with the compressed (C) extension, and 4 bytes otherwise.
+            ///
+            /// # Note
+            ///
+            /// IALIGN is taken from the target features at compile time. If the C extension is
+            /// toggled at runtime (i.e., with `misa.C`), it may not match the IALIGN of the hart.
+            /// Code compiled without the C extension on a hart with `misa.C` set gets a stricter
+            /// alignment (4 bytes) than required, while clearing `misa.C` is not supported in code
+            /// compiled with the C extension, as it contains compressed instructions.
+            pub const IALIGN: usize = if cfg!(target_feature = "c") { 2 } else { 4 };
+
+            #[doc = concat!("Creates a new ", stringify!($csr), " value from raw bits.")]
+            #[inline]
+            pub const fn from_bits(bits: usize) -> Self {
+                Self { bits }
+            }
+
+            /// Returns the contents of the register as raw bits.
+            #[inline]
+            pub const fn bits(&self) -> usize {
+                self.bits
+            }
+
+            /// Reads the CSR.
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub fn read() -> Self {
+                Self::from_bits(read())
+            }
+
+            /// Attempts to read the CSR.
+            #[inline]
+            pub fn try_read() -> $crate::result::Result<Self> {
+                try_read().map(Self::from_bits)
+            }
+
+            /// Writes the value to the CSR.
+            ///
+            /// **WARNING**: panics on non-`riscv` targets.
+            #[inline]
+            pub fn write(self) {
+                write(self.bits)
+            }
+
+            /// Attempts to write the value to the CSR.
+            #[inline]
+            pub fn try_write(self) -> $crate::result::Result<()> {
+                try_write(self.bits)
+            }
+
+            /// Returns the exception program counter.
+            #[inline]
+            pub const fn pc(&self) -> usize {
+                self.bits
+            }
+
+            /// Attempts to set the exception program counter.
+            ///
+            /// Returns [`Error::InvalidAlignment`](crate::result::Error::InvalidAlignment) if `pc`
+            /// is not aligned to [`IALIGN`](Self::IALIGN).
+            #[inline]
+            pub fn try_set_pc(&mut self, pc: usize) -> $crate::result::Result<()> {
+                match pc % Self::IALIGN {
+                    0 => {
+                        self.bits = pc;
+                        Ok(())
+                    }
+                    _ => Err($crate::result::Error::InvalidAlignment {
+                        value: pc,
+                        alignment: Self::IALIGN,
+                    }),
+                }
+            }
+
+            /// Returns the length in bytes of the instruction pointed by the exception program counter.
+            ///
+            /// The `read` closure must return the 16-bit parcel stored at the address it receives.
+            /// Only the low bits of this parcel are used to determine the length of the instruction.
+            #[inline]
+            pub fn instruction_len<F: FnOnce(usize) -> u16>(&self, read: F) -> usize {
+                match read(self.pc()) & 0b11 {
+                    0b11 => 4,
+                    _ => 2,
+                }
+            }
+
+            /// Advances the exception program counter to the instruction after the one it points to.
+            ///
+            /// The `read` closure must return the 16-bit parcel stored at the address it receives.
+            #[doc = concat!("Check [`", stringify!($ty), "::instruction_len`] for more details.")]
+            #[inline]
+            pub fn step_over_instruction<F: FnOnce(usize) -> u16>(&mut self, read: F) {
+                self.bits = self.pc().wrapping_add(self.instruction_len(read));
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use $crate::result::Error;
+
+            #[test]
+            fn test_set_pc() {
+                let mut epc = $ty::from_bits(0);
+
+                for pc in (0..64).step_by($ty::IALIGN) {
+                    assert_eq!(epc.try_set_pc(pc), Ok(()));
+                    assert_eq!(epc.pc(), pc);
+                    assert_eq!(epc.bits(), pc);
+                }
+                for pc in (1..64).filter(|pc| pc % $ty::IALIGN != 0) {
+                    assert_eq!(
+                        epc.try_set_pc(pc),
+                        Err(Error::InvalidAlignment {
+                            value: pc,
+                            alignment: $ty::IALIGN,
+                        })
+                    );
+                    assert_ne!(epc.pc(), pc);
+                }
+            }
+
+            #[test]
+            fn test_step_over_instruction() {
+                // c.nop, ecall, c.ebreak
+                let memory: [u16; 4] = [0x0001, 0x0073, 0x0000, 0x9002];
+                let read = |pc: usize| memory[(pc - 0x100) / 2];
+
+                let mut epc = $ty::from_bits(0x100);
+                assert_eq!(epc.instruction_len(read), 2);
+                epc.step_over_instruction(read);
+                assert_eq!(epc.pc(), 0x102);
+                assert_eq!(epc.instruction_len(read), 4);
+                epc.step_over_instruction(read);
+                assert_eq!(epc.pc(), 0x106);
+                assert_eq!(epc.instruction_len(read), 2);
+                epc.step_over_instruction(read);
+                assert_eq!(epc.pc(), 0x108);
+            }
+        }
+    };
+}
+
+/// Helper macro to define the bit masks of a delegation CSR (e.g., `medeleg`) from a trap enum.
+///
+/// Each constant is the bit of the given enum variant, so masks can be composed with `|` in const context.
+/// At compile time, it checks that the variants do not exceed `max` and that the constants cover
+/// exactly the `BITMASK` of the CSR type.
+#[macro_export]
+macro_rules! delegation_bits {
+    ($ty:ident, $enum:ident, max: $max:expr, {
+        $($(#[$doc:meta])+ $name:ident: $variant:ident,)+
+    }) => {
+        $(
+            $(#[$doc])+
+            pub const $name: usize = {
+                let number = $enum::$variant as usize;
+                assert!(number <= $max && number < usize::BITS as usize);
+                1 << number
+            };
+        )+
+
+        const _: () = assert!(
+            0 $(| $name)+ == $ty::BITMASK,
+            "delegation bits do not match the bitmask of the CSR"
+        );
+    };
+}
+
 #[macro_export]
 macro_rules! csr_field_enum {
     ($(#[$field_ty_doc:meta])*
@@ -2,12 +2,20 @@
 
 pub use crate::interrupt::Trap;
 
+#[cfg(target_arch = "riscv32")]
 read_only_csr! {
     /// `mcause` register
     Mcause: 0x342,
     mask: 0xffff_ffff,
 }
 
+#[cfg(not(target_arch = "riscv32"))]
+read_only_csr! {
+    /// `mcause` register
+    Mcause: 0x342,
+    mask: 0xffff_ffff_ffff_ffff,
+}
+
 #[cfg(target_arch = "riscv32")]
 read_only_csr_field! {
     Mcause,
@@ -45,11 +53,7 @@ impl Mcause {
     /// To get a target-specific trap cause, use [`Trap::try_into`] with your target-specific M-Mode trap cause types.
     #[inline]
     pub fn cause(&self) -> Trap<usize, usize> {
-        if self.is_interrupt() {
-            Trap::Interrupt(self.code())
-        } else {
-            Trap::Exception(self.code())
-        }
+        Trap::from_cause_bits(self.bits())
     }
 
     /// Is trap cause an exception.
@@ -58,3 +62,24 @@ impl Mcause {
         !self.is_interrupt()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcause() {
+        let interrupt = Trap::<usize, usize>::INTERRUPT_BIT;
+        for code in [0, 3, 11, 0x1234] {
+            let mcause = Mcause::from_bits(interrupt | code);
+            assert!(mcause.is_interrupt());
+            assert_eq!(mcause.code(), code);
+            assert_eq!(mcause.cause(), Trap::Interrupt(code));
+
+            let mcause = Mcause::from_bits(code);
+            assert!(mcause.is_exception());
+            assert_eq!(mcause.code(), code);
+            assert_eq!(mcause.cause(), Trap::Exception(code));
+        }
+    }
+}
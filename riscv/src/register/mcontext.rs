@@ -0,0 +1,6 @@
+//! mcontext register (Sdtrig extension)
+//!
+//! Holds the machine-mode context used by the trigger module to match triggers.
+
+read_csr_as_usize!(0x7A8);
+write_csr_as_usize!(0x7A8);
@@ -1,4 +1,9 @@
 //! `mcountinhibit` register
+//!
+//! Setting a bit of `mcountinhibit` stops the corresponding counter, which is useful to reduce power
+//! consumption or to keep sensitive code from being profiled. The register is WARL: bits of unimplemented
+//! counters may be hardwired to zero, and bit 1 is always zero, as the `time` counter cannot be inhibited.
+//! Read the register back to check which counters are actually inhibited (see [`crate::perf::pause_counters`]).
 
 use crate::result::{Error, Result};
 
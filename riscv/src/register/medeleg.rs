@@ -1,5 +1,7 @@
 //! medeleg register
 
+use crate::interrupt::{machine::Exception, ExceptionNumber};
+
 read_write_csr! {
     /// `medeleg` register
     Medeleg: 0x302,
@@ -84,48 +86,77 @@ read_write_csr_field! {
     store_page_fault: 15,
 }
 
+delegation_bits!(Medeleg, Exception, max: Exception::MAX_EXCEPTION_NUMBER, {
+    /// Instruction Address Misaligned Delegate bit
+    INSTRUCTION_MISALIGNED: InstructionMisaligned,
+    /// Instruction Access Fault Delegate bit
+    INSTRUCTION_FAULT: InstructionFault,
+    /// Illegal Instruction Delegate bit
+    ILLEGAL_INSTRUCTION: IllegalInstruction,
+    /// Breakpoint Delegate bit
+    BREAKPOINT: Breakpoint,
+    /// Load Address Misaligned Delegate bit
+    LOAD_MISALIGNED: LoadMisaligned,
+    /// Load Access Fault Delegate bit
+    LOAD_FAULT: LoadFault,
+    /// Store/AMO Address Misaligned Delegate bit
+    STORE_MISALIGNED: StoreMisaligned,
+    /// Store/AMO Access Fault Delegate bit
+    STORE_FAULT: StoreFault,
+    /// Environment Call from U-mode Delegate bit
+    USER_ENV_CALL: UserEnvCall,
+    /// Environment Call from S-mode Delegate bit
+    SUPERVISOR_ENV_CALL: SupervisorEnvCall,
+    /// Instruction Page Fault Delegate bit
+    INSTRUCTION_PAGE_FAULT: InstructionPageFault,
+    /// Load Page Fault Delegate bit
+    LOAD_PAGE_FAULT: LoadPageFault,
+    /// Store/AMO Page Fault Delegate bit
+    STORE_PAGE_FAULT: StorePageFault,
+});
+
 set!(0x302);
 clear!(0x302);
 
 set_clear_csr!(
     /// Instruction Address Misaligned Delegate
-    , set_instruction_misaligned, clear_instruction_misaligned, 1 << 0);
+    , set_instruction_misaligned, clear_instruction_misaligned, INSTRUCTION_MISALIGNED);
 set_clear_csr!(
     /// Instruction Access Fault Delegate
-    , set_instruction_fault, clear_instruction_fault, 1 << 1);
+    , set_instruction_fault, clear_instruction_fault, INSTRUCTION_FAULT);
 set_clear_csr!(
     /// Illegal Instruction Delegate
-    , set_illegal_instruction, clear_illegal_instruction, 1 << 2);
+    , set_illegal_instruction, clear_illegal_instruction, ILLEGAL_INSTRUCTION);
 set_clear_csr!(
     /// Breakpoint Delegate
-    , set_breakpoint, clear_breakpoint, 1 << 3);
+    , set_breakpoint, clear_breakpoint, BREAKPOINT);
 set_clear_csr!(
     /// Load Address Misaligned Delegate
-    , set_load_misaligned, clear_load_misaligned, 1 << 4);
+    , set_load_misaligned, clear_load_misaligned, LOAD_MISALIGNED);
 set_clear_csr!(
     /// Load Access Fault Delegate
-    , set_load_fault, clear_load_fault, 1 << 5);
+    , set_load_fault, clear_load_fault, LOAD_FAULT);
 set_clear_csr!(
     /// Store/AMO Address Misaligned Delegate
-    , set_store_misaligned, clear_store_misaligned, 1 << 6);
+    , set_store_misaligned, clear_store_misaligned, STORE_MISALIGNED);
 set_clear_csr!(
     /// Store/AMO Access fault
-    , set_store_fault, clear_store_fault, 1 << 7);
+    , set_store_fault, clear_store_fault, STORE_FAULT);
 set_clear_csr!(
     /// Environment Call from U-mode Delegate
-    , set_user_env_call, clear_user_env_call, 1 << 8);
+    , set_user_env_call, clear_user_env_call, USER_ENV_CALL);
 set_clear_csr!(
     /// Environment Call from S-mode Delegate
-    , set_supervisor_env_call, clear_supervisor_env_call, 1 << 9);
+    , set_supervisor_env_call, clear_supervisor_env_call, SUPERVISOR_ENV_CALL);
 set_clear_csr!(
     /// Instruction Page Fault Delegate
-    , set_instruction_page_fault, clear_instruction_page_fault, 1 << 12);
+    , set_instruction_page_fault, clear_instruction_page_fault, INSTRUCTION_PAGE_FAULT);
 set_clear_csr!(
     /// Load Page Fault Delegate
-    , set_load_page_fault, clear_load_page_fault, 1 << 13);
+    , set_load_page_fault, clear_load_page_fault, LOAD_PAGE_FAULT);
 set_clear_csr!(
     /// Store/AMO Page Fault Delegate
-    , set_store_page_fault, clear_store_page_fault, 1 << 15);
+    , set_store_page_fault, clear_store_page_fault, STORE_PAGE_FAULT);
 
 #[cfg(test)]
 mod tests {
@@ -149,4 +180,18 @@ mod tests {
         test_csr_field!(m, load_page_fault);
         test_csr_field!(m, store_page_fault);
     }
+
+    #[test]
+    fn test_medeleg_bits() {
+        const PAGE_FAULTS: usize = INSTRUCTION_PAGE_FAULT | LOAD_PAGE_FAULT | STORE_PAGE_FAULT;
+        assert_eq!(PAGE_FAULTS, 0xb000);
+        assert_eq!(BREAKPOINT, 1 << Exception::Breakpoint as usize);
+
+        let m = Medeleg::from_bits(PAGE_FAULTS | USER_ENV_CALL);
+        assert!(m.instruction_page_fault());
+        assert!(m.load_page_fault());
+        assert!(m.store_page_fault());
+        assert!(m.user_env_call());
+        assert!(!m.supervisor_env_call());
+    }
 }
@@ -2,3 +2,4 @@
 
 read_csr_as_usize!(0x341);
 write_csr_as_usize!(0x341);
+exception_pc_csr!(Mepc, mepc);
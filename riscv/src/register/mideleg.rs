@@ -1,5 +1,7 @@
 //! mideleg register
 
+use crate::interrupt::{supervisor::Interrupt, InterruptNumber};
+
 read_write_csr! {
     /// `mideleg` register
     Mideleg: 0x303,
@@ -24,18 +26,27 @@ read_write_csr_field! {
     sext: 9,
 }
 
+delegation_bits!(Mideleg, Interrupt, max: Interrupt::MAX_INTERRUPT_NUMBER, {
+    /// Supervisor Software Interrupt Delegate bit
+    SUPERVISOR_SOFT: SupervisorSoft,
+    /// Supervisor Timer Interrupt Delegate bit
+    SUPERVISOR_TIMER: SupervisorTimer,
+    /// Supervisor External Interrupt Delegate bit
+    SUPERVISOR_EXTERNAL: SupervisorExternal,
+});
+
 set!(0x303);
 clear!(0x303);
 
 set_clear_csr!(
     /// Supervisor Software Interrupt Delegate
-    , set_ssoft, clear_ssoft, 1 << 1);
+    , set_ssoft, clear_ssoft, SUPERVISOR_SOFT);
 set_clear_csr!(
     /// Supervisor Timer Interrupt Delegate
-    , set_stimer, clear_stimer, 1 << 5);
+    , set_stimer, clear_stimer, SUPERVISOR_TIMER);
 set_clear_csr!(
     /// Supervisor External Interrupt Delegate
-    , set_sext, clear_sext, 1 << 9);
+    , set_sext, clear_sext, SUPERVISOR_EXTERNAL);
 
 #[cfg(test)]
 mod tests {
@@ -49,4 +60,16 @@ mod tests {
         test_csr_field!(m, stimer);
         test_csr_field!(m, sext);
     }
+
+    #[test]
+    fn test_mideleg_bits() {
+        const ALL: usize = SUPERVISOR_SOFT | SUPERVISOR_TIMER | SUPERVISOR_EXTERNAL;
+        assert_eq!(ALL, Mideleg::BITMASK);
+        assert_eq!(SUPERVISOR_TIMER, 1 << Interrupt::SupervisorTimer as usize);
+
+        let m = Mideleg::from_bits(SUPERVISOR_TIMER);
+        assert!(!m.ssoft());
+        assert!(m.stimer());
+        assert!(!m.sext());
+    }
 }
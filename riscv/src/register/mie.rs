@@ -44,6 +44,7 @@ read_write_csr_field! {
 
 set!(0x304);
 clear!(0x304);
+swap_csr!(0x304);
 
 set_clear_csr!(
     /// Supervisor Software Interrupt Enable
@@ -64,9 +65,22 @@ set_clear_csr!(
     /// Machine External Interrupt Enable
     , set_mext, clear_mext, 1 << 11);
 
+csr_diff!(
+    Mie,
+    [
+        ssoft: 1,
+        msoft: 3,
+        stimer: 5,
+        mtimer: 7,
+        sext: 9,
+        mext: 11,
+    ]
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::result::Error;
 
     #[test]
     fn test_mie() {
@@ -79,4 +93,29 @@ mod tests {
         test_csr_field!(m, sext);
         test_csr_field!(m, mext);
     }
+
+    #[test]
+    fn test_mie_diff() {
+        let old = Mie::from_bits(0b1000_0000_1000);
+        let new = Mie::from_bits(0b0000_1000_1000);
+        let mut changes = old.diff(&new);
+        assert_eq!(
+            changes.next().map(|c| (c.field, c.old, c.new)),
+            Some(("mtimer", 0, 1))
+        );
+        assert_eq!(
+            changes.next().map(|c| (c.field, c.old, c.new)),
+            Some(("mext", 1, 0))
+        );
+        assert_eq!(changes.next(), None);
+    }
+
+    #[test]
+    fn test_mie_swap() {
+        unsafe {
+            assert_eq!(try_replace(0), Err(Error::Unimplemented));
+            assert_eq!(try_set_and_get(1 << 9), Err(Error::Unimplemented));
+            assert_eq!(try_clear_and_get(1 << 9), Err(Error::Unimplemented));
+        }
+    }
 }
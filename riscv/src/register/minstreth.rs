@@ -1,3 +1,4 @@
 //! minstreth register
 
 read_csr_as_usize_rv32!(0xB82);
+write_csr_as_usize_rv32!(0xB82);
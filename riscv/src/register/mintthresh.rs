@@ -0,0 +1,31 @@
+//! mintthresh register (CLIC)
+
+read_write_csr! {
+    /// Machine Interrupt-Level Threshold Register
+    Mintthresh: 0x347,
+    mask: 0xff,
+}
+
+read_write_csr_field! {
+    Mintthresh,
+    /// Interrupt level threshold.
+    ///
+    /// Interrupts with a level lower than or equal to this threshold are blocked.
+    th: [0:7],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mintthresh() {
+        let mut m = Mintthresh::from_bits(0);
+
+        (0..=u8::MAX as usize).for_each(|th| {
+            m.set_th(th);
+            assert_eq!(m.th(), th);
+            assert_eq!(m.bits(), th);
+        });
+    }
+}
@@ -44,6 +44,7 @@ read_only_csr_field! {
 
 set!(0x344);
 clear!(0x344);
+swap_csr!(0x344);
 
 set_clear_csr!(
     /// Supervisor Software Interrupt Pending
@@ -55,9 +56,35 @@ set_clear_csr!(
     /// Supervisor External Interrupt Pending
     , set_sext, clear_sext, 1 << 9);
 
+read_clear_csr_field!(
+    /// Supervisor Software Interrupt Pending
+    ssoft: 1,
+);
+read_clear_csr_field!(
+    /// Supervisor Timer Interrupt Pending
+    stimer: 5,
+);
+read_clear_csr_field!(
+    /// Supervisor External Interrupt Pending
+    sext: 9,
+);
+
+csr_diff!(
+    Mip,
+    [
+        ssoft: 1,
+        msoft: 3,
+        stimer: 5,
+        mtimer: 7,
+        sext: 9,
+        mext: 11,
+    ]
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::result::Error;
 
     #[test]
     fn test_mip() {
@@ -75,4 +102,22 @@ mod tests {
         assert!(Mip::from_bits(1 << 7).mtimer());
         assert!(Mip::from_bits(1 << 11).mext());
     }
+
+    #[test]
+    fn test_mip_swap() {
+        unsafe {
+            assert_eq!(try_replace(0), Err(Error::Unimplemented));
+            assert_eq!(try_set_and_get(1 << 9), Err(Error::Unimplemented));
+            assert_eq!(try_clear_and_get(1 << 9), Err(Error::Unimplemented));
+        }
+    }
+
+    #[test]
+    fn test_mip_take() {
+        unsafe {
+            assert_eq!(try_take_ssoft(), Err(Error::Unimplemented));
+            assert_eq!(try_take_stimer(), Err(Error::Unimplemented));
+            assert_eq!(try_take_sext(), Err(Error::Unimplemented));
+        }
+    }
 }
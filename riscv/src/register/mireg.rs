@@ -0,0 +1,47 @@
+//! mireg register (Smaia/Smcsrind)
+//!
+//! Alias of the machine-level indirect register selected by `miselect`.
+
+use super::miselect;
+use crate::result::Result;
+
+read_csr_as_usize!(0x351);
+write_csr_as_usize!(0x351);
+
+/// Reads the machine-level indirect register `select`.
+///
+/// Interrupts are disabled while `miselect` is modified, so trap handlers can also access indirect registers.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn read_indirect(select: usize) -> usize {
+    try_read_indirect(select).unwrap()
+}
+
+/// Attempts to read the machine-level indirect register `select`.
+#[inline]
+pub fn try_read_indirect(select: usize) -> Result<usize> {
+    crate::interrupt::machine::free(|| {
+        miselect::try_write(select)?;
+        try_read()
+    })
+}
+
+/// Writes `bits` to the machine-level indirect register `select`.
+///
+/// Interrupts are disabled while `miselect` is modified, so trap handlers can also access indirect registers.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn write_indirect(select: usize, bits: usize) {
+    try_write_indirect(select, bits).unwrap();
+}
+
+/// Attempts to write `bits` to the machine-level indirect register `select`.
+#[inline]
+pub fn try_write_indirect(select: usize, bits: usize) -> Result<()> {
+    crate::interrupt::machine::free(|| {
+        miselect::try_write(select)?;
+        try_write(bits)
+    })
+}
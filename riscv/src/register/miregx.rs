@@ -0,0 +1,17 @@
+macro_rules! reg {
+    (
+        $addr:literal, $csr:ident
+    ) => {
+        /// Alias of the machine-level indirect register selected by `miselect` (Smcsrind extension)
+        pub mod $csr {
+            read_csr_as_usize!($addr);
+            write_csr_as_usize!($addr);
+        }
+    };
+}
+
+reg!(0x352, mireg2);
+reg!(0x353, mireg3);
+reg!(0x355, mireg4);
+reg!(0x356, mireg5);
+reg!(0x357, mireg6);
@@ -0,0 +1,8 @@
+//! miselect register (Smaia/Smcsrind)
+//!
+//! Selects which register of the machine-level indirect register file is accessed through `mireg`.
+//! Use [`mireg::read_indirect`](super::mireg::read_indirect) and
+//! [`mireg::write_indirect`](super::mireg::write_indirect) to access indirect registers.
+
+read_csr_as_usize!(0x350);
+write_csr_as_usize!(0x350);
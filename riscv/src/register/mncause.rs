@@ -0,0 +1,65 @@
+//! mncause register (Smrnmi)
+
+#[cfg(target_arch = "riscv32")]
+read_only_csr! {
+    /// Resumable NMI cause register
+    Mncause: 0x742,
+    mask: 0xffff_ffff,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_only_csr! {
+    /// Resumable NMI cause register
+    Mncause: 0x742,
+    mask: 0xffff_ffff_ffff_ffff,
+}
+
+#[cfg(target_arch = "riscv32")]
+read_only_csr_field! {
+    Mncause,
+    /// Returns the implementation-defined NMI cause `code`.
+    code: [0:30],
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_only_csr_field! {
+    Mncause,
+    /// Returns the implementation-defined NMI cause `code`.
+    code: [0:62],
+}
+
+#[cfg(target_arch = "riscv32")]
+read_only_csr_field! {
+    Mncause,
+    /// Is the NMI cause an interrupt.
+    ///
+    /// It is always set for resumable NMIs caused by interrupts.
+    is_interrupt: 31,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_only_csr_field! {
+    Mncause,
+    /// Is the NMI cause an interrupt.
+    ///
+    /// It is always set for resumable NMIs caused by interrupts.
+    is_interrupt: 63,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mncause() {
+        let msb = usize::BITS as usize - 1;
+
+        let m = Mncause::from_bits((1 << msb) | 0x42);
+        assert!(m.is_interrupt());
+        assert_eq!(m.code(), 0x42);
+
+        let m = Mncause::from_bits(usize::MAX >> 1);
+        assert!(!m.is_interrupt());
+        assert_eq!(m.code(), usize::MAX >> 1);
+    }
+}
@@ -0,0 +1,7 @@
+//! mnepc register (Smrnmi)
+//!
+//! It holds the address of the instruction that was interrupted by the resumable NMI.
+//! The `mnret` instruction resumes execution at this address.
+
+read_csr_as_usize!(0x741);
+write_csr_as_usize!(0x741);
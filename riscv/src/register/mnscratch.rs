@@ -0,0 +1,4 @@
+//! mnscratch register (Smrnmi)
+
+read_csr_as_usize!(0x740);
+write_csr_as_usize!(0x740);
@@ -0,0 +1,71 @@
+//! mnstatus register (Smrnmi)
+
+pub use super::mstatus::MPP;
+
+read_write_csr! {
+    /// Resumable NMI status register
+    Mnstatus: 0x744,
+    mask: 0x1a88,
+}
+
+read_write_csr_field! {
+    Mnstatus,
+    /// Resumable NMI Enable
+    ///
+    /// It is cleared by hardware when a resumable NMI is taken, and set by `mnret`.
+    /// Software can set it, but writes of zero are ignored.
+    nmie: 3,
+}
+
+read_write_csr_field! {
+    Mnstatus,
+    /// Previous Virtualization mode
+    mnpv: 7,
+}
+
+read_write_csr_field! {
+    Mnstatus,
+    /// Previous Expected Landing Pad state
+    mnpelp: 9,
+}
+
+read_write_csr_field! {
+    Mnstatus,
+    /// Previous privilege mode
+    mnpp,
+    MPP: [11:12],
+}
+
+set!(0x744);
+clear!(0x744);
+
+set_csr!(
+    /// Resumable NMI Enable
+    , set_nmie, 1 << 3);
+
+/// Previous privilege mode
+#[inline]
+pub unsafe fn set_mnpp(mnpp: MPP) {
+    let mut value = _read();
+    value &= !(0x3 << 11); // clear previous value
+    value |= (mnpp as usize) << 11;
+    _write(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mnstatus() {
+        let mut mnstatus = Mnstatus::from_bits(0);
+
+        test_csr_field!(mnstatus, mnpp: MPP::User);
+        test_csr_field!(mnstatus, mnpp: MPP::Machine);
+        test_csr_field!(mnstatus, mnpp: MPP::Supervisor);
+
+        test_csr_field!(mnstatus, nmie);
+        test_csr_field!(mnstatus, mnpv);
+        test_csr_field!(mnstatus, mnpelp);
+    }
+}
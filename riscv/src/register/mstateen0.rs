@@ -0,0 +1,153 @@
+//! mstateen0 register (Smstateen extension)
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr! {
+    /// Machine State Enable 0 Register
+    Mstateen0: 0x30C,
+    mask: 0xdf00_0000_0000_0007,
+}
+
+#[cfg(target_arch = "riscv32")]
+read_write_csr! {
+    /// Machine State Enable 0 Register
+    Mstateen0: 0x30C,
+    mask: 0x7,
+}
+
+read_write_csr_field! {
+    Mstateen0,
+    /// Custom state enable
+    c: 0,
+}
+
+read_write_csr_field! {
+    Mstateen0,
+    /// `fcsr` access enable (when `misa.F` is 0)
+    fcsr: 1,
+}
+
+read_write_csr_field! {
+    Mstateen0,
+    /// `jvt` access enable (Zcmt extension)
+    jvt: 2,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// `hedelegh` access enable
+    p1p13: 56,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// `scontext` and `hcontext` access enable
+    context: 57,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// IMSIC state access enable (Ssaia extension)
+    imsic: 58,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// AIA state access enable, except for IMSIC state (Ssaia extension)
+    aia: 59,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// `siselect` and `sireg*` access enable (Sscsrind extension)
+    csrind: 60,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// `henvcfg` and `senvcfg` access enable
+    envcfg: 62,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Mstateen0,
+    /// `hstateen0` and `sstateen0` access enable
+    se0: 63,
+}
+
+set!(0x30C);
+clear!(0x30C);
+
+set_clear_csr!(
+/// Custom state enable
+    , set_c, clear_c, 1 << 0);
+
+set_clear_csr!(
+/// `fcsr` access enable
+    , set_fcsr, clear_fcsr, 1 << 1);
+
+set_clear_csr!(
+/// `jvt` access enable
+    , set_jvt, clear_jvt, 1 << 2);
+
+set_clear_csr!(
+/// `hedelegh` access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_p1p13, clear_p1p13, 1 << 56);
+
+set_clear_csr!(
+/// `scontext` and `hcontext` access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_context, clear_context, 1 << 57);
+
+set_clear_csr!(
+/// IMSIC state access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_imsic, clear_imsic, 1 << 58);
+
+set_clear_csr!(
+/// AIA state access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_aia, clear_aia, 1 << 59);
+
+set_clear_csr!(
+/// `siselect` and `sireg*` access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_csrind, clear_csrind, 1 << 60);
+
+set_clear_csr!(
+/// `henvcfg` and `senvcfg` access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_envcfg, clear_envcfg, 1 << 62);
+
+set_clear_csr!(
+/// `hstateen0` and `sstateen0` access enable
+#[cfg(not(target_arch = "riscv32"))]
+    , set_se0, clear_se0, 1 << 63);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mstateen0() {
+        let mut m = Mstateen0::from_bits(0);
+
+        test_csr_field!(m, c);
+        test_csr_field!(m, fcsr);
+        test_csr_field!(m, jvt);
+        test_csr_field!(m, p1p13);
+        test_csr_field!(m, context);
+        test_csr_field!(m, imsic);
+        test_csr_field!(m, aia);
+        test_csr_field!(m, csrind);
+        test_csr_field!(m, envcfg);
+        test_csr_field!(m, se0);
+    }
+}
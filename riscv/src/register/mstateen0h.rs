@@ -0,0 +1,98 @@
+//! mstateen0h register (Smstateen extension, RISCV-32 only)
+
+read_write_csr! {
+    /// Upper 32 bits of Machine State Enable 0 Register
+    Mstateen0h: 0x31C,
+    mask: 0xdf00_0000,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// `hedelegh` access enable
+    p1p13: 24,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// `scontext` and `hcontext` access enable
+    context: 25,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// IMSIC state access enable (Ssaia extension)
+    imsic: 26,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// AIA state access enable, except for IMSIC state (Ssaia extension)
+    aia: 27,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// `siselect` and `sireg*` access enable (Sscsrind extension)
+    csrind: 28,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// `henvcfg` and `senvcfg` access enable
+    envcfg: 30,
+}
+
+read_write_csr_field! {
+    Mstateen0h,
+    /// `hstateen0` and `sstateen0` access enable
+    se0: 31,
+}
+
+set_rv32!(0x31C);
+clear_rv32!(0x31C);
+
+set_clear_csr!(
+/// `hedelegh` access enable
+    , set_p1p13, clear_p1p13, 1 << 24);
+
+set_clear_csr!(
+/// `scontext` and `hcontext` access enable
+    , set_context, clear_context, 1 << 25);
+
+set_clear_csr!(
+/// IMSIC state access enable
+    , set_imsic, clear_imsic, 1 << 26);
+
+set_clear_csr!(
+/// AIA state access enable
+    , set_aia, clear_aia, 1 << 27);
+
+set_clear_csr!(
+/// `siselect` and `sireg*` access enable
+    , set_csrind, clear_csrind, 1 << 28);
+
+set_clear_csr!(
+/// `henvcfg` and `senvcfg` access enable
+    , set_envcfg, clear_envcfg, 1 << 30);
+
+set_clear_csr!(
+/// `hstateen0` and `sstateen0` access enable
+    , set_se0, clear_se0, 1 << 31);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mstateen0h() {
+        let mut m = Mstateen0h::from_bits(0);
+
+        test_csr_field!(m, p1p13);
+        test_csr_field!(m, context);
+        test_csr_field!(m, imsic);
+        test_csr_field!(m, aia);
+        test_csr_field!(m, csrind);
+        test_csr_field!(m, envcfg);
+        test_csr_field!(m, se0);
+    }
+}
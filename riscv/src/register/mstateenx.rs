@@ -0,0 +1,97 @@
+macro_rules! reg {
+    (
+        $addr:literal, $csr:ident, $ty:ident
+    ) => {
+        /// Machine State Enable Register (Smstateen extension)
+        pub mod $csr {
+            #[cfg(not(target_arch = "riscv32"))]
+            read_write_csr! {
+                /// Machine State Enable Register
+                $ty: $addr,
+                mask: 0x8000_0000_0000_0000,
+            }
+
+            #[cfg(target_arch = "riscv32")]
+            read_write_csr! {
+                /// Machine State Enable Register
+                $ty: $addr,
+                mask: 0x0,
+            }
+
+            #[cfg(not(target_arch = "riscv32"))]
+            read_write_csr_field! {
+                $ty,
+                /// `hstateen` and `sstateen` access enable
+                se: 63,
+            }
+
+            #[cfg(not(target_arch = "riscv32"))]
+            set!($addr);
+            #[cfg(not(target_arch = "riscv32"))]
+            clear!($addr);
+
+            set_clear_csr!(
+            /// `hstateen` and `sstateen` access enable
+            #[cfg(not(target_arch = "riscv32"))]
+                , set_se, clear_se, 1 << 63);
+        }
+    };
+}
+
+macro_rules! regh {
+    (
+        $addr:literal, $csrh:ident, $ty:ident
+    ) => {
+        /// Upper 32 bits of Machine State Enable Register (Smstateen extension, RISCV-32 only)
+        pub mod $csrh {
+            read_write_csr! {
+                /// Upper 32 bits of Machine State Enable Register
+                $ty: $addr,
+                mask: 0x8000_0000,
+            }
+
+            read_write_csr_field! {
+                $ty,
+                /// `hstateen` and `sstateen` access enable
+                se: 31,
+            }
+
+            set_rv32!($addr);
+            clear_rv32!($addr);
+
+            set_clear_csr!(
+            /// `hstateen` and `sstateen` access enable
+                , set_se, clear_se, 1 << 31);
+        }
+    };
+}
+
+reg!(0x30D, mstateen1, Mstateen1);
+reg!(0x30E, mstateen2, Mstateen2);
+reg!(0x30F, mstateen3, Mstateen3);
+
+regh!(0x31D, mstateen1h, Mstateen1h);
+regh!(0x31E, mstateen2h, Mstateen2h);
+regh!(0x31F, mstateen3h, Mstateen3h);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mstateenx() {
+        let mut m = mstateen1::Mstateen1::from_bits(0);
+        test_csr_field!(m, se);
+        let mut m = mstateen2::Mstateen2::from_bits(0);
+        test_csr_field!(m, se);
+        let mut m = mstateen3::Mstateen3::from_bits(0);
+        test_csr_field!(m, se);
+
+        let mut m = mstateen1h::Mstateen1h::from_bits(0);
+        test_csr_field!(m, se);
+        let mut m = mstateen2h::Mstateen2h::from_bits(0);
+        test_csr_field!(m, se);
+        let mut m = mstateen3h::Mstateen3h::from_bits(0);
+        test_csr_field!(m, se);
+    }
+}
@@ -8,7 +8,7 @@ use crate::bits::{bf_extract, bf_insert};
 read_write_csr! {
     /// mstatus register
     Mstatus: 0x300,
-    mask: 0x8000_0000_007f_fffe,
+    mask: 0x8000_00ff_007f_fffe,
 }
 
 #[cfg(target_arch = "riscv32")]
@@ -231,6 +231,44 @@ read_write_csr_field! {
     sd: 63,
 }
 
+csr_diff!(
+    Mstatus,
+    [
+        sie: 1,
+        mie: 3,
+        spie: 5,
+        ube: 6,
+        mpie: 7,
+        spp: 8,
+        vs: [9:10],
+        mpp: [11:12],
+        fs: [13:14],
+        xs: [15:16],
+        mprv: 17,
+        sum: 18,
+        mxr: 19,
+        tvm: 20,
+        tw: 21,
+        tsr: 22,
+        #[cfg(target_arch = "riscv32")]
+        sd: 31,
+        #[cfg(not(target_arch = "riscv32"))]
+        uxl: [32:33],
+        #[cfg(not(target_arch = "riscv32"))]
+        sxl: [34:35],
+        #[cfg(not(target_arch = "riscv32"))]
+        sbe: 36,
+        #[cfg(not(target_arch = "riscv32"))]
+        mbe: 37,
+        #[cfg(not(target_arch = "riscv32"))]
+        gva: 38,
+        #[cfg(not(target_arch = "riscv32"))]
+        mpv: 39,
+        #[cfg(not(target_arch = "riscv32"))]
+        sd: 63,
+    ]
+);
+
 impl Mstatus {
     /// Effective xlen in U-mode (i.e., `UXLEN`).
     ///
@@ -468,6 +506,7 @@ pub unsafe fn set_mbe(endianness: Endianness) {
 
 #[cfg(test)]
 mod test {
+    use super::super::FieldChange;
     use super::*;
 
     #[test]
@@ -509,4 +548,47 @@ mod test {
         test_csr_field!(mstatus, tsr);
         test_csr_field!(mstatus, sd);
     }
+
+    #[test]
+    fn test_mstatus_diff() {
+        extern crate std;
+        use std::string::ToString;
+
+        let old = Mstatus::from_bits(0);
+        assert_eq!(old.diff(&old).count(), 0);
+
+        let mut new = old;
+        new.set_mie(true);
+        new.set_mpp(MPP::Supervisor);
+
+        let changes = [
+            FieldChange {
+                field: "mie",
+                old: 0,
+                new: 1,
+            },
+            FieldChange {
+                field: "mpp",
+                old: 0,
+                new: 1,
+            },
+        ];
+        assert!(old.diff(&new).eq(changes));
+        assert_eq!(changes[0].to_string(), "MIE 0\u{2192}1");
+        assert_eq!(changes[1].to_string(), "MPP 0\u{2192}1");
+
+        #[cfg(not(target_arch = "riscv32"))]
+        {
+            let mut new = old;
+            new.set_sxl(XLEN::XLEN64);
+            assert!(old.diff(&new).eq([FieldChange {
+                field: "sxl",
+                old: 0,
+                new: 2,
+            }]));
+
+            let new = Mstatus::from_bits((1 << 38) | (1 << 39));
+            assert!(old.diff(&new).map(|change| change.field).eq(["gva", "mpv"]));
+        }
+    }
 }
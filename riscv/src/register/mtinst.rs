@@ -0,0 +1,257 @@
+//! mtinst register (Hypervisor extension)
+//!
+//! On traps taken into M-mode, `mtinst` holds either zero, a transformed version of the trapping
+//! instruction, or a pseudoinstruction for guest-page faults caused by implicit memory accesses of the
+//! VS-stage address translation. Trap handlers can use it to emulate the trapping load or store without
+//! reading (and decoding) the instruction from guest memory.
+//!
+//! Transformed instructions keep the `opcode`, `rd`, `funct3`, and `rs2` fields of the trapping instruction.
+//! The `rs1` field is replaced by the offset of the faulting address with respect to the base address
+//! of the access, and the immediate fields are zero. Compressed instructions are transformed into their
+//! 32-bit equivalent with bit 1 cleared.
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr! {
+    /// Machine Trap Instruction Register
+    Mtinst: 0x34a,
+    mask: 0xffff_ffff_ffff_ffff,
+}
+
+#[cfg(target_arch = "riscv32")]
+read_write_csr! {
+    /// Machine Trap Instruction Register
+    Mtinst: 0x34a,
+    mask: 0xffff_ffff,
+}
+
+csr_field_enum! {
+    /// Pseudoinstruction for guest-page faults caused by implicit VS-stage memory accesses
+    Pseudoinstruction {
+        default: Read32,
+        /// 32-bit read of a VS-stage page table entry
+        Read32 = 0x2000,
+        /// 32-bit write of a VS-stage page table entry (e.g., to update the A/D bits)
+        Write32 = 0x2020,
+        /// 64-bit read of a VS-stage page table entry
+        Read64 = 0x3000,
+        /// 64-bit write of a VS-stage page table entry (e.g., to update the A/D bits)
+        Write64 = 0x3020,
+    }
+}
+
+/// Kind of the value of [`Mtinst`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TrapInstruction {
+    /// No information is provided about the trapping instruction
+    None,
+    /// Transformed 32-bit instruction
+    Standard,
+    /// Transformed compressed (16-bit) instruction
+    Compressed,
+    /// Pseudoinstruction for an implicit VS-stage memory access
+    Pseudo(Pseudoinstruction),
+    /// Custom or reserved value
+    Other,
+}
+
+/// Major opcode of scalar loads.
+const LOAD: usize = 0x03;
+/// Major opcode of floating-point loads.
+const LOAD_FP: usize = 0x07;
+/// Major opcode of scalar stores.
+const STORE: usize = 0x23;
+/// Major opcode of floating-point stores.
+const STORE_FP: usize = 0x27;
+/// Major opcode of atomic memory operations.
+const AMO: usize = 0x2f;
+
+read_write_csr_field! {
+    Mtinst,
+    /// Opcode of the transformed instruction (bit 1 is cleared for compressed instructions)
+    opcode: [0:6],
+}
+
+read_write_csr_field! {
+    Mtinst,
+    /// Destination register of the transformed load or AMO instruction
+    rd: [7:11],
+}
+
+read_write_csr_field! {
+    Mtinst,
+    /// `funct3` field of the transformed instruction (i.e., the width of the access)
+    funct3: [12:14],
+}
+
+read_write_csr_field! {
+    Mtinst,
+    /// Offset of the faulting address with respect to the base address of the access
+    ///
+    /// It is non-zero only for misaligned accesses that fault in a page other than the first one.
+    addr_offset: [15:19],
+}
+
+read_write_csr_field! {
+    Mtinst,
+    /// Source register of the transformed store or AMO instruction
+    rs2: [20:24],
+}
+
+impl Mtinst {
+    /// Returns the kind of value held in the register.
+    #[inline]
+    pub fn kind(&self) -> TrapInstruction {
+        match self.bits & 0b11 {
+            0b11 => TrapInstruction::Standard,
+            0b01 => TrapInstruction::Compressed,
+            _ if self.bits == 0 => TrapInstruction::None,
+            _ => Pseudoinstruction::from_usize(self.bits)
+                .map(TrapInstruction::Pseudo)
+                .unwrap_or(TrapInstruction::Other),
+        }
+    }
+
+    /// Returns the length (in bytes) of the trapping instruction, if it is a transformed instruction.
+    ///
+    /// Trap handlers that emulate the instruction must advance `mepc` by this length.
+    #[inline]
+    pub fn instruction_len(&self) -> Option<usize> {
+        match self.kind() {
+            TrapInstruction::Standard => Some(4),
+            TrapInstruction::Compressed => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Returns the major opcode of the transformed instruction, with bit 1 set for compressed instructions.
+    #[inline]
+    fn major_opcode(&self) -> Option<usize> {
+        self.instruction_len().map(|_| self.opcode() | 0b10)
+    }
+
+    /// Returns `true` if the transformed instruction is an integer or floating-point load.
+    #[inline]
+    pub fn is_load(&self) -> bool {
+        matches!(self.major_opcode(), Some(LOAD | LOAD_FP))
+    }
+
+    /// Returns `true` if the transformed instruction is an integer or floating-point store.
+    #[inline]
+    pub fn is_store(&self) -> bool {
+        matches!(self.major_opcode(), Some(STORE | STORE_FP))
+    }
+
+    /// Returns `true` if the transformed instruction is an atomic memory operation (including `lr` and `sc`).
+    #[inline]
+    pub fn is_amo(&self) -> bool {
+        self.major_opcode() == Some(AMO)
+    }
+
+    /// Returns the width (in bytes) of the memory access of a transformed scalar load, store, or AMO.
+    ///
+    /// For pseudoinstructions, use [`Pseudoinstruction::access_width`] instead.
+    #[inline]
+    pub fn access_width(&self) -> Option<usize> {
+        match (self.major_opcode()?, self.funct3()) {
+            (LOAD | STORE, funct3) => Some(1 << (funct3 & 0b11)),
+            (AMO, funct3 @ 2..=3) => Some(1 << funct3),
+            (LOAD_FP | STORE_FP, funct3 @ 1..=4) => Some(1 << funct3),
+            _ => None,
+        }
+    }
+}
+
+impl Pseudoinstruction {
+    /// Returns `true` if the implicit memory access is a write.
+    #[inline]
+    pub const fn is_write(self) -> bool {
+        matches!(self, Self::Write32 | Self::Write64)
+    }
+
+    /// Returns the width (in bytes) of the implicit memory access.
+    #[inline]
+    pub const fn access_width(self) -> usize {
+        match self {
+            Self::Read32 | Self::Write32 => 4,
+            Self::Read64 | Self::Write64 => 8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mtinst() {
+        let mut mtinst = Mtinst::from_bits(0);
+        assert_eq!(mtinst.kind(), TrapInstruction::None);
+        assert_eq!(mtinst.instruction_len(), None);
+        assert_eq!(mtinst.access_width(), None);
+
+        mtinst.set_opcode(0x7f);
+        mtinst.set_rd(0x1f);
+        mtinst.set_funct3(0x7);
+        mtinst.set_addr_offset(0x1f);
+        mtinst.set_rs2(0x1f);
+        assert_eq!(mtinst.bits(), 0x01ff_ffff);
+        assert_eq!(mtinst.opcode(), 0x7f);
+        assert_eq!(mtinst.rd(), 0x1f);
+        assert_eq!(mtinst.funct3(), 0x7);
+        assert_eq!(mtinst.addr_offset(), 0x1f);
+        assert_eq!(mtinst.rs2(), 0x1f);
+
+        mtinst.set_funct3(0);
+        assert_eq!(mtinst.bits(), 0x01ff_8fff);
+    }
+
+    #[test]
+    fn test_mtinst_transformed() {
+        // lw a0, 0(a1) with an address offset of 2
+        let mtinst = Mtinst::from_bits((2 << 15) | (2 << 12) | (10 << 7) | LOAD);
+        assert_eq!(mtinst.kind(), TrapInstruction::Standard);
+        assert_eq!(mtinst.instruction_len(), Some(4));
+        assert!(mtinst.is_load() && !mtinst.is_store() && !mtinst.is_amo());
+        assert_eq!(mtinst.rd(), 10);
+        assert_eq!(mtinst.addr_offset(), 2);
+        assert_eq!(mtinst.access_width(), Some(4));
+
+        // c.sd a0, 0(a1), transformed into sd a0, 0(a1) with bit 1 cleared
+        let mtinst = Mtinst::from_bits((10 << 20) | (3 << 12) | (STORE & !0b10));
+        assert_eq!(mtinst.kind(), TrapInstruction::Compressed);
+        assert_eq!(mtinst.instruction_len(), Some(2));
+        assert!(mtinst.is_store() && !mtinst.is_load());
+        assert_eq!(mtinst.rs2(), 10);
+        assert_eq!(mtinst.access_width(), Some(8));
+
+        // amoadd.w a0, a2, (a1)
+        let mtinst = Mtinst::from_bits((12 << 20) | (2 << 12) | (10 << 7) | AMO);
+        assert!(mtinst.is_amo());
+        assert_eq!(mtinst.access_width(), Some(4));
+
+        // fld fa0, 0(a1)
+        let mtinst = Mtinst::from_bits((3 << 12) | (10 << 7) | LOAD_FP);
+        assert!(mtinst.is_load());
+        assert_eq!(mtinst.access_width(), Some(8));
+    }
+
+    #[test]
+    fn test_mtinst_pseudo() {
+        for (bits, pseudo, write, width) in [
+            (0x2000, Pseudoinstruction::Read32, false, 4),
+            (0x2020, Pseudoinstruction::Write32, true, 4),
+            (0x3000, Pseudoinstruction::Read64, false, 8),
+            (0x3020, Pseudoinstruction::Write64, true, 8),
+        ] {
+            let mtinst = Mtinst::from_bits(bits);
+            assert_eq!(mtinst.kind(), TrapInstruction::Pseudo(pseudo));
+            assert_eq!(mtinst.instruction_len(), None);
+            assert!(!mtinst.is_load() && !mtinst.is_store());
+            assert_eq!(pseudo.is_write(), write);
+            assert_eq!(pseudo.access_width(), width);
+        }
+
+        assert_eq!(Mtinst::from_bits(0x1000).kind(), TrapInstruction::Other);
+        assert_eq!(Mtinst::from_bits(0b10).kind(), TrapInstruction::Other);
+    }
+}
@@ -0,0 +1,72 @@
+//! mtopei register (Smaia)
+//!
+//! Reports the external interrupt with the highest priority that is pending and enabled in the
+//! machine-level interrupt file of the hart's IMSIC. Writing the CSR claims this interrupt
+//! (i.e., clears its pending bit), so [`claim`] reads and claims it with a single instruction.
+
+use crate::result::Result;
+
+read_only_csr! {
+    /// Machine Top External Interrupt register
+    Mtopei: 0x35c,
+    mask: 0x07ff_07ff,
+}
+
+read_only_csr_field! {
+    Mtopei,
+    /// Identity of the external interrupt, or 0 if no interrupt is pending and enabled.
+    iid: [16:26],
+}
+
+read_only_csr_field! {
+    Mtopei,
+    /// Priority of the external interrupt. In IMSICs, it is equal to the identity.
+    iprio: [0:10],
+}
+
+/// Claims the external interrupt with the highest priority, and returns its previous `mtopei` value.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn claim() -> Mtopei {
+    try_claim().unwrap()
+}
+
+/// Attempts to claim the external interrupt with the highest priority, and returns its previous `mtopei` value.
+#[inline]
+pub fn try_claim() -> Result<Mtopei> {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let r: usize;
+            // SAFETY: writing the CSR only clears the pending bit of the interrupt being claimed
+            unsafe { core::arch::asm!("csrrw {0}, 0x35c, x0", out(reg) r) };
+            Ok(Mtopei::from_bits(r))
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => Err(crate::result::Error::Unimplemented),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mtopei() {
+        let m = Mtopei::from_bits(0);
+        assert_eq!(m.iid(), 0);
+        assert_eq!(m.iprio(), 0);
+
+        let m = Mtopei::from_bits((42 << 16) | 42);
+        assert_eq!(m.iid(), 42);
+        assert_eq!(m.iprio(), 42);
+
+        let m = Mtopei::from_bits(usize::MAX);
+        assert_eq!(m.iid(), 0x7ff);
+        assert_eq!(m.iprio(), 0x7ff);
+        assert_eq!(m.bits(), Mtopei::BITMASK);
+
+        assert_eq!(try_claim(), Err(crate::result::Error::Unimplemented));
+    }
+}
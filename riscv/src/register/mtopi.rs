@@ -0,0 +1,43 @@
+//! mtopi register (Smaia)
+
+read_only_csr! {
+    /// Machine Top Interrupt register
+    ///
+    /// It reports the major interrupt with the highest priority that is pending and enabled for M-mode.
+    Mtopi: 0xfb0,
+    mask: 0x0fff_00ff,
+}
+
+read_only_csr_field! {
+    Mtopi,
+    /// Major identity number of the interrupt (i.e., its `mcause` exception code),
+    /// or 0 if no interrupt is pending and enabled.
+    iid: [16:27],
+}
+
+read_only_csr_field! {
+    Mtopi,
+    /// Priority of the interrupt. Lower values have higher priority.
+    iprio: [0:7],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mtopi() {
+        let m = Mtopi::from_bits(0);
+        assert_eq!(m.iid(), 0);
+        assert_eq!(m.iprio(), 0);
+
+        let m = Mtopi::from_bits((11 << 16) | 0x80);
+        assert_eq!(m.iid(), 11);
+        assert_eq!(m.iprio(), 0x80);
+
+        let m = Mtopi::from_bits(usize::MAX);
+        assert_eq!(m.iid(), 0xfff);
+        assert_eq!(m.iprio(), 0xff);
+        assert_eq!(m.bits(), Mtopi::BITMASK);
+    }
+}
@@ -0,0 +1,16 @@
+//! mtval2 register (Hypervisor extension)
+//!
+//! On guest-page faults taken into M-mode, `mtval2` holds the guest physical address that faulted,
+//! shifted right by 2 bits. Otherwise, it is zero.
+
+read_csr_as_usize!(0x34b);
+write_csr_as_usize!(0x34b);
+
+/// Reads the faulting guest physical address of a guest-page fault (i.e., `mtval2 << 2`).
+///
+/// Guest physical addresses are 2 bits wider than virtual addresses, so the address is returned as a [`u64`].
+/// The lower 2 bits of the address are not provided. Use `mtval` to get the full faulting guest virtual address.
+#[inline]
+pub fn read_gpa() -> u64 {
+    (read() as u64) << 2
+}
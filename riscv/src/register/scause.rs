@@ -19,7 +19,7 @@ impl Scause {
     /// Returns the code field
     #[inline]
     pub fn code(&self) -> usize {
-        self.bits & !(1 << (usize::BITS as usize - 1))
+        self.bits & !Trap::INTERRUPT_BIT
     }
 
     /// Returns the trap cause represented by this register.
@@ -30,17 +30,13 @@ impl Scause {
     /// To get a target-specific trap cause, use [`Trap::try_into`] with your target-specific S-Mode trap cause types.
     #[inline]
     pub fn cause(&self) -> Trap<usize, usize> {
-        if self.is_interrupt() {
-            Trap::Interrupt(self.code())
-        } else {
-            Trap::Exception(self.code())
-        }
+        Trap::from_cause_bits(self.bits)
     }
 
     /// Is trap cause an interrupt.
     #[inline]
     pub fn is_interrupt(&self) -> bool {
-        self.bits & (1 << (usize::BITS as usize - 1)) != 0
+        self.bits & Trap::INTERRUPT_BIT != 0
     }
 
     /// Is trap cause an exception.
@@ -62,11 +58,5 @@ pub unsafe fn write(bits: usize) {
 /// Set supervisor cause register to corresponding cause.
 #[inline]
 pub unsafe fn set<I: CoreInterruptNumber, E: ExceptionNumber>(cause: Trap<I, E>) {
-    let bits = match cause {
-        Trap::Interrupt(i) => {
-            i.number() | (1 << (usize::BITS as usize - 1)) // interrupt bit is 1
-        }
-        Trap::Exception(e) => e.number(),
-    };
-    _write(bits);
+    _write(cause.into().to_cause_bits());
 }
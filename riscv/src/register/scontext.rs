@@ -0,0 +1,6 @@
+//! scontext register (Sdtrig extension)
+//!
+//! Holds the supervisor-mode context used by the trigger module to match triggers.
+
+read_csr_as_usize!(0x5A8);
+write_csr_as_usize!(0x5A8);
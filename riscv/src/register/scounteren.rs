@@ -9,6 +9,12 @@ pub struct Scounteren {
 }
 
 impl Scounteren {
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
     /// User "cycle\[h\]" Enable
     #[inline]
     pub fn cy(&self) -> bool {
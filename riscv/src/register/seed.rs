@@ -0,0 +1,243 @@
+//! seed register (Zkr)
+//!
+//! The `seed` CSR provides 16 bits of entropy from a physical entropy source per successful read.
+//! It must be accessed with a read-write instruction (i.e., `csrrw`): reading it with `csrrs`
+//! or `csrrc` raises an illegal instruction exception. Writes to the CSR are ignored.
+//!
+//! Access from S-mode and U-mode is controlled by the `mseccfg.SSEED` and `mseccfg.USEED` bits.
+
+use crate::result::{Error, Result};
+
+csr! {
+    /// Entropy source register
+    Seed,
+    0xc000_ffff
+}
+
+csr_field_enum! {
+    /// Status of the entropy source
+    OPST {
+        default: Bist,
+        /// Built-in self-test in progress. No entropy is available.
+        Bist = 0,
+        /// Entropy is being generated. Retry later.
+        Wait = 1,
+        /// The entropy field contains 16 bits of randomness.
+        Es16 = 2,
+        /// Unrecoverable self-test error. The entropy source is out of order.
+        Dead = 3,
+    }
+}
+
+read_only_csr_field! {
+    Seed,
+    /// Status of the entropy source
+    opst,
+    OPST: [30:31],
+}
+
+read_only_csr_field! {
+    Seed,
+    /// Raw entropy field. It only contains randomness if [`Seed::opst`] is [`OPST::Es16`].
+    entropy: [0:15],
+}
+
+impl Seed {
+    /// Returns the 16 bits of randomness of the sample.
+    ///
+    /// It returns [`Error::InvalidFieldVariant`] with the current status of the
+    /// entropy source if the sample does not contain randomness (i.e., status is not [`OPST::Es16`]).
+    #[inline]
+    pub fn try_entropy16(&self) -> Result<u16> {
+        match self.try_opst()? {
+            OPST::Es16 => Ok(self.entropy() as u16),
+            opst => Err(Error::InvalidFieldVariant {
+                field: "opst",
+                value: opst as usize,
+            }),
+        }
+    }
+}
+
+/// Reads the CSR.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn read() -> Seed {
+    try_read().unwrap()
+}
+
+/// Attempts to read the CSR.
+#[inline]
+pub fn try_read() -> Result<Seed> {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let r: usize;
+            // SAFETY: the seed CSR must be accessed with a read-write instruction. Writes are ignored.
+            unsafe { core::arch::asm!("csrrw {0}, 0x015, x0", out(reg) r) };
+            Ok(Seed::from_bits(r))
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => Err(Error::Unimplemented),
+    }
+}
+
+/// Polls the entropy source once, and returns 16 bits of entropy if available.
+///
+/// It returns [`Error::InvalidFieldVariant`] with the status of the entropy source
+/// if no entropy is available (see [`Seed::try_entropy16`]).
+#[inline]
+pub fn try_read_entropy() -> Result<u16> {
+    try_read()?.try_entropy16()
+}
+
+/// Polls the entropy source until 16 bits of entropy are available.
+///
+/// While the entropy source is running its self-test ([`OPST::Bist`]) or generating entropy
+/// ([`OPST::Wait`]), it retries up to `max_retries` times. It returns [`Error::InvalidFieldVariant`]
+/// with the last status of the entropy source if it is [`OPST::Dead`] or no retries are left.
+#[inline]
+pub fn read_entropy(max_retries: usize) -> Result<u16> {
+    let mut retries = 0;
+    loop {
+        let seed = try_read()?;
+        match seed.try_opst()? {
+            OPST::Bist | OPST::Wait if retries < max_retries => retries += 1,
+            _ => return seed.try_entropy16(),
+        }
+    }
+}
+
+/// Physical entropy source (Zkr) backed by the `seed` CSR.
+///
+/// The entropy source provides raw samples for seeding cryptographically secure PRNGs.
+/// They are not meant to be used directly as random numbers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct EntropySource;
+
+impl EntropySource {
+    /// Creates a new entropy source.
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Polls the entropy source once.
+    ///
+    /// It returns [`Error::InvalidFieldVariant`] with the status of the entropy source
+    /// if no entropy is available (see [`Seed::try_entropy16`]).
+    #[inline]
+    pub fn try_poll(&self) -> Result<u16> {
+        try_read_entropy()
+    }
+
+    /// Polls the entropy source until 16 bits of entropy are available.
+    ///
+    /// It returns [`Error::InvalidFieldVariant`] if the entropy source is [`OPST::Dead`].
+    #[inline]
+    pub fn read_u16(&self) -> Result<u16> {
+        read_entropy(usize::MAX)
+    }
+
+    /// Fills `dest` with entropy, polling the entropy source until enough entropy is available.
+    ///
+    /// It returns [`Error::InvalidFieldVariant`] if the entropy source is [`OPST::Dead`].
+    #[inline]
+    pub fn fill_bytes(&self, dest: &mut [u8]) -> Result<()> {
+        for chunk in dest.chunks_mut(2) {
+            let entropy = self.read_u16()?.to_le_bytes();
+            chunk.copy_from_slice(&entropy[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+/// The entropy source can be used to seed PRNGs (e.g., with `SeedableRng::from_rng`).
+///
+/// `next_u32`, `next_u64`, and `fill_bytes` panic if the entropy source is [`OPST::Dead`].
+/// Use `try_fill_bytes` to handle this error. Its error code is `rand_core::Error::CUSTOM_START`
+/// plus the status of the entropy source.
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for EntropySource {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        EntropySource::fill_bytes(self, dest).expect("entropy source is out of order")
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        EntropySource::fill_bytes(self, dest).map_err(|e| {
+            let status = match e {
+                Error::InvalidFieldVariant { value, .. } => value as u32,
+                _ => OPST::Dead as u32,
+            };
+            let code = core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START + status).unwrap();
+            rand_core::Error::from(code)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed() {
+        let seed = Seed::from_bits(usize::MAX);
+        assert_eq!(seed.bits(), Seed::BITMASK);
+        assert_eq!(seed.opst(), OPST::Dead);
+        assert_eq!(seed.entropy(), 0xffff);
+
+        for (opst, value) in [(OPST::Bist, 0), (OPST::Wait, 1), (OPST::Dead, 3)] {
+            let seed = Seed::from_bits((value << 30) | 0x1234);
+            assert_eq!(seed.opst(), opst);
+            assert_eq!(
+                seed.try_entropy16(),
+                Err(Error::InvalidFieldVariant {
+                    field: "opst",
+                    value
+                })
+            );
+        }
+
+        let seed = Seed::from_bits((2 << 30) | 0xbeef);
+        assert_eq!(seed.opst(), OPST::Es16);
+        assert_eq!(seed.try_entropy16(), Ok(0xbeef));
+    }
+
+    #[test]
+    fn test_entropy_source() {
+        let source = EntropySource::new();
+        assert_eq!(source.try_poll(), Err(Error::Unimplemented));
+        assert_eq!(source.read_u16(), Err(Error::Unimplemented));
+        assert_eq!(source.fill_bytes(&mut [0; 3]), Err(Error::Unimplemented));
+        assert_eq!(source.fill_bytes(&mut []), Ok(()));
+
+        assert_eq!(try_read_entropy(), Err(Error::Unimplemented));
+        assert_eq!(read_entropy(4), Err(Error::Unimplemented));
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn test_rng_core() {
+        use rand_core::RngCore;
+
+        let mut source = EntropySource::new();
+        let err = source.try_fill_bytes(&mut [0; 4]).unwrap_err();
+        assert_eq!(
+            err.code().map(|code| code.get()),
+            Some(rand_core::Error::CUSTOM_START + OPST::Dead as u32)
+        );
+    }
+}
@@ -2,3 +2,4 @@
 
 read_csr_as_usize!(0x141);
 write_csr_as_usize!(0x141);
+exception_pc_csr!(Sepc, sepc);
@@ -0,0 +1,31 @@
+//! sintthresh register (CLIC)
+
+read_write_csr! {
+    /// Supervisor Interrupt-Level Threshold Register
+    Sintthresh: 0x147,
+    mask: 0xff,
+}
+
+read_write_csr_field! {
+    Sintthresh,
+    /// Interrupt level threshold.
+    ///
+    /// Interrupts with a level lower than or equal to this threshold are blocked.
+    th: [0:7],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sintthresh() {
+        let mut m = Sintthresh::from_bits(0);
+
+        (0..=u8::MAX as usize).for_each(|th| {
+            m.set_th(th);
+            assert_eq!(m.th(), th);
+            assert_eq!(m.bits(), th);
+        });
+    }
+}
@@ -35,7 +35,13 @@ impl Sip {
 read_csr_as!(Sip, 0x144);
 set!(0x144);
 clear!(0x144);
+swap_csr!(0x144);
 
 set_clear_csr!(
     /// Supervisor Software Interrupt Pending
     , set_ssoft, clear_ssoft, 1 << 1);
+
+read_clear_csr_field!(
+    /// Supervisor Software Interrupt Pending
+    ssoft: 1,
+);
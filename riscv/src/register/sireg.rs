@@ -0,0 +1,47 @@
+//! sireg register (Ssaia/Sscsrind)
+//!
+//! Alias of the supervisor-level indirect register selected by `siselect`.
+
+use super::siselect;
+use crate::result::Result;
+
+read_csr_as_usize!(0x151);
+write_csr_as_usize!(0x151);
+
+/// Reads the supervisor-level indirect register `select`.
+///
+/// Interrupts are disabled while `siselect` is modified, so trap handlers can also access indirect registers.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn read_indirect(select: usize) -> usize {
+    try_read_indirect(select).unwrap()
+}
+
+/// Attempts to read the supervisor-level indirect register `select`.
+#[inline]
+pub fn try_read_indirect(select: usize) -> Result<usize> {
+    crate::interrupt::supervisor::free(|| {
+        siselect::try_write(select)?;
+        try_read()
+    })
+}
+
+/// Writes `bits` to the supervisor-level indirect register `select`.
+///
+/// Interrupts are disabled while `siselect` is modified, so trap handlers can also access indirect registers.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn write_indirect(select: usize, bits: usize) {
+    try_write_indirect(select, bits).unwrap();
+}
+
+/// Attempts to write `bits` to the supervisor-level indirect register `select`.
+#[inline]
+pub fn try_write_indirect(select: usize, bits: usize) -> Result<()> {
+    crate::interrupt::supervisor::free(|| {
+        siselect::try_write(select)?;
+        try_write(bits)
+    })
+}
@@ -0,0 +1,17 @@
+macro_rules! reg {
+    (
+        $addr:literal, $csr:ident
+    ) => {
+        /// Alias of the supervisor-level indirect register selected by `siselect` (Sscsrind extension)
+        pub mod $csr {
+            read_csr_as_usize!($addr);
+            write_csr_as_usize!($addr);
+        }
+    };
+}
+
+reg!(0x152, sireg2);
+reg!(0x153, sireg3);
+reg!(0x155, sireg4);
+reg!(0x156, sireg5);
+reg!(0x157, sireg6);
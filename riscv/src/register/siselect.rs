@@ -0,0 +1,8 @@
+//! siselect register (Ssaia/Sscsrind)
+//!
+//! Selects which register of the supervisor-level indirect register file is accessed through `sireg`.
+//! Use [`sireg::read_indirect`](super::sireg::read_indirect) and
+//! [`sireg::write_indirect`](super::sireg::write_indirect) to access indirect registers.
+
+read_csr_as_usize!(0x150);
+write_csr_as_usize!(0x150);
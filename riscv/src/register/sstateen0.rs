@@ -0,0 +1,54 @@
+//! sstateen0 register (Ssstateen extension)
+
+read_write_csr! {
+    /// Supervisor State Enable 0 Register
+    Sstateen0: 0x10C,
+    mask: 0x7,
+}
+
+read_write_csr_field! {
+    Sstateen0,
+    /// Custom state enable
+    c: 0,
+}
+
+read_write_csr_field! {
+    Sstateen0,
+    /// `fcsr` access enable (when `misa.F` is 0)
+    fcsr: 1,
+}
+
+read_write_csr_field! {
+    Sstateen0,
+    /// `jvt` access enable (Zcmt extension)
+    jvt: 2,
+}
+
+set!(0x10C);
+clear!(0x10C);
+
+set_clear_csr!(
+/// Custom state enable
+    , set_c, clear_c, 1 << 0);
+
+set_clear_csr!(
+/// `fcsr` access enable
+    , set_fcsr, clear_fcsr, 1 << 1);
+
+set_clear_csr!(
+/// `jvt` access enable
+    , set_jvt, clear_jvt, 1 << 2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sstateen0() {
+        let mut s = Sstateen0::from_bits(0);
+
+        test_csr_field!(s, c);
+        test_csr_field!(s, fcsr);
+        test_csr_field!(s, jvt);
+    }
+}
@@ -0,0 +1,17 @@
+macro_rules! reg {
+    (
+        $addr:literal, $csr:ident
+    ) => {
+        /// Supervisor State Enable Register (Ssstateen extension)
+        ///
+        /// All its bits are reserved for future standard use.
+        pub mod $csr {
+            read_csr_as_usize!($addr);
+            write_csr_as_usize!($addr);
+        }
+    };
+}
+
+reg!(0x10D, sstateen1);
+reg!(0x10E, sstateen2);
+reg!(0x10F, sstateen3);
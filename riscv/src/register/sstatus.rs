@@ -1,105 +1,152 @@
 //! sstatus register
 
 pub use super::misa::XLEN;
-pub use super::mstatus::FS;
+pub use super::mstatus::{Endianness, FS, SPP, VS, XS};
+#[cfg(not(target_arch = "riscv32"))]
+use crate::bits::{bf_extract, bf_insert};
 
-/// Supervisor Status Register
-#[derive(Clone, Copy, Debug)]
-pub struct Sstatus {
-    bits: usize,
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr! {
+    /// Supervisor Status Register
+    Sstatus: 0x100,
+    mask: 0x8000_0003_000d_e762,
 }
 
-/// Supervisor Previous Privilege Mode
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum SPP {
-    Supervisor = 1,
-    User = 0,
+#[cfg(target_arch = "riscv32")]
+read_write_csr! {
+    /// Supervisor Status Register
+    Sstatus: 0x100,
+    mask: 0x800d_e762,
 }
 
-impl Sstatus {
+read_write_csr_field! {
+    Sstatus,
     /// Supervisor Interrupt Enable
-    #[inline]
-    pub fn sie(&self) -> bool {
-        self.bits & (1 << 1) != 0
-    }
+    sie: 1,
+}
 
+read_write_csr_field! {
+    Sstatus,
     /// Supervisor Previous Interrupt Enable
-    #[inline]
-    pub fn spie(&self) -> bool {
-        self.bits & (1 << 5) != 0
-    }
+    spie: 5,
+}
 
+read_write_csr_field! {
+    Sstatus,
+    /// U-mode non-instruction-fetch memory endianness
+    ube: 6,
+}
+
+read_write_csr_field! {
+    Sstatus,
     /// Supervisor Previous Privilege Mode
-    #[inline]
-    pub fn spp(&self) -> SPP {
-        match self.bits & (1 << 8) != 0 {
-            true => SPP::Supervisor,
-            false => SPP::User,
-        }
-    }
+    spp,
+    SPP: [8:8],
+}
 
-    /// The status of the floating-point unit
-    #[inline]
-    pub fn fs(&self) -> FS {
-        let fs = (self.bits >> 13) & 0x3; // bits 13-14
-        match fs {
-            0 => FS::Off,
-            1 => FS::Initial,
-            2 => FS::Clean,
-            3 => FS::Dirty,
-            _ => unreachable!(),
-        }
-    }
+read_write_csr_field! {
+    Sstatus,
+    /// Vector extension state
+    vs,
+    VS: [9:10],
+}
 
-    /// The status of additional user-mode extensions
-    /// and associated state
-    #[inline]
-    pub fn xs(&self) -> FS {
-        let xs = (self.bits >> 15) & 0x3; // bits 15-16
-        match xs {
-            0 => FS::Off,
-            1 => FS::Initial,
-            2 => FS::Clean,
-            3 => FS::Dirty,
-            _ => unreachable!(),
-        }
-    }
+read_write_csr_field! {
+    Sstatus,
+    /// Floating-point extension state
+    ///
+    /// Encodes the status of the floating-point unit, including the CSR `fcsr`
+    /// and floating-point data registers `f0–f31`.
+    fs,
+    FS: [13:14],
+}
+
+read_write_csr_field! {
+    Sstatus,
+    /// Additional extension state
+    ///
+    /// Encodes the status of additional user-mode extensions and associated
+    /// state.
+    xs,
+    XS: [15:16],
+}
 
+read_write_csr_field! {
+    Sstatus,
     /// Permit Supervisor User Memory access
-    #[inline]
-    pub fn sum(&self) -> bool {
-        self.bits & (1 << 18) != 0
-    }
+    sum: 18,
+}
 
+read_write_csr_field! {
+    Sstatus,
     /// Make eXecutable Readable
-    #[inline]
-    pub fn mxr(&self) -> bool {
-        self.bits & (1 << 19) != 0
-    }
+    mxr: 19,
+}
+
+#[cfg(target_arch = "riscv32")]
+read_write_csr_field! {
+    Sstatus,
+    /// Whether either the FS field or XS field signals the presence of some dirty state
+    sd: 31,
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+read_write_csr_field! {
+    Sstatus,
+    /// Whether either the FS field or XS field signals the presence of some dirty state
+    sd: 63,
+}
 
+csr_diff!(
+    Sstatus,
+    [
+        sie: 1,
+        spie: 5,
+        ube: 6,
+        spp: 8,
+        vs: [9:10],
+        fs: [13:14],
+        xs: [15:16],
+        sum: 18,
+        mxr: 19,
+        #[cfg(target_arch = "riscv32")]
+        sd: 31,
+        #[cfg(not(target_arch = "riscv32"))]
+        uxl: [32:33],
+        #[cfg(not(target_arch = "riscv32"))]
+        sd: 63,
+    ]
+);
+
+impl Sstatus {
     /// Effective xlen in U-mode (i.e., `UXLEN`).
     ///
     /// In RISCV-32, UXL does not exist, and `UXLEN` is always [`XLEN::XLEN32`].
     #[inline]
     pub fn uxl(&self) -> XLEN {
         match () {
-            #[cfg(riscv32)]
+            #[cfg(not(target_arch = "riscv32"))]
+            () => XLEN::try_from(bf_extract(self.bits, 32, 2)).unwrap_or_default(),
+            #[cfg(target_arch = "riscv32")]
             () => XLEN::XLEN32,
-            #[cfg(not(riscv32))]
-            () => XLEN::try_from((self.bits >> 32) & 0x3).unwrap_or_default(),
         }
     }
 
-    /// Whether either the FS field or XS field
-    /// signals the presence of some dirty state
+    /// Update Effective xlen in U-mode (i.e., `UXLEN`).
+    ///
+    /// Note this updates a previously read [`Sstatus`] value, but does not
+    /// affect the sstatus CSR itself.
+    ///
+    /// # Note
+    ///
+    /// In RISCV-32, `UXL` does not exist, and `UXLEN` is always [`XLEN::XLEN32`].
     #[inline]
-    pub fn sd(&self) -> bool {
-        self.bits & (1 << (usize::BITS as usize - 1)) != 0
+    #[cfg(not(target_arch = "riscv32"))]
+    pub fn set_uxl(&mut self, uxl: XLEN) {
+        self.bits = bf_insert(self.bits, 32, 2, uxl as usize);
     }
 }
 
-read_csr_as!(Sstatus, 0x100);
-write_csr!(0x100);
 set!(0x100);
 clear!(0x100);
 
@@ -122,6 +169,15 @@ set_clear_csr!(
     /// Make eXecutable Readable
     , set_mxr, clear_mxr, 1 << 19);
 
+/// Set U-mode non-instruction-fetch memory endianness
+#[inline]
+pub unsafe fn set_ube(endianness: Endianness) {
+    match endianness {
+        Endianness::BigEndian => _set(1 << 6),
+        Endianness::LittleEndian => _clear(1 << 6),
+    }
+}
+
 /// Supervisor Previous Privilege Mode
 #[inline]
 pub unsafe fn set_spp(spp: SPP) {
@@ -131,7 +187,7 @@ pub unsafe fn set_spp(spp: SPP) {
     }
 }
 
-/// The status of the floating-point unit
+/// Floating-point extension state
 #[inline]
 pub unsafe fn set_fs(fs: FS) {
     let mut value = _read();
@@ -139,3 +195,115 @@ pub unsafe fn set_fs(fs: FS) {
     value |= (fs as usize) << 13;
     _write(value);
 }
+
+/// Vector extension state
+#[inline]
+pub unsafe fn set_vs(vs: VS) {
+    let mut value = _read();
+    value &= !(0x3 << 9); // clear previous value
+    value |= (vs as usize) << 9;
+    _write(value);
+}
+
+/// Execute closure `f` with `sstatus.SUM` set in the current hart.
+///
+/// While `SUM` is set, S-mode loads and stores may access U-mode pages (e.g., to copy syscall
+/// arguments from user memory). After the closure `f` is executed, `SUM` is restored to its
+/// previous value. Thus, nested calls leave `SUM` set until the outermost call returns.
+#[inline]
+pub fn with_sum<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let sum = read().sum();
+
+    // SAFETY: `SUM` only relaxes the memory protection of U-mode pages during `f`
+    if !sum {
+        unsafe { set_sum() };
+    }
+
+    let r = f();
+
+    // restore the previous value
+    if !sum {
+        unsafe { clear_sum() };
+    }
+
+    r
+}
+
+/// Execute closure `f` with `sstatus.MXR` set in the current hart.
+///
+/// While `MXR` is set, loads from pages marked as executable-only succeed (e.g., to read
+/// the faulting instruction when emulating it). After the closure `f` is executed, `MXR`
+/// is restored to its previous value. Thus, nested calls leave `MXR` set until the outermost
+/// call returns.
+#[inline]
+pub fn with_mxr<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let mxr = read().mxr();
+
+    // SAFETY: `MXR` only relaxes the memory protection of executable pages during `f`
+    if !mxr {
+        unsafe { set_mxr() };
+    }
+
+    let r = f();
+
+    // restore the previous value
+    if !mxr {
+        unsafe { clear_mxr() };
+    }
+
+    r
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sstatus() {
+        let mut sstatus = Sstatus { bits: 0 };
+
+        test_csr_field!(sstatus, spp: SPP::User);
+        test_csr_field!(sstatus, spp: SPP::Supervisor);
+
+        test_csr_field!(sstatus, fs: FS::Off);
+        test_csr_field!(sstatus, fs: FS::Initial);
+        test_csr_field!(sstatus, fs: FS::Clean);
+        test_csr_field!(sstatus, fs: FS::Dirty);
+
+        test_csr_field!(sstatus, vs: VS::Off);
+        test_csr_field!(sstatus, vs: VS::Initial);
+        test_csr_field!(sstatus, vs: VS::Clean);
+        test_csr_field!(sstatus, vs: VS::Dirty);
+
+        test_csr_field!(sstatus, xs: XS::AllOff);
+        test_csr_field!(sstatus, xs: XS::NoneDirtyOrClean);
+        test_csr_field!(sstatus, xs: XS::NoneDirtySomeClean);
+        test_csr_field!(sstatus, xs: XS::SomeDirty);
+
+        test_csr_field!(sstatus, sie);
+        test_csr_field!(sstatus, spie);
+        test_csr_field!(sstatus, ube);
+        test_csr_field!(sstatus, sum);
+        test_csr_field!(sstatus, mxr);
+        test_csr_field!(sstatus, sd);
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "riscv32"))]
+    fn test_sstatus_uxl() {
+        let mut sstatus = Sstatus { bits: 0 };
+        assert_eq!(sstatus.uxl(), XLEN::XLEN32);
+
+        for uxl in [XLEN::XLEN32, XLEN::XLEN64, XLEN::XLEN128] {
+            sstatus.set_uxl(uxl);
+            assert_eq!(sstatus.uxl(), uxl);
+            assert_eq!(sstatus.bits() >> 32, uxl as usize);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! stimecmp register (Sstc)
+//!
+//! A supervisor timer interrupt is pending while `time` is greater than or equal to `stimecmp`.
+//! Access from S-mode is controlled by the `menvcfg.STCE` bit.
+
+read_csr_as_usize!(0x14D);
+write_csr_as_usize!(0x14D);
+read_composite_csr!(super::stimecmph::read(), read());
+
+/// Writes the CSR as a 64-bit value
+///
+/// - `RV32`: the lower half is first set to `u32::MAX` to avoid spurious timer interrupts
+///   while the register is updated.
+/// - `RV64`: writes the full 64-bits of the CSR.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn write64(bits: u64) {
+    match () {
+        #[cfg(target_arch = "riscv32")]
+        () => {
+            write(usize::MAX);
+            super::stimecmph::write((bits >> 32) as usize);
+            write(bits as usize);
+        }
+
+        #[cfg(not(target_arch = "riscv32"))]
+        () => write(bits as usize),
+    }
+}
@@ -0,0 +1,4 @@
+//! stimecmph register (Sstc)
+
+read_csr_as_usize_rv32!(0x15D);
+write_csr_as_usize_rv32!(0x15D);
@@ -0,0 +1,72 @@
+//! stopei register (Ssaia)
+//!
+//! Reports the external interrupt with the highest priority that is pending and enabled in the
+//! supervisor-level interrupt file of the hart's IMSIC. Writing the CSR claims this interrupt
+//! (i.e., clears its pending bit), so [`claim`] reads and claims it with a single instruction.
+
+use crate::result::Result;
+
+read_only_csr! {
+    /// Supervisor Top External Interrupt register
+    Stopei: 0x15c,
+    mask: 0x07ff_07ff,
+}
+
+read_only_csr_field! {
+    Stopei,
+    /// Identity of the external interrupt, or 0 if no interrupt is pending and enabled.
+    iid: [16:26],
+}
+
+read_only_csr_field! {
+    Stopei,
+    /// Priority of the external interrupt. In IMSICs, it is equal to the identity.
+    iprio: [0:10],
+}
+
+/// Claims the external interrupt with the highest priority, and returns its previous `stopei` value.
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn claim() -> Stopei {
+    try_claim().unwrap()
+}
+
+/// Attempts to claim the external interrupt with the highest priority, and returns its previous `stopei` value.
+#[inline]
+pub fn try_claim() -> Result<Stopei> {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let r: usize;
+            // SAFETY: writing the CSR only clears the pending bit of the interrupt being claimed
+            unsafe { core::arch::asm!("csrrw {0}, 0x15c, x0", out(reg) r) };
+            Ok(Stopei::from_bits(r))
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => Err(crate::result::Error::Unimplemented),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stopei() {
+        let m = Stopei::from_bits(0);
+        assert_eq!(m.iid(), 0);
+        assert_eq!(m.iprio(), 0);
+
+        let m = Stopei::from_bits((42 << 16) | 42);
+        assert_eq!(m.iid(), 42);
+        assert_eq!(m.iprio(), 42);
+
+        let m = Stopei::from_bits(usize::MAX);
+        assert_eq!(m.iid(), 0x7ff);
+        assert_eq!(m.iprio(), 0x7ff);
+        assert_eq!(m.bits(), Stopei::BITMASK);
+
+        assert_eq!(try_claim(), Err(crate::result::Error::Unimplemented));
+    }
+}
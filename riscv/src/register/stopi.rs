@@ -0,0 +1,43 @@
+//! stopi register (Ssaia)
+
+read_only_csr! {
+    /// Supervisor Top Interrupt register
+    ///
+    /// It reports the major interrupt with the highest priority that is pending and enabled for S-mode.
+    Stopi: 0xdb0,
+    mask: 0x0fff_00ff,
+}
+
+read_only_csr_field! {
+    Stopi,
+    /// Major identity number of the interrupt (i.e., its `scause` exception code),
+    /// or 0 if no interrupt is pending and enabled.
+    iid: [16:27],
+}
+
+read_only_csr_field! {
+    Stopi,
+    /// Priority of the interrupt. Lower values have higher priority.
+    iprio: [0:7],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stopi() {
+        let m = Stopi::from_bits(0);
+        assert_eq!(m.iid(), 0);
+        assert_eq!(m.iprio(), 0);
+
+        let m = Stopi::from_bits((11 << 16) | 0x80);
+        assert_eq!(m.iid(), 11);
+        assert_eq!(m.iprio(), 0x80);
+
+        let m = Stopi::from_bits(usize::MAX);
+        assert_eq!(m.iid(), 0xfff);
+        assert_eq!(m.iprio(), 0xff);
+        assert_eq!(m.bits(), Stopi::BITMASK);
+    }
+}
@@ -0,0 +1,7 @@
+//! tdata1 register (Sdtrig extension)
+//!
+//! Holds the type and the configuration of the trigger selected by `tselect`.
+//! The layout of the register depends on the type of the trigger, stored in the most significant 4 bits.
+
+read_csr_as_usize!(0x7A1);
+write_csr_as_usize!(0x7A1);
@@ -0,0 +1,6 @@
+//! tdata2 register (Sdtrig extension)
+//!
+//! Holds trigger-specific data of the trigger selected by `tselect` (e.g., the address to match).
+
+read_csr_as_usize!(0x7A2);
+write_csr_as_usize!(0x7A2);
@@ -1,3 +1,4 @@
+mod read_clear_csr;
 mod read_only_csr;
 mod read_write_csr;
 mod write_only_csr;
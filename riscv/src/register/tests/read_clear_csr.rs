@@ -0,0 +1,24 @@
+// functions that panic on non-`riscv` targets are not tested
+#![allow(dead_code)]
+
+use crate::result::Error;
+
+swap_csr!(0x000);
+
+read_clear_csr_field! {
+    /// test single-bit field
+    single: 0,
+}
+
+read_clear_csr_field! {
+    /// test multi-bit field
+    multi_field: [1:5],
+}
+
+#[test]
+fn test_mtest_read_clear() {
+    unsafe {
+        assert_eq!(try_take_single(), Err(Error::Unimplemented));
+        assert_eq!(try_take_multi_field(), Err(Error::Unimplemented));
+    }
+}
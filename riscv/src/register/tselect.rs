@@ -0,0 +1,7 @@
+//! tselect register (Sdtrig extension)
+//!
+//! Selects which trigger is accessed through the `tdata1` and `tdata2` registers.
+//! Writing an index of an unimplemented trigger is ignored (i.e., the register keeps another value).
+
+read_csr_as_usize!(0x7A0);
+write_csr_as_usize!(0x7A0);
@@ -0,0 +1,153 @@
+//! `xcause` facade over the `mcause` and `scause` registers
+//!
+//! Libraries that run in either M-mode or S-mode (e.g., shared trap dispatch code or RTOS kernels)
+//! can read the trap cause with [`read`] instead of duplicating their code for each register.
+//! The register is selected at runtime with the privilege mode returned by [`mode`]. The mode defaults
+//! to [`Mode::Supervisor`] if the `s-mode` feature is enabled, and to [`Mode::Machine`] otherwise.
+//! Programs that detect their privilege mode at runtime can set it once with [`set_mode`].
+
+pub use crate::interrupt::Trap;
+use crate::{
+    register::{mcause, scause},
+    result::Result,
+};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Privilege mode used to select the cause register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Mode {
+    /// M-mode: reads `mcause`.
+    Machine = 0,
+    /// S-mode: reads `scause`.
+    Supervisor = 1,
+}
+
+impl Mode {
+    /// Default mode: [`Mode::Supervisor`] with the `s-mode` feature, [`Mode::Machine`] otherwise.
+    pub const DEFAULT: Self = match cfg!(feature = "s-mode") {
+        true => Self::Supervisor,
+        false => Self::Machine,
+    };
+}
+
+static MODE: AtomicU8 = AtomicU8::new(Mode::DEFAULT as u8);
+
+/// Returns the privilege mode used to select the cause register.
+#[inline]
+pub fn mode() -> Mode {
+    match MODE.load(Ordering::Relaxed) {
+        0 => Mode::Machine,
+        _ => Mode::Supervisor,
+    }
+}
+
+/// Sets the privilege mode used to select the cause register.
+///
+/// It is usually called once at startup, before enabling interrupts.
+#[inline]
+pub fn set_mode(mode: Mode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Trap cause read from `mcause` or `scause`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Xcause {
+    bits: usize,
+}
+
+impl Xcause {
+    /// Creates a new trap cause from raw bits.
+    #[inline]
+    pub const fn from_bits(bits: usize) -> Self {
+        Self { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub const fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Returns the code field
+    #[inline]
+    pub fn code(&self) -> usize {
+        self.bits & !Trap::<usize, usize>::INTERRUPT_BIT
+    }
+
+    /// Returns the trap cause represented by this register.
+    ///
+    /// # Note
+    ///
+    /// This method returns a **raw trap cause**, which means that values are represented as `usize`.
+    /// To get a target-specific trap cause, use [`Trap::try_into`] with your target-specific trap cause types.
+    #[inline]
+    pub fn cause(&self) -> Trap<usize, usize> {
+        Trap::from_cause_bits(self.bits)
+    }
+
+    /// Is trap cause an interrupt.
+    #[inline]
+    pub fn is_interrupt(&self) -> bool {
+        self.bits & Trap::<usize, usize>::INTERRUPT_BIT != 0
+    }
+
+    /// Is trap cause an exception.
+    #[inline]
+    pub fn is_exception(&self) -> bool {
+        !self.is_interrupt()
+    }
+}
+
+/// Reads the cause register of the current privilege mode (see [`mode`]).
+///
+/// **WARNING**: panics on non-`riscv` targets.
+#[inline]
+pub fn read() -> Xcause {
+    try_read().unwrap()
+}
+
+/// Attempts to read the cause register of the current privilege mode (see [`mode`]).
+///
+/// **NOTE**: returns an error on non-`riscv` targets.
+#[inline]
+pub fn try_read() -> Result<Xcause> {
+    let bits = match mode() {
+        Mode::Machine => mcause::try_read()?.bits(),
+        Mode::Supervisor => scause::try_read()?.bits(),
+    };
+    Ok(Xcause::from_bits(bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xcause() {
+        let interrupt = Trap::<usize, usize>::INTERRUPT_BIT;
+        for code in [0, 5, 9, 0x1234] {
+            let xcause = Xcause::from_bits(interrupt | code);
+            assert!(xcause.is_interrupt());
+            assert_eq!(xcause.code(), code);
+            assert_eq!(xcause.cause(), Trap::Interrupt(code));
+
+            let xcause = Xcause::from_bits(code);
+            assert!(xcause.is_exception());
+            assert_eq!(xcause.code(), code);
+            assert_eq!(xcause.cause(), Trap::Exception(code));
+        }
+    }
+
+    #[test]
+    fn test_mode() {
+        assert_eq!(mode(), Mode::DEFAULT);
+        assert!(try_read().is_err());
+
+        set_mode(Mode::Supervisor);
+        assert_eq!(mode(), Mode::Supervisor);
+        set_mode(Mode::Machine);
+        assert_eq!(mode(), Mode::Machine);
+        set_mode(Mode::DEFAULT);
+    }
+}
@@ -0,0 +1,218 @@
+//! Supervisor Binary Interface (SBI) calls
+//!
+//! S-mode software requests services to the supervisor execution environment (SEE) via `ecall`
+//! instructions, following the [RISC-V SBI specification](https://github.com/riscv-non-isa/riscv-sbi-doc).
+//! This module provides a minimal subset of the SBI calls:
+//!
+//! - Base extension: [`get_spec_version`] and [`probe_extension`].
+//! - Timer extension: [`set_timer`].
+//! - System reset extension: [`system_reset`].
+//! - Debug console extension: [`debug_console_write`] and [`debug_console_write_byte`].
+//!
+//! **WARNING**: all the SBI calls panic on non-`riscv` targets and on RVE targets,
+//! as the SBI calling convention uses registers `a6` and `a7`.
+
+/// Base extension ID.
+pub const EID_BASE: usize = 0x10;
+/// Timer extension ID.
+pub const EID_TIME: usize = 0x5449_4D45;
+/// System reset extension ID.
+pub const EID_SRST: usize = 0x5352_5354;
+/// Debug console extension ID.
+pub const EID_DBCN: usize = 0x4442_434E;
+
+pub use crate::result::SbiError;
+use crate::result::{Error, Result};
+
+/// Raw return value of SBI calls.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SbiRet {
+    /// Error code (`a0`). It is 0 on success.
+    pub error: isize,
+    /// Return value (`a1`).
+    pub value: usize,
+}
+
+impl SbiRet {
+    /// Converts the raw return value into a [`Result`].
+    ///
+    /// Non-zero error codes are converted into [`Error::Sbi`].
+    #[inline]
+    pub const fn into_result(self) -> Result<usize> {
+        match self.error {
+            0 => Ok(self.value),
+            code => Err(Error::Sbi(SbiError::from_code(code))),
+        }
+    }
+}
+
+/// Performs a raw SBI call with up to three arguments.
+///
+/// # Safety
+///
+/// The SBI call may have side effects that break the assumptions of the caller
+/// (e.g., memory accessed by the SEE). Prefer the dedicated functions of this module.
+#[inline]
+#[cfg_attr(
+    any(
+        not(any(target_arch = "riscv32", target_arch = "riscv64")),
+        target_feature = "e"
+    ),
+    allow(unused_variables)
+)]
+pub unsafe fn call(eid: usize, fid: usize, arg0: usize, arg1: usize, arg2: usize) -> SbiRet {
+    match () {
+        #[cfg(all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            not(target_feature = "e")
+        ))]
+        () => {
+            let error: isize;
+            let value: usize;
+            core::arch::asm!(
+                "ecall",
+                inlateout("a0") arg0 => error,
+                inlateout("a1") arg1 => value,
+                in("a2") arg2,
+                in("a6") fid,
+                in("a7") eid,
+                options(nostack),
+            );
+            SbiRet { error, value }
+        }
+        #[cfg(any(
+            not(any(target_arch = "riscv32", target_arch = "riscv64")),
+            target_feature = "e"
+        ))]
+        () => unimplemented!(),
+    }
+}
+
+/// Returns the version of the SBI specification implemented by the SEE.
+///
+/// The minor number is in bits 23:0, and the major number in bits 30:24.
+#[inline]
+pub fn get_spec_version() -> usize {
+    // SAFETY: this call has no side effects
+    unsafe { call(EID_BASE, 0, 0, 0, 0) }.value
+}
+
+/// Returns `true` if the SBI extension with ID `eid` is available.
+#[inline]
+pub fn probe_extension(eid: usize) -> bool {
+    // SAFETY: this call has no side effects
+    unsafe { call(EID_BASE, 3, eid, 0, 0) }.value != 0
+}
+
+/// Programs the clock for the next timer event at `stime_value` (absolute time).
+///
+/// It also clears the pending supervisor timer interrupt bit.
+/// To clear the timer interrupt without scheduling the next timer event,
+/// request a timer interrupt infinitely far into the future (i.e., `u64::MAX`).
+#[inline]
+pub fn set_timer(stime_value: u64) -> Result<()> {
+    #[cfg(target_pointer_width = "32")]
+    let (arg0, arg1) = (stime_value as usize, (stime_value >> 32) as usize);
+    #[cfg(not(target_pointer_width = "32"))]
+    let (arg0, arg1) = (stime_value as usize, 0);
+    // SAFETY: this call only affects the supervisor timer
+    unsafe { call(EID_TIME, 0, arg0, arg1, 0) }
+        .into_result()
+        .map(|_| ())
+}
+
+/// System reset types.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(usize)]
+pub enum ResetType {
+    /// Shuts down the system.
+    Shutdown = 0,
+    /// Powers off all the hardware and performs a cold boot.
+    ColdReboot = 1,
+    /// Resets the processors and some hardware.
+    WarmReboot = 2,
+}
+
+/// System reset reasons.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(usize)]
+pub enum ResetReason {
+    /// No reason.
+    NoReason = 0,
+    /// Unexpected system failure.
+    SystemFailure = 1,
+}
+
+/// Resets the system.
+///
+/// It only returns if the reset request failed, with the [`Error::Sbi`] error of the SEE.
+#[inline]
+pub fn system_reset(reset_type: ResetType, reason: ResetReason) -> Error {
+    // SAFETY: the system is reset, so no assumptions of the caller are broken
+    let ret = unsafe { call(EID_SRST, 0, reset_type as usize, reason as usize, 0) };
+    Error::Sbi(SbiError::from_code(ret.error))
+}
+
+/// Writes `bytes` to the debug console.
+///
+/// It returns the number of bytes written, which may be less than `bytes.len()`.
+///
+/// # Note
+///
+/// The SEE accesses `bytes` using its physical address. Thus, `bytes` must be
+/// identity-mapped if address translation is enabled.
+#[inline]
+pub fn debug_console_write(bytes: &[u8]) -> Result<usize> {
+    let addr = bytes.as_ptr() as usize;
+    // SAFETY: the SEE only reads `bytes`
+    unsafe { call(EID_DBCN, 0, bytes.len(), addr, 0) }.into_result()
+}
+
+/// Writes a single byte to the debug console.
+#[inline]
+pub fn debug_console_write_byte(byte: u8) -> Result<()> {
+    // SAFETY: this call only affects the debug console
+    unsafe { call(EID_DBCN, 2, byte as usize, 0, 0) }
+        .into_result()
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sbi_ret() {
+        assert_eq!(
+            SbiRet {
+                error: 0,
+                value: 42
+            }
+            .into_result(),
+            Ok(42)
+        );
+        assert_eq!(
+            SbiRet {
+                error: -2,
+                value: 0
+            }
+            .into_result(),
+            Err(Error::Sbi(SbiError::NotSupported))
+        );
+        assert_eq!(
+            SbiRet {
+                error: -42,
+                value: 0
+            }
+            .into_result(),
+            Err(Error::Sbi(SbiError::Unknown(-42)))
+        );
+    }
+
+    #[test]
+    fn test_sbi_error() {
+        (-13..0).for_each(|code| assert_ne!(SbiError::from_code(code), SbiError::Unknown(code)));
+        assert_eq!(SbiError::from_code(1), SbiError::Unknown(1));
+        assert_eq!(SbiError::from_code(-14), SbiError::Unknown(-14));
+    }
+}
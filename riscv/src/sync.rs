@@ -0,0 +1,310 @@
+//! Synchronization primitives for multi-hart bare-metal targets
+//!
+//! [`Backoff`] implements an exponential backoff for spin loops. It waits with `rdcycle`
+//! and `pause` hints, so contending harts do not saturate the interconnect while polling a lock.
+//!
+//! [`TicketLock`] is a fair spinlock: harts acquire the lock in the same order they requested it,
+//! so no hart starves under contention. It requires atomic read-modify-write instructions
+//! (i.e., the `A` extension), and it is only available in targets with `target_has_atomic = "ptr"`.
+//!
+//! # Interrupts
+//!
+//! If an interrupt handler of a hart tries to acquire a lock already held by the same hart, the
+//! hart deadlocks. Locks shared with interrupt handlers must be acquired with [`TicketLock::lock_irq`],
+//! which disables interrupts in the current hart until the guard is dropped. Interrupts of the machine
+//! mode are disabled, or supervisor mode interrupts if the `s-mode` feature is enabled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use riscv::sync::TicketLock;
+//!
+//! static COUNTER: TicketLock<usize> = TicketLock::new(0);
+//!
+//! let mut counter = COUNTER.lock_irq();
+//! *counter += 1;
+//! ```
+//!
+//! **WARNING**: [`Backoff::spin`] reads the `cycle` CSR. In S-mode and U-mode, the counter must be
+//! enabled in `mcounteren` (and in `scounteren` for U-mode), or reading it traps.
+
+#[cfg(target_has_atomic = "ptr")]
+use core::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Exponential backoff for spin loops.
+///
+/// Each call to [`Backoff::spin`] waits twice as many cycles as the previous one,
+/// until the wait reaches `2^MAX_STEP` cycles.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    /// Maximum step of the backoff. The longest wait is `2^MAX_STEP` cycles.
+    pub const MAX_STEP: u32 = 10;
+
+    /// Creates a new backoff that waits 1 cycle in the first call to [`Backoff::spin`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { step: 0 }
+    }
+
+    /// Resets the backoff to its initial state.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+
+    /// Returns the number of cycles that the next call to [`Backoff::spin`] will wait.
+    #[inline]
+    pub const fn cycles(&self) -> usize {
+        1 << self.step
+    }
+
+    /// Returns `true` if the backoff reached its longest wait.
+    ///
+    /// Spin loops can use it to fall back to a blocking mechanism (e.g., `wfi`).
+    #[inline]
+    pub const fn is_completed(&self) -> bool {
+        self.step == Self::MAX_STEP
+    }
+
+    /// Waits for [`Backoff::cycles`] cycles, and doubles the wait of the next call.
+    #[inline]
+    pub fn spin(&mut self) {
+        wait_cycles(self.cycles());
+        if self.step < Self::MAX_STEP {
+            self.step += 1;
+        }
+    }
+}
+
+/// Spins for *at least* `cycles` cycles, emitting a `pause` hint in every iteration.
+#[inline]
+fn wait_cycles(cycles: usize) {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let start = crate::register::cycle::read();
+            while crate::register::cycle::read().wrapping_sub(start) < cycles {
+                core::hint::spin_loop();
+            }
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => {
+            for _ in 0..cycles {
+                core::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/// Fair spinlock that grants the lock to harts in the same order they requested it.
+///
+/// Each hart takes a ticket, and waits with a [`Backoff`] until the ticket is being served.
+/// Dropping the guard returned by [`TicketLock::lock`] serves the next ticket.
+#[cfg(target_has_atomic = "ptr")]
+pub struct TicketLock<T: ?Sized> {
+    next: AtomicUsize,
+    serving: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the lock ensures that only one hart accesses the data at a time
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl<T: ?Sized + Send> Send for TicketLock<T> {}
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl<T: ?Sized + Send> Sync for TicketLock<T> {}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TicketLock<T> {
+    /// Creates a new unlocked ticket lock that protects `data`.
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+            serving: AtomicUsize::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the lock and returns the protected data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: ?Sized> TicketLock<T> {
+    /// Acquires the lock, spinning with a [`Backoff`] until it is available.
+    ///
+    /// Interrupts are not disabled. Thus, the lock must not be shared with interrupt handlers.
+    /// Use [`TicketLock::lock_irq`] instead in that case.
+    #[inline]
+    pub fn lock(&self) -> TicketLockGuard<'_, T> {
+        self.acquire();
+        TicketLockGuard {
+            lock: self,
+            restore_interrupts: false,
+        }
+    }
+
+    /// Disables interrupts in the current hart, and acquires the lock.
+    ///
+    /// The previous interrupt state is restored when the guard is dropped, after releasing the lock.
+    #[inline]
+    pub fn lock_irq(&self) -> TicketLockGuard<'_, T> {
+        #[cfg(not(feature = "s-mode"))]
+        let enabled = crate::register::mstatus::read().mie();
+        #[cfg(feature = "s-mode")]
+        let enabled = crate::register::sstatus::read().sie();
+
+        crate::interrupt::disable();
+        self.acquire();
+        TicketLockGuard {
+            lock: self,
+            restore_interrupts: enabled,
+        }
+    }
+
+    /// Acquires the lock only if it is available, without waiting.
+    ///
+    /// Interrupts are not disabled, as in [`TicketLock::lock`].
+    #[inline]
+    pub fn try_lock(&self) -> Option<TicketLockGuard<'_, T>> {
+        let serving = self.serving.load(Ordering::Relaxed);
+        self.next
+            .compare_exchange(
+                serving,
+                serving.wrapping_add(1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .ok()
+            .map(|_| TicketLockGuard {
+                lock: self,
+                restore_interrupts: false,
+            })
+    }
+
+    /// Returns `true` if the lock is held or there are harts waiting for it.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.next.load(Ordering::Relaxed) != self.serving.load(Ordering::Relaxed)
+    }
+
+    /// Returns a mutable reference to the protected data.
+    ///
+    /// As the lock is borrowed mutably, no locking is required.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Takes a ticket and waits until it is being served.
+    #[inline]
+    fn acquire(&self) {
+        let ticket = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut backoff = Backoff::new();
+        while self.serving.load(Ordering::Acquire) != ticket {
+            backoff.spin();
+        }
+    }
+}
+
+/// Guard of a [`TicketLock`]. The lock is released when the guard is dropped.
+#[cfg(target_has_atomic = "ptr")]
+pub struct TicketLockGuard<'a, T: ?Sized> {
+    lock: &'a TicketLock<T>,
+    restore_interrupts: bool,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: ?Sized> Deref for TicketLockGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the lock
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: ?Sized> DerefMut for TicketLockGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the lock
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: ?Sized> Drop for TicketLockGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // only the holder of the lock modifies `serving`
+        let serving = self.lock.serving.load(Ordering::Relaxed);
+        self.lock
+            .serving
+            .store(serving.wrapping_add(1), Ordering::Release);
+        if self.restore_interrupts {
+            // SAFETY: interrupts were enabled before acquiring the lock
+            unsafe { crate::interrupt::enable() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new();
+        assert_eq!(backoff.cycles(), 1);
+        assert!(!backoff.is_completed());
+
+        for step in 0..Backoff::MAX_STEP {
+            assert_eq!(backoff.cycles(), 1 << step);
+            backoff.spin();
+        }
+        assert!(backoff.is_completed());
+        assert_eq!(backoff.cycles(), 1 << Backoff::MAX_STEP);
+
+        backoff.spin(); // the wait does not grow further
+        assert_eq!(backoff.cycles(), 1 << Backoff::MAX_STEP);
+
+        backoff.reset();
+        assert_eq!(backoff, Backoff::new());
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn test_ticket_lock() {
+        let lock = TicketLock::new(0);
+        assert!(!lock.is_locked());
+
+        {
+            let mut guard = lock.lock();
+            assert!(lock.is_locked());
+            assert!(lock.try_lock().is_none());
+            *guard += 1;
+        }
+        assert!(!lock.is_locked());
+
+        *lock.try_lock().unwrap() += 1;
+        *lock.lock() += 1;
+        assert!(!lock.is_locked());
+
+        let mut lock = lock;
+        *lock.get_mut() += 1;
+        assert_eq!(lock.into_inner(), 4);
+    }
+}
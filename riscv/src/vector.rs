@@ -0,0 +1,185 @@
+//! Save and restore of the vector context (V extension)
+//!
+//! The size of the vector register file depends on `VLEN`, which is only known at runtime.
+//! [`context_size`] reads `vlenb` to compute the size of the buffer required to hold the vector
+//! context of a hart, and [`save`] and [`restore`] move the context with whole register loads and
+//! stores, so trap handlers and context switchers work for any `VLEN` configuration.
+//!
+//! The context is stored with the following layout:
+//!
+//! | Offset (in `usize` words) | Content                                      |
+//! |---------------------------|----------------------------------------------|
+//! | 0                         | `vl`                                         |
+//! | 1                         | `vtype`                                      |
+//! | 2                         | `vstart`                                     |
+//! | 3                         | `vcsr`                                       |
+//! | 4                         | `v0`-`v31` (`vlenb` bytes for each register) |
+//!
+//! This module is only available in targets with the V extension (i.e., `target_feature = "v"`).
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use riscv::vector;
+//!
+//! // buffer of a task, allocated when the task is created
+//! let mut context = [0usize; 1024];
+//! assert!(vector::context_size() <= core::mem::size_of_val(&context));
+//!
+//! unsafe {
+//!     vector::save(context.as_mut_ptr() as *mut u8);
+//!     // ... run another task ...
+//!     vector::restore(context.as_ptr() as *const u8);
+//! }
+//! ```
+//!
+//! **WARNING**: the vector unit must be enabled (i.e., `mstatus.VS` or `sstatus.VS` must not be `Off`),
+//! or the vector instructions trap. These functions panic on non-`riscv` targets.
+
+/// Number of vector registers.
+pub const NUM_REGISTERS: usize = 32;
+
+/// Number of `usize` words used to store `vl`, `vtype`, `vstart`, and `vcsr`.
+pub const CSR_WORDS: usize = 4;
+
+/// Returns the size (in bytes) of the vector context for a given `vlenb`.
+#[inline]
+const fn size(vlenb: usize) -> usize {
+    CSR_WORDS * core::mem::size_of::<usize>() + NUM_REGISTERS * vlenb
+}
+
+/// Reads the `vlenb` CSR (i.e., the length of a vector register in bytes).
+#[inline]
+pub fn vlenb() -> usize {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let r: usize;
+            unsafe { core::arch::asm!("csrr {0}, vlenb", out(reg) r, options(nomem, nostack)) };
+            r
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => unimplemented!(),
+    }
+}
+
+/// Returns the size (in bytes) of the buffer required by [`save`] and [`restore`].
+#[inline]
+pub fn context_size() -> usize {
+    size(vlenb())
+}
+
+/// Saves the vector context of the current hart to `ptr`.
+///
+/// `vstart` is saved before the vector registers are stored, and it is zero when this function returns.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of [`context_size`] bytes, and aligned to `usize`.
+#[inline]
+#[cfg_attr(
+    not(any(target_arch = "riscv32", target_arch = "riscv64")),
+    allow(unused_variables)
+)]
+pub unsafe fn save(ptr: *mut u8) {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let (vl, vtype, vstart, vcsr): (usize, usize, usize, usize);
+            core::arch::asm!(
+                "csrr {0}, vl",
+                "csrr {1}, vtype",
+                "csrr {2}, vstart",
+                "csrr {3}, vcsr",
+                out(reg) vl,
+                out(reg) vtype,
+                out(reg) vstart,
+                out(reg) vcsr,
+                options(nomem, nostack),
+            );
+            let csrs = ptr as *mut usize;
+            csrs.write(vl);
+            csrs.add(1).write(vtype);
+            csrs.add(2).write(vstart);
+            csrs.add(3).write(vcsr);
+
+            // whole register stores do not depend on vtype, but they start at element vstart
+            core::arch::asm!(
+                "csrw vstart, zero",
+                "vs8r.v v0, ({0})",
+                "add {0}, {0}, {1}",
+                "vs8r.v v8, ({0})",
+                "add {0}, {0}, {1}",
+                "vs8r.v v16, ({0})",
+                "add {0}, {0}, {1}",
+                "vs8r.v v24, ({0})",
+                inout(reg) csrs.add(CSR_WORDS) => _,
+                in(reg) 8 * vlenb(),
+                options(nostack),
+            );
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => unimplemented!(),
+    }
+}
+
+/// Restores the vector context of the current hart from `ptr`.
+///
+/// The vector registers are loaded first. Then, `vl` and `vtype` are restored with `vsetvl`,
+/// and `vcsr` and `vstart` are written back.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of [`context_size`] bytes, aligned to `usize`, and hold a
+/// context previously saved with [`save`] in a hart with the same `VLEN`.
+#[inline]
+#[cfg_attr(
+    not(any(target_arch = "riscv32", target_arch = "riscv64")),
+    allow(unused_variables)
+)]
+pub unsafe fn restore(ptr: *const u8) {
+    match () {
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        () => {
+            let csrs = ptr as *const usize;
+            core::arch::asm!(
+                "csrw vstart, zero",
+                "vl8re8.v v0, ({0})",
+                "add {0}, {0}, {1}",
+                "vl8re8.v v8, ({0})",
+                "add {0}, {0}, {1}",
+                "vl8re8.v v16, ({0})",
+                "add {0}, {0}, {1}",
+                "vl8re8.v v24, ({0})",
+                // vector instructions reset vstart, so it is restored last
+                "vsetvl zero, {2}, {3}",
+                "csrw vcsr, {4}",
+                "csrw vstart, {5}",
+                inout(reg) csrs.add(CSR_WORDS) => _,
+                in(reg) 8 * vlenb(),
+                in(reg) csrs.read(),
+                in(reg) csrs.add(1).read(),
+                in(reg) csrs.add(3).read(),
+                in(reg) csrs.add(2).read(),
+                // the vector registers are caller-saved
+                clobber_abi("C"),
+                options(nostack, readonly),
+            );
+        }
+        #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+        () => unimplemented!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        let word = core::mem::size_of::<usize>();
+        assert_eq!(size(0), 4 * word);
+        assert_eq!(size(16), 4 * word + 512); // VLEN = 128
+        assert_eq!(size(128), 4 * word + 4096); // VLEN = 1024
+    }
+}
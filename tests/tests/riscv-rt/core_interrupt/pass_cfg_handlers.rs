@@ -0,0 +1,16 @@
+use riscv::interrupt::Interrupt::*;
+
+#[riscv_rt::core_interrupt(SupervisorSoft)]
+#[cfg(all())]
+fn enabled_interrupt() {}
+
+// Handlers of other devices may use the same export name or interrupt sources that do not exist
+#[riscv_rt::core_interrupt(SupervisorSoft)]
+#[cfg(not(all()))]
+fn disabled_interrupt() {}
+
+#[riscv_rt::core_interrupt(MissingInterrupt)]
+#[cfg(not(all()))]
+fn missing_interrupt() {}
+
+fn main() {}
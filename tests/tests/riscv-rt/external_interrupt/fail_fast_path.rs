@@ -0,0 +1,7 @@
+#[riscv_rt::external_interrupt(ExternalInterrupt::GPIO, fast)]
+fn fast_interrupt() {}
+
+#[riscv_rt::external_interrupt(ExternalInterrupt::UART, slow)]
+fn slow_interrupt() {}
+
+fn main() {}
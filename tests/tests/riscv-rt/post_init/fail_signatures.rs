@@ -0,0 +1,13 @@
+#[riscv_rt::post_init]
+fn no_arguments() {}
+
+#[riscv_rt::post_init]
+fn immutable_reference(boot_info: &riscv_rt::BootInfo) {}
+
+#[riscv_rt::post_init]
+fn return_value(boot_info: &mut riscv_rt::BootInfo) -> usize {}
+
+#[riscv_rt::post_init]
+async fn async_post_init(boot_info: &mut riscv_rt::BootInfo) {}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use riscv_rt::BootInfo;
+
+#[riscv_rt::post_init]
+fn before_main(boot_info: &mut BootInfo) {
+    boot_info.a0 = boot_info.a1;
+}
+
+fn main() {}
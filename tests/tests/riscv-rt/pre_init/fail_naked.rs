@@ -0,0 +1,34 @@
+#[riscv_rt::pre_init(naked)]
+unsafe extern "C" fn not_naked() {
+    core::arch::naked_asm!("ret")
+}
+
+#[riscv_rt::pre_init(naked)]
+#[unsafe(naked)]
+unsafe fn rust_abi() {
+    core::arch::naked_asm!("ret")
+}
+
+#[riscv_rt::pre_init(naked)]
+#[unsafe(naked)]
+unsafe extern "C" fn arguments(hartid: usize) {
+    core::arch::naked_asm!("ret")
+}
+
+#[riscv_rt::pre_init(naked)]
+#[unsafe(naked)]
+#[unsafe(link_section = ".text")]
+unsafe extern "C" fn wrong_section() {
+    core::arch::naked_asm!("ret")
+}
+
+#[riscv_rt::pre_init(naked)]
+#[unsafe(naked)]
+unsafe extern "C" fn no_naked_asm() {
+    let _ = 0;
+}
+
+#[riscv_rt::pre_init(other)]
+unsafe fn wrong_argument() {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[riscv_rt::pre_init(naked)]
+#[unsafe(naked)]
+unsafe extern "C" fn before_main() {
+    core::arch::naked_asm!("ret")
+}
+
+fn main() {}
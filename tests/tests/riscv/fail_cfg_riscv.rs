@@ -0,0 +1,7 @@
+riscv::cfg_riscv!(if md {
+    fn mul(a: u32, b: u32) -> u32 {
+        a * b
+    }
+});
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[riscv::pac_enum(unsafe CoreInterruptNumber, plic = PLIC)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Interrupt {
+    I1 = 1,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+#[riscv::pac_enum(unsafe PriorityNumber, table = "ram")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Priority {
+    P0 = 0,
+}
+
+#[riscv::pac_enum(unsafe CoreInterruptNumber, table = "flash")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Interrupt {
+    I1 = 1,
+}
+
+#[riscv::pac_enum(unsafe CoreInterruptNumber, vectors = "ram")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OtherInterrupt {
+    I1 = 1,
+}
+
+fn main() {}
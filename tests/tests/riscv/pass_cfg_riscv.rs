@@ -0,0 +1,21 @@
+riscv::cfg_riscv!(if m {
+    fn mul(a: u32, b: u32) -> u32 {
+        a * b
+    }
+} else {
+    fn mul(a: u32, b: u32) -> u32 {
+        (0..b).fold(0, |acc, _| acc + a)
+    }
+});
+
+riscv::cfg_riscv!(if f {
+    const HAS_F: bool = true;
+});
+
+#[cfg(not(target_feature = "f"))]
+const HAS_F: bool = false;
+
+fn main() {
+    assert_eq!(mul(3, 4), 12);
+    assert!(!HAS_F);
+}
@@ -0,0 +1,51 @@
+use riscv::*;
+
+riscv_peripheral::plic_codegen!(base 0x0C00_0000, ctxs [ctx0 = (HartId::H0, "`H0`")],);
+
+#[pac_enum(unsafe HartIdNumber)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HartId {
+    H0 = 0,
+}
+
+#[pac_enum(unsafe PriorityNumber)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Priority {
+    P0 = 0,
+    P1 = 1,
+}
+
+#[pac_enum(unsafe ExternalInterruptNumber, table = "ram", plic = PLIC)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Interrupt {
+    I1 = 1,
+    I3 = 3,
+}
+
+mod isr {
+    #[export_name = "DefaultHandler"]
+    fn default_handler() {}
+
+    #[export_name = "I1"]
+    fn i1() {}
+
+    #[export_name = "I3"]
+    fn i3() {}
+}
+
+// Not called, as the PLIC registers are not available in the host
+#[allow(dead_code)]
+fn configure() {
+    unsafe {
+        Interrupt::I1.set_priority(Priority::P1);
+        Interrupt::I1.enable(PLIC::ctx0());
+    }
+    Interrupt::I3.disable(PLIC::ctx0());
+    assert!(!Interrupt::I3.is_enabled(PLIC::ctx0()));
+    assert_eq!(Interrupt::I3.priority::<Priority>(), Priority::P0);
+    assert!(!Interrupt::I1.is_pending());
+}
+
+fn main() {
+    assert_eq!(Interrupt::MAX_INTERRUPT_NUMBER, 3);
+}
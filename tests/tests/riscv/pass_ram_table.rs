@@ -0,0 +1,45 @@
+use riscv::*;
+
+#[pac_enum(unsafe ExternalInterruptNumber, table = "ram")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Interrupt {
+    I1 = 1,
+    I3 = 3,
+}
+
+#[pac_enum(unsafe ExceptionNumber, table = "rom")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Exception {
+    E1 = 1,
+}
+
+mod isr {
+    #[export_name = "DefaultHandler"]
+    fn default_handler() {}
+
+    #[export_name = "ExceptionHandler"]
+    fn exception_handler() {}
+
+    #[export_name = "I1"]
+    fn i1() {}
+
+    #[export_name = "I3"]
+    fn i3() {}
+
+    #[export_name = "E1"]
+    fn e1() {}
+}
+
+fn main() {
+    assert_eq!(Interrupt::MAX_INTERRUPT_NUMBER, 3);
+    assert_eq!(Exception::MAX_EXCEPTION_NUMBER, 1);
+
+    assert_eq!(
+        unsafe { (*core::ptr::addr_of!(__EXTERNAL_INTERRUPTS)).len() },
+        Interrupt::MAX_INTERRUPT_NUMBER + 1
+    );
+    assert!(Interrupt::I1.handler().is_some());
+    assert!(Interrupt::I3.handler().is_some());
+
+    assert!(__EXCEPTIONS[1].is_some());
+}
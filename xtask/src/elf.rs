@@ -0,0 +1,167 @@
+//! Minimal reader of little-endian ELF32/ELF64 files, to patch `riscv-rt` binaries after linking.
+
+use crate::Result;
+
+/// Section type of symbol tables.
+const SHT_SYMTAB: u32 = 2;
+/// Section type of sections without contents in the file (e.g., `.bss`).
+const SHT_NOBITS: u32 = 8;
+/// Section flag of sections that occupy memory during execution.
+const SHF_ALLOC: u64 = 0x2;
+
+/// Section of an ELF file.
+struct Section {
+    kind: u32,
+    flags: u64,
+    addr: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+}
+
+/// Symbol of an ELF file.
+pub struct Symbol {
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+}
+
+/// ELF file loaded in memory.
+pub struct Elf {
+    data: Vec<u8>,
+    sections: Vec<Section>,
+    symbols: Vec<Symbol>,
+}
+
+impl Elf {
+    /// Parses the sections and symbols of an ELF file.
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        if data.get(..4) != Some(b"\x7fELF") {
+            return Err("not an ELF file".into());
+        }
+        if data.get(5) != Some(&1) {
+            return Err("big-endian ELF files are not supported".into());
+        }
+        let is64 = match data.get(4) {
+            Some(1) => false,
+            Some(2) => true,
+            _ => return Err("invalid ELF class".into()),
+        };
+        let mut elf = Self {
+            data,
+            sections: Vec::new(),
+            symbols: Vec::new(),
+        };
+
+        let (shoff, shentsize, shnum) = match is64 {
+            true => (elf.u64(0x28)?, elf.u16(0x3a)?, elf.u16(0x3c)?),
+            false => (elf.u32(0x20)? as u64, elf.u16(0x2e)?, elf.u16(0x30)?),
+        };
+        for i in 0..shnum as u64 {
+            let sh = (shoff + i * shentsize as u64) as usize;
+            let section = match is64 {
+                true => Section {
+                    kind: elf.u32(sh + 0x4)?,
+                    flags: elf.u64(sh + 0x8)?,
+                    addr: elf.u64(sh + 0x10)?,
+                    offset: elf.u64(sh + 0x18)?,
+                    size: elf.u64(sh + 0x20)?,
+                    link: elf.u32(sh + 0x28)?,
+                },
+                false => Section {
+                    kind: elf.u32(sh + 0x4)?,
+                    flags: elf.u32(sh + 0x8)? as u64,
+                    addr: elf.u32(sh + 0xc)? as u64,
+                    offset: elf.u32(sh + 0x10)? as u64,
+                    size: elf.u32(sh + 0x14)? as u64,
+                    link: elf.u32(sh + 0x18)?,
+                },
+            };
+            elf.sections.push(section);
+        }
+
+        let mut symbols = Vec::new();
+        for symtab in elf.sections.iter().filter(|s| s.kind == SHT_SYMTAB) {
+            let strtab = elf
+                .sections
+                .get(symtab.link as usize)
+                .ok_or("invalid string table")?;
+            let entsize = if is64 { 24 } else { 16 };
+            for i in 0..symtab.size / entsize {
+                let sym = (symtab.offset + i * entsize) as usize;
+                let (name, value, size) = match is64 {
+                    true => (elf.u32(sym)?, elf.u64(sym + 0x8)?, elf.u64(sym + 0x10)?),
+                    false => (
+                        elf.u32(sym)?,
+                        elf.u32(sym + 0x4)? as u64,
+                        elf.u32(sym + 0x8)? as u64,
+                    ),
+                };
+                let name = elf.str(strtab.offset as usize + name as usize)?;
+                symbols.push(Symbol { name, value, size });
+            }
+        }
+        elf.symbols = symbols;
+        Ok(elf)
+    }
+
+    /// Returns the symbol `name`, if defined.
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|s| s.name == name)
+    }
+
+    /// Returns the contents of the `len` bytes of memory starting at `addr`.
+    pub fn read(&self, addr: u64, len: u64) -> Result<&[u8]> {
+        let offset = self.offset(addr, len)?;
+        Ok(&self.data[offset..offset + len as usize])
+    }
+
+    /// Overwrites the contents of the memory starting at `addr` with `bytes`.
+    pub fn write(&mut self, addr: u64, bytes: &[u8]) -> Result<()> {
+        let offset = self.offset(addr, bytes.len() as u64)?;
+        self.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Returns the raw contents of the ELF file.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns the file offset of the `len` bytes of memory starting at `addr`.
+    ///
+    /// The memory range must be within a section with contents in the file.
+    fn offset(&self, addr: u64, len: u64) -> Result<usize> {
+        self.sections
+            .iter()
+            .filter(|s| s.flags & SHF_ALLOC != 0 && s.kind != SHT_NOBITS)
+            .find(|s| addr >= s.addr && addr + len <= s.addr + s.size)
+            .map(|s| (s.offset + addr - s.addr) as usize)
+            .ok_or_else(|| format!("address {addr:#x} is not in a loadable section"))
+    }
+
+    fn bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        self.data
+            .get(offset..offset + N)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| "truncated ELF file".into())
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16> {
+        self.bytes(offset).map(u16::from_le_bytes)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32> {
+        self.bytes(offset).map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, offset: usize) -> Result<u64> {
+        self.bytes(offset).map(u64::from_le_bytes)
+    }
+
+    fn str(&self, offset: usize) -> Result<String> {
+        let bytes = self.data.get(offset..).ok_or("truncated ELF file")?;
+        let len = bytes.iter().position(|&b| b == 0).ok_or("invalid string")?;
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
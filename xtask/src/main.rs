@@ -0,0 +1,592 @@
+//! Development tasks for the `riscv` workspace.
+//!
+//! Run `cargo xtask help` for a list of the available tasks.
+
+mod elf;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Targets used by `cargo xtask qemu` when none is given.
+const DEFAULT_TARGETS: &[&str] = &[
+    "riscv32imac-unknown-none-elf",
+    "riscv64imac-unknown-none-elf",
+];
+
+/// A `riscv-rt` application example run in QEMU.
+struct QemuExample {
+    /// Package of the example.
+    package: &'static str,
+    /// Name of the example. It must have an expected output in `xtask/expected/<name>.txt`.
+    name: &'static str,
+    /// Linker script used to build the example.
+    linker_script: &'static str,
+    /// Firmware loaded by QEMU: `none` for M-mode examples, `default` for OpenSBI.
+    bios: &'static str,
+    /// Number of harts of the QEMU `virt` machine.
+    smp: usize,
+    /// Modes to test: (name, features).
+    modes: &'static [(&'static str, &'static str)],
+}
+
+/// Examples run in QEMU.
+const QEMU_EXAMPLES: &[QemuExample] = &[
+    QemuExample {
+        package: "riscv-peripheral",
+        name: "qemu_mswi",
+        linker_script: "riscv-rt/examples/qemu.x",
+        bios: "none",
+        smp: 1,
+        modes: &[
+            ("direct", ""),
+            ("vectored", "riscv-rt/v-trap"),
+            ("single-hart", "riscv-rt/single-hart"),
+            (
+                "vectored-single-hart",
+                "riscv-rt/v-trap,riscv-rt/single-hart",
+            ),
+            ("u-boot", "riscv-rt/u-boot"),
+            ("vectored-u-boot", "riscv-rt/v-trap,riscv-rt/u-boot"),
+            ("compact", "riscv-rt/compact-trap"),
+            ("vectored-compact", "riscv-rt/v-trap,riscv-rt/compact-trap"),
+        ],
+    },
+    QemuExample {
+        package: "riscv-rt",
+        name: "qemu_async",
+        linker_script: "riscv-rt/examples/qemu.x",
+        bios: "none",
+        smp: 1,
+        modes: &[
+            ("async-main", "async-main"),
+            ("async-main-vectored", "async-main,v-trap"),
+        ],
+    },
+    QemuExample {
+        package: "riscv-rt",
+        name: "qemu_ram",
+        linker_script: "riscv-rt/examples/qemu_ram.x",
+        bios: "none",
+        smp: 1,
+        modes: &[
+            ("direct", ""),
+            ("vectored", "v-trap"),
+            ("single-hart", "single-hart"),
+        ],
+    },
+    QemuExample {
+        package: "riscv-rt",
+        name: "qemu_smp",
+        linker_script: "riscv-rt/examples/qemu_smp.x",
+        bios: "none",
+        smp: 2,
+        modes: &[("direct", ""), ("vectored", "v-trap")],
+    },
+    QemuExample {
+        package: "riscv-rt",
+        name: "s-mode-kernel",
+        linker_script: "riscv-rt/examples/qemu_sbi.x",
+        bios: "default",
+        smp: 1,
+        modes: &[
+            ("s-mode", "s-mode,riscv/s-mode"),
+            ("s-mode-vectored", "s-mode,riscv/s-mode,v-trap"),
+            ("s-mode-single-hart", "s-mode,riscv/s-mode,single-hart"),
+            ("s-mode-u-boot", "s-mode,riscv/s-mode,v-trap,u-boot"),
+        ],
+    },
+];
+
+/// `riscv-rt` feature combinations checked by `cargo xtask boot-asm`: (name, features).
+const BOOT_ASM_MODES: &[(&str, &str)] = &[
+    ("default", ""),
+    ("single-hart", "single-hart"),
+    ("v-trap", "v-trap"),
+    ("pmp-wx", "pmp-wx"),
+    ("s-mode", "s-mode,riscv/s-mode"),
+    (
+        "s-mode-single-hart",
+        "s-mode,riscv/s-mode,single-hart,v-trap",
+    ),
+    ("u-boot", "u-boot"),
+    ("v-trap-u-boot", "v-trap,u-boot"),
+    ("s-mode-u-boot", "s-mode,riscv/s-mode,u-boot"),
+];
+
+/// Maximum time an example may run in QEMU before being considered stuck.
+const QEMU_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Result<T> = core::result::Result<T, String>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let res = match args.first().map(String::as_str) {
+        Some("qemu") => qemu(&args[1..]),
+        Some("boot-asm") => boot_asm(&args[1..]),
+        Some("vectors-crc") => vectors_crc(&args[1..]),
+        Some("help") | None => {
+            help();
+            Ok(())
+        }
+        Some(task) => Err(format!("unknown task `{task}`")),
+    };
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn help() {
+    eprintln!(
+        "Tasks:
+
+qemu [TARGET]... [-- ARG...]  build the QEMU examples of riscv-rt applications for TARGETs (default: {}) in all
+                  their modes (e.g., direct and vectored), run them in QEMU, and check their output.
+                  Examples using `riscv_semihosting::report` write their JUnit XML reports to
+                  target/xtask/reports/<example>-<target>-<mode>.xml
+                  ARGs (e.g., `key=value`) are appended to the command line of the examples,
+                  which can read them with `riscv_semihosting::env`
+
+boot-asm [TARGET]...  build the riscv-rt `empty` example for TARGETs (default: {}) with several
+                  feature combinations, disassemble it, and check that `_start`, `_abs_start`, and
+                  the initialization code up to the jump to `main` form one contiguous block.
+                  The disassembler is taken from the OBJDUMP environment variable, the `llvm-tools`
+                  rustup component, or `llvm-objdump` in the PATH, in this order
+
+vectors-crc ELF...  compute the CRC-32 of the trap dispatch tables of the riscv-rt ELF files and
+                  write it to their `_vector_table_crc` word, which `riscv_rt::verify_vectors` checks",
+        DEFAULT_TARGETS.join(", "),
+        DEFAULT_TARGETS.join(", ")
+    );
+}
+
+/// Returns the root directory of the workspace.
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+/// Returns the given targets, or [`DEFAULT_TARGETS`] if none is given.
+fn targets_or_default(targets: &[String]) -> Vec<&str> {
+    match targets.is_empty() {
+        true => DEFAULT_TARGETS.to_vec(),
+        false => targets.iter().map(String::as_str).collect(),
+    }
+}
+
+/// Builds, runs, and checks every QEMU example for every target and mode.
+///
+/// Arguments after `--` are forwarded to the command line of the examples.
+fn qemu(args: &[String]) -> Result<()> {
+    let (targets, cmdline_args) = match args.iter().position(|arg| arg == "--") {
+        Some(i) => (&args[..i], &args[i + 1..]),
+        None => (args, &[][..]),
+    };
+    let targets = targets_or_default(targets);
+    let root = workspace_root();
+
+    let mut failures = Vec::new();
+    for target in &targets {
+        for example in QEMU_EXAMPLES {
+            for (mode, features) in example.modes {
+                let name = example.name;
+                eprint!("qemu {name} ({target}, {mode}) ... ");
+                match qemu_example(&root, target, mode, features, example, cmdline_args) {
+                    Ok(()) => eprintln!("ok"),
+                    Err(e) => {
+                        eprintln!("FAILED\n{e}");
+                        failures.push(format!("{name} ({target}, {mode})"));
+                    }
+                }
+            }
+        }
+    }
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(format!("QEMU examples failed: {}", failures.join(", "))),
+    }
+}
+
+/// Returns the command line of a QEMU example: the path of its report, followed by `args`.
+fn cmdline(report: &Path, args: &[String]) -> String {
+    let mut cmdline = format!("report={}", report.display());
+    for arg in args {
+        cmdline.push(' ');
+        cmdline.push_str(arg);
+    }
+    cmdline
+}
+
+/// Builds, runs, and checks a single QEMU example.
+fn qemu_example(
+    root: &Path,
+    target: &str,
+    mode: &str,
+    features: &str,
+    example: &QemuExample,
+    cmdline_args: &[String],
+) -> Result<()> {
+    let qemu = match target.split('-').next() {
+        Some(arch) if arch.starts_with("riscv32") => "qemu-system-riscv32",
+        Some(arch) if arch.starts_with("riscv64") => "qemu-system-riscv64",
+        _ => return Err(format!("unsupported target `{target}`")),
+    };
+    // Each mode gets its own target directory to avoid rebuilding everything on every switch
+    let target_dir = root.join("target").join("xtask").join(mode);
+
+    let mut cargo = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".into()));
+    cargo
+        .current_dir(root)
+        .env(
+            "RUSTFLAGS",
+            format!("-C link-arg=-T{}", example.linker_script),
+        )
+        .args(["build", "--release", "--package", example.package])
+        .args(["--example", example.name, "--target", target])
+        .arg("--target-dir")
+        .arg(&target_dir);
+    if !features.is_empty() {
+        cargo.args(["--features", features]);
+    }
+    let status = cargo
+        .status()
+        .map_err(|e| format!("failed to run cargo: {e}"))?;
+    if !status.success() {
+        return Err(format!("cargo build failed ({status})"));
+    }
+
+    // Examples using `riscv_semihosting::report` get the report path from the command line
+    let report_dir = root.join("target").join("xtask").join("reports");
+    std::fs::create_dir_all(&report_dir)
+        .map_err(|e| format!("failed to create {}: {e}", report_dir.display()))?;
+    let report = report_dir.join(format!("{}-{target}-{mode}.xml", example.name));
+    if report.exists() {
+        std::fs::remove_file(&report)
+            .map_err(|e| format!("failed to remove {}: {e}", report.display()))?;
+    }
+
+    let elf = target_dir
+        .join(target)
+        .join("release")
+        .join("examples")
+        .join(example.name);
+    // The output is only taken from semihosting: hide the serial console (e.g., the OpenSBI banner)
+    let mut child = Command::new(qemu)
+        .args(["-machine", "virt", "-bios", example.bios])
+        .args(["-smp", &example.smp.to_string()])
+        .args(["-display", "none", "-serial", "null", "-monitor", "none"])
+        .args(["-semihosting-config", "enable=on,target=native"])
+        .arg("-kernel")
+        .arg(&elf)
+        .arg("-append")
+        .arg(cmdline(&report, cmdline_args))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("failed to run {qemu}: {e}"))?;
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if start.elapsed() > QEMU_TIMEOUT {
+            let _ = child.kill();
+            return Err(format!("{qemu} timed out after {QEMU_TIMEOUT:?}"));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to read {qemu} output: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n");
+
+    let expected_path = root
+        .join("xtask")
+        .join("expected")
+        .join(format!("{}.txt", example.name));
+    let expected = std::fs::read_to_string(&expected_path)
+        .map_err(|e| format!("failed to read {}: {e}", expected_path.display()))?;
+
+    if stdout != expected {
+        return Err(format!(
+            "unexpected output\n--- expected\n{expected}--- got\n{stdout}"
+        ));
+    }
+    if !status.success() {
+        return Err(format!("{qemu} exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Builds and disassembles the `empty` example for every target and feature combination,
+/// and checks that the boot code forms one contiguous block.
+fn boot_asm(targets: &[String]) -> Result<()> {
+    let targets = targets_or_default(targets);
+    let root = workspace_root();
+    let objdump = objdump()?;
+
+    let mut failures = Vec::new();
+    for target in &targets {
+        for (mode, features) in BOOT_ASM_MODES {
+            eprint!("boot-asm ({target}, {mode}) ... ");
+            match boot_asm_mode(&root, &objdump, target, mode, features) {
+                Ok(()) => eprintln!("ok"),
+                Err(e) => {
+                    eprintln!("FAILED\n{e}");
+                    failures.push(format!("{target}, {mode}"));
+                }
+            }
+        }
+    }
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(format!("boot code checks failed: {}", failures.join("; "))),
+    }
+}
+
+/// Returns the path of the `llvm-objdump` disassembler.
+fn objdump() -> Result<PathBuf> {
+    if let Some(objdump) = env::var_os("OBJDUMP") {
+        return Ok(objdump.into());
+    }
+    // The `llvm-tools` rustup component installs it in the host directory of the sysroot
+    let rustc = |args: &[&str]| -> Result<String> {
+        let output = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+            .args(args)
+            .output()
+            .map_err(|e| format!("failed to run rustc: {e}"))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let sysroot = PathBuf::from(rustc(&["--print", "sysroot"])?.trim());
+    let version = rustc(&["-vV"])?;
+    let host = version
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .ok_or("failed to get the host target from rustc")?;
+    let objdump = sysroot
+        .join("lib")
+        .join("rustlib")
+        .join(host)
+        .join("bin")
+        .join("llvm-objdump");
+    match objdump.exists() {
+        true => Ok(objdump),
+        false => Ok("llvm-objdump".into()),
+    }
+}
+
+/// Builds and disassembles the `empty` example for a single target and feature combination,
+/// and checks that the boot code forms one contiguous block.
+fn boot_asm_mode(
+    root: &Path,
+    objdump: &Path,
+    target: &str,
+    mode: &str,
+    features: &str,
+) -> Result<()> {
+    let target_dir = root
+        .join("target")
+        .join("xtask")
+        .join("boot-asm")
+        .join(mode);
+
+    let mut cargo = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".into()));
+    cargo
+        .current_dir(root)
+        .env("RUSTFLAGS", "-C link-arg=-Triscv-rt/examples/qemu.x")
+        .args(["build", "--release", "--package", "riscv-rt"])
+        .args(["--example", "empty", "--target", target])
+        .arg("--target-dir")
+        .arg(&target_dir);
+    if !features.is_empty() {
+        cargo.args(["--features", features]);
+    }
+    let status = cargo
+        .status()
+        .map_err(|e| format!("failed to run cargo: {e}"))?;
+    if !status.success() {
+        return Err(format!("cargo build failed ({status})"));
+    }
+
+    let elf = target_dir
+        .join(target)
+        .join("release")
+        .join("examples")
+        .join("empty");
+    let output = Command::new(objdump)
+        .args(["--disassemble", "--disassemble-zeroes", "--section=.init"])
+        .arg(&elf)
+        .output()
+        .map_err(|e| format!("failed to run {}: {e}", objdump.display()))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed ({})\n{}",
+            objdump.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    check_boot_block(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Line of the output of `llvm-objdump --disassemble`.
+#[derive(Debug, PartialEq)]
+enum DisasmLine<'a> {
+    /// Start of a symbol (e.g., `0000000080000000 <_start>:`).
+    Symbol(&'a str),
+    /// Instruction with its address, size in bytes, and text.
+    Instruction(u64, u64, &'a str),
+}
+
+/// Parses a line of the output of `llvm-objdump --disassemble`.
+fn parse_disasm_line(line: &str) -> Option<DisasmLine<'_>> {
+    let line = line.trim();
+    if let Some(symbol) = line.strip_suffix(">:") {
+        let (_, name) = symbol.split_once(" <")?;
+        return Some(DisasmLine::Symbol(name));
+    }
+    let (addr, rest) = line.split_once(':')?;
+    let addr = u64::from_str_radix(addr.trim(), 16).ok()?;
+    // Encoding bytes are separated from the instruction by a tab
+    let (bytes, text) = rest.trim_start().split_once('\t').unwrap_or((rest, ""));
+    let digits = bytes.chars().filter(|c| !c.is_whitespace()).count();
+    if digits == 0
+        || !bytes
+            .trim()
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c == ' ')
+    {
+        return None;
+    }
+    Some(DisasmLine::Instruction(
+        addr,
+        digits as u64 / 2,
+        text.trim(),
+    ))
+}
+
+/// Checks that `_start` is immediately followed by `_abs_start`, and that `_abs_start`
+/// is one contiguous block of instructions that ends with the jump to `main`.
+fn check_boot_block(disasm: &str) -> Result<()> {
+    let mut lines = disasm.lines().filter_map(parse_disasm_line);
+    lines
+        .by_ref()
+        .find(|line| *line == DisasmLine::Symbol("_start"))
+        .ok_or("`_start` not found in `.init`")?;
+
+    let mut symbol = "_start";
+    let mut next_addr = None;
+    for line in lines {
+        match line {
+            DisasmLine::Symbol("_abs_start") if symbol == "_start" => symbol = "_abs_start",
+            // Assembler local labels (e.g., `.Lpcrel_hi0`) may be kept in the symbol table
+            DisasmLine::Symbol(name) if name.starts_with(".L") => {}
+            DisasmLine::Symbol(name) => {
+                return Err(format!("unexpected symbol `{name}` after `{symbol}`"))
+            }
+            DisasmLine::Instruction(addr, size, text) => {
+                if let Some(expected) = next_addr {
+                    if addr != expected {
+                        return Err(format!(
+                            "gap in `{symbol}`: expected address {expected:#x}, found {addr:#x}"
+                        ));
+                    }
+                }
+                if symbol == "_abs_start" && text.ends_with("<main>") {
+                    return Ok(());
+                }
+                next_addr = Some(addr + size);
+            }
+        }
+    }
+    match symbol {
+        "_start" => Err("`_abs_start` does not follow `_start`".into()),
+        _ => Err("jump to `main` not found after `_abs_start`".into()),
+    }
+}
+
+/// Trap dispatch tables covered by `riscv_rt::vectors_crc`, in order: (start symbol, end symbol).
+///
+/// Tables without an end symbol span the size of the start symbol.
+/// Tables that are not present in the binary (e.g., depending on the features) are skipped.
+const VECTORS_CRC_TABLES: &[(&str, Option<&str>)] = &[
+    ("__svector_table", Some("__evector_table")),
+    ("__CORE_INTERRUPTS", None),
+    ("__EXCEPTIONS", None),
+];
+
+/// Updates the CRC-32 (IEEE 802.3) `crc` with `bytes`, as `riscv_rt::vectors_crc` does.
+fn crc32(mut crc: u32, bytes: &[u8]) -> u32 {
+    /// CRC-32 (IEEE 802.3) reversed polynomial.
+    const POLY: u32 = 0xEDB8_8320;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (POLY & (crc & 1).wrapping_neg());
+        }
+    }
+    crc
+}
+
+/// Patches the `_vector_table_crc` word of every ELF file with the CRC-32 of its trap dispatch tables.
+fn vectors_crc(files: &[String]) -> Result<()> {
+    if files.is_empty() {
+        return Err("no ELF file given".into());
+    }
+    for file in files {
+        let data = fs::read(file).map_err(|e| format!("failed to read `{file}`: {e}"))?;
+        let mut elf = elf::Elf::parse(data).map_err(|e| format!("`{file}`: {e}"))?;
+
+        let mut crc = !0;
+        for (start, end) in VECTORS_CRC_TABLES {
+            let Some(start) = elf.symbol(start) else {
+                continue;
+            };
+            let len = match end {
+                Some(end) => match elf.symbol(end) {
+                    Some(end) => end.value - start.value,
+                    None => continue,
+                },
+                None => start.size,
+            };
+            crc = crc32(crc, elf.read(start.value, len)?);
+        }
+        let crc = !crc;
+
+        let addr = elf
+            .symbol("_vector_table_crc")
+            .ok_or_else(|| {
+                format!("`{file}`: `_vector_table_crc` not found (is `verify_vectors` called?)")
+            })?
+            .value;
+        elf.write(addr, &crc.to_le_bytes())?;
+        fs::write(file, elf.into_bytes()).map_err(|e| format!("failed to write `{file}`: {e}"))?;
+        eprintln!("{file}: _vector_table_crc = {crc:#010x}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc32;
+
+    #[test]
+    fn test_crc32() {
+        // CRC-32 check value of the IEEE 802.3 polynomial
+        assert_eq!(!crc32(!0, b"123456789"), 0xCBF4_3926);
+        assert_eq!(!crc32(crc32(!0, b"1234"), b"56789"), 0xCBF4_3926);
+    }
+}